@@ -1,46 +1,219 @@
 # Define all known sftpman commands
-set --local commands create update help ls mount mount_all umount umount_all rm preflight_check
+set --local commands create update help ls status mount mount_all umount umount_all rm prune preflight_check supervise umount_idle gc lsof daemon browse bench automount_units desktop config sync validate verify cp events check import_rclone import-rclone import_bookmarks import-bookmarks setup pause resume accept_hostkey accept-hostkey keygen profile m ma u um ua l
 
 # Main command completions
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "create" --no-files --description "Creates a new filesystem mount definition"
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "update" --no-files --description "Updates an existing filesystem mount definition"
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "help" --no-files --description "Show help"
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "ls" --no-files --description "List SFTP systems"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "status" --no-files --description "Show the state of SFTP systems"
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "mount" --no-files --description "Mount SFTP systems"
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "mount_all" --no-files --description "Mount all SFTP systems"
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "umount" --no-files --description "Unmount SFTP systems"
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "umount_all" --no-files --description "Unmount all SFTP systems"
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "rm" --no-files --description "Remove SFTP systems"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "prune" --no-files --description "Remove SFTP systems which look abandoned"
 complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "preflight_check" --no-files --description "Check if all system requirements are satisfied"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "umount_idle" --no-files --description "Unmount idle SFTP systems"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "gc" --no-files --description "Forget temporary mounts which are no longer mounted"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "lsof" --no-files --description "List processes keeping an SFTP system busy"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "supervise" --no-files --description "Mount an SFTP system and keep sshfs running in the foreground"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "daemon" --no-files --description "Run a daemon which performs mount operations"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "browse" --no-files --description "Pick the remote path of an SFTP system interactively"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "bench" --no-files --description "Measure the throughput and latency of an SFTP system"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "automount_units" --no-files --description "Generate systemd units for mounting an SFTP system on first access"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "desktop" --no-files --description "Manage desktop launchers for SFTP systems"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "config" --no-files --description "Show or change global configuration settings"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "sync" --no-files --description "Synchronize definitions with a git remote"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "validate" --no-files --description "Check all definitions for problems"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "verify" --no-files --description "Check that SFTP systems can be connected to"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "cp" --no-files --description "Copy files to or from an SFTP system without mounting it"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "events" --no-files --description "Print mount state changes as they happen"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "check" --no-files --description "Check SFTP systems for monitoring systems (Nagios/Icinga)"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "import_rclone" --no-files --description "Create definitions for the SFTP remotes in rclone's configuration"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "import_bookmarks" --no-files --description "Create definitions for the SFTP bookmarks of file managers"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "setup" --no-files --description "Get started by creating your first SFTP system interactively"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "pause" --no-files --description "Pause the automatic management of SFTP systems"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "resume" --no-files --description "Resume the automatic management of SFTP systems"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "accept_hostkey" --no-files --description "Verify and accept the host key of an SFTP system"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "keygen" --no-files --description "Generate a dedicated SSH key for an SFTP system"
+complete --command sftpman --condition "not __fish_seen_subcommand_from $commands" --arguments "profile" --no-files --description "Manage profiles (separate sets of SFTP systems)"
+
+# Global options
+complete --command sftpman --long-option output --description "Output format" --arguments "plain json" --no-files --require-parameter
+complete --command sftpman --long-option profile --description "Profile to use" --arguments "(sftpman profile list | string sub --start 3)" --no-files --require-parameter
 
 # ls subcommand completions
-complete --command sftpman --condition "__fish_seen_subcommand_from ls" --arguments "available" --no-files --description "List all available systems"
-complete --command sftpman --condition "__fish_seen_subcommand_from ls" --arguments "mounted" --no-files --description "List mounted systems"
-complete --command sftpman --condition "__fish_seen_subcommand_from ls" --arguments "unmounted" --no-files --description "List unmounted systems"
+complete --command sftpman --condition "__fish_seen_subcommand_from ls l" --arguments "available" --no-files --description "List all available systems"
+complete --command sftpman --condition "__fish_seen_subcommand_from ls l" --arguments "mounted" --no-files --description "List mounted systems"
+complete --command sftpman --condition "__fish_seen_subcommand_from ls l" --arguments "unmounted" --no-files --description "List unmounted systems"
+complete --command sftpman --condition "__fish_seen_subcommand_from ls l" --long-option host --description "Only list systems connecting to this host" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from ls l" --long-option auth_type --description "Only list systems using this authentication type" --arguments "publickey authentication-agent password keyboard-interactive hostbased gssapi-with-mic" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from ls l" --long-option search --description "Only list systems matching this text" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from ls l" --long-option long --short-option l --description "Show when systems were last mounted and how many times"
+complete --command sftpman --condition "__fish_seen_subcommand_from ls l" --long-option sort --description "Order to list systems in" --arguments "id last-used" --no-files --require-parameter
+
+# status subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from status" --arguments "(sftpman ls available)" --no-files
+complete --command sftpman --condition "__fish_seen_subcommand_from status" --long-option probe --description "Check that mounts respond" --no-files
+complete --command sftpman --condition "__fish_seen_subcommand_from status" --long-option probe-write --description "Check that mounts respond, also writing a temporary file" --no-files
+
+# check subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from check" --arguments "(sftpman ls available)" --no-files
+complete --command sftpman --condition "__fish_seen_subcommand_from check" --long-option format --description "Output format" --arguments "nagios" --no-files --require-parameter
+
+# mount/supervise subcommand completions - suggest unmounted systems
+complete --command sftpman --condition "__fish_seen_subcommand_from mount m supervise" --arguments "(sftpman ls unmounted)" --no-files
+
+# mount subcommand completions - temporary mounts
+complete --command sftpman --condition "__fish_seen_subcommand_from mount m" --long-option temporary --description "Mount a URL once, without storing a definition (e.g. sftp://user@host/path)" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from mount m" --long-option at --description "Local path to mount the --temporary URL at" --arguments "(__fish_complete_directories)" --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from mount m" --long-option open --description "Open a file manager at each system once mounted"
+complete --command sftpman --condition "__fish_seen_subcommand_from mount m" --long-option restore --description "Mount the systems of the previous session"
+
+# umount/lsof subcommand completions - suggest mounted systems
+complete --command sftpman --condition "__fish_seen_subcommand_from umount u um lsof" --arguments "(sftpman ls mounted)" --no-files
+
+# rm/browse/bench/automount_units/pause/resume/accept_hostkey/keygen subcommand completions - suggest available systems
+complete --command sftpman --condition "__fish_seen_subcommand_from rm browse bench automount_units pause resume accept_hostkey accept-hostkey keygen" --arguments "(sftpman ls available)" --no-files
+
+# cp subcommand completions - suggest systems (as ID: prefixes) besides local files
+complete --command sftpman --condition "__fish_seen_subcommand_from cp" --arguments "(sftpman ls available | string replace --regex '$' ':')"
+complete --command sftpman --condition "__fish_seen_subcommand_from cp" --short-option r --long-option recursive --description "Copy directories recursively"
+
+# verify subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from verify" --arguments "(sftpman ls available)" --no-files
+complete --command sftpman --condition "__fish_seen_subcommand_from verify" --long-option all --description "Verify all systems" --no-files
+complete --command sftpman --condition "__fish_seen_subcommand_from verify" --long-option jobs --description "Number of systems to verify in parallel" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from verify" --long-option host --description "Only verify systems connecting to this host" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from verify" --long-option search --description "Only verify systems matching this text" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from verify" --long-option create-remote-path --description "Create missing remote paths" --no-files
+
+# profile subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from profile; and not __fish_seen_subcommand_from list ls create switch" --arguments "list" --no-files --description "List the profiles"
+complete --command sftpman --condition "__fish_seen_subcommand_from profile; and not __fish_seen_subcommand_from list ls create switch" --arguments "create" --no-files --description "Create a profile"
+complete --command sftpman --condition "__fish_seen_subcommand_from profile; and not __fish_seen_subcommand_from list ls create switch" --arguments "switch" --no-files --description "Switch to a profile"
+complete --command sftpman --condition "__fish_seen_subcommand_from profile; and __fish_seen_subcommand_from switch" --arguments "(sftpman profile list | string sub --start 3)" --no-files
 
-# mount subcommand completions - suggest unmounted systems
-complete --command sftpman --condition "__fish_seen_subcommand_from mount" --arguments "(sftpman ls unmounted)" --no-files
+# desktop subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from desktop; and not __fish_seen_subcommand_from export remove" --arguments "export" --no-files --description "Write a desktop launcher for a system"
+complete --command sftpman --condition "__fish_seen_subcommand_from desktop; and not __fish_seen_subcommand_from export remove" --arguments "remove" --no-files --description "Remove the desktop launcher of a system"
+complete --command sftpman --condition "__fish_seen_subcommand_from desktop; and __fish_seen_subcommand_from export remove" --arguments "(sftpman ls available)" --no-files
+complete --command sftpman --condition "__fish_seen_subcommand_from desktop" --long-option output-dir --description "Directory the desktop launchers are in" --arguments "(__fish_complete_directories)" --require-parameter
 
-# umount subcommand completions - suggest mounted systems
-complete --command sftpman --condition "__fish_seen_subcommand_from umount" --arguments "(sftpman ls mounted)" --no-files
+# config subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from list get set" --arguments "list" --no-files --description "List all settings"
+complete --command sftpman --condition "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from list get set" --arguments "get" --no-files --description "Show the value of a setting"
+complete --command sftpman --condition "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from list get set" --arguments "set" --no-files --description "Change the value of a setting"
+complete --command sftpman --condition "__fish_seen_subcommand_from config; and __fish_seen_subcommand_from get set" --arguments "(sftpman config list | cut -d ' ' -f 1)" --no-files
 
-# rm subcommand completions - suggest available systems
-complete --command sftpman --condition "__fish_seen_subcommand_from rm" --arguments "(sftpman ls available)" --no-files
+# mount_all/umount_all/umount_idle/gc/sync/validate subcommand completions - nothing to suggest
+complete --command sftpman --condition "__fish_seen_subcommand_from mount_all ma umount_all ua umount_idle gc sync validate" --no-files
 
-# mount_all/umount_all subcommand completions - nothing to suggest
-complete --command sftpman --condition "__fish_seen_subcommand_from mount_all umount_all" --no-files
+# mount/mount_all/umount/umount_all progress reporting
+complete --command sftpman --condition "__fish_seen_subcommand_from mount m mount_all ma umount u um umount_all ua" --long-option progress --description "Print progress events as they happen" --arguments "json" --no-files --require-parameter
+
+# mount/mount_all/umount_all stopping at the first failure
+complete --command sftpman --condition "__fish_seen_subcommand_from mount m mount_all ma umount_all ua" --long-option fail-fast --description "Stop at the first failure, leaving the remaining systems alone"
+
+# mount/mount_all skipping the pre-mount checks
+complete --command sftpman --condition "__fish_seen_subcommand_from mount m mount_all ma" --long-option no-preflight --description "Skip the checks performed before mounting (sshfs installed, /dev/fuse accessible, etc.)"
+
+# mount_all subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from mount_all ma" --long-option auto --description "Only mount systems marked with auto_mount, skipping paused ones" --no-files
+
+# umount_all subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from umount_all ua" --long-option prefix --description "Only unmount systems mounted inside this directory" --arguments "(__fish_complete_directories)" --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from umount u um umount_all ua" --long-option kill-users --description "Terminate the processes using the systems, then unmount cleanly"
+complete --command sftpman --condition "__fish_seen_subcommand_from umount u um umount_all ua" --long-option signal --description "Signal to terminate processes with (for --kill-users)" --arguments "TERM INT HUP KILL" --no-files --require-parameter
+
+# daemon subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from daemon" --long-option socket --description "Path to the unix socket to listen on" --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from daemon" --long-option metrics-listen --description "Serve Prometheus metrics on this address" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from daemon" --long-option metrics-textfile --description "Write Prometheus metrics to this file" --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from daemon" --long-option ignore-network --description "Don't unmount and remount as the network goes down and comes back"
+complete --command sftpman --condition "__fish_seen_subcommand_from daemon" --long-option ignore-sleep --description "Don't unmount before suspending and remount after resuming"
 
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --no-files \
     --arguments "--" \
     --description "Available options (use --option=value format)"
 
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option id --description "Unique identifier" --arguments "(sftpman ls available)" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option description --description "Human-friendly name" --no-files --require-parameter
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option host --description "Hostname or IP address" --arguments "(__fish_complete_hostnames)" --no-files --require-parameter
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option port --description "SSH port number" --arguments "22" --no-files --require-parameter
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option user --description "Remote username to authenticate with" --arguments "(__fish_complete_users)" --no-files --require-parameter
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option auth_type --description "Authentication method" --arguments "publickey authentication-agent password keyboard-interactive hostbased gssapi-with-mic" --require-parameter
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option ssh_key --description "SSH private key path" -r
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option certificate_file --description "OpenSSH certificate issued for the SSH key" -r
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option known_hosts_file --description "known_hosts file to use instead of the default one" -r
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option proxy_command --description "Command to connect through (ssh's ProxyCommand)" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option socks_proxy --description "SOCKS5 proxy (host:port) to connect through" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option skip_host_resolution --description "Don't resolve the host before mounting" --arguments "true false" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option cipher --description "Cipher for ssh to use (repeatable)" --arguments "aes128-gcm@openssh.com aes256-gcm@openssh.com chacha20-poly1305@openssh.com aes128-ctr" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option ssh_binary --description "ssh program to connect with" -r
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option password_command --description "Command whose output is the password" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option password_env --description "Environment variable holding the password" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option password_file --description "File holding the password" -r
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option gssapi_delegate_credentials --description "Forward Kerberos credentials" --arguments "true false" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option gssapi_key_exchange --description "Use GSSAPI key exchange" --arguments "true false" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option env --description "Environment variable for sshfs/ssh (NAME=VALUE)" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option nice --description "Scheduling priority to run sshfs with (-20 to 19)" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option ionice_class --description "I/O scheduling class to run sshfs with" --arguments "best-effort idle" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option ionice_level --description "Priority within the I/O scheduling class (0 to 7)" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option download_limit --description "Maximum download rate in KiB/s (via trickle)" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option upload_limit --description "Maximum upload rate in KiB/s (via trickle)" --no-files --require-parameter
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option mount_opt --description "Mount options" --arguments "(sshfs --help 2>&1 | grep '\-o' | cut --description '-' --no-files 2 | cut --description ' ' --no-files 2 | grep -vE '^\$')"
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option remote_path --description "Remote path to mount (e.g. /storage)" --arguments "(__fish_complete_directories)"
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option mount_path --description "Local path to mount to (defaults to /mnt/sshfs/{id})" --arguments "(__fish_complete_directories)"
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option sub_mount --description "Remote path to mount into a subdirectory (REMOTE_PATH:DIRECTORY)" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option extra_mount_path --description "Additional local path to expose the filesystem at (repeatable)" --arguments "(__fish_complete_directories)" --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option extra_mount_path_method --description "How to expose the filesystem at extra mount paths" --arguments "symlink bind" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option allow_non_empty_mount_path --description "Mount even if the mount path is not empty" --arguments "true false" --no-files --require-parameter
 complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option cmd_before_mount --description "Command to run before mounting"
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option debug_sshfs --description "Capture sshfs debug output to a log file" --arguments "true false" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option idle_unmount_minutes --description "Unmount after this many idle minutes (0 disables)" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option auto_mount --description "Mount automatically when a session starts" --arguments "true false" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option requires --description "Condition for mounting (interface:NAME or reachable:HOST:PORT)" --arguments "interface: reachable:" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option enabled --description "Allow mounting (disabled systems are skipped by mount_all)" --arguments "true false" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from create update" --long-option force --description "Save even if another definition uses the same remote path"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_description --description "Remove the description"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option add_mount_opt --description "Mount option to add" --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option remove_mount_opt --description "Mount option to remove" --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_mount_opt --description "Remove all mount options"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_mount_path --description "Mount under the default mount path"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_sub_mounts --description "Remove all sub-mounts"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_extra_mount_paths --description "Stop exposing the filesystem at additional local paths"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_ssh_key --description "Remove the SSH private key path"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_certificate_file --description "Remove the certificate file path"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_known_hosts_file --description "Use the default known_hosts file"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_proxy --description "Connect directly instead of through a proxy"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_ciphers --description "Use ssh's default ciphers"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_ssh_binary --description "Use the default ssh program"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_cmd_before_mount --description "Remove the command to run before mounting"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_idle_unmount_minutes --description "Stop unmounting automatically when idle"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_requires --description "Remove all conditions for mounting"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_password_source --description "Go back to prompting for the password"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_env --description "Environment variable to stop setting for sshfs/ssh" --no-files --require-parameter
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_nice --description "Run sshfs with the default scheduling priority"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_ionice --description "Run sshfs with the default I/O scheduling class"
+complete --command sftpman --condition "__fish_seen_subcommand_from update" --long-option unset_bandwidth_limit --description "Remove the download and upload limits"
+
+# import_rclone subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from import_rclone import-rclone" --long-option config --description "Path to rclone's configuration file" --require-parameter --force-files
+
+# import_bookmarks subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from import_bookmarks import-bookmarks" --long-option file --description "Path to a bookmarks file" --require-parameter --force-files
+
+# accept_hostkey subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from accept_hostkey accept-hostkey" --long-option yes --description "Accept the host keys without asking"
+
+# keygen subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from keygen" --long-option path --description "Where to store the private key" --require-parameter --force-files
+complete --command sftpman --condition "__fish_seen_subcommand_from keygen" --long-option deploy --description "Add the public key to the remote user's authorized keys"
+
+# prune subcommand completions
+complete --command sftpman --condition "__fish_seen_subcommand_from prune" --long-option unused-for --description "Prune systems which haven't been mounted for this long (e.g. 180d)" --require-parameter --no-files
+complete --command sftpman --condition "__fish_seen_subcommand_from prune" --long-option unresolvable --description "Prune systems whose host no longer resolves"
+complete --command sftpman --condition "__fish_seen_subcommand_from prune" --long-option dry-run --description "Only list the systems which would be pruned"
+complete --command sftpman --condition "__fish_seen_subcommand_from prune" --long-option yes --description "Remove the systems without asking"
@@ -0,0 +1,68 @@
+/*
+ * C bindings for libsftpman.
+ *
+ * Build the shared library (target/release/liblibsftpman.so) with:
+ *   cargo build --release --lib --no-default-features --features capi
+ *
+ * Conventions:
+ * - definitions are passed around as JSON strings (the same format as the stored definition files)
+ * - functions returning `int` return 0 on success and -1 on failure
+ * - functions returning `char *` return NULL on failure and the result must be freed with sftpman_string_free()
+ * - the message for the last failure (on the calling thread) is available via sftpman_last_error()
+ * - panics inside the library are reported as failures, instead of unwinding into the caller
+ */
+
+#ifndef SFTPMAN_H
+#define SFTPMAN_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct SftpmanManager SftpmanManager;
+
+/* Creates a new manager. Returns NULL on failure. Free with sftpman_manager_free(). */
+SftpmanManager *sftpman_manager_new(void);
+
+void sftpman_manager_free(SftpmanManager *manager);
+
+/* Returns all definitions, as a JSON array. */
+char *sftpman_definitions_json(const SftpmanManager *manager);
+
+/* Returns the definition with the given ID, as a JSON object. */
+char *sftpman_definition_json(const SftpmanManager *manager, const char *id);
+
+/* Validates and saves (creates or updates) a definition given as a JSON object.
+ * A mounted definition gets remounted. If that fails, -1 is returned even though the definition was saved. */
+int sftpman_definition_save(const SftpmanManager *manager, const char *json);
+
+/* Removes the definition with the given ID (unmounting it first, if mounted). */
+int sftpman_definition_remove(const SftpmanManager *manager, const char *id);
+
+/* Mounts the definition with the given ID (unless already mounted). */
+int sftpman_mount(const SftpmanManager *manager, const char *id);
+
+/* Unmounts the definition with the given ID (unless already unmounted). */
+int sftpman_umount(const SftpmanManager *manager, const char *id);
+
+/* Returns 1 if the definition with the given ID is mounted, 0 if it's not and -1 on failure. */
+int sftpman_is_mounted(const SftpmanManager *manager, const char *id);
+
+/* Returns the mount state of all definitions, as a JSON array of {"id": "..", "mounted": true} objects. */
+char *sftpman_state_json(const SftpmanManager *manager);
+
+/* Returns what this build and the system it runs on support (platform, fusermount flavor, programs found on PATH, features),
+ * as a JSON object. Useful for hiding options which can't work. */
+char *sftpman_capabilities_json(const SftpmanManager *manager);
+
+/* Returns the message for the last failure on the calling thread, or NULL if the last call succeeded.
+ * The string is owned by the library and stays valid until the next call on the same thread. */
+const char *sftpman_last_error(void);
+
+void sftpman_string_free(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SFTPMAN_H */
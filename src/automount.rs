@@ -0,0 +1,238 @@
+use std::path::Path;
+
+use crate::auth_type::AuthType;
+use crate::errors::SftpManError;
+use crate::model::{FilesystemMountDefinition, SSH_DEFAULT_TIMEOUT};
+use crate::mount_options::escape_mount_option;
+
+/// A pair of systemd units (`.mount` and `.automount`) which make a filesystem get mounted on first access.
+///
+/// systemd only supports automounting via the system manager, so these units are meant to be installed
+/// system-wide (e.g. into `/etc/systemd/system`). The mount is performed by root on behalf of the owner
+/// (see `AutomountOwner`), with the owner's SSH key and `known_hosts` file.
+#[derive(Debug, Clone)]
+pub struct AutomountUnits {
+    /// The name of the mount unit (e.g. `mnt-sshfs-my\x2dfs.mount`), derived from the local mount path.
+    pub mount_unit_name: String,
+
+    pub mount_unit: String,
+
+    /// The name of the automount unit (e.g. `mnt-sshfs-my\x2dfs.automount`).
+    pub automount_unit_name: String,
+
+    pub automount_unit: String,
+}
+
+/// The local user that the mounted files should belong to.
+#[derive(Debug, Clone)]
+pub struct AutomountOwner<'a> {
+    pub uid: u32,
+    pub gid: u32,
+
+    /// Path to the user's `known_hosts` file (e.g. `/home/user/.ssh/known_hosts`).
+    pub known_hosts_path: &'a Path,
+}
+
+impl AutomountUnits {
+    pub fn new(
+        definition: &FilesystemMountDefinition,
+        owner: &AutomountOwner,
+    ) -> Result<Self, SftpManError> {
+        match definition.auth_type {
+            AuthType::Password | AuthType::KeyboardInteractive => {
+                return Err(SftpManError::MountCommandBuilding(format!(
+                    "{0} authentication requires interaction, so it cannot be used for automounting",
+                    definition.auth_type.to_static_str()
+                )));
+            }
+            AuthType::AuthenticationAgent => {
+                return Err(SftpManError::MountCommandBuilding(
+                    "mounts performed by systemd have no access to your SSH agent, so authentication-agent cannot be used for automounting".to_owned(),
+                ));
+            }
+            _ => {}
+        }
+
+        if !definition.sub_mounts.is_empty() {
+            return Err(SftpManError::MountCommandBuilding(
+                "a mount unit can only mount a single remote path, so definitions with sub-mounts cannot be automounted".to_owned(),
+            ));
+        }
+
+        if !definition.cmd_before_mount.is_empty() {
+            log::warn!(
+                "{0}: the before-mount command ({1}) does not run when automounting",
+                definition.id,
+                definition.cmd_before_mount
+            );
+        }
+
+        let local_mount_path = definition.local_mount_path();
+        let unit_base_name = escape_path_for_unit_name(&local_mount_path);
+
+        let mut options: Vec<String> = vec![
+            "_netdev".to_owned(),
+            "allow_other".to_owned(),
+            format!("uid={0}", owner.uid),
+            format!("gid={0}", owner.gid),
+            format!("port={0}", definition.port),
+            format!(
+                "UserKnownHostsFile={0}",
+                definition
+                    .known_hosts_file
+                    .clone()
+                    .unwrap_or_else(|| owner.known_hosts_path.to_string_lossy().to_string())
+            ),
+            format!("ConnectTimeout={0}", SSH_DEFAULT_TIMEOUT),
+        ];
+
+        if let Some(command) = definition.effective_proxy_command() {
+            // The options get joined with commas, so any commas within the command need escaping.
+            options.push(escape_mount_option(&format!("ProxyCommand={0}", command)));
+        }
+
+        if !definition.ciphers.is_empty() {
+            options.push(escape_mount_option(&format!(
+                "Ciphers={0}",
+                definition.ciphers.join(",")
+            )));
+        }
+
+        if let Some(binary) = definition.custom_ssh_binary() {
+            options.push(escape_mount_option(&format!("ssh_command={0}", binary)));
+        }
+
+        match definition.auth_type {
+            AuthType::PublicKey => {
+                options.push(format!(
+                    "PreferredAuthentications={0}",
+                    AuthType::PublicKey.to_static_str()
+                ));
+                for key in definition.ssh_keys() {
+                    options.push(format!("IdentityFile={0}", key));
+                }
+                if let Some(path) = &definition.certificate_file {
+                    options.push(format!("CertificateFile={0}", path));
+                }
+            }
+            ref any_other => {
+                options.push(format!(
+                    "PreferredAuthentications={0}",
+                    any_other.to_static_str()
+                ));
+            }
+        }
+
+        if definition.auth_type == AuthType::GSSAPIWithMic {
+            options.push("GSSAPIAuthentication=yes".to_owned());
+
+            if definition.gssapi_delegate_credentials {
+                options.push("GSSAPIDelegateCredentials=yes".to_owned());
+            }
+
+            if definition.gssapi_key_exchange {
+                options.push("GSSAPIKeyExchange=yes".to_owned());
+            }
+        }
+
+        options.extend(definition.mount_options.iter().cloned());
+
+        // trickle needs to wrap ssh (rather than sshfs), like it does when mounting directly.
+        if let Some(wrapper) = definition.bandwidth_limiter() {
+            options.push(format!("ssh_command={0} ssh", wrapper.join(" ")));
+        }
+
+        // systemd applies these to the sshfs process it starts, like `nice` and `ionice` do when mounting directly.
+        let mut priority_settings = String::new();
+        if let Some(nice) = definition.nice {
+            priority_settings.push_str(&format!("Nice={0}\n", nice));
+        }
+        if let Some(class) = &definition.ionice_class {
+            priority_settings.push_str(&format!("IOSchedulingClass={0}\n", class));
+
+            if let Some(level) = definition.ionice_level {
+                priority_settings.push_str(&format!("IOSchedulingPriority={0}\n", level));
+            }
+        }
+
+        let mount_unit = format!(
+            "[Unit]
+Description=sftpman: {id}
+Wants=network-online.target
+After=network-online.target
+
+[Mount]
+What={what}
+Where={where_}
+Type=fuse.sshfs
+Options={options}
+TimeoutSec=30
+{priority_settings}",
+            id = definition.id,
+            // `%` starts a specifier in unit files (as found in the zone ID of IPv6 link-local addresses, e.g. `fe80::1%eth0`).
+            what = definition
+                .remote_target(&definition.remote_path)
+                .replace('%', "%%"),
+            where_ = local_mount_path,
+            options = options.join(","),
+            priority_settings = priority_settings,
+        );
+
+        // The idle timeout mirrors `idle_unmount_minutes` (0 disables unmounting when idle).
+        let idle_timeout_seconds = definition.idle_unmount_minutes.unwrap_or(0) as u64 * 60;
+
+        let automount_unit = format!(
+            "[Unit]
+Description=sftpman: {id} (automount)
+
+[Automount]
+Where={where_}
+TimeoutIdleSec={idle_timeout_seconds}
+
+[Install]
+WantedBy=remote-fs.target
+",
+            id = definition.id,
+            where_ = local_mount_path,
+            idle_timeout_seconds = idle_timeout_seconds,
+        );
+
+        Ok(Self {
+            mount_unit_name: format!("{0}.mount", unit_base_name),
+            mount_unit,
+            automount_unit_name: format!("{0}.automount", unit_base_name),
+            automount_unit,
+        })
+    }
+}
+
+/// Escapes a path for use in a unit name, like `systemd-escape --path` does (e.g. `/mnt/sshfs/my-fs` -> `mnt-sshfs-my\x2dfs`).
+fn escape_path_for_unit_name(path: &Path) -> String {
+    // Like systemd, the raw bytes get escaped, so that paths which are not valid UTF-8 work too.
+    let bytes = path.as_os_str().as_encoded_bytes();
+
+    let Some(start) = bytes.iter().position(|byte| *byte != b'/') else {
+        return "-".to_owned();
+    };
+    let end = bytes
+        .iter()
+        .rposition(|byte| *byte != b'/')
+        .expect("A byte other than / should have been found")
+        + 1;
+
+    let mut escaped = String::new();
+
+    for (idx, byte) in bytes[start..end].iter().copied().enumerate() {
+        match byte {
+            b'/' => escaped.push('-'),
+            // A leading dot is escaped, so that the name doesn't look like a hidden file.
+            b'.' if idx == 0 => escaped.push_str("\\x2e"),
+            b if b.is_ascii_alphanumeric() || b == b'_' || b == b'.' || b == b':' => {
+                escaped.push(b as char)
+            }
+            b => escaped.push_str(&format!("\\x{0:02x}", b)),
+        }
+    }
+
+    escaped
+}
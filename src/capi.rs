@@ -0,0 +1,390 @@
+//! C bindings (enabled via the `capi` feature), for embedding the library into non-Rust frontends.
+//!
+//! See `include/sftpman.h` for the C declarations. Build a shared library (`target/release/liblibsftpman.so`) with:
+//! `cargo build --release --lib --no-default-features --features capi`
+//!
+//! Conventions:
+//!
+//! - definitions are passed around as JSON strings (the same format as the stored definition files)
+//! - functions returning `int` return `0` on success and `-1` on failure
+//! - functions returning strings return `NULL` on failure and the caller must free the result with `sftpman_string_free()`
+//! - the message for the last failure (on the calling thread) is available via `sftpman_last_error()`
+//! - panics never cross into C: they're reported as failures (see `guarded()`)
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use serde::Serialize;
+use validator::Validate;
+
+use crate::manager::Manager;
+use crate::model::FilesystemMountDefinition;
+
+/// An opaque handle to a `Manager`.
+pub struct SftpmanManager {
+    manager: Manager,
+}
+
+#[derive(Serialize)]
+struct StateEntry {
+    id: String,
+    mounted: bool,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior NUL bytes cannot be represented in a C string.
+    let message = CString::new(message.replace('\0', "")).expect("NUL bytes were removed");
+
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = None);
+}
+
+/// Runs the given function, turning a panic into an error, as unwinding into C is undefined behavior.
+fn guarded<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|payload| Err(format!("panicked: {0}", panic_message(&*payload))))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown cause"
+    }
+}
+
+/// Runs the given function (see `guarded()`) and converts its result into the `0`/`-1` convention, recording the error (if any).
+fn status(f: impl FnOnce() -> Result<(), String>) -> c_int {
+    match guarded(f) {
+        Ok(()) => {
+            clear_last_error();
+            0
+        }
+        Err(err) => {
+            set_last_error(err);
+            -1
+        }
+    }
+}
+
+/// Runs the given function (see `guarded()`) and converts its result into a newly-allocated C string (or `NULL`),
+/// recording the error (if any).
+fn string(f: impl FnOnce() -> Result<String, String>) -> *mut c_char {
+    match guarded(f) {
+        Ok(value) => {
+            clear_last_error();
+            CString::new(value)
+                .map(CString::into_raw)
+                .unwrap_or(ptr::null_mut())
+        }
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Borrows a Rust string from a C string.
+///
+/// # Safety
+///
+/// `value` must be `NULL` or a valid NUL-terminated string.
+unsafe fn str_from<'a>(value: *const c_char) -> Result<&'a str, String> {
+    if value.is_null() {
+        return Err("unexpected NULL string argument".to_owned());
+    }
+
+    unsafe { CStr::from_ptr(value) }
+        .to_str()
+        .map_err(|err| format!("invalid UTF-8 in string argument: {0}", err))
+}
+
+/// Borrows the manager behind a handle.
+///
+/// # Safety
+///
+/// `handle` must be `NULL` or a handle returned by `sftpman_manager_new()`, which has not been freed yet.
+unsafe fn manager_from<'a>(handle: *const SftpmanManager) -> Result<&'a Manager, String> {
+    match unsafe { handle.as_ref() } {
+        Some(handle) => Ok(&handle.manager),
+        None => Err("unexpected NULL manager handle".to_owned()),
+    }
+}
+
+/// Creates a new manager. Returns `NULL` on failure.
+/// The result must be freed with `sftpman_manager_free()`.
+#[unsafe(no_mangle)]
+pub extern "C" fn sftpman_manager_new() -> *mut SftpmanManager {
+    match guarded(|| Manager::new().map_err(|err| format!("{0:?}", err))) {
+        Ok(manager) => {
+            clear_last_error();
+            Box::into_raw(Box::new(SftpmanManager { manager }))
+        }
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a manager created by `sftpman_manager_new()`.
+///
+/// # Safety
+///
+/// `handle` must be `NULL` or a handle returned by `sftpman_manager_new()`, which has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_manager_free(handle: *mut SftpmanManager) {
+    if !handle.is_null() {
+        // Dropping the manager may drop things provided by the frontend (e.g. a command runner), which may panic.
+        if let Err(err) = guarded(|| {
+            drop(unsafe { Box::from_raw(handle) });
+            Ok(())
+        }) {
+            set_last_error(err);
+        }
+    }
+}
+
+/// Returns all definitions, as a JSON array.
+///
+/// # Safety
+///
+/// See `manager_from()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_definitions_json(handle: *const SftpmanManager) -> *mut c_char {
+    string(|| {
+        let manager = unsafe { manager_from(handle) }?;
+        let definitions = manager.definitions().map_err(|err| format!("{0:?}", err))?;
+        serde_json::to_string(&definitions).map_err(|err| err.to_string())
+    })
+}
+
+/// Returns the definition with the given ID, as a JSON object.
+///
+/// # Safety
+///
+/// See `manager_from()` and `str_from()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_definition_json(
+    handle: *const SftpmanManager,
+    id: *const c_char,
+) -> *mut c_char {
+    string(|| {
+        let manager = unsafe { manager_from(handle) }?;
+        let id = unsafe { str_from(id) }?;
+        let definition = manager
+            .definition(id)
+            .map_err(|err| format!("{0:?}", err))?;
+        definition.to_json_string().map_err(|err| err.to_string())
+    })
+}
+
+/// Validates and saves (creates or updates) a definition given as a JSON object.
+///
+/// A mounted definition gets remounted. If that fails, `-1` is returned even though the definition was saved.
+///
+/// # Safety
+///
+/// See `manager_from()` and `str_from()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_definition_save(
+    handle: *const SftpmanManager,
+    json: *const c_char,
+) -> c_int {
+    status(|| {
+        let manager = unsafe { manager_from(handle) }?;
+        let json = unsafe { str_from(json) }?;
+
+        let definition =
+            FilesystemMountDefinition::from_json_string(json).map_err(|err| err.to_string())?;
+        definition.validate().map_err(|err| err.to_string())?;
+
+        let outcome = manager
+            .persist(&definition)
+            .map_err(|err| format!("{0:?}", err))?;
+
+        if let Some(err) = outcome.umount_error {
+            return Err(format!(
+                "The definition was saved, but it's still mounted with the old settings, because unmounting failed: {0:?}",
+                err
+            ));
+        }
+
+        if let Some(err) = outcome.remount_error {
+            return Err(format!(
+                "The definition was saved, but it's no longer mounted, because remounting failed: {0:?}",
+                err
+            ));
+        }
+
+        Ok(())
+    })
+}
+
+/// Removes the definition with the given ID (unmounting it first, if mounted).
+///
+/// # Safety
+///
+/// See `manager_from()` and `str_from()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_definition_remove(
+    handle: *const SftpmanManager,
+    id: *const c_char,
+) -> c_int {
+    status(|| {
+        let manager = unsafe { manager_from(handle) }?;
+        let id = unsafe { str_from(id) }?;
+        let definition = manager
+            .definition(id)
+            .map_err(|err| format!("{0:?}", err))?;
+        manager
+            .remove(&definition)
+            .map_err(|err| format!("{0:?}", err))
+    })
+}
+
+/// Mounts the definition with the given ID (unless already mounted).
+///
+/// # Safety
+///
+/// See `manager_from()` and `str_from()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_mount(handle: *const SftpmanManager, id: *const c_char) -> c_int {
+    status(|| {
+        let manager = unsafe { manager_from(handle) }?;
+        let id = unsafe { str_from(id) }?;
+        let definition = manager
+            .definition(id)
+            .map_err(|err| format!("{0:?}", err))?;
+        manager
+            .mount(&definition)
+            .map(|_| ())
+            .map_err(|err| format!("{0:?}", err))
+    })
+}
+
+/// Unmounts the definition with the given ID (unless already unmounted).
+///
+/// # Safety
+///
+/// See `manager_from()` and `str_from()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_umount(handle: *const SftpmanManager, id: *const c_char) -> c_int {
+    status(|| {
+        let manager = unsafe { manager_from(handle) }?;
+        let id = unsafe { str_from(id) }?;
+        let definition = manager
+            .definition(id)
+            .map_err(|err| format!("{0:?}", err))?;
+        manager
+            .umount(&definition)
+            .map(|_| ())
+            .map_err(|err| format!("{0:?}", err))
+    })
+}
+
+/// Tells if the definition with the given ID is mounted.
+/// Returns `1` if mounted, `0` if not mounted and `-1` on failure.
+///
+/// # Safety
+///
+/// See `manager_from()` and `str_from()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_is_mounted(
+    handle: *const SftpmanManager,
+    id: *const c_char,
+) -> c_int {
+    let result = guarded(|| {
+        let manager = unsafe { manager_from(handle) }?;
+        let id = unsafe { str_from(id) }?;
+        let definition = manager
+            .definition(id)
+            .map_err(|err| format!("{0:?}", err))?;
+        manager
+            .is_definition_mounted(&definition)
+            .map_err(|err| format!("{0:?}", err))
+    });
+
+    match result {
+        Ok(mounted) => {
+            clear_last_error();
+            c_int::from(mounted)
+        }
+        Err(err) => {
+            set_last_error(err);
+            -1
+        }
+    }
+}
+
+/// Returns the mount state of all definitions, as a JSON array of `{"id": "..", "mounted": true}` objects.
+///
+/// # Safety
+///
+/// See `manager_from()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_state_json(handle: *const SftpmanManager) -> *mut c_char {
+    string(|| {
+        let manager = unsafe { manager_from(handle) }?;
+        let states = manager.full_state().map_err(|err| format!("{0:?}", err))?;
+
+        let entries: Vec<StateEntry> = states
+            .into_iter()
+            .map(|state| StateEntry {
+                id: state.definition.id,
+                mounted: state.mounted,
+            })
+            .collect();
+
+        serde_json::to_string(&entries).map_err(|err| err.to_string())
+    })
+}
+
+/// Returns what this build and the system it runs on support (see `Manager::capabilities()`), as a JSON object.
+///
+/// # Safety
+///
+/// See `manager_from()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_capabilities_json(handle: *const SftpmanManager) -> *mut c_char {
+    string(|| {
+        let manager = unsafe { manager_from(handle) }?;
+
+        serde_json::to_string(&manager.capabilities()).map_err(|err| err.to_string())
+    })
+}
+
+/// Returns the message for the last failure on the calling thread, or `NULL` if the last call succeeded.
+///
+/// The returned string is owned by the library and stays valid until the next call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn sftpman_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| match last_error.borrow().as_ref() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Frees a string returned by the library.
+///
+/// # Safety
+///
+/// `value` must be `NULL` or a string returned by the library, which has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sftpman_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
@@ -0,0 +1,129 @@
+use std::io::{BufRead, IsTerminal, Write};
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::errors::SftpManError;
+use crate::manager::Manager;
+use crate::model::HostKey;
+
+use super::exit;
+use super::output::Output;
+
+const ARG_ID: &str = "id";
+const ARG_YES: &str = "yes";
+
+pub fn build() -> Command {
+    Command::new("accept_hostkey")
+        .alias("accept-hostkey")
+        .about("Retrieves the host keys of the specified system's SSH server (via ssh-keyscan), shows their fingerprints and, once confirmed, adds them to the known_hosts file in use. Mounting fails with `Host key verification failed` until the host key is known")
+        .arg(Arg::new(ARG_ID).required(true))
+        .arg(
+            Arg::new(ARG_YES)
+                .long(ARG_YES)
+                .action(ArgAction::SetTrue)
+                .help("Accept the host keys without asking. Only use this if the fingerprints have been verified some other way"),
+        )
+}
+
+/// Retrieves a definition's host keys and adds them to the known_hosts file, after confirmation.
+/// Returns exit::Status::Success if the host keys got added (or were already known).
+/// Returns exit::Status::DefinitionNotFound if the filesystem was not found.
+/// Returns exit::Status::Failure if the host keys could not be retrieved or added, or were not accepted.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let id = matches.get_one::<String>(ARG_ID).expect("required");
+    let output = Output::from_matches(matches);
+
+    let definition = match manager.resolve_definition(id) {
+        Ok(definition) => definition,
+        Err(err) => {
+            log::error!("Failed to find or load definition: {0}: {1}", id, err);
+            return exit::Status::DefinitionNotFound;
+        }
+    };
+
+    let keys = match manager.scan_host_keys(&definition) {
+        Ok(keys) => keys,
+        Err(SftpManError::NoHostKeys(_, details)) => {
+            log::error!(
+                "{0}: no host keys could be retrieved from {1}:{2}: {3}",
+                id,
+                definition.host,
+                definition.port,
+                details
+            );
+            return exit::Status::Failure;
+        }
+        Err(err) => {
+            log::error!("{0}: failed to retrieve the host keys: {1:?}", id, err);
+            return exit::Status::Failure;
+        }
+    };
+
+    if !output.is_json() {
+        println!(
+            "Host keys of {0} (port {1}):",
+            definition.host, definition.port
+        );
+        for key in &keys {
+            println!(
+                "  {0} ({1}){2}",
+                key.fingerprint,
+                key.key_type,
+                if key.known { ", already known" } else { "" }
+            );
+        }
+    }
+
+    if keys.iter().all(|key| key.known) {
+        log::info!("{0}: all host keys are already known", id);
+        output.print_json(&keys);
+        return exit::Status::Success;
+    }
+
+    if !matches.get_flag(ARG_YES) && !confirm() {
+        log::error!("{0}: the host keys were not accepted", id);
+        return exit::Status::Failure;
+    }
+
+    match manager.accept_host_keys(&definition, &keys) {
+        Ok(path) => {
+            log::info!("{0}: added the host keys to {1}", id, path.display());
+
+            if output.is_json() {
+                let keys: Vec<HostKey> = keys
+                    .into_iter()
+                    .map(|key| HostKey { known: true, ..key })
+                    .collect();
+                output.print_json(&keys);
+            } else {
+                println!("Added the host keys to {0}", path.display());
+            }
+
+            exit::Status::Success
+        }
+        Err(err) => {
+            log::error!("{0}: failed to add the host keys: {1:?}", id, err);
+            exit::Status::Failure
+        }
+    }
+}
+
+/// Asks whether the host keys should be trusted. Without a terminal to ask on, they're not.
+fn confirm() -> bool {
+    if !std::io::stdin().is_terminal() {
+        log::error!(
+            "Not running in a terminal, so there's no way to confirm. Verify the fingerprints and pass --yes to accept them"
+        );
+        return false;
+    }
+
+    print!("Only accept them if the fingerprints match those of the server. Accept? (y/N): ");
+    let _ = std::io::stdout().flush();
+
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::{Arg, ArgMatches, Command};
+use nix::unistd::{getgid, getuid};
+
+use crate::automount::{AutomountOwner, AutomountUnits};
+use crate::manager::Manager;
+
+use super::exit;
+
+const ARG_ID: &str = "id";
+const ARG_OUTPUT_DIR: &str = "output-dir";
+
+pub fn build() -> Command {
+    Command::new("automount_units")
+        .about("Generates systemd .mount and .automount units, which make the specified SFTP system get mounted on first access. The units are meant to be installed system-wide (e.g. into /etc/systemd/system)")
+        .arg(Arg::new(ARG_ID).required(true))
+        .arg(
+            Arg::new(ARG_OUTPUT_DIR)
+                .long(ARG_OUTPUT_DIR)
+                .num_args(1)
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .help("Directory to write the unit files to. If not provided, the units are printed to stdout")
+        )
+}
+
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let id = matches.get_one::<String>(ARG_ID).expect("required");
+
+    let definition = match manager.resolve_definition(id) {
+        Ok(definition) => definition,
+        Err(err) => {
+            log::error!("Failed to find or load definition: {0}: {1}", id, err);
+            return exit::Status::DefinitionNotFound;
+        }
+    };
+
+    let Some(base_dirs) = directories::BaseDirs::new() else {
+        log::error!("The home directory could not be determined");
+        return exit::Status::Failure;
+    };
+
+    let known_hosts_path = base_dirs.home_dir().join(".ssh").join("known_hosts");
+
+    let owner = AutomountOwner {
+        uid: getuid().as_raw(),
+        gid: getgid().as_raw(),
+        known_hosts_path: &known_hosts_path,
+    };
+
+    let units = match AutomountUnits::new(&definition, &owner) {
+        Ok(units) => units,
+        Err(err) => {
+            log::error!("{0}: failed to generate units: {1:?}", id, err);
+            return exit::Status::Failure;
+        }
+    };
+
+    let Some(output_dir) = matches.get_one::<PathBuf>(ARG_OUTPUT_DIR) else {
+        println!("# {0}\n{1}", units.mount_unit_name, units.mount_unit);
+        println!(
+            "# {0}\n{1}",
+            units.automount_unit_name, units.automount_unit
+        );
+        return exit::Status::Success;
+    };
+
+    for (name, contents) in [
+        (&units.mount_unit_name, &units.mount_unit),
+        (&units.automount_unit_name, &units.automount_unit),
+    ] {
+        let path = output_dir.join(name);
+
+        if let Err(err) = fs::write(&path, contents) {
+            log::error!("Failed to write {0}: {1}", path.display(), err);
+            return exit::Status::Failure;
+        }
+
+        println!("{0}", path.display());
+    }
+
+    exit::Status::Success
+}
@@ -0,0 +1,197 @@
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use clap::{Arg, ArgMatches, Command, value_parser};
+
+use crate::manager::Manager;
+use crate::model::FilesystemMountDefinition;
+
+use super::exit;
+
+const ARG_ID: &str = "id";
+const ARG_SIZE: &str = "size";
+const ARG_LATENCY_SAMPLES: &str = "latency-samples";
+
+/// Data is written and read in chunks of this size.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+pub fn build() -> Command {
+    Command::new("bench")
+        .about("Measures the throughput and latency of the specified SFTP system, by writing and reading a temporary file through its mount (mounting it first, if necessary)")
+        .arg(Arg::new(ARG_ID).required(true))
+        .arg(
+            Arg::new(ARG_SIZE)
+                .long(ARG_SIZE)
+                .num_args(1)
+                .value_parser(value_parser!(u64).range(1..))
+                .value_name("MIB")
+                .default_value("64")
+                .help("Size (in MiB) of the temporary file to write and read")
+        )
+        .arg(
+            Arg::new(ARG_LATENCY_SAMPLES)
+                .long(ARG_LATENCY_SAMPLES)
+                .num_args(1)
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("20")
+                .help("Number of small-file round trips (create, sync, delete) to measure latency with")
+        )
+}
+
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let id = matches.get_one::<String>(ARG_ID).expect("required");
+    let size_mib = *matches.get_one::<u64>(ARG_SIZE).expect("defaulted");
+    let latency_samples = *matches
+        .get_one::<u32>(ARG_LATENCY_SAMPLES)
+        .expect("defaulted");
+
+    let definition = match manager.resolve_definition(id) {
+        Ok(definition) => definition,
+        Err(err) => {
+            log::error!("Failed to find or load definition: {0}: {1}", id, err);
+            return exit::Status::DefinitionNotFound;
+        }
+    };
+
+    let was_mounted = match manager.is_definition_mounted(&definition) {
+        Ok(mounted) => mounted,
+        Err(err) => {
+            log::error!("{0}: failed to determine the mount state: {1:?}", id, err);
+            return exit::Status::Failure;
+        }
+    };
+
+    if !was_mounted {
+        log::info!("{0}: mounting for the benchmark..", id);
+
+        if let Err(err) = manager.mount(&definition) {
+            log::error!("Failure mounting {0}: {1:?}", id, err);
+            return exit::Status::Failure;
+        }
+    }
+
+    let bench_dir = definition.local_mount_path().into_path_buf();
+
+    let exit_status = match bench(&bench_dir, size_mib, latency_samples) {
+        Ok(()) => {
+            print_cipher_note(&definition);
+            exit::Status::Success
+        }
+        Err(err) => {
+            log::error!("{0}: benchmark failed: {1}", id, err);
+            exit::Status::Failure
+        }
+    };
+
+    // Leave things the way we've found them.
+    if !was_mounted && let Err(err) = manager.umount(&definition) {
+        log::error!("Failure unmounting {0}: {1:?}", id, err);
+        return exit::Status::Failure;
+    }
+
+    exit_status
+}
+
+/// Points out the cipher in use, as it's often what limits the throughput on fast networks.
+fn print_cipher_note(definition: &FilesystemMountDefinition) {
+    if definition.ciphers.is_empty() {
+        println!(
+            "Cipher: ssh's default. On fast networks, another one may be 2-3x faster. Compare by running the benchmark again after e.g. `sftpman update --id {0} --cipher aes128-gcm@openssh.com`",
+            definition.id
+        );
+    } else {
+        println!(
+            "Ciphers: {0} (change via `sftpman update --id {1} --cipher ..` and compare)",
+            definition.ciphers.join(", "),
+            definition.id
+        );
+    }
+}
+
+fn bench(dir: &Path, size_mib: u64, latency_samples: u32) -> std::io::Result<()> {
+    let file_path = dir.join(format!(".sftpman-bench-{0}", rand::random::<u32>()));
+
+    let result = bench_throughput(&file_path, size_mib);
+
+    if let Err(err) = fs::remove_file(&file_path) {
+        log::warn!(
+            "Failed to remove temporary file {0}: {1}",
+            file_path.display(),
+            err
+        );
+    }
+
+    let (write_duration, read_duration) = result?;
+
+    let latencies = bench_latency(dir, latency_samples)?;
+
+    println!(
+        "Write: {0} MiB in {1:.2?} ({2:.2} MiB/s)",
+        size_mib,
+        write_duration,
+        size_mib as f64 / write_duration.as_secs_f64()
+    );
+    println!(
+        "Read: {0} MiB in {1:.2?} ({2:.2} MiB/s)",
+        size_mib,
+        read_duration,
+        size_mib as f64 / read_duration.as_secs_f64()
+    );
+
+    let total: Duration = latencies.iter().sum();
+    println!(
+        "Latency (create/sync/delete, {0} samples): min {1:.2?}, avg {2:.2?}, max {3:.2?}",
+        latencies.len(),
+        latencies.iter().min().expect("at least one sample"),
+        total / latencies.len() as u32,
+        latencies.iter().max().expect("at least one sample"),
+    );
+
+    Ok(())
+}
+
+/// Writes and then reads back a file of the given size, returning how long each took.
+fn bench_throughput(file_path: &Path, size_mib: u64) -> std::io::Result<(Duration, Duration)> {
+    // Random data, so that compression (e.g. `ssh -C`) doesn't skew the results.
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    rand::fill(&mut chunk[..]);
+
+    let started_at = Instant::now();
+    let mut file = File::create(file_path)?;
+    for _ in 0..size_mib {
+        file.write_all(&chunk)?;
+    }
+    file.sync_all()?;
+    drop(file);
+    let write_duration = started_at.elapsed();
+
+    // sshfs doesn't keep file contents cached across opens (unless `-o kernel_cache` is used),
+    // so re-opening the file makes the data actually travel over the network again.
+    let started_at = Instant::now();
+    let mut file = File::open(file_path)?;
+    while file.read(&mut chunk)? > 0 {}
+    let read_duration = started_at.elapsed();
+
+    Ok((write_duration, read_duration))
+}
+
+/// Measures how long small-file round trips take.
+fn bench_latency(dir: &Path, samples: u32) -> std::io::Result<Vec<Duration>> {
+    let mut latencies: Vec<Duration> = Vec::new();
+
+    for _ in 0..samples {
+        let file_path = dir.join(format!(".sftpman-bench-{0}", rand::random::<u32>()));
+
+        let started_at = Instant::now();
+        let mut file = File::create(&file_path)?;
+        file.write_all(b"x")?;
+        file.sync_all()?;
+        drop(file);
+        fs::remove_file(&file_path)?;
+        latencies.push(started_at.elapsed());
+    }
+
+    Ok(latencies)
+}
@@ -0,0 +1,112 @@
+use std::io::{BufRead, Write};
+
+use clap::{Arg, ArgMatches, Command};
+
+use crate::manager::Manager;
+use crate::remote_browser::{RemoteBrowser, join_remote_path, parent_remote_path};
+
+use super::create_update::status_for_persist_outcome;
+use super::exit;
+
+const ARG_ID: &str = "id";
+
+pub fn build() -> Command {
+    Command::new("browse")
+        .about("Interactively browses the remote directories of the specified SFTP system (without mounting it) and saves the selected one as its remote path")
+        .arg(Arg::new(ARG_ID).required(true))
+}
+
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let id = matches.get_one::<String>(ARG_ID).expect("required");
+
+    let mut definition = match manager.resolve_definition(id) {
+        Ok(definition) => definition,
+        Err(err) => {
+            log::error!("Failed to find or load definition: {0}: {1}", id, err);
+            return exit::Status::DefinitionNotFound;
+        }
+    };
+
+    let mut browser = match RemoteBrowser::open(&definition) {
+        Ok(browser) => browser,
+        Err(err) => {
+            log::error!("{0}: failed to connect: {1:?}", id, err);
+            return exit::Status::Failure;
+        }
+    };
+
+    // Start at the currently configured remote path, staying in the home directory if it cannot be entered.
+    if let Err(err) = browser.change_directory(&definition.remote_path) {
+        log::warn!(
+            "{0}: starting at the home directory instead: {1:?}",
+            id,
+            err
+        );
+    }
+
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock().lines();
+
+    loop {
+        let current_path = browser.current_directory().to_owned();
+
+        let directories = match browser.list_directories() {
+            Ok(directories) => directories,
+            Err(err) => {
+                log::error!("{0}: failed to list {1}: {2:?}", id, current_path, err);
+                return exit::Status::Failure;
+            }
+        };
+
+        println!();
+        println!("{0}", current_path);
+        for (idx, name) in directories.iter().enumerate() {
+            println!("  {0:>3}) {1}/", idx + 1, name);
+        }
+        println!();
+        print!(
+            "Enter a number to open a directory, `..` to go up, `s` to select the current directory or `q` to quit: "
+        );
+        let _ = std::io::stdout().flush();
+
+        let Some(Ok(line)) = input.next() else {
+            return exit::Status::Failure;
+        };
+
+        let target_path = match line.trim() {
+            "q" => return exit::Status::Success,
+            "s" => break,
+            ".." => parent_remote_path(&current_path),
+            choice => match choice.parse::<usize>() {
+                Ok(number) if number >= 1 && number <= directories.len() => {
+                    join_remote_path(&current_path, &directories[number - 1])
+                }
+                _ => {
+                    println!("Invalid choice: {0}", choice);
+                    continue;
+                }
+            },
+        };
+
+        if let Err(err) = browser.change_directory(&target_path) {
+            log::error!("{0}: {1:?}", id, err);
+        }
+    }
+
+    definition.remote_path = browser.current_directory().to_owned();
+
+    let outcome = match manager.persist(&definition) {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            log::error!("{0}: failed to save the definition: {1:?}", id, err);
+            return exit::Status::Failure;
+        }
+    };
+
+    println!(
+        "Saved remote path {0} for {1}",
+        definition.remote_path, definition.id
+    );
+
+    status_for_persist_outcome(&definition.id, &outcome)
+}
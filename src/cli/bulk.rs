@@ -0,0 +1,83 @@
+use clap::{Arg, ArgAction};
+
+use crate::model::{BulkSummary, FilesystemMountDefinition, MountOutcome, UmountOutcome};
+
+use super::output::OperationResult;
+
+pub const ARG_FAIL_FAST: &str = "fail-fast";
+
+/// The argument for commands working on multiple systems one after another (e.g. `mount_all`),
+/// which makes them stop at the first failure instead of carrying on with the rest.
+pub fn fail_fast_arg() -> Arg {
+    Arg::new(ARG_FAIL_FAST)
+        .long(ARG_FAIL_FAST)
+        .action(ArgAction::SetTrue)
+        .help("Stop at the first failure, leaving the remaining systems alone (useful when they depend on one another). By default, the remaining systems are still worked on")
+}
+
+/// Records the systems which were left alone because working on an earlier one failed (see `--fail-fast`).
+pub fn skip_remaining(
+    definitions: &[&FilesystemMountDefinition],
+    failed_id: &str,
+    results: &mut Vec<OperationResult>,
+) {
+    for definition in definitions {
+        let reason = format!("{0} failed before it (--{1})", failed_id, ARG_FAIL_FAST);
+
+        log::warn!("{0}: skipped: {1}", definition.id, reason);
+        results.push(OperationResult::skipped(&definition.id, &reason));
+    }
+}
+
+/// Summarizes the results of working on multiple systems, like `Manager::mount_many()` does.
+pub fn summarize(results: &[OperationResult]) -> BulkSummary {
+    let mut summary = BulkSummary::new();
+
+    let unchanged_codes = [
+        MountOutcome::AlreadyMounted.code(),
+        UmountOutcome::AlreadyUnmounted.code(),
+    ];
+
+    for result in results {
+        if !result.success {
+            summary.push_failed(&result.id, &result.error.as_deref().unwrap_or_default());
+            continue;
+        }
+
+        match result.outcome.as_deref() {
+            Some("skipped") => {
+                summary.push_skipped(&result.id, &result.details.as_deref().unwrap_or_default())
+            }
+            Some(code) if unchanged_codes.contains(&code) => {
+                summary.unchanged.push(result.id.clone())
+            }
+            _ => summary.succeeded.push(result.id.clone()),
+        }
+    }
+
+    summary
+}
+
+/// Prints what an operation on multiple systems did (e.g. `2 mounted, 1 failed, 0 skipped, 3 already mounted`),
+/// followed by why each system failed or got skipped.
+///
+/// `done` and `unchanged` describe the outcomes of the operation (e.g. `mounted` and `already mounted`).
+pub fn print_summary(summary: &BulkSummary, done: &str, unchanged: &str) {
+    println!(
+        "Summary: {0} {1}, {2} failed, {3} skipped, {4} {5}",
+        summary.succeeded.len(),
+        done,
+        summary.failed.len(),
+        summary.skipped.len(),
+        summary.unchanged.len(),
+        unchanged
+    );
+
+    for entry in &summary.failed {
+        println!("  failed: {0} ({1})", entry.id, entry.reason);
+    }
+
+    for entry in &summary.skipped {
+        println!("  skipped: {0} ({1})", entry.id, entry.reason);
+    }
+}
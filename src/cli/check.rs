@@ -0,0 +1,207 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::manager::Manager;
+use crate::model::{MountState, ProbeOutcome};
+use crate::utils::id_resolution::resolve_id;
+
+use super::exit;
+
+const ARG_ID: &str = "id";
+const ARG_FORMAT: &str = "format";
+
+const FORMAT_NAGIOS: &str = "nagios";
+
+pub fn build() -> Command {
+    Command::new("check")
+        .about("Checks that the specified SFTP systems (or, by default, all mounted ones and those meant to be mounted automatically) are mounted and working, for monitoring systems. Example: sftpman check --format nagios my-fs")
+        .arg(Arg::new(ARG_ID).num_args(1..))
+        .arg(
+            Arg::new(ARG_FORMAT)
+                .long(ARG_FORMAT)
+                .num_args(1)
+                .value_parser([FORMAT_NAGIOS])
+                .default_value(FORMAT_NAGIOS)
+                .help("Output format. With nagios, the output and exit code follow the Nagios/Icinga plugin conventions (0 = OK, 1 = WARNING, 2 = CRITICAL, 3 = UNKNOWN)"),
+        )
+}
+
+/// The state of a check, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CheckState {
+    Ok,
+    Warning,
+    Unknown,
+    Critical,
+}
+
+impl CheckState {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::Warning => "WARNING",
+            Self::Unknown => "UNKNOWN",
+            Self::Critical => "CRITICAL",
+        }
+    }
+
+    fn exit_status(&self) -> exit::Status {
+        match self {
+            Self::Ok => exit::Status::Success,
+            Self::Warning => exit::Status::MonitoringWarning,
+            Self::Unknown => exit::Status::MonitoringUnknown,
+            Self::Critical => exit::Status::MonitoringCritical,
+        }
+    }
+}
+
+/// The outcome of checking a single system.
+struct SystemCheck {
+    id: String,
+    state: CheckState,
+    description: String,
+}
+
+/// Checks the given (or all relevant) filesystems and reports in the format of a monitoring plugin.
+///
+/// Filesystems which are mounted, but don't respond (see `Manager::probe()`) are CRITICAL.
+/// Filesystems which are not mounted are CRITICAL if they were asked for explicitly,
+/// and WARNING if they were only checked for being meant to be mounted automatically (see `FilesystemMountDefinition::auto_mount`),
+/// unless their automatic management is paused (see `Manager::pause()`).
+/// Unknown filesystems are UNKNOWN.
+///
+/// Returns the exit status corresponding to the worst state found.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let mut states = match manager.full_state() {
+        Ok(states) => states,
+        Err(err) => {
+            println!(
+                "SFTPMAN UNKNOWN - failed to determine the mount state: {0}",
+                err
+            );
+            return CheckState::Unknown.exit_status();
+        }
+    };
+
+    let mut checks: Vec<SystemCheck> = Vec::new();
+
+    let explicit = match matches.get_many::<String>(ARG_ID) {
+        Some(queries) => {
+            let all_ids: Vec<&str> = states
+                .iter()
+                .map(|state| state.definition.id.as_str())
+                .collect();
+
+            let mut ids: Vec<String> = Vec::new();
+
+            for query in queries {
+                match resolve_id(query, &all_ids) {
+                    Ok(id) => ids.push(id.to_owned()),
+                    Err(err) => checks.push(SystemCheck {
+                        id: query.to_string(),
+                        state: CheckState::Unknown,
+                        description: err.to_string(),
+                    }),
+                }
+            }
+
+            states.retain(|state| ids.contains(&state.definition.id));
+
+            true
+        }
+        None => {
+            // Paused systems are expected to be down (e.g. for maintenance), so they're not checked unless mounted.
+            let paused_ids = manager.paused_ids().unwrap_or_default();
+
+            states.retain(|state| {
+                state.mounted
+                    || (state.definition.auto_mount
+                        && state.definition.enabled
+                        && !paused_ids.contains(&state.definition.id))
+            });
+
+            false
+        }
+    };
+
+    checks.extend(
+        states
+            .iter()
+            .map(|state| check_system(manager, state, explicit)),
+    );
+
+    let state = checks
+        .iter()
+        .map(|check| check.state)
+        .max()
+        .unwrap_or(CheckState::Ok);
+
+    print_nagios(state, &checks);
+
+    state.exit_status()
+}
+
+fn check_system(manager: &Manager, state: &MountState, explicit: bool) -> SystemCheck {
+    let id = state.definition.id.clone();
+
+    if !state.mounted {
+        return SystemCheck {
+            id,
+            state: if explicit {
+                CheckState::Critical
+            } else {
+                CheckState::Warning
+            },
+            description: "not mounted".to_owned(),
+        };
+    }
+
+    let outcome = match manager.probe(&state.definition, false) {
+        Ok(outcome) => outcome,
+        Err(err) => ProbeOutcome::Failed(err.to_string()),
+    };
+
+    let description = match outcome.details() {
+        Some(details) => format!("{0} ({1})", outcome, details),
+        None => outcome.to_string(),
+    };
+
+    SystemCheck {
+        id,
+        state: if outcome.is_failure() {
+            CheckState::Critical
+        } else {
+            CheckState::Ok
+        },
+        description,
+    }
+}
+
+/// Prints a summary line (with performance data), followed by a line per system.
+fn print_nagios(state: CheckState, checks: &[SystemCheck]) {
+    let problems: Vec<String> = checks
+        .iter()
+        .filter(|check| check.state != CheckState::Ok)
+        .map(|check| format!("{0}: {1}", check.id, check.description))
+        .collect();
+
+    let summary = if !problems.is_empty() {
+        problems.join(", ")
+    } else if checks.is_empty() {
+        "nothing to check".to_owned()
+    } else {
+        format!("{0} mount(s) healthy", checks.len())
+    };
+
+    println!(
+        "SFTPMAN {0} - {1} | checked={2} healthy={3} problems={4}",
+        state.label(),
+        summary,
+        checks.len(),
+        checks.len() - problems.len(),
+        problems.len()
+    );
+
+    for check in checks {
+        println!("{0}: {1}", check.id, check.description);
+    }
+}
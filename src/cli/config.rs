@@ -0,0 +1,112 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::errors::SftpManError;
+use crate::manager::Manager;
+
+use super::exit;
+use super::output::Output;
+
+const ARG_KEY: &str = "key";
+const ARG_VALUE: &str = "value";
+
+pub fn build() -> Command {
+    Command::new("config")
+        .about("Shows or changes global configuration settings (stored in config.json)")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(Command::new("list").about("Lists all settings and their current values"))
+        .subcommand(
+            Command::new("get")
+                .about("Shows the current value of a setting")
+                .arg(
+                    Arg::new(ARG_KEY)
+                        .required(true)
+                        .help("The name of the setting. Example: useSystemdScope"),
+                ),
+        )
+        .subcommand(
+            Command::new("set")
+                .about("Changes the value of a setting")
+                .arg(
+                    Arg::new(ARG_KEY)
+                        .required(true)
+                        .help("The name of the setting. Example: useSystemdScope"),
+                )
+                .arg(
+                    Arg::new(ARG_VALUE)
+                        .required(true)
+                        .help("The new value for the setting. Example: true"),
+                ),
+        )
+}
+
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let output = Output::from_matches(matches);
+
+    match matches.subcommand() {
+        Some(("list", _sub_matches)) => {
+            let settings = manager.config().settings();
+
+            if output.is_json() {
+                output.print_json(&settings.into_iter().collect::<serde_json::Map<_, _>>());
+                return exit::Status::Success;
+            }
+
+            for (key, value) in settings {
+                println!("{0} = {1}", key, value);
+            }
+
+            exit::Status::Success
+        }
+
+        Some(("get", sub_matches)) => {
+            let key = sub_matches.get_one::<String>(ARG_KEY).expect("required");
+
+            match manager.config().get(key) {
+                Ok(value) => {
+                    // Values are printed as JSON either way (e.g. `true`, `500`, `"text"`).
+                    println!("{0}", value);
+                    exit::Status::Success
+                }
+                Err(err) => status_for_config_error(err),
+            }
+        }
+
+        Some(("set", sub_matches)) => {
+            let key = sub_matches.get_one::<String>(ARG_KEY).expect("required");
+            let value = sub_matches.get_one::<String>(ARG_VALUE).expect("required");
+
+            let mut config = manager.config().clone();
+            if let Err(err) = config.set(key, value) {
+                return status_for_config_error(err);
+            }
+
+            let mut manager = manager.clone();
+            if let Err(err) = manager.persist_config(config) {
+                log::error!("Failed to persist the configuration: {0:?}", err);
+                return exit::Status::Failure;
+            }
+
+            exit::Status::Success
+        }
+
+        _ => unreachable!(),
+    }
+}
+
+fn status_for_config_error(err: SftpManError) -> exit::Status {
+    match err {
+        SftpManError::UnknownConfigSetting(_) => {
+            log::error!("{0}. See `sftpman config list` for the known settings", err);
+        }
+        SftpManError::InvalidConfigValue(_, ref json_err) => {
+            log::error!("{0}: {1}", err, json_err);
+        }
+        _ => {
+            log::error!("{0:?}", err);
+            return exit::Status::Failure;
+        }
+    }
+
+    exit::Status::ValidationFailure
+}
@@ -0,0 +1,102 @@
+use std::path::Path;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::manager::Manager;
+
+use super::exit;
+
+const ARG_SOURCE: &str = "source";
+const ARG_DESTINATION: &str = "destination";
+const ARG_RECURSIVE: &str = "recursive";
+
+pub fn build() -> Command {
+    Command::new("cp")
+        .about("Copies a file between the local machine and the specified SFTP system (via scp), without mounting it. Remote paths are given as ID:PATH, with relative paths being relative to the system's remote path. Example: sftpman cp my-fs:notes.txt ./")
+        .arg(Arg::new(ARG_SOURCE).required(true))
+        .arg(Arg::new(ARG_DESTINATION).required(true))
+        .arg(
+            Arg::new(ARG_RECURSIVE)
+                .short('r')
+                .long(ARG_RECURSIVE)
+                .action(ArgAction::SetTrue)
+                .help("Copy directories recursively"),
+        )
+}
+
+/// One side of a copy operation.
+enum Location<'a> {
+    /// A path on the remote machine of the definition with the given ID (given as `ID:PATH`).
+    Remote(&'a str, &'a str),
+
+    Local(&'a Path),
+}
+
+impl<'a> Location<'a> {
+    /// Parses a location like `scp` does: anything with a colon before the first slash is remote.
+    fn parse(value: &'a str) -> Self {
+        match value.split_once(':') {
+            Some((id, path)) if !id.is_empty() && !id.contains('/') => Self::Remote(id, path),
+            _ => Self::Local(Path::new(value)),
+        }
+    }
+}
+
+/// Copies a file (or directory) between the local machine and the remote machine of a definition.
+/// Returns exit::Status::Success if copying succeeded.
+/// Returns exit::Status::DefinitionNotFound if the filesystem was not found.
+/// Returns exit::Status::Failure if copying failed or neither (or both) of the paths are remote.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let source = matches.get_one::<String>(ARG_SOURCE).expect("required");
+    let destination = matches
+        .get_one::<String>(ARG_DESTINATION)
+        .expect("required");
+    let recursive = matches.get_flag(ARG_RECURSIVE);
+
+    let (id, remote_path, local_path, upload) = match (
+        Location::parse(source),
+        Location::parse(destination),
+    ) {
+        (Location::Remote(id, remote_path), Location::Local(local_path)) => {
+            (id, remote_path, local_path, false)
+        }
+
+        (Location::Local(local_path), Location::Remote(id, remote_path)) => {
+            (id, remote_path, local_path, true)
+        }
+
+        (Location::Remote(_, _), Location::Remote(_, _)) => {
+            log::error!("Copying between two remote systems is not supported");
+            return exit::Status::Failure;
+        }
+
+        (Location::Local(_), Location::Local(_)) => {
+            log::error!(
+                "One of the paths needs to be on a remote system, given as ID:PATH (e.g. my-fs:/storage/notes.txt)"
+            );
+            return exit::Status::Failure;
+        }
+    };
+
+    let definition = match manager.resolve_definition(id) {
+        Ok(definition) => definition,
+        Err(err) => {
+            log::error!("Failed to find or load definition: {0}: {1}", id, err);
+            return exit::Status::DefinitionNotFound;
+        }
+    };
+
+    let result = if upload {
+        manager.upload(&definition, local_path, remote_path, recursive)
+    } else {
+        manager.download(&definition, remote_path, local_path, recursive)
+    };
+
+    match result {
+        Ok(_) => exit::Status::Success,
+        Err(err) => {
+            log::error!("{0}: failed to copy: {1:?}", id, err);
+            exit::Status::Failure
+        }
+    }
+}
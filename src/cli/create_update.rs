@@ -1,27 +1,83 @@
 use std::path::PathBuf;
 
-use clap::{Arg, ArgMatches, Command, value_parser};
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use serde_json::json;
 use validator::Validate;
 use validator::ValidationErrors;
 
 use crate::AuthType;
 use crate::Manager;
 use crate::errors::SftpManError;
-use crate::model::{DEFAULT_MOUNT_PATH_PREFIX, FilesystemMountDefinition};
-use crate::utils::validation::errors_to_string_list;
+use crate::i18n::{self, Language};
+use crate::model::{
+    DEFAULT_MOUNT_PATH_PREFIX, ExtraMountPathMethod, FieldValidationError,
+    FilesystemMountDefinition, IoniceClass, MountRequirement, PersistOutcome, SubMount,
+};
+use crate::mount_options::{check_mount_options, normalize_mount_options};
 
 use super::exit;
+use super::output::Output;
 
 const ARG_ID: &str = "id";
+const ARG_DESCRIPTION: &str = "description";
 const ARG_HOST: &str = "host";
 const ARG_PORT: &str = "port";
 const ARG_USER: &str = "user";
 const ARG_REMOTE_PATH: &str = "remote_path";
 const ARG_MOUNT_OPT: &str = "mount_opt";
 const ARG_MOUNT_PATH: &str = "mount_path";
+const ARG_SUB_MOUNT: &str = "sub_mount";
+const ARG_EXTRA_MOUNT_PATH: &str = "extra_mount_path";
+const ARG_EXTRA_MOUNT_PATH_METHOD: &str = "extra_mount_path_method";
+const ARG_ALLOW_NON_EMPTY_MOUNT_PATH: &str = "allow_non_empty_mount_path";
 const ARG_AUTH_TYPE: &str = "auth_type";
 const ARG_SSH_KEY: &str = "ssh_key";
+const ARG_CERTIFICATE_FILE: &str = "certificate_file";
+const ARG_KNOWN_HOSTS_FILE: &str = "known_hosts_file";
+const ARG_PROXY_COMMAND: &str = "proxy_command";
+const ARG_SOCKS_PROXY: &str = "socks_proxy";
+const ARG_SKIP_HOST_RESOLUTION: &str = "skip_host_resolution";
+const ARG_CIPHER: &str = "cipher";
+const ARG_SSH_BINARY: &str = "ssh_binary";
+const ARG_PASSWORD_COMMAND: &str = "password_command";
+const ARG_PASSWORD_ENV: &str = "password_env";
+const ARG_PASSWORD_FILE: &str = "password_file";
+const ARG_GSSAPI_DELEGATE_CREDENTIALS: &str = "gssapi_delegate_credentials";
+const ARG_GSSAPI_KEY_EXCHANGE: &str = "gssapi_key_exchange";
+const ARG_ENV: &str = "env";
+const ARG_NICE: &str = "nice";
+const ARG_IONICE_CLASS: &str = "ionice_class";
+const ARG_IONICE_LEVEL: &str = "ionice_level";
+const ARG_DOWNLOAD_LIMIT: &str = "download_limit";
+const ARG_UPLOAD_LIMIT: &str = "upload_limit";
 const ARG_CMD_BEFORE_MOUNT: &str = "cmd_before_mount";
+const ARG_DEBUG_SSHFS: &str = "debug_sshfs";
+const ARG_IDLE_UNMOUNT_MINUTES: &str = "idle_unmount_minutes";
+const ARG_AUTO_MOUNT: &str = "auto_mount";
+const ARG_REQUIRES: &str = "requires";
+const ARG_ENABLED: &str = "enabled";
+const ARG_FORCE: &str = "force";
+const ARG_ADD_MOUNT_OPT: &str = "add_mount_opt";
+const ARG_REMOVE_MOUNT_OPT: &str = "remove_mount_opt";
+const ARG_UNSET_DESCRIPTION: &str = "unset_description";
+const ARG_UNSET_MOUNT_OPT: &str = "unset_mount_opt";
+const ARG_UNSET_MOUNT_PATH: &str = "unset_mount_path";
+const ARG_UNSET_SUB_MOUNTS: &str = "unset_sub_mounts";
+const ARG_UNSET_EXTRA_MOUNT_PATHS: &str = "unset_extra_mount_paths";
+const ARG_UNSET_SSH_KEY: &str = "unset_ssh_key";
+const ARG_UNSET_CERTIFICATE_FILE: &str = "unset_certificate_file";
+const ARG_UNSET_KNOWN_HOSTS_FILE: &str = "unset_known_hosts_file";
+const ARG_UNSET_PROXY: &str = "unset_proxy";
+const ARG_UNSET_CIPHERS: &str = "unset_ciphers";
+const ARG_UNSET_SSH_BINARY: &str = "unset_ssh_binary";
+const ARG_UNSET_CMD_BEFORE_MOUNT: &str = "unset_cmd_before_mount";
+const ARG_UNSET_IDLE_UNMOUNT_MINUTES: &str = "unset_idle_unmount_minutes";
+const ARG_UNSET_REQUIRES: &str = "unset_requires";
+const ARG_UNSET_PASSWORD_SOURCE: &str = "unset_password_source";
+const ARG_UNSET_ENV: &str = "unset_env";
+const ARG_UNSET_NICE: &str = "unset_nice";
+const ARG_UNSET_IONICE: &str = "unset_ionice";
+const ARG_UNSET_BANDWIDTH_LIMIT: &str = "unset_bandwidth_limit";
 
 pub fn build_create() -> Command {
     Command::new("create")
@@ -33,6 +89,13 @@ pub fn build_create() -> Command {
                 .required(true)
                 .help("Unique identifier. Example: my-machine")
         )
+        .arg(
+            Arg::new(ARG_DESCRIPTION)
+                .long(ARG_DESCRIPTION)
+                .num_args(1)
+                .required(false)
+                .help("Human-friendly name, shown instead of the ID where there's room for it (e.g. in desktop launchers). Example: \"Company NAS\"")
+        )
         .arg(
             Arg::new(ARG_HOST)
                 .long(ARG_HOST)
@@ -60,7 +123,7 @@ pub fn build_create() -> Command {
             Arg::new(ARG_MOUNT_OPT)
                 .long(ARG_MOUNT_OPT)
                 .num_args(1)
-                .help("Options to pass to sshfs (via -o), separated by comma. Example: follow_symlinks,workaround=rename")
+                .help("Options to pass to sshfs (via -o), separated by comma. Commas within values need to be escaped with a backslash. Example: follow_symlinks,workaround=rename")
         )
         .arg(
             Arg::new(ARG_REMOTE_PATH)
@@ -72,10 +135,46 @@ pub fn build_create() -> Command {
             Arg::new(ARG_MOUNT_PATH)
                 .long(ARG_MOUNT_PATH)
                 .help(format!(
-                    "Path on the current machine where the remote path would be mounted. Example: /home/user/Desktop/http. Default: {0}/my-machine",
+                    "Path on the current machine where the remote path would be mounted. May contain {{id}}, {{host}}, {{user}} and {{port}} placeholders, environment variables and a leading ~. Example: /home/user/Desktop/http. Default: the mountPathTemplate config setting, or {0}/my-machine",
                     DEFAULT_MOUNT_PATH_PREFIX
                 ))
         )
+        .arg(
+            Arg::new(ARG_SUB_MOUNT)
+                .long(ARG_SUB_MOUNT)
+                .num_args(1)
+                .action(ArgAction::Append)
+                .value_parser(SubMount::from_string)
+                .required(false)
+                .help("Remote path to mount into a subdirectory of the mount path (instead of mounting the remote path there), as REMOTE_PATH:DIRECTORY. All of them get mounted and unmounted together. Can be repeated. Example: /var/www:web")
+        )
+        .arg(
+            Arg::new(ARG_EXTRA_MOUNT_PATH)
+                .long(ARG_EXTRA_MOUNT_PATH)
+                .num_args(1)
+                .action(ArgAction::Append)
+                .required(false)
+                .help("Additional local path to expose the filesystem at once mounted (replacing any previously set ones), e.g. for tools with hardcoded paths. It gets removed when unmounting. Can be repeated. Placeholders ({id}, {host}, {user}, {port}) are supported, like for the mount path. Example: ~/projects/website/uploads")
+        )
+        .arg(
+            Arg::new(ARG_EXTRA_MOUNT_PATH_METHOD)
+                .long(ARG_EXTRA_MOUNT_PATH_METHOD)
+                .num_args(1)
+                .value_parser([
+                    ExtraMountPathMethod::Symlink.to_static_str(),
+                    ExtraMountPathMethod::Bind.to_static_str(),
+                ])
+                .required(false)
+                .help("How to expose the filesystem at the extra mount paths: symlink (default), or bind (a bind mount via bindfs, for tools which resolve symlinks). Example: bind")
+        )
+        .arg(
+            Arg::new(ARG_ALLOW_NON_EMPTY_MOUNT_PATH)
+                .long(ARG_ALLOW_NON_EMPTY_MOUNT_PATH)
+                .num_args(1)
+                .value_parser(value_parser!(bool))
+                .required(false)
+                .help("Mount even if the mount path is not empty (hiding its files while mounted), instead of failing. Example: true")
+        )
         .arg(
             Arg::new(ARG_AUTH_TYPE)
                 .long(ARG_AUTH_TYPE)
@@ -87,18 +186,227 @@ pub fn build_create() -> Command {
             Arg::new(ARG_SSH_KEY)
                 .long(ARG_SSH_KEY)
                 .required_if_eq(ARG_AUTH_TYPE, AuthType::PublicKey.to_static_str())
+                .num_args(1)
+                .action(ArgAction::Append)
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .help(format!(
+                    "SSH private key path. Only applies when --auth_type={0}. Can be repeated, for keys to try in order (if the remote machine doesn't accept the first one). Example: /home/user/.ssh/id_ed25519",
+                    AuthType::PublicKey.to_static_str(),
+                ))
+        )
+        .arg(
+            Arg::new(ARG_CERTIFICATE_FILE)
+                .long(ARG_CERTIFICATE_FILE)
+                .required(false)
                 .value_parser(clap::builder::PathBufValueParser::new())
                 .help(format!(
-                    "SSH private key path. Only applies when --auth_type={0}. Example: /home/user/.ssh/id_ed25519",
+                    "OpenSSH certificate issued for the SSH key by an SSH certificate authority (ssh's CertificateFile). Only applies when --auth_type={0}. Example: /home/user/.ssh/id_ed25519-cert.pub",
                     AuthType::PublicKey.to_static_str(),
                 ))
         )
+        .arg(
+            Arg::new(ARG_KNOWN_HOSTS_FILE)
+                .long(ARG_KNOWN_HOSTS_FILE)
+                .required(false)
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .help("Path to a known_hosts file to use instead of the default one (~/.ssh/known_hosts), e.g. for short-lived machines. Example: /home/user/.ssh/known_hosts_vms")
+        )
+        .arg(
+            Arg::new(ARG_PROXY_COMMAND)
+                .long(ARG_PROXY_COMMAND)
+                .required(false)
+                .conflicts_with(ARG_SOCKS_PROXY)
+                .help("Command to connect to the remote machine through (ssh's ProxyCommand), for hosts only reachable via a custom tunnel. %h and %p are replaced with the host and port. Example: ssh -W %h:%p bastion.example.com")
+        )
+        .arg(
+            Arg::new(ARG_SOCKS_PROXY)
+                .long(ARG_SOCKS_PROXY)
+                .required(false)
+                .help("Address (host:port) of a SOCKS5 proxy to connect to the remote machine through. Requires nc (OpenBSD netcat). Example: 127.0.0.1:1080")
+        )
+        .arg(
+            Arg::new(ARG_SKIP_HOST_RESOLUTION)
+                .long(ARG_SKIP_HOST_RESOLUTION)
+                .num_args(1)
+                .value_parser(value_parser!(bool))
+                .required(false)
+                .help("Don't resolve the host before mounting, even if the resolveHostsBeforeMount config setting is enabled (e.g. for hosts only resolvable by a proxy configured elsewhere). Example: true")
+        )
+        .arg(
+            Arg::new(ARG_CIPHER)
+                .long(ARG_CIPHER)
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Cipher for ssh to use (replacing any previously set ones). Can be repeated, in order of preference. On fast networks, this can make a big difference in throughput (compare via `sftpman bench`). Example: aes128-gcm@openssh.com")
+        )
+        .arg(
+            Arg::new(ARG_SSH_BINARY)
+                .long(ARG_SSH_BINARY)
+                .required(false)
+                .help("The ssh program to connect with, instead of the sshBinary config setting (or the ssh found in PATH). It needs to accept OpenSSH's ssh arguments. Example: /opt/openssh/bin/ssh")
+        )
+        .arg(
+            Arg::new(ARG_PASSWORD_COMMAND)
+                .long(ARG_PASSWORD_COMMAND)
+                .required(false)
+                .conflicts_with_all([ARG_PASSWORD_ENV, ARG_PASSWORD_FILE])
+                .help(format!(
+                    "Command whose output (first line) is the password, obtained at mount time instead of being prompted for. Only applies when --auth_type={0}. Example: pass show nas",
+                    AuthType::Password.to_static_str(),
+                ))
+        )
+        .arg(
+            Arg::new(ARG_PASSWORD_ENV)
+                .long(ARG_PASSWORD_ENV)
+                .required(false)
+                .conflicts_with(ARG_PASSWORD_FILE)
+                .help(format!(
+                    "Environment variable holding the password, obtained at mount time instead of being prompted for. Only applies when --auth_type={0}. Example: NAS_PASSWORD",
+                    AuthType::Password.to_static_str(),
+                ))
+        )
+        .arg(
+            Arg::new(ARG_PASSWORD_FILE)
+                .long(ARG_PASSWORD_FILE)
+                .required(false)
+                .help(format!(
+                    "File holding the password, obtained at mount time instead of being prompted for. Only applies when --auth_type={0}. Example: /run/secrets/nas",
+                    AuthType::Password.to_static_str(),
+                ))
+        )
+        .arg(
+            Arg::new(ARG_GSSAPI_DELEGATE_CREDENTIALS)
+                .long(ARG_GSSAPI_DELEGATE_CREDENTIALS)
+                .num_args(1)
+                .value_parser(value_parser!(bool))
+                .required(false)
+                .help(format!(
+                    "Forward Kerberos credentials to the remote machine (GSSAPIDelegateCredentials). Only applies when --auth_type={0}. Example: true",
+                    AuthType::GSSAPIWithMic.to_static_str(),
+                ))
+        )
+        .arg(
+            Arg::new(ARG_GSSAPI_KEY_EXCHANGE)
+                .long(ARG_GSSAPI_KEY_EXCHANGE)
+                .num_args(1)
+                .value_parser(value_parser!(bool))
+                .required(false)
+                .help(format!(
+                    "Use GSSAPI key exchange (GSSAPIKeyExchange), which requires an ssh build supporting it (e.g. on Debian or Fedora). Only applies when --auth_type={0}. Example: true",
+                    AuthType::GSSAPIWithMic.to_static_str(),
+                ))
+        )
+        .arg(
+            Arg::new(ARG_ENV)
+                .long(ARG_ENV)
+                .num_args(1)
+                .action(ArgAction::Append)
+                .value_parser(parse_env_var)
+                .value_name("NAME=VALUE")
+                .help("Environment variable to set for sshfs/ssh (replacing a variable with the same name, if any). Can be repeated. Example: SSH_AUTH_SOCK=/run/user/1000/gnupg/S.gpg-agent.ssh")
+        )
+        .arg(
+            Arg::new(ARG_NICE)
+                .long(ARG_NICE)
+                .num_args(1)
+                .allow_negative_numbers(true)
+                .value_parser(value_parser!(i32).range(-20..=19))
+                .required(false)
+                .help("Scheduling priority (niceness) to run sshfs with, from -20 to 19. Higher values keep background mounts (e.g. for backups) from starving interactive ones. Example: 10")
+        )
+        .arg(
+            Arg::new(ARG_IONICE_CLASS)
+                .long(ARG_IONICE_CLASS)
+                .num_args(1)
+                .value_parser([
+                    IoniceClass::BestEffort.to_static_str(),
+                    IoniceClass::Idle.to_static_str(),
+                ])
+                .required(false)
+                .help("I/O scheduling class to run sshfs with (via ionice). Example: idle")
+        )
+        .arg(
+            Arg::new(ARG_IONICE_LEVEL)
+                .long(ARG_IONICE_LEVEL)
+                .num_args(1)
+                .value_parser(value_parser!(u8).range(0..=7))
+                .required(false)
+                .help(format!(
+                    "Priority within the I/O scheduling class, from 0 (highest) to 7 (lowest). Only applies when --ionice_class={0}. Example: 7",
+                    IoniceClass::BestEffort.to_static_str(),
+                ))
+        )
+        .arg(
+            Arg::new(ARG_DOWNLOAD_LIMIT)
+                .long(ARG_DOWNLOAD_LIMIT)
+                .num_args(1)
+                .value_parser(value_parser!(u32).range(1..))
+                .value_name("KIB_PER_SECOND")
+                .required(false)
+                .help("Maximum download rate (in KiB/s), enforced via trickle. Keeps mounts from saturating slow or metered links. Example: 500")
+        )
+        .arg(
+            Arg::new(ARG_UPLOAD_LIMIT)
+                .long(ARG_UPLOAD_LIMIT)
+                .num_args(1)
+                .value_parser(value_parser!(u32).range(1..))
+                .value_name("KIB_PER_SECOND")
+                .required(false)
+                .help("Maximum upload rate (in KiB/s), enforced via trickle. Example: 100")
+        )
         .arg(
             Arg::new(ARG_CMD_BEFORE_MOUNT)
                 .long(ARG_CMD_BEFORE_MOUNT)
                 .required(false)
                 .help("Custom command to run every time before mounting. Example: /bin/true")
         )
+        .arg(
+            Arg::new(ARG_DEBUG_SSHFS)
+                .long(ARG_DEBUG_SSHFS)
+                .num_args(1)
+                .value_parser(value_parser!(bool))
+                .required(false)
+                .help("Run sshfs with debug output enabled, capturing it to a per-mount log file (e.g. ~/.local/state/sftpman/logs/my-machine.log). Example: true")
+        )
+        .arg(
+            Arg::new(ARG_IDLE_UNMOUNT_MINUTES)
+                .long(ARG_IDLE_UNMOUNT_MINUTES)
+                .num_args(1)
+                .value_parser(value_parser!(u32))
+                .required(false)
+                .help("Unmount automatically after this many minutes without filesystem activity (see the umount_idle command). 0 disables this. Example: 30")
+        )
+        .arg(
+            Arg::new(ARG_AUTO_MOUNT)
+                .long(ARG_AUTO_MOUNT)
+                .num_args(1)
+                .value_parser(value_parser!(bool))
+                .required(false)
+                .help("Mount automatically when a session starts (see the pam_helper command). Example: true")
+        )
+        .arg(
+            Arg::new(ARG_REQUIRES)
+                .long(ARG_REQUIRES)
+                .num_args(1)
+                .action(ArgAction::Append)
+                .value_parser(MountRequirement::from_string)
+                .required(false)
+                .help("Condition which needs to be met for mounting, as interface:NAME (the interface is up) or reachable:HOST:PORT (a TCP connection is possible). mount_all skips systems whose conditions are not met. Can be repeated. Example: interface:wg0")
+        )
+        .arg(
+            Arg::new(ARG_ENABLED)
+                .long(ARG_ENABLED)
+                .num_args(1)
+                .value_parser(value_parser!(bool))
+                .required(false)
+                .help("Allow mounting. Disabled systems are kept, but skipped by mount_all and pam_helper and refused by mount. Example: false")
+        )
+        .arg(
+            Arg::new(ARG_FORCE)
+                .long(ARG_FORCE)
+                .action(ArgAction::SetTrue)
+                .help("Save even if another definition already points to the same remote path (on the same host and port)")
+        )
 }
 
 pub fn run_create(manager: &Manager, matches: &ArgMatches) -> exit::Status {
@@ -136,15 +444,27 @@ fn create(manager: &Manager, id: &str, matches: &ArgMatches) -> exit::Status {
     bind_command_arguments_to_definition(matches, &mut definition, true);
 
     if let Err(errors) = definition.validate() {
-        return abort_with_validation_errors(errors);
+        return abort_with_validation_errors(errors, Output::from_matches(matches));
     }
 
-    if let Err(err) = manager.persist(&definition) {
-        log::error!("Failed to persist definition: {0}", err);
-        return exit::Status::Failure;
+    warn_about_mount_options(&definition);
+
+    if !is_flag_set(matches, ARG_FORCE)
+        && let Err(status) = ensure_no_definitions_with_same_remote(manager, &definition)
+    {
+        return status;
     }
 
-    exit::Status::Success
+    match manager.persist(&definition) {
+        Ok(outcome) => {
+            Output::from_matches(matches).print_json(&json!({ "definition": definition }));
+            status_for_persist_outcome(&definition.id, &outcome)
+        }
+        Err(err) => {
+            log::error!("Failed to persist definition: {0}", err);
+            exit::Status::Failure
+        }
+    }
 }
 
 /// Creates the update subcommand based on the create subcommand, with only the id argument being required
@@ -165,6 +485,8 @@ pub fn build_update() -> Command {
 
             let mut arg_ssh_key = Arg::new(arg.get_id())
                 .long(ARG_SSH_KEY)
+                .num_args(1)
+                .action(ArgAction::Append)
                 .value_parser(value_parser.to_owned());
 
             if let Some(help) = arg.get_help() {
@@ -177,13 +499,163 @@ pub fn build_update() -> Command {
         cmd = cmd.arg(arg);
     }
 
-    cmd
+    cmd.arg(
+        Arg::new(ARG_UNSET_DESCRIPTION)
+            .long(ARG_UNSET_DESCRIPTION)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_DESCRIPTION)
+            .help("Remove the description")
+    )
+    .arg(
+        Arg::new(ARG_ADD_MOUNT_OPT)
+            .long(ARG_ADD_MOUNT_OPT)
+            .num_args(1)
+            .action(ArgAction::Append)
+            .help("Option to add to the existing mount options (replacing an option with the same name, if any). Can be repeated. Example: reconnect")
+    )
+    .arg(
+        Arg::new(ARG_REMOVE_MOUNT_OPT)
+            .long(ARG_REMOVE_MOUNT_OPT)
+            .num_args(1)
+            .action(ArgAction::Append)
+            .help("Option to remove from the existing mount options. A name alone removes the option regardless of its value. Can be repeated. Example: idmap")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_MOUNT_OPT)
+            .long(ARG_UNSET_MOUNT_OPT)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_MOUNT_OPT)
+            .help("Remove all mount options")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_MOUNT_PATH)
+            .long(ARG_UNSET_MOUNT_PATH)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_MOUNT_PATH)
+            .help(format!(
+                "Go back to mounting under the default mount path (the mountPathTemplate config setting, or {0}/my-machine)",
+                DEFAULT_MOUNT_PATH_PREFIX
+            ))
+    )
+    .arg(
+        Arg::new(ARG_UNSET_SUB_MOUNTS)
+            .long(ARG_UNSET_SUB_MOUNTS)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_SUB_MOUNT)
+            .help("Remove all sub-mounts, going back to mounting the remote path")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_EXTRA_MOUNT_PATHS)
+            .long(ARG_UNSET_EXTRA_MOUNT_PATHS)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_EXTRA_MOUNT_PATH)
+            .help("Stop exposing the filesystem at additional local paths")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_SSH_KEY)
+            .long(ARG_UNSET_SSH_KEY)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_SSH_KEY)
+            .help("Remove the SSH private key path")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_CERTIFICATE_FILE)
+            .long(ARG_UNSET_CERTIFICATE_FILE)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_CERTIFICATE_FILE)
+            .help("Remove the certificate file path")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_KNOWN_HOSTS_FILE)
+            .long(ARG_UNSET_KNOWN_HOSTS_FILE)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_KNOWN_HOSTS_FILE)
+            .help("Go back to using the default known_hosts file (~/.ssh/known_hosts)")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_PROXY)
+            .long(ARG_UNSET_PROXY)
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all([ARG_PROXY_COMMAND, ARG_SOCKS_PROXY])
+            .help("Go back to connecting to the remote machine directly (instead of through a proxy command or SOCKS proxy)")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_CIPHERS)
+            .long(ARG_UNSET_CIPHERS)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_CIPHER)
+            .help("Go back to using ssh's default ciphers")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_SSH_BINARY)
+            .long(ARG_UNSET_SSH_BINARY)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_SSH_BINARY)
+            .help("Go back to using the sshBinary config setting (or the ssh found in PATH)")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_CMD_BEFORE_MOUNT)
+            .long(ARG_UNSET_CMD_BEFORE_MOUNT)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_CMD_BEFORE_MOUNT)
+            .help("Remove the command to run before mounting")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_IDLE_UNMOUNT_MINUTES)
+            .long(ARG_UNSET_IDLE_UNMOUNT_MINUTES)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_IDLE_UNMOUNT_MINUTES)
+            .help("Stop unmounting automatically when idle")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_REQUIRES)
+            .long(ARG_UNSET_REQUIRES)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_REQUIRES)
+            .help("Remove all conditions for mounting")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_PASSWORD_SOURCE)
+            .long(ARG_UNSET_PASSWORD_SOURCE)
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all([ARG_PASSWORD_COMMAND, ARG_PASSWORD_ENV, ARG_PASSWORD_FILE])
+            .help("Go back to prompting for the password (instead of using a password command, environment variable or file)")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_NICE)
+            .long(ARG_UNSET_NICE)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_NICE)
+            .help("Go back to running sshfs with the default scheduling priority")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_IONICE)
+            .long(ARG_UNSET_IONICE)
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all([ARG_IONICE_CLASS, ARG_IONICE_LEVEL])
+            .help("Go back to running sshfs with the default I/O scheduling class and priority")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_BANDWIDTH_LIMIT)
+            .long(ARG_UNSET_BANDWIDTH_LIMIT)
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all([ARG_DOWNLOAD_LIMIT, ARG_UPLOAD_LIMIT])
+            .help("Remove the download and upload limits")
+    )
+    .arg(
+        Arg::new(ARG_UNSET_ENV)
+            .long(ARG_UNSET_ENV)
+            .num_args(1)
+            .action(ArgAction::Append)
+            .value_name("NAME")
+            .help("Environment variable to stop setting for sshfs/ssh. Can be repeated. Example: SSH_AUTH_SOCK")
+    )
 }
 
 pub fn run_update(manager: &Manager, matches: &ArgMatches) -> exit::Status {
     let id = matches.get_one::<String>(ARG_ID).expect("required");
 
-    match manager.definition(id) {
+    match manager.resolve_definition(id) {
         Ok(mut definition) => update(manager, &mut definition, matches),
 
         Err(err) => {
@@ -201,11 +673,44 @@ fn update(
     bind_command_arguments_to_definition(matches, definition, false);
 
     if let Err(errors) = definition.validate() {
-        return abort_with_validation_errors(errors);
+        return abort_with_validation_errors(errors, Output::from_matches(matches));
+    }
+
+    warn_about_mount_options(definition);
+
+    if !is_flag_set(matches, ARG_FORCE)
+        && let Err(status) = ensure_no_definitions_with_same_remote(manager, definition)
+    {
+        return status;
+    }
+
+    match manager.persist(definition) {
+        Ok(outcome) => {
+            Output::from_matches(matches).print_json(&json!({ "definition": definition }));
+            status_for_persist_outcome(&definition.id, &outcome)
+        }
+        Err(err) => {
+            log::error!("Failed to persist definition: {0}", err);
+            exit::Status::Failure
+        }
     }
+}
 
-    if let Err(err) = manager.persist(definition) {
-        log::error!("Failed to persist definition: {0}", err);
+/// Reports a definition which got saved, but could not be remounted (or unmounted) along the way, as a failure.
+pub(super) fn status_for_persist_outcome(id: &str, outcome: &PersistOutcome) -> exit::Status {
+    if outcome.umount_error.is_some() {
+        log::error!(
+            "{0}: the definition was saved, but it's still mounted with the old settings, because unmounting failed",
+            id
+        );
+        return exit::Status::Failure;
+    }
+
+    if outcome.remount_error.is_some() {
+        log::error!(
+            "{0}: the definition was saved, but it's no longer mounted, because remounting failed",
+            id
+        );
         return exit::Status::Failure;
     }
 
@@ -223,6 +728,14 @@ fn bind_command_arguments_to_definition(
     definition: &mut FilesystemMountDefinition,
     is_new: bool,
 ) {
+    if let Some(value) = matches.get_one::<String>(ARG_DESCRIPTION) {
+        definition.description = Some(value.clone());
+    }
+
+    if is_flag_set(matches, ARG_UNSET_DESCRIPTION) {
+        definition.description = None;
+    }
+
     if let Some(value) = matches.get_one::<String>(ARG_HOST) {
         definition.host = value.clone().to_owned();
     }
@@ -238,13 +751,30 @@ fn bind_command_arguments_to_definition(
     if let Some(value) = matches.get_one::<String>(ARG_MOUNT_OPT) {
         definition.mount_options.clear();
 
-        for v in value.split(',') {
-            if !v.is_empty() {
-                definition.mount_options.push(v.to_owned());
-            }
+        definition.mount_options = split_mount_options(value);
+    }
+
+    if is_flag_set(matches, ARG_UNSET_MOUNT_OPT) {
+        definition.mount_options.clear();
+    }
+
+    // These only exist for the update subcommand, and are applied on top of --mount_opt (if also provided).
+    if let Ok(Some(values)) = matches.try_get_many::<String>(ARG_REMOVE_MOUNT_OPT) {
+        for value in values.flat_map(|value| split_mount_options(value)) {
+            remove_mount_option(&mut definition.mount_options, &value);
         }
     }
 
+    if let Ok(Some(values)) = matches.try_get_many::<String>(ARG_ADD_MOUNT_OPT) {
+        for value in values.flat_map(|value| split_mount_options(value)) {
+            remove_mount_option(&mut definition.mount_options, mount_option_name(&value));
+            definition.mount_options.push(value);
+        }
+    }
+
+    // Definitions created before options were stored one per entry may contain several options in one.
+    definition.mount_options = normalize_mount_options(&definition.mount_options);
+
     if let Some(value) = matches.get_one::<String>(ARG_REMOTE_PATH) {
         definition.remote_path = value.clone().to_owned();
     }
@@ -261,24 +791,347 @@ fn bind_command_arguments_to_definition(
         definition.cmd_before_mount = value.clone().to_owned();
     }
 
+    if is_flag_set(matches, ARG_UNSET_MOUNT_PATH) {
+        definition.mount_dest_path = None;
+    }
+
+    if let Some(values) = matches.get_many::<SubMount>(ARG_SUB_MOUNT) {
+        definition.sub_mounts = values.cloned().collect();
+    }
+
+    if is_flag_set(matches, ARG_UNSET_SUB_MOUNTS) {
+        definition.sub_mounts.clear();
+    }
+
+    if let Some(values) = matches.get_many::<String>(ARG_EXTRA_MOUNT_PATH) {
+        definition.extra_mount_paths = values.cloned().collect();
+    }
+
+    if is_flag_set(matches, ARG_UNSET_EXTRA_MOUNT_PATHS) {
+        definition.extra_mount_paths.clear();
+    }
+
+    if let Some(value) = matches.get_one::<String>(ARG_EXTRA_MOUNT_PATH_METHOD) {
+        definition.extra_mount_path_method =
+            ExtraMountPathMethod::from_string(value).expect("validated by clap");
+    }
+
+    if let Some(value) = matches.get_one::<bool>(ARG_ALLOW_NON_EMPTY_MOUNT_PATH) {
+        definition.allow_non_empty_mount_path = *value;
+    }
+
+    if is_flag_set(matches, ARG_UNSET_CMD_BEFORE_MOUNT) {
+        definition.cmd_before_mount = "".to_owned();
+    }
+
+    if is_flag_set(matches, ARG_UNSET_IDLE_UNMOUNT_MINUTES) {
+        definition.idle_unmount_minutes = None;
+    }
+
+    if let Some(value) = matches.get_one::<bool>(ARG_DEBUG_SSHFS) {
+        definition.debug_sshfs = *value;
+    }
+
+    if let Some(value) = matches.get_one::<u32>(ARG_IDLE_UNMOUNT_MINUTES) {
+        definition.idle_unmount_minutes = if *value == 0 { None } else { Some(*value) };
+    }
+
+    if let Some(value) = matches.get_one::<i32>(ARG_NICE) {
+        definition.nice = Some(*value);
+    }
+
+    if is_flag_set(matches, ARG_UNSET_NICE) {
+        definition.nice = None;
+    }
+
+    if let Some(value) = matches.get_one::<String>(ARG_IONICE_CLASS) {
+        definition.ionice_class = Some(IoniceClass::from_string(value).expect("validated by clap"));
+
+        // The level only applies to the best-effort class.
+        if definition.ionice_class != Some(IoniceClass::BestEffort) {
+            definition.ionice_level = None;
+        }
+    }
+
+    if let Some(value) = matches.get_one::<u8>(ARG_IONICE_LEVEL) {
+        definition.ionice_level = Some(*value);
+    }
+
+    if is_flag_set(matches, ARG_UNSET_IONICE) {
+        definition.ionice_class = None;
+        definition.ionice_level = None;
+    }
+
+    if let Some(value) = matches.get_one::<u32>(ARG_DOWNLOAD_LIMIT) {
+        definition.download_limit = Some(*value);
+    }
+
+    if let Some(value) = matches.get_one::<u32>(ARG_UPLOAD_LIMIT) {
+        definition.upload_limit = Some(*value);
+    }
+
+    if is_flag_set(matches, ARG_UNSET_BANDWIDTH_LIMIT) {
+        definition.download_limit = None;
+        definition.upload_limit = None;
+    }
+
+    if let Some(value) = matches.get_one::<bool>(ARG_AUTO_MOUNT) {
+        definition.auto_mount = *value;
+    }
+
+    if let Some(values) = matches.get_many::<MountRequirement>(ARG_REQUIRES) {
+        definition.requires = values.cloned().collect();
+    }
+
+    if is_flag_set(matches, ARG_UNSET_REQUIRES) {
+        definition.requires.clear();
+    }
+
+    if let Some(value) = matches.get_one::<bool>(ARG_ENABLED) {
+        definition.enabled = *value;
+    }
+
     if let Some(value) = matches.get_one::<AuthType>(ARG_AUTH_TYPE) {
         definition.auth_type = value.clone().to_owned();
     }
     // When binding to existing records, make changing the auth type to one that doesn't use SSH keys also unset the SSH key.
     if !is_new && definition.auth_type != AuthType::PublicKey {
         definition.ssh_key = "".to_owned();
+        definition.fallback_ssh_keys.clear();
+    }
+
+    if let Some(values) = matches.get_many::<PathBuf>(ARG_SSH_KEY) {
+        let mut keys = values.map(|value| value.to_string_lossy().to_string());
+
+        // The first key is the main one and any others are fallbacks.
+        definition.ssh_key = keys.next().unwrap_or_default();
+        definition.fallback_ssh_keys = keys.collect();
+    }
+
+    if is_flag_set(matches, ARG_UNSET_SSH_KEY) {
+        definition.ssh_key = "".to_owned();
+        definition.fallback_ssh_keys.clear();
     }
 
-    if let Some(value) = matches.get_one::<PathBuf>(ARG_SSH_KEY) {
-        definition.ssh_key = value.to_string_lossy().into();
+    if let Some(value) = matches.get_one::<PathBuf>(ARG_CERTIFICATE_FILE) {
+        definition.certificate_file = Some(value.to_string_lossy().into());
     }
+
+    if is_flag_set(matches, ARG_UNSET_CERTIFICATE_FILE) {
+        definition.certificate_file = None;
+    }
+
+    if let Some(value) = matches.get_one::<PathBuf>(ARG_KNOWN_HOSTS_FILE) {
+        definition.known_hosts_file = Some(value.to_string_lossy().into());
+    }
+
+    if is_flag_set(matches, ARG_UNSET_KNOWN_HOSTS_FILE) {
+        definition.known_hosts_file = None;
+    }
+
+    let proxy_command = matches.get_one::<String>(ARG_PROXY_COMMAND);
+    let socks_proxy = matches.get_one::<String>(ARG_SOCKS_PROXY);
+
+    // Only a single proxy can be used, so providing one replaces the other.
+    if proxy_command.is_some() || socks_proxy.is_some() {
+        definition.proxy_command = proxy_command.cloned();
+        definition.socks_proxy = socks_proxy.cloned();
+    }
+
+    if is_flag_set(matches, ARG_UNSET_PROXY) {
+        definition.proxy_command = None;
+        definition.socks_proxy = None;
+    }
+
+    if let Some(value) = matches.get_one::<bool>(ARG_SKIP_HOST_RESOLUTION) {
+        definition.skip_host_resolution = *value;
+    }
+
+    if let Some(values) = matches.get_many::<String>(ARG_CIPHER) {
+        definition.ciphers = values.cloned().collect();
+    }
+
+    if is_flag_set(matches, ARG_UNSET_CIPHERS) {
+        definition.ciphers.clear();
+    }
+
+    if let Some(value) = matches.get_one::<String>(ARG_SSH_BINARY) {
+        definition.ssh_binary = Some(value.clone());
+    }
+
+    if is_flag_set(matches, ARG_UNSET_SSH_BINARY) {
+        definition.ssh_binary = None;
+    }
+
+    // When binding to existing records, make changing the auth type to one that doesn't use passwords also unset the password source.
+    if !is_new && definition.auth_type != AuthType::Password {
+        definition.password_command = None;
+        definition.password_env = None;
+        definition.password_file = None;
+    }
+
+    // Likewise for the GSSAPI options.
+    if !is_new && definition.auth_type != AuthType::GSSAPIWithMic {
+        definition.gssapi_delegate_credentials = false;
+        definition.gssapi_key_exchange = false;
+    }
+
+    // Likewise for the certificate, which only goes along with a key.
+    if !is_new && definition.auth_type != AuthType::PublicKey {
+        definition.certificate_file = None;
+    }
+
+    if let Some(value) = matches.get_one::<bool>(ARG_GSSAPI_DELEGATE_CREDENTIALS) {
+        definition.gssapi_delegate_credentials = *value;
+    }
+
+    if let Some(value) = matches.get_one::<bool>(ARG_GSSAPI_KEY_EXCHANGE) {
+        definition.gssapi_key_exchange = *value;
+    }
+
+    let password_command = matches.get_one::<String>(ARG_PASSWORD_COMMAND);
+    let password_env = matches.get_one::<String>(ARG_PASSWORD_ENV);
+    let password_file = matches.get_one::<String>(ARG_PASSWORD_FILE);
+
+    // Only a single password source can be used, so providing one replaces any other.
+    if password_command.is_some() || password_env.is_some() || password_file.is_some() {
+        definition.password_command = password_command.cloned();
+        definition.password_env = password_env.cloned();
+        definition.password_file = password_file.cloned();
+    }
+
+    if is_flag_set(matches, ARG_UNSET_PASSWORD_SOURCE) {
+        definition.password_command = None;
+        definition.password_env = None;
+        definition.password_file = None;
+    }
+
+    if let Ok(Some(values)) = matches.try_get_many::<(String, String)>(ARG_ENV) {
+        for (name, value) in values {
+            definition.env.insert(name.clone(), value.clone());
+        }
+    }
+
+    // This only exists for the update subcommand.
+    if let Ok(Some(names)) = matches.try_get_many::<String>(ARG_UNSET_ENV) {
+        for name in names {
+            definition.env.remove(name);
+        }
+    }
+}
+
+/// Parses a `NAME=VALUE` environment variable assignment.
+fn parse_env_var(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_owned(), value.to_owned())),
+        _ => Err(format!("expected NAME=VALUE, got {0}", value)),
+    }
+}
+
+/// Tells if the given flag was passed. Flags which only exist for some subcommands (e.g. `--unset_ssh_key` for update) are considered unset for others.
+fn is_flag_set(matches: &ArgMatches, id: &str) -> bool {
+    matches!(matches.try_get_one::<bool>(id), Ok(Some(true)))
+}
+
+/// Splits a comma-separated list of mount options (e.g. `follow_symlinks,idmap=user`).
+///
+/// Commas which are part of a value (e.g. `ProxyCommand=..`) can be escaped with a backslash (e.g. `Ciphers=aes128-ctr\\,aes256-ctr`).
+fn split_mount_options(value: &str) -> Vec<String> {
+    let mut options: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(',') => current.push(','),
+                Some(other) => {
+                    current.push('\\');
+                    current.push(other);
+                }
+                None => current.push('\\'),
+            },
+            ',' => options.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    options.push(current);
+
+    options.retain(|option| !option.is_empty());
+
+    options
+}
+
+/// Returns the name of a mount option (e.g. `idmap` for `idmap=user`).
+fn mount_option_name(option: &str) -> &str {
+    option.split_once('=').map_or(option, |(name, _)| name)
 }
 
-fn abort_with_validation_errors(errors: ValidationErrors) -> exit::Status {
-    log::error!("Validation failed with the following errors:");
+/// Removes a mount option, matching either exactly (e.g. `idmap=user`) or by name (e.g. `idmap`).
+fn remove_mount_option(mount_options: &mut Vec<String>, option: &str) {
+    mount_options.retain(|existing| existing != option && mount_option_name(existing) != option);
+}
 
-    for err in errors_to_string_list(errors) {
-        log::error!("- {0}", err);
+fn ensure_no_definitions_with_same_remote(
+    manager: &Manager,
+    definition: &FilesystemMountDefinition,
+) -> Result<(), exit::Status> {
+    let others = manager
+        .definitions_with_same_remote(definition)
+        .map_err(|err| {
+            log::error!("Failed to check for duplicate definitions: {0:?}", err);
+            exit::Status::Failure
+        })?;
+
+    if others.is_empty() {
+        return Ok(());
+    }
+
+    let ids: Vec<&str> = others.iter().map(|other| other.id.as_str()).collect();
+
+    log::error!(
+        "{0}: the remote path {1} on {2} is already used by: {3}. Mounting the same remote path more than once is usually a mistake. Pass --{4} to save anyway.",
+        definition.id,
+        definition.remote_path,
+        definition.host,
+        ids.join(", "),
+        ARG_FORCE
+    );
+
+    Err(exit::Status::ValidationFailure)
+}
+
+/// Warns about mount options which are likely mistakes (e.g. typos), without preventing them from being saved.
+fn warn_about_mount_options(definition: &FilesystemMountDefinition) {
+    for warning in check_mount_options(&definition.mount_options) {
+        log::warn!("{0}", warning);
+    }
+}
+
+pub(super) fn abort_with_validation_errors(
+    errors: ValidationErrors,
+    output: Output,
+) -> exit::Status {
+    output.print_json(&json!({
+        "validationErrors": FieldValidationError::list_from(&errors),
+    }));
+
+    let language = Language::detect();
+
+    log::error!(
+        "{0}",
+        i18n::translate_or(
+            language,
+            "message.validation_failed",
+            "Validation failed with the following errors:"
+        )
+    );
+
+    for err in FieldValidationError::list_from(&errors) {
+        match err.localized_message(language) {
+            Some(message) => log::error!("- {0}", message),
+            None => log::error!("- {0}: {1}", err.field, err.code),
+        }
     }
 
     exit::Status::ValidationFailure
@@ -0,0 +1,108 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::daemon::{Daemon, DaemonClient};
+use crate::manager::Manager;
+
+use super::exit;
+use super::signals::{STOP_REQUESTED, install_stop_signal_handlers};
+
+const ARG_SOCKET: &str = "socket";
+const ARG_METRICS_LISTEN: &str = "metrics-listen";
+const ARG_METRICS_TEXTFILE: &str = "metrics-textfile";
+const ARG_IGNORE_NETWORK: &str = "ignore-network";
+const ARG_IGNORE_SLEEP: &str = "ignore-sleep";
+
+/// Global flag which prevents routing operations through a running daemon.
+pub const ARG_NO_DAEMON: &str = "no-daemon";
+
+pub fn build() -> Command {
+    Command::new("daemon")
+        .about("Runs a daemon which serves list/mount/umount/subscribe requests (JSON-RPC) over a unix socket. While it's running, mount and umount operations get routed through it")
+        .arg(
+            Arg::new(ARG_SOCKET)
+                .long(ARG_SOCKET)
+                .num_args(1)
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .help("Path to the unix socket to listen on. Default: $XDG_RUNTIME_DIR/sftpman/daemon.sock")
+        )
+        .arg(
+            Arg::new(ARG_METRICS_LISTEN)
+                .long(ARG_METRICS_LISTEN)
+                .num_args(1)
+                .value_name("ADDRESS")
+                .value_parser(clap::value_parser!(SocketAddr))
+                .help("Serve Prometheus metrics over HTTP on the given address (e.g. 127.0.0.1:9877)")
+        )
+        .arg(
+            Arg::new(ARG_METRICS_TEXTFILE)
+                .long(ARG_METRICS_TEXTFILE)
+                .num_args(1)
+                .value_name("PATH")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .help("Periodically write Prometheus metrics to the given file (e.g. for the node exporter's textfile collector)")
+        )
+        .arg(
+            Arg::new(ARG_IGNORE_NETWORK)
+                .long(ARG_IGNORE_NETWORK)
+                .action(ArgAction::SetTrue)
+                .help("Don't follow network changes. By default, mounts whose network went down get lazily unmounted (so that processes don't hang on them) and get remounted once it's back")
+        )
+        .arg(
+            Arg::new(ARG_IGNORE_SLEEP)
+                .long(ARG_IGNORE_SLEEP)
+                .action(ArgAction::SetTrue)
+                .help("Don't follow suspend and resume. By default, everything gets unmounted before the system goes to sleep and gets remounted after it resumes")
+        )
+}
+
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let socket_path = match matches.get_one::<PathBuf>(ARG_SOCKET) {
+        Some(path) => path.clone(),
+        None => manager.daemon_socket_path(),
+    };
+
+    if let Err(err) = install_stop_signal_handlers() {
+        log::error!("Failed to install signal handlers: {0}", err);
+        return exit::Status::Failure;
+    }
+
+    let mut daemon = Daemon::new(manager.clone(), socket_path)
+        .with_network_following(!matches.get_flag(ARG_IGNORE_NETWORK))
+        .with_sleep_following(!matches.get_flag(ARG_IGNORE_SLEEP));
+
+    if let Some(address) = matches.get_one::<SocketAddr>(ARG_METRICS_LISTEN) {
+        daemon = daemon.with_metrics_address(*address);
+    }
+
+    if let Some(path) = matches.get_one::<PathBuf>(ARG_METRICS_TEXTFILE) {
+        daemon = daemon.with_metrics_textfile(path.clone());
+    }
+
+    match daemon.run(&STOP_REQUESTED) {
+        Ok(()) => exit::Status::Success,
+        Err(err) => {
+            log::error!("Failure running the daemon: {0:?}", err);
+            exit::Status::Failure
+        }
+    }
+}
+
+/// Returns a client for the daemon, if one is running and routing through it was not disabled (via `--no-daemon`).
+pub fn client_if_running(manager: &Manager, matches: &ArgMatches) -> Option<DaemonClient> {
+    if matches.get_flag(ARG_NO_DAEMON) {
+        return None;
+    }
+
+    let client = DaemonClient::new(manager.daemon_socket_path());
+
+    if !client.is_running() {
+        return None;
+    }
+
+    log::debug!("A daemon is running. Routing operations through it..");
+
+    Some(client)
+}
@@ -0,0 +1,122 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use clap::{Arg, ArgMatches, Command};
+
+use crate::desktop_entry::DesktopEntry;
+use crate::manager::Manager;
+
+use super::exit;
+
+const ARG_ID: &str = "id";
+const ARG_OUTPUT_DIR: &str = "output-dir";
+
+pub fn build() -> Command {
+    Command::new("desktop")
+        .about("Manages desktop launchers (.desktop files), which make SFTP systems show up in application menus and docks")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("export")
+                .about("Writes a desktop launcher which mounts the specified SFTP system and opens a file manager at it. It's named after the system's description (or its ID)")
+                .arg(Arg::new(ARG_ID).required(true))
+                .arg(output_dir_arg()),
+        )
+        .subcommand(
+            Command::new("remove")
+                .about("Removes the desktop launcher of the specified SFTP system")
+                .arg(Arg::new(ARG_ID).required(true))
+                .arg(output_dir_arg()),
+        )
+}
+
+fn output_dir_arg() -> Arg {
+    Arg::new(ARG_OUTPUT_DIR)
+        .long(ARG_OUTPUT_DIR)
+        .num_args(1)
+        .value_parser(clap::builder::PathBufValueParser::new())
+        .help("Directory the desktop launchers are in. Default: ~/.local/share/applications")
+}
+
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let Some((name, sub_matches)) = matches.subcommand() else {
+        unreachable!("subcommand_required");
+    };
+
+    let id = sub_matches.get_one::<String>(ARG_ID).expect("required");
+
+    let output_dir = match sub_matches.get_one::<PathBuf>(ARG_OUTPUT_DIR) {
+        Some(path) => path.clone(),
+        None => match directories::BaseDirs::new() {
+            Some(base_dirs) => base_dirs.data_dir().join("applications"),
+            None => {
+                log::error!("The home directory could not be determined");
+                return exit::Status::Failure;
+            }
+        },
+    };
+
+    let definition = match manager.resolve_definition(id) {
+        Ok(definition) => definition,
+        Err(err) => {
+            // Launchers may outlive their definitions, so they can still be removed by their exact ID.
+            let path = output_dir.join(DesktopEntry::file_name_for(id));
+            if name == "remove" && path.exists() {
+                return remove(&path);
+            }
+
+            log::error!("Failed to find or load definition: {0}: {1}", id, err);
+            return exit::Status::DefinitionNotFound;
+        }
+    };
+
+    let path = output_dir.join(DesktopEntry::file_name_for(&definition.id));
+
+    if name == "remove" {
+        return remove(&path);
+    }
+
+    // Desktop environments don't necessarily run launchers with the PATH of a login shell (e.g. lacking ~/.cargo/bin),
+    // so sftpman is referenced by its full path.
+    let program = std::env::current_exe().unwrap_or_else(|err| {
+        log::warn!(
+            "Failed to determine the path to the sftpman executable, relying on PATH instead: {0}",
+            err
+        );
+        PathBuf::from(env!("CARGO_PKG_NAME"))
+    });
+
+    let entry = DesktopEntry::new(&definition, &program);
+
+    if let Err(err) = fs::create_dir_all(&output_dir) {
+        log::error!("Failed to create {0}: {1}", output_dir.display(), err);
+        return exit::Status::Failure;
+    }
+
+    if let Err(err) = fs::write(&path, &entry.contents) {
+        log::error!("Failed to write {0}: {1}", path.display(), err);
+        return exit::Status::Failure;
+    }
+
+    println!("{0}", path.display());
+
+    exit::Status::Success
+}
+
+fn remove(path: &Path) -> exit::Status {
+    match fs::remove_file(path) {
+        Ok(()) => {
+            println!("{0}", path.display());
+            exit::Status::Success
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            log::info!("There is no desktop launcher at {0}", path.display());
+            exit::Status::Success
+        }
+        Err(err) => {
+            log::error!("Failed to remove {0}: {1}", path.display(), err);
+            exit::Status::Failure
+        }
+    }
+}
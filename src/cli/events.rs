@@ -0,0 +1,55 @@
+use clap::{ArgMatches, Command};
+
+use crate::daemon::DaemonClient;
+use crate::manager::Manager;
+
+use super::exit;
+use super::output::Output;
+
+pub fn build() -> Command {
+    Command::new("events")
+        .about("Follows the daemon's events and prints one line per change (e.g. `mounted my-fs`, `unmounted my-fs` or `disconnected my-fs`), for status bars and scripts. Requires a running daemon (see `sftpman daemon`)")
+}
+
+/// Prints events received from the daemon, until it goes away.
+/// Returns exit::Status::Failure if no daemon is running or the connection to it broke.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let output = Output::from_matches(matches);
+
+    let client = DaemonClient::new(manager.daemon_socket_path());
+
+    if !client.is_running() {
+        log::error!(
+            "No daemon is running, so there are no events to follow. Start one via `sftpman daemon`"
+        );
+        return exit::Status::Failure;
+    }
+
+    let events = match client.subscribe() {
+        Ok(events) => events,
+        Err(err) => {
+            log::error!("Failed to subscribe to events: {0:?}", err);
+            return exit::Status::Failure;
+        }
+    };
+
+    for event in events {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => {
+                log::error!("Failed to receive an event: {0:?}", err);
+                return exit::Status::Failure;
+            }
+        };
+
+        if output.is_json() {
+            output.print_json(&event);
+        } else {
+            println!("{0} {1}", event.kind.to_static_str(), event.id);
+        }
+    }
+
+    log::error!("The daemon closed the connection");
+
+    exit::Status::Failure
+}
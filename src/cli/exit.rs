@@ -5,6 +5,11 @@ pub enum Status {
     Failure,
     UnknownCommand,
     ValidationFailure,
+
+    // The following follow the monitoring plugin conventions (see `sftpman check`).
+    MonitoringWarning,
+    MonitoringCritical,
+    MonitoringUnknown,
 }
 
 impl From<Status> for i32 {
@@ -16,6 +21,9 @@ impl From<Status> for i32 {
             Status::Failure => 20,
             Status::UnknownCommand => 30,
             Status::ValidationFailure => 40,
+            Status::MonitoringWarning => 1,
+            Status::MonitoringCritical => 2,
+            Status::MonitoringUnknown => 3,
         }
     }
 }
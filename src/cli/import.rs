@@ -0,0 +1,215 @@
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use serde_json::json;
+use validator::Validate;
+
+use crate::i18n::Language;
+use crate::manager::Manager;
+use crate::model::{FieldValidationError, FilesystemMountDefinition};
+
+use super::exit;
+use super::output::Output;
+
+const ARG_CONFIG: &str = "config";
+const ARG_FILE: &str = "file";
+
+pub fn build_rclone() -> Command {
+    Command::new("import_rclone")
+        .alias("import-rclone")
+        .about("Creates definitions for the SFTP remotes found in rclone's configuration file. Remotes whose ID (or remote) is already used by a definition are skipped")
+        .arg(
+            Arg::new(ARG_CONFIG)
+                .long(ARG_CONFIG)
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf))
+                .help("Path to rclone's configuration file (defaults to $RCLONE_CONFIG or ~/.config/rclone/rclone.conf)"),
+        )
+}
+
+/// Imports the SFTP remotes found in rclone's configuration file.
+/// Returns exit::Status::Success if all remotes were imported (or skipped).
+/// Returns exit::Status::Failure if the configuration file can't be read or at least one remote failed to get imported.
+pub fn run_rclone(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let path = matches
+        .get_one::<PathBuf>(ARG_CONFIG)
+        .cloned()
+        .unwrap_or_else(|| manager.rclone_config_path());
+
+    let definitions = match manager.definitions_from_rclone_config(&path) {
+        Ok(definitions) => definitions,
+        Err(err) => {
+            log::error!(
+                "Failed to read rclone remotes from {0}: {1:?}",
+                path.display(),
+                err
+            );
+            return exit::Status::Failure;
+        }
+    };
+
+    if definitions.is_empty() {
+        log::warn!("No SFTP remotes found in {0}", path.display());
+    }
+
+    import(manager, definitions, Output::from_matches(matches))
+}
+
+pub fn build_bookmarks() -> Command {
+    Command::new("import_bookmarks")
+        .alias("import-bookmarks")
+        .about("Creates definitions for the SFTP bookmarks (e.g. sftp://user@example.com/storage) of file managers (GTK bookmarks, KDE places). Bookmarks whose ID (or remote) is already used by a definition are skipped")
+        .arg(
+            Arg::new(ARG_FILE)
+                .long(ARG_FILE)
+                .num_args(1)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf))
+                .help("Path to a bookmarks file to import from (can be repeated). XBEL files are recognized by their .xbel extension. Defaults to ~/.config/gtk-3.0/bookmarks and ~/.local/share/user-places.xbel"),
+        )
+}
+
+/// Imports the SFTP bookmarks found in file manager bookmark files.
+/// Returns exit::Status::Success if all bookmarks were imported (or skipped).
+/// Returns exit::Status::Failure if a bookmarks file can't be read or at least one bookmark failed to get imported.
+pub fn run_bookmarks(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let paths: Vec<PathBuf> = match matches.get_many::<PathBuf>(ARG_FILE) {
+        Some(paths) => paths.cloned().collect(),
+        // Not everyone uses both GTK and KDE, so only the default files which exist are read.
+        None => manager
+            .bookmarks_paths()
+            .into_iter()
+            .filter(|path| path.exists())
+            .collect(),
+    };
+
+    let definitions = match manager.definitions_from_bookmarks(&paths) {
+        Ok(definitions) => definitions,
+        Err(err) => {
+            log::error!("Failed to read bookmarks: {0:?}", err);
+            return exit::Status::Failure;
+        }
+    };
+
+    if definitions.is_empty() {
+        log::warn!("No SFTP bookmarks found");
+    }
+
+    import(manager, definitions, Output::from_matches(matches))
+}
+
+/// What happened to a definition being imported.
+enum ImportResult {
+    Imported,
+    Skipped(String),
+    Failed(String),
+}
+
+impl ImportResult {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Imported => "imported",
+            Self::Skipped(_) => "skipped",
+            Self::Failed(_) => "failed",
+        }
+    }
+
+    fn details(&self) -> Option<&str> {
+        match self {
+            Self::Imported => None,
+            Self::Skipped(details) | Self::Failed(details) => Some(details),
+        }
+    }
+}
+
+/// Persists the given definitions (unless their ID or remote is already taken) and reports what happened to each one.
+fn import(
+    manager: &Manager,
+    definitions: Vec<FilesystemMountDefinition>,
+    output: Output,
+) -> exit::Status {
+    let results: Vec<(FilesystemMountDefinition, ImportResult)> = definitions
+        .into_iter()
+        .map(|definition| {
+            let result = import_one(manager, &definition);
+            (definition, result)
+        })
+        .collect();
+
+    if output.is_json() {
+        let list: Vec<serde_json::Value> = results
+            .iter()
+            .map(|(definition, result)| {
+                json!({
+                    "id": definition.id,
+                    "result": result.code(),
+                    "details": result.details(),
+                })
+            })
+            .collect();
+
+        output.print_json(&list);
+    } else {
+        for (definition, result) in &results {
+            match result.details() {
+                Some(details) => println!("{0} {1}: {2}", result.code(), definition.id, details),
+                None => println!(
+                    "{0} {1} ({2}@{3}:{4})",
+                    result.code(),
+                    definition.id,
+                    definition.user,
+                    definition.host,
+                    definition.port
+                ),
+            }
+        }
+    }
+
+    if results
+        .iter()
+        .any(|(_, result)| matches!(result, ImportResult::Failed(_)))
+    {
+        return exit::Status::Failure;
+    }
+
+    exit::Status::Success
+}
+
+fn import_one(manager: &Manager, definition: &FilesystemMountDefinition) -> ImportResult {
+    if manager.definition(&definition.id).is_ok() {
+        return ImportResult::Skipped("a definition with this ID already exists".to_owned());
+    }
+
+    match manager.definitions_with_same_remote(definition) {
+        Ok(others) if !others.is_empty() => {
+            let ids: Vec<&str> = others.iter().map(|other| other.id.as_str()).collect();
+            return ImportResult::Skipped(format!(
+                "the same remote is used by: {0}",
+                ids.join(", ")
+            ));
+        }
+        Ok(_) => {}
+        Err(err) => {
+            return ImportResult::Failed(format!("failed to check for duplicates: {0}", err));
+        }
+    }
+
+    if let Err(errors) = definition.validate() {
+        let language = Language::detect();
+
+        let messages: Vec<String> = FieldValidationError::list_from(&errors)
+            .into_iter()
+            .map(|err| {
+                err.localized_message(language)
+                    .unwrap_or_else(|| format!("{0}: {1}", err.field, err.code))
+            })
+            .collect();
+
+        return ImportResult::Failed(messages.join(" "));
+    }
+
+    match manager.persist(definition) {
+        Ok(_) => ImportResult::Imported,
+        Err(err) => ImportResult::Failed(format!("failed to persist: {0}", err)),
+    }
+}
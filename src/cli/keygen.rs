@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use validator::Validate;
+
+use crate::auth_type::AuthType;
+use crate::manager::Manager;
+
+use super::create_update::{abort_with_validation_errors, status_for_persist_outcome};
+use super::exit;
+use super::output::Output;
+
+const ARG_ID: &str = "id";
+const ARG_PATH: &str = "path";
+const ARG_DEPLOY: &str = "deploy";
+
+pub fn build() -> Command {
+    Command::new("keygen")
+        .about("Generates a dedicated SSH key (ed25519) for the specified system and makes it use the key. An existing key at the same path is reused instead of being overwritten")
+        .arg(Arg::new(ARG_ID).required(true))
+        .arg(
+            Arg::new(ARG_PATH)
+                .long(ARG_PATH)
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf))
+                .help("Where to store the private key (the public key goes next to it, with a .pub extension). Defaults to ~/.ssh/sftpman_ID"),
+        )
+        .arg(
+            Arg::new(ARG_DEPLOY)
+                .long(ARG_DEPLOY)
+                .action(ArgAction::SetTrue)
+                .help("Add the public key to the remote user's ~/.ssh/authorized_keys (like ssh-copy-id does), logging in with the password once"),
+        )
+}
+
+/// Generates (or reuses) a dedicated SSH key for a definition, points the definition to it and optionally deploys it.
+/// Returns exit::Status::Success if the key is in place (and, if requested, got deployed).
+/// Returns exit::Status::DefinitionNotFound if the filesystem was not found.
+/// Returns exit::Status::ValidationFailure if the updated definition is invalid.
+/// Returns exit::Status::Failure if generating the key, saving the definition or deploying the key failed.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let id = matches.get_one::<String>(ARG_ID).expect("required");
+
+    let mut definition = match manager.resolve_definition(id) {
+        Ok(definition) => definition,
+        Err(err) => {
+            log::error!("Failed to find or load definition: {0}: {1}", id, err);
+            return exit::Status::DefinitionNotFound;
+        }
+    };
+
+    let path = matches
+        .get_one::<PathBuf>(ARG_PATH)
+        .cloned()
+        .unwrap_or_else(|| manager.default_ssh_key_path(&definition));
+
+    let mut public_key_path = path.clone().into_os_string();
+    public_key_path.push(".pub");
+    let public_key_path = PathBuf::from(public_key_path);
+
+    if path.exists() && public_key_path.exists() {
+        log::info!("{0}: using the existing key at {1}", id, path.display());
+    } else {
+        if let Err(err) = manager.generate_ssh_key(&definition, &path) {
+            log::error!("{0}: failed to generate an SSH key: {1:?}", id, err);
+            return exit::Status::Failure;
+        }
+
+        println!("Generated {0}", path.display());
+    }
+
+    let path_str = path.display().to_string();
+
+    if definition.ssh_key != path_str || definition.auth_type != AuthType::PublicKey {
+        // Until the new key is deployed, the previous one keeps mounting working.
+        if definition.auth_type == AuthType::PublicKey
+            && !definition.ssh_key.is_empty()
+            && !definition.fallback_ssh_keys.contains(&definition.ssh_key)
+        {
+            definition
+                .fallback_ssh_keys
+                .insert(0, definition.ssh_key.clone());
+        }
+
+        definition.ssh_key = path_str;
+        definition.auth_type = AuthType::PublicKey;
+
+        if let Err(errors) = definition.validate() {
+            return abort_with_validation_errors(errors, Output::from_matches(matches));
+        }
+
+        let outcome = match manager.persist(&definition) {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                log::error!("{0}: failed to save the definition: {1:?}", id, err);
+                return exit::Status::Failure;
+            }
+        };
+
+        let status = status_for_persist_outcome(id, &outcome);
+        if !matches!(status, exit::Status::Success) {
+            return status;
+        }
+
+        println!("{0} now authenticates with {1}", id, path.display());
+    }
+
+    if !matches.get_flag(ARG_DEPLOY) {
+        println!(
+            "Add {0} to ~/.ssh/authorized_keys on {1}, or run this again with --deploy to have it added",
+            public_key_path.display(),
+            definition.host
+        );
+        return exit::Status::Success;
+    }
+
+    println!(
+        "Adding the public key to the authorized keys of {0} on {1}. Enter the password when asked..",
+        definition.user, definition.host
+    );
+
+    if let Err(err) = manager.deploy_public_key(&definition, &public_key_path) {
+        log::error!("{0}: failed to deploy the public key: {1:?}", id, err);
+        return exit::Status::Failure;
+    }
+
+    println!("Deployed the public key. Mount via `sftpman mount {0}`", id);
+
+    exit::Status::Success
+}
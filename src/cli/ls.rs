@@ -1,11 +1,30 @@
-use clap::{Arg, ArgMatches, Command};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::manager::Manager;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::auth_type::AuthType;
+use crate::{
+    manager::Manager,
+    model::{DefinitionFilter, FilesystemMountDefinition, MountState, UsageStats},
+};
 
 use super::exit;
+use super::output::Output;
+
+const ARG_HOST: &str = "host";
+const ARG_AUTH_TYPE: &str = "auth_type";
+const ARG_SEARCH: &str = "search";
+const ARG_LONG: &str = "long";
+const ARG_SORT: &str = "sort";
+
+const SORT_ID: &str = "id";
+const SORT_LAST_USED: &str = "last-used";
 
 pub fn build() -> Command {
     Command::new("ls")
+        .visible_alias("l")
         .about("Lists the available/mounted/unmounted SFTP systems.")
         .arg(
             Arg::new("what")
@@ -13,43 +32,182 @@ pub fn build() -> Command {
                 .help("Specifies what to operate on")
                 .value_parser(["available", "mounted", "unmounted"]),
         )
+        .arg(
+            Arg::new(ARG_HOST)
+                .long(ARG_HOST)
+                .num_args(1)
+                .help("Only list systems connecting to this host. Example: example.com"),
+        )
+        .arg(
+            Arg::new(ARG_AUTH_TYPE)
+                .long(ARG_AUTH_TYPE)
+                .num_args(1)
+                .value_parser(clap::builder::EnumValueParser::<AuthType>::new())
+                .help("Only list systems using this SSH authentication type"),
+        )
+        .arg(
+            Arg::new(ARG_SEARCH)
+                .long(ARG_SEARCH)
+                .num_args(1)
+                .help("Only list systems whose ID, host, user or remote path contains this text. Example: backup"),
+        )
+        .arg(
+            Arg::new(ARG_LONG)
+                .long(ARG_LONG)
+                .short('l')
+                .action(ArgAction::SetTrue)
+                .help("Print a table with the state of each system, when it was last mounted and how many times"),
+        )
+        .arg(
+            Arg::new(ARG_SORT)
+                .long(ARG_SORT)
+                .num_args(1)
+                .value_parser([SORT_ID, SORT_LAST_USED])
+                .default_value(SORT_ID)
+                .help("Order to list systems in. With last-used, the most recently mounted ones come first and never mounted ones last"),
+        )
 }
 
 pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
     let what = matches.get_one::<String>("what").expect("required");
-    do_ls(manager, what)
+
+    let mut filter = match what.as_str() {
+        "available" => DefinitionFilter::new(),
+        "mounted" => DefinitionFilter::new().with_mounted(true),
+        "unmounted" => DefinitionFilter::new().with_mounted(false),
+        _ => unreachable!(),
+    };
+
+    if let Some(host) = matches.get_one::<String>(ARG_HOST) {
+        filter = filter.with_host(host);
+    }
+
+    if let Some(auth_type) = matches.get_one::<AuthType>(ARG_AUTH_TYPE) {
+        filter = filter.with_auth_type(auth_type.clone());
+    }
+
+    if let Some(text) = matches.get_one::<String>(ARG_SEARCH) {
+        filter = filter.with_text(text);
+    }
+
+    do_ls(
+        manager,
+        &filter,
+        matches.get_flag(ARG_LONG),
+        matches.get_one::<String>(ARG_SORT).expect("defaulted"),
+        Output::from_matches(matches),
+    )
 }
 
-pub fn do_ls(manager: &Manager, what: &str) -> exit::Status {
-    match what {
-        "available" => {
-            for definition in manager.definitions().unwrap() {
-                println!("{0}", definition.id)
+pub fn do_ls(
+    manager: &Manager,
+    filter: &DefinitionFilter,
+    long: bool,
+    sort: &str,
+    output: Output,
+) -> exit::Status {
+    let mut states = manager.definitions_filtered(filter).unwrap();
+
+    // Usage stats are only loaded when needed, as plain listing is used by shell completion.
+    let usage_stats = if long || sort == SORT_LAST_USED {
+        match manager.usage_stats() {
+            Ok(usage_stats) => usage_stats,
+            Err(err) => {
+                log::warn!("Failed to load the usage stats: {0:?}", err);
+                HashMap::new()
             }
         }
+    } else {
+        HashMap::new()
+    };
 
-        "mounted" => {
-            for state in manager.full_state().unwrap() {
-                if !state.mounted {
-                    continue;
-                }
+    if sort == SORT_LAST_USED {
+        // The sort is stable, so systems which were never mounted stay ordered by ID.
+        states.sort_by_key(|state| {
+            std::cmp::Reverse(
+                usage_stats
+                    .get(&state.definition.id)
+                    .and_then(|stats| stats.last_mounted_at),
+            )
+        });
+    }
 
-                println!("{0}", state.definition.id)
-            }
+    if output.is_json() {
+        output.print_states(&states);
+    } else if long {
+        print_long(&states, &usage_stats);
+    } else {
+        for state in states {
+            print_definition(&state.definition)
         }
+    }
 
-        "unmounted" => {
-            for state in manager.full_state().unwrap() {
-                if state.mounted {
-                    continue;
-                }
+    exit::Status::Success
+}
 
-                println!("{0}", state.definition.id)
-            }
-        }
+fn print_long(states: &[MountState], usage_stats: &HashMap<String, UsageStats>) {
+    let id_width = states
+        .iter()
+        .map(|state| state.definition.id.len())
+        .chain(std::iter::once("ID".len()))
+        .max()
+        .unwrap_or_default();
 
-        _ => unreachable!(),
+    let state_width = "unmounted".len();
+    let last_mounted_width = "LAST MOUNTED".len();
+
+    println!(
+        "{0:id_width$}  {1:state_width$}  {2:last_mounted_width$}  MOUNTS",
+        "ID", "STATE", "LAST MOUNTED"
+    );
+
+    for state in states {
+        let stats = usage_stats.get(&state.definition.id);
+
+        let state_label = if state.mounted {
+            "mounted"
+        } else {
+            "unmounted"
+        };
+
+        println!(
+            "{0:id_width$}  {1:state_width$}  {2:last_mounted_width$}  {3}",
+            state.definition.id,
+            state_label,
+            format_last_mounted(stats),
+            stats.map(|stats| stats.mount_count).unwrap_or_default()
+        );
     }
+}
 
-    exit::Status::Success
+/// Tells how long ago a system was last mounted (e.g. `3d ago`), or `never`.
+pub fn format_last_mounted(stats: Option<&UsageStats>) -> String {
+    let Some(last_mounted_at) = stats.and_then(|stats| stats.last_mounted_at) else {
+        return "never".to_owned();
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let seconds = now.saturating_sub(last_mounted_at);
+
+    match seconds {
+        0..60 => "just now".to_owned(),
+        60..3600 => format!("{0}m ago", seconds / 60),
+        3600..86400 => format!("{0}h ago", seconds / 3600),
+        _ => format!("{0}d ago", seconds / 86400),
+    }
+}
+
+/// Prints the definition's ID, greying it out if the definition is disabled (when printing to a terminal).
+///
+/// Only the ID is printed, so that the output remains usable by scripts (e.g. shell completion).
+fn print_definition(definition: &FilesystemMountDefinition) {
+    if !definition.enabled && std::io::stdout().is_terminal() {
+        println!("\x1b[2m{0}\x1b[0m", definition.id)
+    } else {
+        println!("{0}", definition.id)
+    }
 }
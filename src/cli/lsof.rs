@@ -0,0 +1,108 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::manager::Manager;
+use crate::model::BlockingProcess;
+
+use super::exit;
+use super::output::Output;
+
+const ARG_ID: &str = "id";
+
+pub fn build() -> Command {
+    Command::new("lsof")
+        .about("Lists the processes using the specified mounted SFTP system (having files open or their working directory within it), which keep it from being unmounted")
+        .arg(Arg::new(ARG_ID).required(true))
+}
+
+/// Lists the processes using the given filesystem.
+/// Returns exit::Status::Success if the processes could be determined (even if there are none).
+/// Returns exit::Status::DefinitionNotFound if the filesystem was not found.
+/// Returns exit::Status::Failure if the processes could not be determined.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let id = matches.get_one::<String>(ARG_ID).expect("required");
+
+    let definition = match manager.resolve_definition(id) {
+        Ok(definition) => definition,
+        Err(err) => {
+            log::error!("Failed to find or load definition: {0}: {1}", id, err);
+            return exit::Status::DefinitionNotFound;
+        }
+    };
+
+    match manager.is_definition_mounted(&definition) {
+        Ok(true) => {}
+        Ok(false) => log::warn!("{0} is not mounted", definition.id),
+        Err(err) => log::warn!(
+            "{0}: failed to determine whether it is mounted: {1:?}",
+            definition.id,
+            err
+        ),
+    }
+
+    let processes = match manager.blocking_processes(&definition) {
+        Ok(processes) => processes,
+        Err(err) => {
+            log::error!(
+                "{0}: failed to determine the processes using it: {1}",
+                definition.id,
+                err
+            );
+            return exit::Status::Failure;
+        }
+    };
+
+    let output = Output::from_matches(matches);
+
+    if output.is_json() {
+        output.print_json(&processes);
+    } else {
+        print_processes(&processes);
+    }
+
+    exit::Status::Success
+}
+
+fn print_processes(processes: &[BlockingProcess]) {
+    let pid_width = processes
+        .iter()
+        .map(|process| process.pid.to_string().len())
+        .chain(std::iter::once("PID".len()))
+        .max()
+        .unwrap_or_default();
+
+    // Long command lines would push the paths far to the right, so they're cut short.
+    let commands: Vec<String> = processes
+        .iter()
+        .map(|process| shorten(&process.command, 40))
+        .collect();
+
+    let command_width = commands
+        .iter()
+        .map(|command| command.chars().count())
+        .chain(std::iter::once("COMMAND".len()))
+        .max()
+        .unwrap_or_default();
+
+    println!("{0:pid_width$}  {1:command_width$}  PATH", "PID", "COMMAND");
+
+    for (process, command) in processes.iter().zip(&commands) {
+        for path in &process.paths {
+            println!(
+                "{0:pid_width$}  {1:command_width$}  {2}",
+                process.pid,
+                command,
+                path.display()
+            );
+        }
+    }
+}
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_owned();
+    }
+
+    let mut shortened: String = text.chars().take(max_chars - 1).collect();
+    shortened.push('…');
+    shortened
+}
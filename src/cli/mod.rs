@@ -1,15 +1,43 @@
 use clap::{Arg, ArgAction, Command};
 
+mod accept_hostkey;
+mod automount_units;
+mod bench;
+mod browse;
+mod bulk;
+mod check;
+mod config;
+mod cp;
 mod create_update;
+mod daemon;
+mod desktop;
+mod events;
 mod exit;
+mod import;
+mod keygen;
 mod ls;
+mod lsof;
 mod mount;
+mod output;
+mod pam_helper;
+mod pause;
 mod preflight_check;
+mod profile;
+mod progress;
+mod prune;
 mod remove;
 mod runner;
+mod setup;
+mod signals;
+mod status;
+mod supervise;
+mod sync;
 mod umount;
+mod validate;
+mod verify;
 
 pub use exit::Status as ExitStatus;
+pub use profile::ARG_PROFILE;
 pub use runner::run;
 
 pub fn build() -> Command {
@@ -26,13 +54,49 @@ pub fn build() -> Command {
             .action(ArgAction::Count)
             .help("Control logging verbosity (none for warn; -v for info; -vv for debug; -vvv for trace)")
     )
+    .arg(output::arg())
+    .arg(
+        Arg::new(daemon::ARG_NO_DAEMON)
+            .global(true)
+            .long(daemon::ARG_NO_DAEMON)
+            .action(ArgAction::SetTrue)
+            .help("Perform mount/umount operations directly, even if a daemon is running")
+    )
+    .arg(profile::arg())
     .subcommand(ls::build())
+    .subcommand(status::build())
     .subcommand(mount::build())
     .subcommand(mount::build_mount_all())
     .subcommand(umount::build())
     .subcommand(umount::build_umount_all())
+    .subcommand(umount::build_umount_idle())
+    .subcommand(umount::build_gc())
+    .subcommand(lsof::build())
     .subcommand(preflight_check::build())
     .subcommand(remove::build())
+    .subcommand(prune::build())
     .subcommand(create_update::build_create())
     .subcommand(create_update::build_update())
+    .subcommand(supervise::build())
+    .subcommand(daemon::build())
+    .subcommand(browse::build())
+    .subcommand(bench::build())
+    .subcommand(pam_helper::build())
+    .subcommand(automount_units::build())
+    .subcommand(desktop::build())
+    .subcommand(config::build())
+    .subcommand(sync::build())
+    .subcommand(validate::build())
+    .subcommand(verify::build())
+    .subcommand(cp::build())
+    .subcommand(events::build())
+    .subcommand(check::build())
+    .subcommand(import::build_rclone())
+    .subcommand(import::build_bookmarks())
+    .subcommand(setup::build())
+    .subcommand(pause::build_pause())
+    .subcommand(pause::build_resume())
+    .subcommand(accept_hostkey::build())
+    .subcommand(keygen::build())
+    .subcommand(profile::build())
 }
@@ -1,87 +1,653 @@
-use clap::{Arg, ArgMatches, Command};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use crate::{manager::Manager, model::FilesystemMountDefinition};
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command, value_parser};
+use validator::Validate;
 
+use crate::daemon::DaemonClient;
+use crate::errors::SftpManError;
+use crate::i18n::{self, Language};
+use crate::manager::Manager;
+use crate::model::{FilesystemMountDefinition, MountFailureReport};
+use crate::utils::id_resolution::find_definition;
+
+use super::bulk::{self, print_summary, skip_remaining, summarize};
+use super::create_update::abort_with_validation_errors;
+use super::daemon::client_if_running;
 use super::exit;
+use super::output::{OperationResult, Output};
+use super::progress::{self, Operation, Progress};
+
+const ARG_ID: &str = "id";
+const ARG_TEMPORARY: &str = "temporary";
+const ARG_AT: &str = "at";
+const ARG_DEBUG_SSHFS: &str = "debug-sshfs";
+const ARG_WAIT_FOR_NETWORK: &str = "wait-for-network";
+const ARG_WAIT_FOR_NETWORK_TIMEOUT: &str = "wait-for-network-timeout";
+const ARG_AUTO: &str = "auto";
+const ARG_OPEN: &str = "open";
+const ARG_NO_PREFLIGHT: &str = "no-preflight";
+const ARG_RESTORE: &str = "restore";
 
 pub fn build() -> Command {
     Command::new("mount")
+        .visible_alias("m")
         .about("Mounts the specified SFTP system or systems, unless already mounted")
-        .arg(Arg::new("id").num_args(1..).required(true))
+        .arg(Arg::new(ARG_ID).num_args(1..))
+        .arg(
+            Arg::new(ARG_TEMPORARY)
+                .long(ARG_TEMPORARY)
+                .num_args(1)
+                .value_name("URL")
+                .help("Mount the given URL once, without storing a definition for it. Example: sftp://user@example.com:2222/storage")
+        )
+        .arg(
+            Arg::new(ARG_RESTORE)
+                .long(ARG_RESTORE)
+                .action(ArgAction::SetTrue)
+                .help("Mount the systems of the previous session (those mounted before, except for the ones unmounted on their own since), e.g. after a reboot or after umount_all")
+        )
+        .group(
+            ArgGroup::new("what")
+                .args([ARG_ID, ARG_TEMPORARY, ARG_RESTORE])
+                .required(true),
+        )
+        .arg(
+            Arg::new(ARG_AT)
+                .long(ARG_AT)
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf))
+                // Either IDs, --temporary or --restore are required, so conflicting with the others means requiring --temporary.
+                .conflicts_with_all([ARG_ID, ARG_RESTORE])
+                .help("Local path to mount the --temporary URL at (defaults to a path under /mnt/sshfs). Example: /tmp/storage")
+        )
+        .arg(
+            Arg::new(ARG_DEBUG_SSHFS)
+                .long(ARG_DEBUG_SSHFS)
+                .action(ArgAction::SetTrue)
+                .help("Run sshfs with debug output enabled, capturing it to a per-mount log file (e.g. ~/.local/state/sftpman/logs/my-machine.log)")
+        )
+        .arg(
+            Arg::new(ARG_OPEN)
+                .long(ARG_OPEN)
+                .action(ArgAction::SetTrue)
+                .conflicts_with(ARG_TEMPORARY)
+                .help("Open a file manager at the local mount path of each system, once mounted")
+        )
+        .arg(no_preflight_arg())
+        .arg(bulk::fail_fast_arg())
+        .arg(progress::arg())
+}
+
+fn no_preflight_arg() -> Arg {
+    Arg::new(ARG_NO_PREFLIGHT)
+        .long(ARG_NO_PREFLIGHT)
+        .action(ArgAction::SetTrue)
+        .help("Skip the checks performed before mounting (programs like sshfs being installed, /dev/fuse being accessible, SSH keys being readable)")
+}
+
+/// Returns the manager to mount with, which skips the pre-mount checks if asked to (see `--no-preflight`).
+fn manager_for_mounting(manager: &Manager, matches: &ArgMatches) -> Manager {
+    manager
+        .clone()
+        .with_premount_checks(!matches.get_flag(ARG_NO_PREFLIGHT))
+}
+
+/// Returns a client for the running daemon (if any), unless mounting needs to be performed by this process.
+///
+/// The daemon always performs the pre-mount checks, so mounts skipping them are never routed through it.
+fn mounting_daemon_client(manager: &Manager, matches: &ArgMatches) -> Option<DaemonClient> {
+    if matches.get_flag(ARG_NO_PREFLIGHT) {
+        return None;
+    }
+
+    client_if_running(manager, matches)
 }
 
 pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
-    let ids: Vec<&str> = matches
-        .get_many::<String>("id")
-        .expect("required")
-        .map(|s| s.as_str())
-        .collect();
+    let manager = &manager_for_mounting(manager, matches);
+    let debug_sshfs = matches.get_flag(ARG_DEBUG_SSHFS);
 
-    mount(manager, ids)
+    if let Some(url) = matches.get_one::<String>(ARG_TEMPORARY) {
+        // Mount paths are absolute, so relative paths are resolved against the current directory.
+        let mount_dest_path = match matches.get_one::<PathBuf>(ARG_AT).map(std::path::absolute) {
+            None => None,
+            Some(Ok(path)) => Some(path.to_string_lossy().into_owned()),
+            Some(Err(err)) => {
+                log::error!("Invalid --{0} path: {1}", ARG_AT, err);
+                return exit::Status::Failure;
+            }
+        };
+
+        return mount_temporary(
+            manager,
+            url,
+            mount_dest_path,
+            debug_sshfs,
+            Progress::from_matches(matches),
+            Output::from_matches(matches),
+        );
+    }
+
+    let session_ids = if matches.get_flag(ARG_RESTORE) {
+        match session_ids_to_restore(manager) {
+            Ok(ids) if ids.is_empty() => {
+                log::info!(
+                    "There is nothing to restore, as no systems were mounted in the previous session"
+                );
+                return exit::Status::Success;
+            }
+            Ok(ids) => Some(ids),
+            Err(err) => {
+                log::error!(
+                    "Failed to determine the systems of the previous session: {0:?}",
+                    err
+                );
+                return exit::Status::Failure;
+            }
+        }
+    } else {
+        None
+    };
+
+    let ids: Vec<&str> = match &session_ids {
+        Some(ids) => ids.iter().map(|s| s.as_str()).collect(),
+        None => matches
+            .get_many::<String>(ARG_ID)
+            .expect("required")
+            .map(|s| s.as_str())
+            .collect(),
+    };
+
+    // Debug logs are captured by the process performing the mount, so such mounts are never routed through the daemon.
+    let daemon_client = if debug_sshfs {
+        None
+    } else {
+        mounting_daemon_client(manager, matches)
+    };
+
+    let exit_status = mount(
+        manager,
+        daemon_client.as_ref(),
+        ids.clone(),
+        debug_sshfs,
+        matches.get_flag(bulk::ARG_FAIL_FAST),
+        Progress::from_matches(matches),
+        Output::from_matches(matches),
+    );
+
+    if matches.get_flag(ARG_OPEN) {
+        open_mounted(manager, &ids);
+    }
+
+    exit_status
 }
 
-pub fn run_mount_all(manager: &Manager) -> exit::Status {
-    mount_all(manager)
+/// Returns the IDs of the filesystems of the previous session (see `Manager::session_ids()`), which still have definitions.
+fn session_ids_to_restore(manager: &Manager) -> Result<Vec<String>, SftpManError> {
+    let definitions = manager.definitions()?;
+
+    Ok(manager
+        .session_ids()?
+        .into_iter()
+        // IDs get matched exactly, as the start of another definition's ID (see `find_definition()`) is not what was mounted.
+        .filter(|id| {
+            let exists = definitions.iter().any(|definition| &definition.id == id);
+            if !exists {
+                log::warn!("{0}: skipping, because its definition no longer exists", id);
+            }
+            exists
+        })
+        .collect())
+}
+
+/// Opens a file manager at the local mount path of each of the given filesystems which is mounted.
+fn open_mounted(manager: &Manager, ids: &[&str]) {
+    for id in ids {
+        let Ok(definition) = manager.resolve_definition(id) else {
+            continue;
+        };
+
+        if let Ok(true) = manager.is_definition_mounted(&definition)
+            && let Err(err) = manager.open(&definition)
+        {
+            log::error!("{0}: failed to open: {1:?}", definition.id, err);
+        }
+    }
+}
+
+pub fn run_mount_all(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let manager = &manager_for_mounting(manager, matches);
+
+    if let Some(address) = matches.get_one::<String>(ARG_WAIT_FOR_NETWORK) {
+        let timeout = matches
+            .get_one::<u64>(ARG_WAIT_FOR_NETWORK_TIMEOUT)
+            .expect("defaulted");
+
+        // An empty value means that the option was passed without an address.
+        let address = if address.is_empty() {
+            None
+        } else {
+            Some(address.as_str())
+        };
+
+        if let Err(err) = manager.wait_for_network(address, Duration::from_secs(*timeout)) {
+            log::error!("Not mounting anything: {0}", err);
+            return exit::Status::Failure;
+        }
+    }
+
+    mount_all(
+        manager,
+        mounting_daemon_client(manager, matches).as_ref(),
+        matches.get_flag(ARG_AUTO),
+        matches.get_flag(bulk::ARG_FAIL_FAST),
+        Progress::from_matches(matches),
+        Output::from_matches(matches),
+    )
 }
 
 pub fn build_mount_all() -> Command {
-    Command::new("mount_all").about("Mounts all known SFTP systems")
+    Command::new("mount_all")
+        .visible_alias("ma")
+        .about("Mounts all known SFTP systems")
+        .arg(
+            Arg::new(ARG_WAIT_FOR_NETWORK)
+                .long(ARG_WAIT_FOR_NETWORK)
+                .num_args(0..=1)
+                .default_missing_value("")
+                .value_name("HOST:PORT")
+                .help("Wait for the network before mounting. Without a value, waits for a default route to be present. With a value, waits for the given address to be reachable. Example: nas.local:22")
+        )
+        .arg(
+            Arg::new(ARG_WAIT_FOR_NETWORK_TIMEOUT)
+                .long(ARG_WAIT_FOR_NETWORK_TIMEOUT)
+                .num_args(1)
+                .value_parser(value_parser!(u64))
+                .value_name("SECONDS")
+                .default_value("30")
+                .help("Maximum number of seconds to wait for the network (see --wait-for-network)")
+        )
+        .arg(
+            Arg::new(ARG_AUTO)
+                .long(ARG_AUTO)
+                .action(ArgAction::SetTrue)
+                .help("Only mount the systems marked with auto_mount, skipping those whose automatic management is paused (see the pause command)")
+        )
+        .arg(no_preflight_arg())
+        .arg(bulk::fail_fast_arg())
+        .arg(progress::arg())
 }
 
 /// Mounts the given filesystems by id.
+/// When a single filesystem gets mounted successfully, its local mount path is printed to stdout.
 /// Returns exit::Status::Success if all mounting succeeded.
 /// Returns exit::Status::DefinitionNotFound if at least one filesystem was not found.
 /// Returns exit::Status::Failure if at least one filesystem failed to mount.
-pub fn mount(manager: &Manager, ids: Vec<&str>) -> exit::Status {
-    let definitions = manager.definitions().unwrap();
+///
+/// If `debug_sshfs` is `true`, sshfs runs with debug output enabled, regardless of what the definitions say.
+/// If `fail_fast` is `true`, nothing else gets mounted after a failure (including a filesystem not being found).
+/// If `daemon_client` is provided, mounting is performed by the daemon.
+pub fn mount(
+    manager: &Manager,
+    daemon_client: Option<&DaemonClient>,
+    ids: Vec<&str>,
+    debug_sshfs: bool,
+    fail_fast: bool,
+    progress: Progress,
+    output: Output,
+) -> exit::Status {
+    let definitions: Vec<FilesystemMountDefinition> = manager
+        .definitions()
+        .unwrap()
+        .into_iter()
+        .map(|mut definition| {
+            definition.debug_sshfs |= debug_sshfs;
+            definition
+        })
+        .collect();
 
     let mut exit_status = exit::Status::Success;
 
+    let mut results: Vec<OperationResult> = Vec::new();
+
     let mut definitions_to_work_on: Vec<&FilesystemMountDefinition> = Vec::new();
 
-    for id in ids {
-        let definition_or_none = definitions.iter().find(|&x| x.id == id);
+    // The first ID which could not be found, if any.
+    let mut missing_id: Option<&str> = None;
 
-        match definition_or_none {
-            None => {
-                log::error!("Failed to find filesystem with an id of: {0}", id);
+    for id in ids {
+        match find_definition(&definitions, id) {
+            Err(err) => {
+                log::error!("Failed to find filesystem: {0}", err);
+                progress.failed(Operation::Mount, id, &err);
+                results.push(OperationResult::failure(id, &err));
                 exit_status = exit::Status::DefinitionNotFound;
+                missing_id.get_or_insert(id);
             }
 
-            Some(definition) => {
+            Ok(definition) => {
                 definitions_to_work_on.push(definition);
             }
         };
     }
 
-    if !mount_definitions(manager, &definitions_to_work_on) {
+    if let Some(missing_id) = missing_id
+        && fail_fast
+    {
+        skip_remaining(&definitions_to_work_on, missing_id, &mut results);
+        definitions_to_work_on.clear();
+    }
+
+    if !mount_definitions(
+        manager,
+        daemon_client,
+        &definitions_to_work_on,
+        false,
+        fail_fast,
+        progress,
+        &mut results,
+    ) {
         exit_status = exit::Status::Failure
     }
 
+    output.print_results(&results);
+
+    // When mounting a single filesystem, its local path is printed, so that scripts can use it directly
+    // (e.g. `cp file "$(sftpman mount my-machine)"/`).
+    if let [definition] = definitions_to_work_on.as_slice()
+        && matches!(exit_status, exit::Status::Success)
+        && !output.is_json()
+        && !progress.is_enabled()
+    {
+        println!("{0}", definition.local_mount_path());
+    }
+
     exit_status
 }
 
 /// Mounts all known filesystems.
+/// If `auto` is `true`, only filesystems marked with `auto_mount` whose automatic management is not paused get mounted.
+/// If `fail_fast` is `true`, nothing else gets mounted after a failure.
+/// Once done, a summary of what happened to each filesystem is printed (unless printing JSON or progress events).
 /// Returns exit::Status::Success if all mounting succeeded.
 /// Returns exit::Status::Failure if at least one filesystem failed to mount.
-pub fn mount_all(manager: &Manager) -> exit::Status {
-    if mount_definitions(manager, &manager.definitions().unwrap().iter().collect()) {
+pub fn mount_all(
+    manager: &Manager,
+    daemon_client: Option<&DaemonClient>,
+    auto: bool,
+    fail_fast: bool,
+    progress: Progress,
+    output: Output,
+) -> exit::Status {
+    let definitions = manager.definitions().unwrap();
+
+    let paused_ids = if auto {
+        match manager.paused_ids() {
+            Ok(paused_ids) => paused_ids,
+            Err(err) => {
+                log::error!(
+                    "Not mounting anything: failed to determine paused systems: {0:?}",
+                    err
+                );
+                return exit::Status::Failure;
+            }
+        }
+    } else {
+        BTreeSet::new()
+    };
+
+    let definitions_to_work_on: Vec<&FilesystemMountDefinition> = definitions
+        .iter()
+        .filter(|definition| {
+            if !definition.enabled {
+                log::info!("{0}: skipping, because it's disabled", definition.id);
+                return false;
+            }
+
+            if auto && !definition.auto_mount {
+                log::debug!(
+                    "{0}: skipping, because it's not marked with auto_mount",
+                    definition.id
+                );
+                return false;
+            }
+
+            if paused_ids.contains(&definition.id) {
+                log::info!("{0}: skipping, because it's paused", definition.id);
+                return false;
+            }
+
+            true
+        })
+        .collect();
+
+    let mut results: Vec<OperationResult> = Vec::new();
+
+    // Systems whose requirements are not met (e.g. their VPN being down) would only fail (possibly after a timeout), so they're skipped.
+    let definitions_to_work_on: Vec<&FilesystemMountDefinition> = definitions_to_work_on
+        .into_iter()
+        .filter(|definition| match manager.check_requirements(definition) {
+            Ok(()) => true,
+            Err(SftpManError::RequirementUnmet(_, reason)) => {
+                log::warn!("{0}: skipped: {1}", definition.id, reason);
+                results.push(OperationResult::skipped(&definition.id, &reason));
+                false
+            }
+            // Other errors are left for mounting to report.
+            Err(_) => true,
+        })
+        .collect();
+
+    let all_good = mount_definitions(
+        manager,
+        daemon_client,
+        &definitions_to_work_on,
+        false,
+        fail_fast,
+        progress,
+        &mut results,
+    );
+
+    output.print_results(&results);
+
+    let summary = summarize(&results);
+    if summary.total() > 0 && !output.is_json() && !progress.is_enabled() {
+        print_summary(&summary, "mounted", "already mounted");
+    }
+
+    if all_good {
         exit::Status::Success
     } else {
         exit::Status::Failure
     }
 }
 
-/// Mounts the given filesystems.
-fn mount_definitions(manager: &Manager, definitions: &Vec<&FilesystemMountDefinition>) -> bool {
+/// Mounts a filesystem from the given URL (see `FilesystemMountDefinition::from_url()`), without storing a definition for it.
+/// When mounting succeeds, the local mount path is printed to stdout (like `mount()` does for a single filesystem).
+/// Returns exit::Status::Success if mounting succeeded.
+/// Returns exit::Status::ValidationFailure if the URL leads to an invalid definition.
+/// Returns exit::Status::Failure if the URL cannot be parsed or mounting failed.
+///
+/// Temporary mounts are never routed through the daemon, as it only knows about stored definitions.
+pub fn mount_temporary(
+    manager: &Manager,
+    url: &str,
+    mount_dest_path: Option<String>,
+    debug_sshfs: bool,
+    progress: Progress,
+    output: Output,
+) -> exit::Status {
+    let mut definition = match FilesystemMountDefinition::from_url(url) {
+        Ok(definition) => definition,
+        Err(err) => {
+            log::error!("{0}", err);
+            return exit::Status::Failure;
+        }
+    };
+
+    definition.mount_dest_path = mount_dest_path;
+    manager.apply_global_defaults(&mut definition);
+    definition.debug_sshfs = debug_sshfs;
+
+    if let Err(errors) = definition.validate() {
+        return abort_with_validation_errors(errors, output);
+    }
+
+    let mut results: Vec<OperationResult> = Vec::new();
+
+    let mounted = mount_definitions(
+        manager,
+        None,
+        &vec![&definition],
+        true,
+        false,
+        progress,
+        &mut results,
+    );
+
+    output.print_results(&results);
+
+    if !mounted {
+        return exit::Status::Failure;
+    }
+
+    if !output.is_json() && !progress.is_enabled() {
+        println!("{0}", definition.local_mount_path());
+    }
+
+    log::warn!(
+        "{0}: mounted temporarily. Unmount via `sftpman umount {0}`",
+        definition.id
+    );
+
+    exit::Status::Success
+}
+
+/// Mounts the given filesystems, recording the result for each into `results`.
+/// If `temporary` is `true`, the definitions are not stored ones (see `Manager::mount_temporary()`).
+/// If `fail_fast` is `true`, the remaining filesystems are skipped after the first failure.
+fn mount_definitions(
+    manager: &Manager,
+    daemon_client: Option<&DaemonClient>,
+    definitions: &Vec<&FilesystemMountDefinition>,
+    temporary: bool,
+    fail_fast: bool,
+    progress: Progress,
+    results: &mut Vec<OperationResult>,
+) -> bool {
     let mut all_good = true;
 
-    for definition in definitions {
-        if let Err(err) = manager.mount(definition) {
-            log::error!("Failure mounting {0}: {1:?}", definition.id, err);
-            all_good = false
+    for (idx, definition) in definitions.iter().enumerate() {
+        progress.started(Operation::Mount, &definition.id);
+
+        // Besides the error, a description of what went wrong is kept (e.g. which step of mounting failed).
+        let result = match daemon_client {
+            Some(client) => client.mount(&definition.id).map_err(|err| {
+                let description = err.to_string();
+                (err, description)
+            }),
+            None if temporary => manager
+                .mount_temporary(definition)
+                .map_err(|report| describe_mount_failure(definition, report)),
+            None => manager
+                .mount_with_report(definition)
+                .map_err(|report| describe_mount_failure(definition, report)),
+        };
+
+        match result {
+            Err((err, description)) => {
+                progress.failed(Operation::Mount, &definition.id, &description);
+                results.push(OperationResult::failure(&definition.id, &description));
+
+                let language = Language::detect();
+
+                log::error!(
+                    "{0} {1}: {2:?}",
+                    i18n::translate_or(language, "message.mount_failed", "Failure mounting"),
+                    definition.id,
+                    err
+                );
+
+                if let SftpManError::DefinitionDisabled(_) = &err {
+                    log::error!(
+                        "{0}: enable it first (e.g. `sftpman update --id {0} --enabled true`)",
+                        definition.id
+                    );
+                }
+
+                if let SftpManError::AgentUnavailable(_, _)
+                | SftpManError::BandwidthLimiterMissing(_)
+                | SftpManError::ExtraMountPathOccupied(_, _)
+                | SftpManError::RequirementUnmet(_, _)
+                | SftpManError::ProgramMissing(_, _)
+                | SftpManError::FuseUnavailable(_, _)
+                | SftpManError::KeyFileUnreadable(_, _, _) = &err
+                {
+                    log::error!("{0}: {1}", definition.id, err);
+                }
+
+                if let SftpManError::HostResolutionFailed(_, _, _) = &err {
+                    log::error!(
+                        "{0}: if the host is only resolvable through a proxy configured elsewhere, skip resolving it via `sftpman update --id {0} --skip_host_resolution true`",
+                        definition.id
+                    );
+                }
+
+                if let SftpManError::MountPathNotEmpty(_, path) = &err {
+                    log::error!(
+                        "{0}: the files in {1} would be hidden while mounted. Move them elsewhere, or mount anyway via `sftpman update --id {0} --allow_non_empty_mount_path true`",
+                        definition.id,
+                        path.display()
+                    );
+                }
+
+                if let SftpManError::MountFailure(kind, _, _) = &err {
+                    log::error!(
+                        "{0}: {1}. {2}",
+                        definition.id,
+                        kind.localized_description(language),
+                        kind.localized_hint(language)
+                    );
+                }
+
+                all_good = false;
+
+                if fail_fast {
+                    skip_remaining(&definitions[idx + 1..], &definition.id, results);
+                    break;
+                }
+            }
+            Ok(outcome) => {
+                progress.succeeded(Operation::Mount, &definition.id);
+                results.push(OperationResult::success(&definition.id).with_outcome(outcome.code()));
+            }
         }
     }
 
     all_good
 }
+
+/// Turns a mount failure report into its main error and a description of what went wrong (e.g. which step of mounting failed),
+/// logging the failures along the way.
+fn describe_mount_failure(
+    definition: &FilesystemMountDefinition,
+    report: MountFailureReport,
+) -> (SftpManError, String) {
+    let description = report.to_string();
+
+    log::error!("{0}: {1} failed", definition.id, report.cause().step);
+
+    // Failures while cleaning up would otherwise go unnoticed.
+    for failure in report.failures.iter().skip(1) {
+        log::error!(
+            "{0}: {1} failed too: {2:?}",
+            definition.id,
+            failure.step,
+            failure.error
+        );
+    }
+
+    (report.into_error(), description)
+}
@@ -0,0 +1,157 @@
+//! Machine-readable output (`--output json`).
+//!
+//! In JSON mode, supported commands print a single JSON document (on a single line) to stdout once they're done.
+//! Log messages always go to stderr, so stdout stays machine-readable. The schema is stable:
+//!
+//! - `ls` - an array of `{"id": "my-fs", "mounted": true, "localMountPath": "/mnt/sshfs/my-fs", "enabled": true}` objects
+//! - `status` - an array of `{"id": "my-fs", "mounted": true, "health": "unresponsive", "details": "..", "lastMountedAt": 1700000000, "mountCount": 3}` objects
+//!   (`health` is `null` unless probing; otherwise one of `healthy`, `not_mounted`, `failed` or `unresponsive`;
+//!   `lastMountedAt` is a Unix timestamp, or `null` if the system was never mounted)
+//! - `mount`, `mount_all`, `umount`, `umount_all`, `rm` - an array of `{"id": "my-fs", "success": true, "error": null, "outcome": "unmounted_by_kill", "details": null}` objects
+//!   (one per system worked on; `error` is `null` on success; `outcome` is `null` on failure and for `rm`,
+//!   otherwise one of `already_mounted`, `mounted`, `already_unmounted`, `unmounted_cleanly` or `unmounted_by_kill`;
+//!   `mount_all` reports systems whose requirements are not met as `skipped`, with `details` telling why, e.g. `interface wg0 is down`;
+//!   with `--fail-fast`, the systems left alone after a failure are reported as `skipped` too;
+//!   `details` is `null` otherwise)
+//! - `pause`, `resume` - like the above, with `outcome` being one of `paused`, `already_paused`, `resumed` or `not_paused`
+//! - `prune` - like the above, with `outcome` being `removed` (or `would_remove` with `--dry-run`) and `details` telling why (e.g. `last mounted: 200d ago`)
+//! - `create`, `update` - `{"definition": {..}}` (the saved definition, as stored) on success,
+//!   or `{"validationErrors": [..]}` (see `FieldValidationError`) when validation fails
+//! - `validate` - an array of `{"path": "..", "id": "my-fs", "problems": [{"severity": "error", "message": ".."}]}` objects
+//!   (one per definition file; `id` is `null` if the file can't be parsed)
+//! - `verify` - an array of `{"id": "my-fs", "result": "auth_failed", "details": ".."}` objects
+//!   (`result` is one of `reachable`, `path_created`, `unreachable`, `auth_failed`, `path_missing`, `path_unreadable` or `skipped`; `details` may be `null`)
+//! - `import_rclone`, `import_bookmarks` - an array of `{"id": "my-fs", "result": "skipped", "details": ".."}` objects
+//!   (`result` is one of `imported`, `skipped` or `failed`; `details` is `null` when imported)
+//! - `accept_hostkey` - an array of `{"keyType": "ssh-ed25519", "fingerprint": "SHA256:..", "knownHostsLine": "..", "known": true}` objects
+//!   (`known` tells whether the key is in the known_hosts file, once done)
+//! - `config list` - an object mapping setting names to values
+//! - `config get` - the setting's value
+//! - `events` - instead of a single document, one `{"type": "mounted", "id": "my-fs"}` object per line, as events happen
+//!   (`type` is one of `mounted`, `unmounted`, `disconnected`, `definitionAdded`, `definitionChanged` or `definitionRemoved`)
+//!
+//! Other failures are only reported via the exit code and log messages.
+
+use clap::{Arg, ArgMatches};
+use serde::Serialize;
+
+use crate::daemon::protocol::MountStatus;
+use crate::model::MountState;
+
+pub const ARG_OUTPUT: &str = "output";
+
+const OUTPUT_PLAIN: &str = "plain";
+const OUTPUT_JSON: &str = "json";
+
+pub fn arg() -> Arg {
+    Arg::new(ARG_OUTPUT)
+        .global(true)
+        .long(ARG_OUTPUT)
+        .num_args(1)
+        .value_parser([OUTPUT_PLAIN, OUTPUT_JSON])
+        .default_value(OUTPUT_PLAIN)
+        .help("Output format. With json, commands print a single JSON document with their results to stdout (see the README for the schema)")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Output {
+    #[default]
+    Plain,
+    Json,
+}
+
+impl Output {
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        match matches.get_one::<String>(ARG_OUTPUT).map(|s| s.as_str()) {
+            Some(OUTPUT_JSON) => Self::Json,
+            _ => Self::Plain,
+        }
+    }
+
+    pub fn is_json(&self) -> bool {
+        *self == Self::Json
+    }
+
+    /// Prints the given value as JSON (in JSON mode only).
+    pub fn print_json<T: Serialize + ?Sized>(&self, value: &T) {
+        if !self.is_json() {
+            return;
+        }
+
+        println!(
+            "{0}",
+            serde_json::to_string(value).expect("Output should be serializable")
+        );
+    }
+
+    /// Prints the results of an operation on multiple systems (in JSON mode only).
+    pub fn print_results(&self, results: &[OperationResult]) {
+        self.print_json(results);
+    }
+
+    /// Prints the state of the given systems (in JSON mode only).
+    pub fn print_states(&self, states: &[MountState]) {
+        let list: Vec<MountStatus> = states
+            .iter()
+            .map(|state| MountStatus {
+                id: state.definition.id.clone(),
+                mounted: state.mounted,
+                local_mount_path: state.definition.local_mount_path().to_string(),
+                enabled: state.definition.enabled,
+            })
+            .collect();
+
+        self.print_json(&list);
+    }
+}
+
+/// The result of working on a single system, as part of an operation on multiple systems (e.g. `mount_all`).
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationResult {
+    pub id: String,
+
+    pub success: bool,
+
+    pub error: Option<String>,
+
+    /// What the operation did (e.g. `already_mounted`, see `MountOutcome` and `UmountOutcome`), if it succeeded and tells.
+    pub outcome: Option<String>,
+
+    /// Why the system was skipped (e.g. `interface wg0 is down`), when the outcome is `skipped`, or pruned (see `prune`).
+    pub details: Option<String>,
+}
+
+impl OperationResult {
+    pub fn success(id: &str) -> Self {
+        Self {
+            id: id.to_owned(),
+            success: true,
+            error: None,
+            outcome: None,
+            details: None,
+        }
+    }
+
+    /// Creates a successful result for a system which was not worked on (e.g. because its requirements are not met).
+    pub fn skipped(id: &str, details: &dyn std::fmt::Display) -> Self {
+        Self {
+            details: Some(details.to_string()),
+            ..Self::success(id).with_outcome("skipped")
+        }
+    }
+
+    pub fn with_outcome(mut self, outcome: &str) -> Self {
+        self.outcome = Some(outcome.to_owned());
+        self
+    }
+
+    pub fn failure(id: &str, error: &dyn std::fmt::Display) -> Self {
+        Self {
+            id: id.to_owned(),
+            success: false,
+            error: Some(error.to_string()),
+            outcome: None,
+            details: None,
+        }
+    }
+}
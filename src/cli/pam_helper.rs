@@ -0,0 +1,199 @@
+use std::env;
+use std::ffi::CString;
+use std::path::Path;
+
+use clap::{ArgMatches, Command};
+use nix::unistd::{Uid, User, getuid, initgroups, setgid, setuid};
+
+use crate::AuthType;
+use crate::manager::Manager;
+use crate::model::KillPolicy;
+
+use super::exit;
+
+/// `pam_exec` tells which stage of the PAM stack is running via this environment variable.
+const ENV_PAM_TYPE: &str = "PAM_TYPE";
+
+/// `pam_exec` passes the name of the user being logged in via this environment variable.
+const ENV_PAM_USER: &str = "PAM_USER";
+
+const DEFAULT_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+
+pub fn build() -> Command {
+    Command::new("pam_helper")
+        .about("Mounts filesystems marked with auto_mount when a session opens and unmounts them when it closes. Meant to be invoked by pam_exec (e.g. `session optional pam_exec.so /usr/bin/sftpman pam_helper`)")
+}
+
+pub fn run(_manager: &Manager, _matches: &ArgMatches) -> exit::Status {
+    let pam_type = env::var(ENV_PAM_TYPE).unwrap_or_default();
+
+    let is_opening = match pam_type.as_str() {
+        "open_session" => true,
+        "close_session" => false,
+        _ => {
+            log::debug!("Nothing to do for PAM type: {0:?}", pam_type);
+            return exit::Status::Success;
+        }
+    };
+
+    let Ok(user_name) = env::var(ENV_PAM_USER) else {
+        log::error!("The {0} environment variable is not set", ENV_PAM_USER);
+        return exit::Status::Failure;
+    };
+
+    let user = match User::from_name(&user_name) {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            log::error!("Unknown user: {0}", user_name);
+            return exit::Status::Failure;
+        }
+        Err(err) => {
+            log::error!("Failed to look up user {0}: {1}", user_name, err);
+            return exit::Status::Failure;
+        }
+    };
+
+    if let Err(err) = become_user(&user) {
+        log::error!("{0}", err);
+        return exit::Status::Failure;
+    }
+
+    // The manager passed to us was initialized for the invoking user (usually root),
+    // so a new one is created for the user whose session it is.
+    let manager = match Manager::new() {
+        Ok(manager) => manager,
+        Err(err) => {
+            log::error!("Failed to initialize for user {0}: {1:?}", user_name, err);
+            return exit::Status::Failure;
+        }
+    };
+
+    if is_opening {
+        mount_auto(&manager)
+    } else {
+        umount_auto(&manager)
+    }
+}
+
+/// Switches to the given user (if running as root) and sets up an environment suitable for them.
+fn become_user(user: &User) -> Result<(), String> {
+    if getuid().is_root() && !user.uid.is_root() {
+        let name = CString::new(user.name.as_str())
+            .map_err(|err| format!("Invalid user name {0}: {1}", user.name, err))?;
+
+        // The group-related calls must happen while still privileged.
+        initgroups(&name, user.gid)
+            .map_err(|err| format!("Failed to initialize groups for {0}: {1}", user.name, err))?;
+        setgid(user.gid)
+            .map_err(|err| format!("Failed to switch to group {0}: {1}", user.gid, err))?;
+        setuid(user.uid)
+            .map_err(|err| format!("Failed to switch to user {0}: {1}", user.name, err))?;
+    } else if getuid() != user.uid {
+        return Err(format!(
+            "Running as uid {0}, which can neither act as user {1}, nor switch to it",
+            getuid(),
+            user.name
+        ));
+    }
+
+    // SAFETY: this runs before any other threads get spawned.
+    unsafe {
+        env::set_var("HOME", &user.dir);
+        env::set_var("USER", &user.name);
+        env::set_var("LOGNAME", &user.name);
+
+        // Directories inherited from the invoking process may belong to another user.
+        for name in [
+            "XDG_CONFIG_HOME",
+            "XDG_DATA_HOME",
+            "XDG_STATE_HOME",
+            "XDG_CACHE_HOME",
+        ] {
+            env::remove_var(name);
+        }
+
+        let runtime_dir = runtime_dir_for_uid(user.uid);
+        if Path::new(&runtime_dir).is_dir() {
+            env::set_var("XDG_RUNTIME_DIR", runtime_dir);
+        } else {
+            env::remove_var("XDG_RUNTIME_DIR");
+        }
+
+        if env::var_os("PATH").is_none() {
+            env::set_var("PATH", DEFAULT_PATH);
+        }
+    }
+
+    Ok(())
+}
+
+fn runtime_dir_for_uid(uid: Uid) -> String {
+    format!("/run/user/{0}", uid)
+}
+
+fn mount_auto(manager: &Manager) -> exit::Status {
+    let mut exit_status = exit::Status::Success;
+
+    let paused_ids = manager.paused_ids().unwrap_or_else(|err| {
+        log::warn!("Failed to determine paused systems: {0:?}", err);
+        Default::default()
+    });
+
+    for state in manager.full_state().unwrap() {
+        let definition = state.definition;
+
+        if !definition.auto_mount || !definition.enabled || state.mounted {
+            continue;
+        }
+
+        if paused_ids.contains(&definition.id) {
+            log::info!("{0}: not mounting, because it's paused", definition.id);
+            continue;
+        }
+
+        if let Err(err) = manager.check_requirements(&definition) {
+            log::warn!("{0}: not mounting: {1}", definition.id, err);
+            continue;
+        }
+
+        // There's no terminal to prompt for a password on (unless the password is obtained from elsewhere).
+        if matches!(
+            definition.auth_type,
+            AuthType::Password | AuthType::KeyboardInteractive
+        ) && !definition.has_password_source()
+        {
+            log::warn!(
+                "{0}: not mounting, because {1} authentication requires interaction",
+                definition.id,
+                definition.auth_type.to_static_str()
+            );
+            continue;
+        }
+
+        if let Err(err) = manager.mount(&definition) {
+            log::error!("Failure mounting {0}: {1:?}", definition.id, err);
+            exit_status = exit::Status::Failure;
+        }
+    }
+
+    exit_status
+}
+
+fn umount_auto(manager: &Manager) -> exit::Status {
+    let mut exit_status = exit::Status::Success;
+
+    for state in manager.full_state().unwrap() {
+        let definition = state.definition;
+
+        if !definition.auto_mount || !state.mounted {
+            continue;
+        }
+
+        if let Err(err) = manager.umount_with_kill_policy(&definition, KillPolicy::default()) {
+            log::error!("Failure unmounting {0}: {1:?}", definition.id, err);
+            exit_status = exit::Status::Failure;
+        }
+    }
+
+    exit_status
+}
@@ -0,0 +1,99 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::errors::SftpManError;
+use crate::manager::Manager;
+use crate::model::FilesystemMountDefinition;
+use crate::utils::id_resolution::find_definition;
+
+use super::exit;
+use super::output::{OperationResult, Output};
+
+pub fn build_pause() -> Command {
+    Command::new("pause")
+        .about("Pauses the automatic management of the specified system or systems (e.g. while the server is down for maintenance): the daemon stops unmounting them when idle and mount_all --auto skips them, until resumed. They can still be mounted and unmounted explicitly")
+        .arg(Arg::new("id").num_args(1..).required(true))
+}
+
+pub fn build_resume() -> Command {
+    Command::new("resume")
+        .about("Resumes the automatic management of the specified system or systems, paused via the pause command")
+        .arg(Arg::new("id").num_args(1..).required(true))
+}
+
+/// Pauses the automatic management of the given filesystems by id.
+/// Returns exit::Status::Success if all pausing succeeded (or they were already paused).
+/// Returns exit::Status::DefinitionNotFound if at least one filesystem was not found.
+/// Returns exit::Status::Failure if at least one filesystem failed to be paused.
+pub fn run_pause(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    change(manager, matches, |definition| {
+        Ok(if manager.pause(definition)? {
+            log::info!("{0}: paused", definition.id);
+            "paused"
+        } else {
+            log::info!("{0}: already paused, nothing to do..", definition.id);
+            "already_paused"
+        })
+    })
+}
+
+/// Resumes the automatic management of the given filesystems by id.
+/// Returns exit::Status::Success if all resuming succeeded (or they were not paused).
+/// Returns exit::Status::DefinitionNotFound if at least one filesystem was not found.
+/// Returns exit::Status::Failure if at least one filesystem failed to be resumed.
+pub fn run_resume(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    change(manager, matches, |definition| {
+        Ok(if manager.resume(definition)? {
+            log::info!("{0}: resumed", definition.id);
+            "resumed"
+        } else {
+            log::info!("{0}: not paused, nothing to do..", definition.id);
+            "not_paused"
+        })
+    })
+}
+
+/// Applies the given change to each filesystem specified by id, reporting its outcome (e.g. `paused`).
+fn change(
+    manager: &Manager,
+    matches: &ArgMatches,
+    apply: impl Fn(&FilesystemMountDefinition) -> Result<&'static str, SftpManError>,
+) -> exit::Status {
+    let ids: Vec<&str> = matches
+        .get_many::<String>("id")
+        .expect("required")
+        .map(|s| s.as_str())
+        .collect();
+
+    let definitions = manager.definitions().unwrap();
+
+    let mut exit_status = exit::Status::Success;
+
+    let mut results: Vec<OperationResult> = Vec::new();
+
+    for id in ids {
+        let definition = match find_definition(&definitions, id) {
+            Ok(definition) => definition,
+            Err(err) => {
+                log::error!("Failed to find filesystem: {0}", err);
+                results.push(OperationResult::failure(id, &err));
+                exit_status = exit::Status::DefinitionNotFound;
+                continue;
+            }
+        };
+
+        match apply(definition) {
+            Ok(outcome) => {
+                results.push(OperationResult::success(&definition.id).with_outcome(outcome));
+            }
+            Err(err) => {
+                log::error!("Failure changing {0}: {1:?}", definition.id, err);
+                results.push(OperationResult::failure(&definition.id, &err));
+                exit_status = exit::Status::Failure;
+            }
+        }
+    }
+
+    Output::from_matches(matches).print_results(&results);
+
+    exit_status
+}
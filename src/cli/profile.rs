@@ -0,0 +1,102 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::manager::Manager;
+
+use super::exit;
+use super::output::Output;
+
+/// The global argument selecting the profile to use (see `Manager::for_profile()`), instead of the one switched to.
+pub const ARG_PROFILE: &str = "profile";
+
+const ARG_NAME: &str = "name";
+
+pub fn arg() -> Arg {
+    Arg::new(ARG_PROFILE)
+        .global(true)
+        .long(ARG_PROFILE)
+        .num_args(1)
+        .value_name("NAME")
+        .help("Profile to use (a separate set of systems, with its own configuration), instead of the one switched to via `sftpman profile switch`. Can also be set via SFTPMAN_PROFILE")
+}
+
+pub fn build() -> Command {
+    Command::new("profile")
+        .about("Manages profiles, which keep separate sets of systems (e.g. work and personal ones), each with its own configuration")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("list")
+                .visible_alias("ls")
+                .about("Lists the profiles, marking the one in use with *"),
+        )
+        .subcommand(
+            Command::new("create")
+                .about("Creates a profile, without any systems")
+                .arg(Arg::new(ARG_NAME).required(true)),
+        )
+        .subcommand(
+            Command::new("switch")
+                .about("Switches to the specified profile (`default` for the default one), which gets used from now on unless --profile (or SFTPMAN_PROFILE) says otherwise")
+                .arg(Arg::new(ARG_NAME).required(true)),
+        )
+}
+
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    match matches.subcommand() {
+        Some(("list", sub_matches)) => list(manager, Output::from_matches(sub_matches)),
+        Some(("create", sub_matches)) => {
+            let name = sub_matches.get_one::<String>(ARG_NAME).expect("required");
+
+            match manager.create_profile(name) {
+                Ok(()) => {
+                    log::info!(
+                        "Created profile {0}. Use it via `sftpman --profile {0} ..` or `sftpman profile switch {0}`",
+                        name
+                    );
+                    exit::Status::Success
+                }
+                Err(err) => {
+                    log::error!("{0}", err);
+                    exit::Status::Failure
+                }
+            }
+        }
+        Some(("switch", sub_matches)) => {
+            let name = sub_matches.get_one::<String>(ARG_NAME).expect("required");
+
+            match manager.switch_profile(name) {
+                Ok(()) => {
+                    log::info!("Switched to profile {0}", name);
+                    exit::Status::Success
+                }
+                Err(err) => {
+                    log::error!("{0}", err);
+                    exit::Status::Failure
+                }
+            }
+        }
+        _ => unreachable!("subcommand_required"),
+    }
+}
+
+fn list(manager: &Manager, output: Output) -> exit::Status {
+    let profiles = match manager.profiles() {
+        Ok(profiles) => profiles,
+        Err(err) => {
+            log::error!("Failed to list profiles: {0:?}", err);
+            return exit::Status::Failure;
+        }
+    };
+
+    if output.is_json() {
+        output.print_json(&profiles);
+        return exit::Status::Success;
+    }
+
+    for profile in profiles {
+        let marker = if profile.current { "*" } else { " " };
+        println!("{0} {1}", marker, profile.name);
+    }
+
+    exit::Status::Success
+}
@@ -0,0 +1,94 @@
+use clap::{Arg, ArgMatches};
+use serde::Serialize;
+
+pub const ARG_PROGRESS: &str = "progress";
+
+const PROGRESS_FORMAT_JSON: &str = "json";
+
+pub fn arg() -> Arg {
+    Arg::new(ARG_PROGRESS)
+        .long(ARG_PROGRESS)
+        .num_args(1)
+        .value_parser([PROGRESS_FORMAT_JSON])
+        .help("Print an event for each system being worked on to stdout, as it happens. With json, each event is a JSON object on its own line. Example: json")
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Operation {
+    Mount,
+    Umount,
+}
+
+impl Operation {
+    fn to_static_str(self) -> &'static str {
+        match self {
+            Self::Mount => "mount",
+            Self::Umount => "umount",
+        }
+    }
+}
+
+/// Reports the progress of operations on multiple systems (e.g. `mount_all`), for wrappers (e.g. GUIs) to render.
+///
+/// Events are only printed when requested via `--progress`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Progress {
+    json: bool,
+}
+
+#[derive(Serialize)]
+struct ProgressEvent<'a> {
+    event: &'static str,
+
+    operation: &'static str,
+
+    id: &'a str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Progress {
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        Self {
+            json: matches.get_one::<String>(ARG_PROGRESS).map(|s| s.as_str())
+                == Some(PROGRESS_FORMAT_JSON),
+        }
+    }
+
+    /// Tells if events get printed to stdout (in which case nothing else should be printed there).
+    pub fn is_enabled(&self) -> bool {
+        self.json
+    }
+
+    pub fn started(&self, operation: Operation, id: &str) {
+        self.emit("started", operation, id, None);
+    }
+
+    pub fn succeeded(&self, operation: Operation, id: &str) {
+        self.emit("succeeded", operation, id, None);
+    }
+
+    pub fn failed(&self, operation: Operation, id: &str, error: &dyn std::fmt::Display) {
+        self.emit("failed", operation, id, Some(error.to_string()));
+    }
+
+    fn emit(&self, event: &'static str, operation: Operation, id: &str, error: Option<String>) {
+        if !self.json {
+            return;
+        }
+
+        let event = ProgressEvent {
+            event,
+            operation: operation.to_static_str(),
+            id,
+            error,
+        };
+
+        // Stdout is line-buffered, so each event reaches the reader as soon as it's printed.
+        println!(
+            "{0}",
+            serde_json::to_string(&event).expect("Progress events should be serializable")
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::io::{BufRead, IsTerminal, Write};
+use std::time::Duration;
+
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
+
+use crate::manager::Manager;
+use crate::model::FilesystemMountDefinition;
+use crate::utils::network::has_default_route;
+
+use super::exit;
+use super::ls::format_last_mounted;
+use super::output::{OperationResult, Output};
+
+const ARG_UNUSED_FOR: &str = "unused-for";
+const ARG_UNRESOLVABLE: &str = "unresolvable";
+const ARG_DRY_RUN: &str = "dry-run";
+const ARG_YES: &str = "yes";
+
+pub fn build() -> Command {
+    Command::new("prune")
+        .about("Lists the systems which look abandoned and, once confirmed, removes them. Systems which are currently mounted are never pruned")
+        .arg(
+            Arg::new(ARG_UNUSED_FOR)
+                .long(ARG_UNUSED_FOR)
+                .num_args(1)
+                .value_parser(parse_duration)
+                .help("Prune systems which haven't been mounted for this long (s, m, h, d or w suffixed). Systems which were never mounted count from when their definition was last changed. Example: 180d"),
+        )
+        .arg(
+            Arg::new(ARG_UNRESOLVABLE)
+                .long(ARG_UNRESOLVABLE)
+                .action(ArgAction::SetTrue)
+                .help("Prune systems whose host no longer resolves. When combined with --unused-for, both need to apply"),
+        )
+        .group(
+            ArgGroup::new("criteria")
+                .args([ARG_UNUSED_FOR, ARG_UNRESOLVABLE])
+                .multiple(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new(ARG_DRY_RUN)
+                .long(ARG_DRY_RUN)
+                .action(ArgAction::SetTrue)
+                .help("Only list the systems which would be pruned"),
+        )
+        .arg(
+            Arg::new(ARG_YES)
+                .long(ARG_YES)
+                .action(ArgAction::SetTrue)
+                .conflicts_with(ARG_DRY_RUN)
+                .help("Remove the systems without asking"),
+        )
+}
+
+/// Finds the systems matching all of the given criteria and removes them (after confirmation).
+/// Returns exit::Status::Success if there was nothing to prune, or if everything got listed (dry run) or removed.
+/// Returns exit::Status::Failure if finding the systems failed, removal was not confirmed or at least one removal failed.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let output = Output::from_matches(matches);
+    let unresolvable = matches.get_flag(ARG_UNRESOLVABLE);
+
+    if unresolvable && !has_default_route() {
+        // Nothing resolves while offline, which would make every system look abandoned.
+        log::error!("The network seems to be down, so host resolution can't be relied on");
+        return exit::Status::Failure;
+    }
+
+    let candidates = match matches.get_one::<Duration>(ARG_UNUSED_FOR) {
+        Some(unused_for) => manager.unused_definitions(*unused_for),
+        None => manager.definitions().map(|definitions| {
+            definitions
+                .into_iter()
+                .filter(|definition| !manager.is_definition_mounted(definition).unwrap_or(true))
+                .collect()
+        }),
+    };
+
+    let candidates = match candidates {
+        Ok(candidates) => candidates,
+        Err(err) => {
+            log::error!("Failed to find the systems to prune: {0:?}", err);
+            return exit::Status::Failure;
+        }
+    };
+
+    let usage_stats = manager.usage_stats().unwrap_or_else(|err| {
+        log::warn!("Failed to load the usage stats: {0:?}", err);
+        HashMap::new()
+    });
+
+    // Each system to prune, together with why.
+    let mut prunable: Vec<(&FilesystemMountDefinition, String)> = Vec::new();
+
+    for definition in &candidates {
+        let mut reasons: Vec<String> = Vec::new();
+
+        if matches.contains_id(ARG_UNUSED_FOR) {
+            reasons.push(format!(
+                "last mounted: {0}",
+                format_last_mounted(usage_stats.get(&definition.id))
+            ));
+        }
+
+        if unresolvable {
+            match manager.does_host_resolve(definition) {
+                Ok(false) => reasons.push(format!("host {0} does not resolve", definition.host)),
+                Ok(true) => continue,
+                Err(err) => {
+                    log::warn!(
+                        "{0}: failed to check whether the host resolves: {1:?}",
+                        definition.id,
+                        err
+                    );
+                    continue;
+                }
+            }
+        }
+
+        prunable.push((definition, reasons.join(", ")));
+    }
+
+    if prunable.is_empty() {
+        log::info!("Nothing to prune");
+        output.print_results(&[]);
+        return exit::Status::Success;
+    }
+
+    if !output.is_json() {
+        println!("Systems to prune:");
+        for (definition, reasons) in &prunable {
+            println!("  {0} ({1})", definition.id, reasons);
+        }
+    }
+
+    if matches.get_flag(ARG_DRY_RUN) {
+        let results: Vec<OperationResult> = prunable
+            .iter()
+            .map(|(definition, reasons)| OperationResult {
+                details: Some(reasons.clone()),
+                ..OperationResult::success(&definition.id).with_outcome("would_remove")
+            })
+            .collect();
+
+        output.print_results(&results);
+        return exit::Status::Success;
+    }
+
+    if !matches.get_flag(ARG_YES) && !confirm(prunable.len()) {
+        log::error!("Removal was not confirmed");
+        return exit::Status::Failure;
+    }
+
+    let mut exit_status = exit::Status::Success;
+    let mut results: Vec<OperationResult> = Vec::new();
+
+    for (definition, reasons) in &prunable {
+        match manager.remove(definition) {
+            Ok(()) => {
+                log::info!("{0}: removed", definition.id);
+                results.push(OperationResult {
+                    details: Some(reasons.clone()),
+                    ..OperationResult::success(&definition.id).with_outcome("removed")
+                });
+            }
+            Err(err) => {
+                log::error!("Failure removing {0}: {1:?}", definition.id, err);
+                results.push(OperationResult::failure(&definition.id, &err));
+                exit_status = exit::Status::Failure;
+            }
+        }
+    }
+
+    output.print_results(&results);
+
+    exit_status
+}
+
+/// Parses a duration like `180d` (supported units being `s`, `m`, `h`, `d` and `w`).
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let unit_idx = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or("Expected a unit (s, m, h, d or w) after the number. Example: 180d")?;
+
+    let (amount, unit) = value.split_at(unit_idx);
+
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| "Expected a number followed by a unit. Example: 180d".to_owned())?;
+
+    let seconds_per_unit: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => {
+            return Err(format!(
+                "Unknown unit: {0} (expected s, m, h, d or w)",
+                unit
+            ));
+        }
+    };
+
+    Ok(Duration::from_secs(amount.saturating_mul(seconds_per_unit)))
+}
+
+/// Asks whether the systems should be removed. Without a terminal to ask on, they're not.
+fn confirm(count: usize) -> bool {
+    if !std::io::stdin().is_terminal() {
+        log::error!(
+            "Not running in a terminal, so there's no way to confirm. Check the list with --dry-run and pass --yes to remove them"
+        );
+        return false;
+    }
+
+    print!("Remove these {0} systems? (y/N): ", count);
+    let _ = std::io::stdout().flush();
+
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
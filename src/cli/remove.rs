@@ -1,8 +1,10 @@
 use clap::{Arg, ArgMatches, Command};
 
+use crate::utils::id_resolution::find_definition;
 use crate::{manager::Manager, model::FilesystemMountDefinition};
 
 use super::exit;
+use super::output::{OperationResult, Output};
 
 pub fn build() -> Command {
     Command::new("rm")
@@ -17,50 +19,60 @@ pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
         .map(|s| s.as_str())
         .collect();
 
-    remove(manager, &ids)
+    remove(manager, &ids, Output::from_matches(matches))
 }
 
 /// Removes the given filesystems by id.
 /// Returns exit::Status::Success if all removing succeeded.
 /// Returns exit::Status::DefinitionNotFound if at least one filesystem was not found.
 /// Returns exit::Status::Failure if at least one filesystem failed to be removed.
-pub fn remove(manager: &Manager, ids: &Vec<&str>) -> exit::Status {
+pub fn remove(manager: &Manager, ids: &Vec<&str>, output: Output) -> exit::Status {
     let definitions = manager.definitions().unwrap();
 
     let mut exit_status = exit::Status::Success;
 
+    let mut results: Vec<OperationResult> = Vec::new();
+
     let mut definitions_to_work_on: Vec<&FilesystemMountDefinition> = Vec::new();
 
     for id in ids {
-        let definition_or_none = definitions.iter().find(|&x| &x.id == id);
-
-        match definition_or_none {
-            None => {
-                log::error!("Failed to find filesystem with an id of: {0}", id);
+        match find_definition(&definitions, id) {
+            Err(err) => {
+                log::error!("Failed to find filesystem: {0}", err);
+                results.push(OperationResult::failure(id, &err));
                 exit_status = exit::Status::DefinitionNotFound;
             }
 
-            Some(definition) => {
+            Ok(definition) => {
                 definitions_to_work_on.push(definition);
             }
         };
     }
 
-    if !remove_definitions(manager, &definitions_to_work_on) {
+    if !remove_definitions(manager, &definitions_to_work_on, &mut results) {
         exit_status = exit::Status::Failure
     }
 
+    output.print_results(&results);
+
     exit_status
 }
 
-/// Removes the given filesystems.
-fn remove_definitions(manager: &Manager, definitions: &Vec<&FilesystemMountDefinition>) -> bool {
+/// Removes the given filesystems, recording the result for each into `results`.
+fn remove_definitions(
+    manager: &Manager,
+    definitions: &Vec<&FilesystemMountDefinition>,
+    results: &mut Vec<OperationResult>,
+) -> bool {
     let mut all_good = true;
 
     for definition in definitions {
         if let Err(err) = manager.remove(definition) {
             log::error!("Failure removing {0}: {1:?}", definition.id, err);
+            results.push(OperationResult::failure(&definition.id, &err));
             all_good = false
+        } else {
+            results.push(OperationResult::success(&definition.id));
         }
     }
 
@@ -8,20 +8,50 @@ use super::preflight_check::preflight_check;
 pub fn run(manager: &Manager, arg_matches: &ArgMatches) -> exit::Status {
     match arg_matches.subcommand() {
         Some(("ls", sub_matches)) => super::ls::run(manager, sub_matches),
+        Some(("status", sub_matches)) => super::status::run(manager, sub_matches),
 
         Some(("mount", sub_matches)) => super::mount::run(manager, sub_matches),
-        Some(("mount_all", _sub_matches)) => super::mount::run_mount_all(manager),
+        Some(("mount_all", sub_matches)) => super::mount::run_mount_all(manager, sub_matches),
 
         Some(("umount", sub_matches)) => super::umount::run(manager, sub_matches),
-        Some(("umount_all", _sub_matches)) => super::umount::run_umount_all(manager),
+        Some(("umount_all", sub_matches)) => super::umount::run_umount_all(manager, sub_matches),
+        Some(("umount_idle", _sub_matches)) => super::umount::run_umount_idle(manager),
+        Some(("gc", _sub_matches)) => super::umount::run_gc(manager),
+        Some(("lsof", sub_matches)) => super::lsof::run(manager, sub_matches),
 
         Some(("preflight_check", _sub_matches)) => preflight_check(manager),
 
         Some(("rm", sub_matches)) => super::remove::run(manager, sub_matches),
+        Some(("prune", sub_matches)) => super::prune::run(manager, sub_matches),
 
         Some(("create", sub_matches)) => super::create_update::run_create(manager, sub_matches),
         Some(("update", sub_matches)) => super::create_update::run_update(manager, sub_matches),
 
+        Some(("supervise", sub_matches)) => super::supervise::run(manager, sub_matches),
+        Some(("daemon", sub_matches)) => super::daemon::run(manager, sub_matches),
+        Some(("browse", sub_matches)) => super::browse::run(manager, sub_matches),
+        Some(("bench", sub_matches)) => super::bench::run(manager, sub_matches),
+        Some(("pam_helper", sub_matches)) => super::pam_helper::run(manager, sub_matches),
+        Some(("automount_units", sub_matches)) => super::automount_units::run(manager, sub_matches),
+        Some(("desktop", sub_matches)) => super::desktop::run(manager, sub_matches),
+        Some(("config", sub_matches)) => super::config::run(manager, sub_matches),
+        Some(("sync", sub_matches)) => super::sync::run(manager, sub_matches),
+        Some(("validate", sub_matches)) => super::validate::run(manager, sub_matches),
+        Some(("verify", sub_matches)) => super::verify::run(manager, sub_matches),
+        Some(("cp", sub_matches)) => super::cp::run(manager, sub_matches),
+        Some(("events", sub_matches)) => super::events::run(manager, sub_matches),
+        Some(("check", sub_matches)) => super::check::run(manager, sub_matches),
+        Some(("import_rclone", sub_matches)) => super::import::run_rclone(manager, sub_matches),
+        Some(("import_bookmarks", sub_matches)) => {
+            super::import::run_bookmarks(manager, sub_matches)
+        }
+        Some(("setup", sub_matches)) => super::setup::run(manager, sub_matches),
+        Some(("pause", sub_matches)) => super::pause::run_pause(manager, sub_matches),
+        Some(("resume", sub_matches)) => super::pause::run_resume(manager, sub_matches),
+        Some(("accept_hostkey", sub_matches)) => super::accept_hostkey::run(manager, sub_matches),
+        Some(("keygen", sub_matches)) => super::keygen::run(manager, sub_matches),
+        Some(("profile", sub_matches)) => super::profile::run(manager, sub_matches),
+
         Some((cmd, _)) => {
             log::error!(
                 "Unknown subcommand {0}. Try removing it and running --help",
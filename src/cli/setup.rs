@@ -0,0 +1,411 @@
+use std::io::{BufRead, IsTerminal, Lines, StdinLock, Write};
+use std::path::PathBuf;
+use std::process::Command as ProcessCommand;
+
+use clap::{ArgMatches, Command};
+
+use crate::auth_type::AuthType;
+use crate::errors::PreflightCheckError;
+use crate::manager::Manager;
+use crate::model::{DEFAULT_MOUNT_PATH_PREFIX, FilesystemMountDefinition, HostCandidate};
+
+use super::create_update::{abort_with_validation_errors, status_for_persist_outcome};
+use super::exit;
+use super::output::Output;
+
+pub fn build() -> Command {
+    Command::new("setup")
+        .about("Guides you through getting started: checks the prerequisites (offering to fix the default mount path), then walks you through creating (and mounting) your first SFTP system, suggesting hosts from your SSH configuration and keys from ~/.ssh")
+}
+
+/// Reads answers to questions from stdin.
+struct Prompter {
+    input: Lines<StdinLock<'static>>,
+}
+
+impl Prompter {
+    /// Asks a question, returning the answer (or the default, if the answer is empty).
+    /// Returns `None` if stdin got closed.
+    fn ask(&mut self, question: &str, default: Option<&str>) -> Option<String> {
+        match default {
+            Some(default) if !default.is_empty() => print!("{0} [{1}]: ", question, default),
+            _ => print!("{0}: ", question),
+        }
+        let _ = std::io::stdout().flush();
+
+        let answer = self.input.next()?.ok()?;
+        let answer = answer.trim();
+
+        if answer.is_empty() {
+            return Some(default.unwrap_or_default().to_owned());
+        }
+
+        Some(answer.to_owned())
+    }
+
+    /// Asks a yes/no question. Returns `None` if stdin got closed.
+    fn confirm(&mut self, question: &str, default: bool) -> Option<bool> {
+        let hint = if default { "Y/n" } else { "y/N" };
+
+        loop {
+            let answer = self.ask(&format!("{0} ({1})", question, hint), None)?;
+
+            match answer.to_lowercase().as_str() {
+                "" => return Some(default),
+                "y" | "yes" => return Some(true),
+                "n" | "no" => return Some(false),
+                _ => println!("Please answer with y or n"),
+            }
+        }
+    }
+}
+
+/// Walks the user through checking the prerequisites and creating their first definition.
+/// Returns exit::Status::Success if a definition got created (and, if requested, mounted).
+/// Returns exit::Status::ValidationFailure if the entered definition is invalid.
+/// Returns exit::Status::Failure if not running interactively, the input ended early or saving (or mounting) failed.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    if !std::io::stdin().is_terminal() {
+        log::error!(
+            "The setup is interactive, so it needs to be run in a terminal. See `sftpman create --help` for creating definitions non-interactively"
+        );
+        return exit::Status::Failure;
+    }
+
+    let mut prompter = Prompter {
+        input: std::io::stdin().lock().lines(),
+    };
+
+    match setup(manager, matches, &mut prompter) {
+        Some(status) => status,
+        None => {
+            println!();
+            log::error!("Setup aborted");
+            exit::Status::Failure
+        }
+    }
+}
+
+fn setup(manager: &Manager, matches: &ArgMatches, prompter: &mut Prompter) -> Option<exit::Status> {
+    println!("Checking the prerequisites..");
+
+    let default_mount_path_usable = check_prerequisites(manager, prompter)?;
+
+    println!();
+    println!("Let's create your first SFTP system.");
+
+    let hosts = manager.discover_hosts().unwrap_or_else(|err| {
+        log::warn!(
+            "Failed to look for hosts in your SSH configuration: {0:?}",
+            err
+        );
+        Vec::new()
+    });
+
+    let candidate = pick_host(prompter, &hosts)?;
+
+    let host = match &candidate {
+        Some(candidate) => candidate.host.clone(),
+        None => loop {
+            let host = prompter.ask("Host name or IP address of the remote machine", None)?;
+            if !host.is_empty() {
+                break host;
+            }
+        },
+    };
+
+    let default_user = candidate
+        .as_ref()
+        .and_then(|candidate| candidate.user.clone())
+        .or_else(|| std::env::var("USER").ok());
+    let user = prompter.ask("User to log in as", default_user.as_deref())?;
+
+    let default_port = candidate
+        .as_ref()
+        .and_then(|candidate| candidate.port)
+        .unwrap_or(22)
+        .to_string();
+    let port = loop {
+        match prompter
+            .ask("SSH port", Some(&default_port))?
+            .parse::<u16>()
+        {
+            Ok(port) => break port,
+            Err(_) => println!("Please enter a port number (e.g. 22)"),
+        }
+    };
+
+    let remote_path = prompter.ask(
+        "Remote directory to mount (e.g. /home/user or /storage)",
+        Some(&format!("/home/{0}", user)),
+    )?;
+
+    let (auth_type, ssh_key) = pick_authentication(manager, prompter)?;
+
+    let default_id = candidate
+        .as_ref()
+        .map(|candidate| candidate.name.clone())
+        .unwrap_or_else(|| host.clone())
+        .replace(
+            |c: char| !c.is_alphanumeric() && c != '_' && c != '-' && c != '.',
+            "-",
+        );
+    let id = prompter.ask(
+        "Name (ID) for this system, used in commands like `sftpman mount NAME`",
+        Some(&default_id),
+    )?;
+
+    // When the default mount path can't be used, mounting there would fail, so a directory in the home directory is used instead.
+    let mount_dest_path = if default_mount_path_usable {
+        None
+    } else {
+        directories::BaseDirs::new().map(|base_dirs| {
+            base_dirs
+                .home_dir()
+                .join("sshfs")
+                .join(&id)
+                .display()
+                .to_string()
+        })
+    };
+
+    let mut builder = FilesystemMountDefinition::builder(&id, &host, &user, &remote_path)
+        .with_port(port)
+        .with_auth_type(auth_type)
+        .with_ssh_key(&ssh_key);
+
+    if let Some(path) = &mount_dest_path {
+        builder = builder.with_mount_dest_path(path);
+    }
+
+    let mut definition = match builder.build() {
+        Ok(definition) => definition,
+        Err(errors) => {
+            return Some(abort_with_validation_errors(
+                errors,
+                Output::from_matches(matches),
+            ));
+        }
+    };
+
+    manager.apply_global_defaults(&mut definition);
+
+    if manager.definition(&definition.id).is_ok() {
+        log::error!(
+            "There already is a definition with an id of: {0}. Run the setup again and pick another name",
+            definition.id
+        );
+        return Some(exit::Status::DefinitionAlreadyExists);
+    }
+
+    let outcome = match manager.persist(&definition) {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            log::error!(
+                "{0}: failed to save the definition: {1:?}",
+                definition.id,
+                err
+            );
+            return Some(exit::Status::Failure);
+        }
+    };
+
+    let status = status_for_persist_outcome(&definition.id, &outcome);
+    if !matches!(status, exit::Status::Success) {
+        return Some(status);
+    }
+
+    println!();
+    println!(
+        "Saved {0}. You can change it later via `sftpman update --id {0} ..`",
+        definition.id
+    );
+
+    if !prompter.confirm("Mount it now?", true)? {
+        println!(
+            "Mount it whenever you like via `sftpman mount {0}`",
+            definition.id
+        );
+        return Some(exit::Status::Success);
+    }
+
+    match manager.mount(&definition) {
+        Ok(_) => {
+            println!(
+                "Mounted at {0}. Unmount it via `sftpman umount {1}`",
+                definition.local_mount_path(),
+                definition.id
+            );
+            Some(exit::Status::Success)
+        }
+        Err(err) => {
+            log::error!("{0}: failed to mount: {1:?}", definition.id, err);
+            log::error!(
+                "The definition is saved, so you can fix it via `sftpman update --id {0} ..` and try mounting again via `sftpman mount {0}`",
+                definition.id
+            );
+            Some(exit::Status::Failure)
+        }
+    }
+}
+
+/// Runs the preflight check, reporting any problems and offering to fix the default mount path.
+/// Returns whether the default mount path can be used.
+fn check_prerequisites(manager: &Manager, prompter: &mut Prompter) -> Option<bool> {
+    let errors = match manager.preflight_check() {
+        Ok(()) => {
+            println!("All checks pass!");
+            return Some(true);
+        }
+        Err(errors) => errors,
+    };
+
+    let mut default_mount_path_usable = true;
+
+    for err in &errors {
+        match err {
+            PreflightCheckError::CommandExecution(cmd, _)
+            | PreflightCheckError::CommandUnsuccessful(cmd, _) => {
+                println!("- {0}: {1}", err, cmd.get_program().to_string_lossy());
+            }
+            PreflightCheckError::AgentUnavailable(id, reason)
+            | PreflightCheckError::ConflictingMountManager(id, reason) => {
+                println!("- {0} ({1}: {2})", err, id, reason);
+            }
+            _ => println!("- {0}", err),
+        }
+
+        if matches!(
+            err,
+            PreflightCheckError::DefaultBasePathIO(_, _)
+                | PreflightCheckError::TestUnderBasePathIO(_, _)
+        ) {
+            default_mount_path_usable = false;
+        }
+    }
+
+    if default_mount_path_usable {
+        println!("You can continue, but mounting may fail until these problems are fixed.");
+        return Some(true);
+    }
+
+    // Creating the directory requires elevated privileges, but the user needs to be able to create mount points in it.
+    let user = std::env::var("USER").unwrap_or_else(|_| "root".to_owned());
+    let mut cmd = ProcessCommand::new("sudo");
+    cmd.args([
+        "install",
+        "-d",
+        "-o",
+        &user,
+        "-m",
+        "0755",
+        DEFAULT_MOUNT_PATH_PREFIX,
+    ]);
+
+    println!();
+    println!(
+        "Systems get mounted under {0} by default, which you need to be able to create directories in.",
+        DEFAULT_MOUNT_PATH_PREFIX
+    );
+
+    if !prompter.confirm(
+        &format!(
+            "Create {0} (owned by {1}) by running `sudo install -d -o {1} -m 0755 {0}`?",
+            DEFAULT_MOUNT_PATH_PREFIX, user
+        ),
+        true,
+    )? {
+        println!("Your first system will be mounted under your home directory instead.");
+        return Some(false);
+    }
+
+    match cmd.status() {
+        Ok(status) if status.success() => {
+            println!("Created {0}", DEFAULT_MOUNT_PATH_PREFIX);
+            Some(true)
+        }
+        Ok(status) => {
+            log::warn!(
+                "Creating {0} failed ({1})",
+                DEFAULT_MOUNT_PATH_PREFIX,
+                status
+            );
+            println!("Your first system will be mounted under your home directory instead.");
+            Some(false)
+        }
+        Err(err) => {
+            log::warn!("Failed to run sudo: {0}", err);
+            println!("Your first system will be mounted under your home directory instead.");
+            Some(false)
+        }
+    }
+}
+
+/// Lets the user pick one of the hosts found in the SSH configuration, or enter another one (`None`).
+fn pick_host(prompter: &mut Prompter, hosts: &[HostCandidate]) -> Option<Option<HostCandidate>> {
+    if hosts.is_empty() {
+        return Some(None);
+    }
+
+    println!();
+    println!("Hosts found in your SSH configuration:");
+    for (idx, candidate) in hosts.iter().enumerate() {
+        if candidate.name == candidate.host {
+            println!("  {0:>3}) {1}", idx + 1, candidate.name);
+        } else {
+            println!(
+                "  {0:>3}) {1} ({2})",
+                idx + 1,
+                candidate.name,
+                candidate.host
+            );
+        }
+    }
+
+    loop {
+        let choice = prompter.ask(
+            "Enter a number to pick a host, or leave empty to enter another one",
+            None,
+        )?;
+
+        if choice.is_empty() {
+            return Some(None);
+        }
+
+        match choice.parse::<usize>() {
+            Ok(number) if number >= 1 && number <= hosts.len() => {
+                return Some(Some(hosts[number - 1].clone()));
+            }
+            _ => println!("Invalid choice: {0}", choice),
+        }
+    }
+}
+
+/// Lets the user pick one of the SSH keys found in `~/.ssh`, falling back to the SSH agent.
+fn pick_authentication(manager: &Manager, prompter: &mut Prompter) -> Option<(AuthType, String)> {
+    let keys: Vec<PathBuf> = manager.discover_ssh_keys().unwrap_or_else(|err| {
+        log::warn!("Failed to look for SSH keys: {0:?}", err);
+        Vec::new()
+    });
+
+    println!();
+    println!("How to authenticate:");
+    println!("    0) Leave it to the SSH agent");
+    for (idx, key) in keys.iter().enumerate() {
+        println!("  {0:>3}) SSH key {1}", idx + 1, key.display());
+    }
+
+    let default_choice = if keys.is_empty() { "0" } else { "1" };
+
+    loop {
+        let choice = prompter.ask("Enter a number", Some(default_choice))?;
+
+        match choice.parse::<usize>() {
+            Ok(0) => return Some((AuthType::AuthenticationAgent, String::new())),
+            Ok(number) if number <= keys.len() => {
+                return Some((AuthType::PublicKey, keys[number - 1].display().to_string()));
+            }
+            _ => println!("Invalid choice: {0}", choice),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use nix::sys::signal::{SaFlags, SigAction, SigHandler, SigSet, Signal, sigaction};
+
+/// Becomes `true` when a stop signal (see `install_stop_signal_handlers()`) is received.
+pub static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handle_stop_signal(_signal: nix::libc::c_int) {
+    STOP_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Makes `SIGTERM`, `SIGINT` and `SIGHUP` set `STOP_REQUESTED`, instead of terminating the process.
+///
+/// Meant for long-running commands, which need to clean up before exiting.
+pub fn install_stop_signal_handlers() -> nix::Result<()> {
+    let action = SigAction::new(
+        SigHandler::Handler(handle_stop_signal),
+        SaFlags::empty(),
+        SigSet::empty(),
+    );
+
+    for signal in [Signal::SIGTERM, Signal::SIGINT, Signal::SIGHUP] {
+        // Safety: the handler only stores to an atomic, which is async-signal-safe.
+        unsafe { sigaction(signal, &action) }?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::json;
+
+use crate::manager::Manager;
+use crate::model::{MountState, ProbeOutcome, UsageStats};
+use crate::utils::id_resolution::resolve_id;
+
+use super::exit;
+use super::ls::format_last_mounted;
+use super::output::Output;
+
+const ARG_ID: &str = "id";
+const ARG_PROBE: &str = "probe";
+const ARG_PROBE_WRITE: &str = "probe-write";
+
+pub fn build() -> Command {
+    Command::new("status")
+        .about("Shows whether the specified (or all) SFTP systems are mounted (and when they last were) and, optionally, whether their mounts actually work")
+        .arg(Arg::new(ARG_ID).num_args(1..))
+        .arg(
+            Arg::new(ARG_PROBE)
+                .long(ARG_PROBE)
+                .action(ArgAction::SetTrue)
+                .help("Check that mounted systems respond, by listing their mount point. Tells healthy mounts from ones which are mounted but dead")
+        )
+        .arg(
+            Arg::new(ARG_PROBE_WRITE)
+                .long(ARG_PROBE_WRITE)
+                .action(ArgAction::SetTrue)
+                .help("Like --probe, but also create (and delete) a temporary file in the mount point")
+        )
+}
+
+/// Shows the state of the given (or all) filesystems.
+/// Returns exit::Status::Success unless probing was requested and at least one mounted filesystem failed the probe (exit::Status::Failure).
+/// Returns exit::Status::DefinitionNotFound if at least one filesystem was not found.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let probe_write = matches.get_flag(ARG_PROBE_WRITE);
+    let probe = probe_write || matches.get_flag(ARG_PROBE);
+
+    let mut exit_status = exit::Status::Success;
+
+    let mut states = match manager.full_state() {
+        Ok(states) => states,
+        Err(err) => {
+            log::error!("Failed to determine the mount state: {0:?}", err);
+            return exit::Status::Failure;
+        }
+    };
+
+    if let Some(queries) = matches.get_many::<String>(ARG_ID) {
+        let all_ids: Vec<&str> = states
+            .iter()
+            .map(|state| state.definition.id.as_str())
+            .collect();
+
+        let mut ids: Vec<String> = Vec::new();
+
+        for query in queries {
+            match resolve_id(query, &all_ids) {
+                Ok(id) => ids.push(id.to_owned()),
+                Err(err) => {
+                    log::error!("Failed to find filesystem: {0}", err);
+                    exit_status = exit::Status::DefinitionNotFound;
+                }
+            }
+        }
+
+        states.retain(|state| ids.contains(&state.definition.id));
+    }
+
+    let outcomes: Vec<Option<ProbeOutcome>> = states
+        .iter()
+        .map(|state| {
+            if !probe || !state.mounted {
+                return None;
+            }
+
+            match manager.probe(&state.definition, probe_write) {
+                Ok(outcome) => Some(outcome),
+                Err(err) => Some(ProbeOutcome::Failed(err.to_string())),
+            }
+        })
+        .collect();
+
+    let usage_stats = match manager.usage_stats() {
+        Ok(usage_stats) => usage_stats,
+        Err(err) => {
+            log::warn!("Failed to load the usage stats: {0:?}", err);
+            HashMap::new()
+        }
+    };
+
+    let output = Output::from_matches(matches);
+
+    if output.is_json() {
+        print_statuses_json(output, &states, &outcomes, &usage_stats);
+    } else {
+        print_statuses(&states, &outcomes, &usage_stats, probe);
+    }
+
+    if matches!(exit_status, exit::Status::Success)
+        && outcomes
+            .iter()
+            .flatten()
+            .any(|outcome| outcome.is_failure())
+    {
+        exit_status = exit::Status::Failure;
+    }
+
+    exit_status
+}
+
+fn print_statuses(
+    states: &[MountState],
+    outcomes: &[Option<ProbeOutcome>],
+    usage_stats: &HashMap<String, UsageStats>,
+    probe: bool,
+) {
+    let id_width = states
+        .iter()
+        .map(|state| state.definition.id.len())
+        .chain(std::iter::once("ID".len()))
+        .max()
+        .unwrap_or_default();
+
+    let state_width = "unmounted".len();
+    let last_mounted_width = "LAST MOUNTED".len();
+
+    if !probe {
+        println!(
+            "{0:id_width$}  {1:state_width$}  LAST MOUNTED",
+            "ID", "STATE"
+        );
+
+        for state in states {
+            println!(
+                "{0:id_width$}  {1:state_width$}  {2}",
+                state.definition.id,
+                state_label(state),
+                format_last_mounted(usage_stats.get(&state.definition.id))
+            );
+        }
+
+        return;
+    }
+
+    let health_width = outcomes
+        .iter()
+        .flatten()
+        .map(|outcome| outcome.to_string().len())
+        .chain(std::iter::once("HEALTH".len()))
+        .max()
+        .unwrap_or_default();
+
+    println!(
+        "{0:id_width$}  {1:state_width$}  {2:last_mounted_width$}  {3:health_width$}  DETAILS",
+        "ID", "STATE", "LAST MOUNTED", "HEALTH"
+    );
+
+    for (state, outcome) in states.iter().zip(outcomes) {
+        let health = outcome
+            .as_ref()
+            .map(|outcome| outcome.to_string())
+            .unwrap_or_else(|| "-".to_owned());
+
+        let details = outcome
+            .as_ref()
+            .and_then(|outcome| outcome.details())
+            .unwrap_or_default();
+
+        let line = format!(
+            "{0:id_width$}  {1:state_width$}  {2:last_mounted_width$}  {3:health_width$}  {4}",
+            state.definition.id,
+            state_label(state),
+            format_last_mounted(usage_stats.get(&state.definition.id)),
+            health,
+            details
+        );
+
+        println!("{0}", line.trim_end());
+    }
+}
+
+fn state_label(state: &MountState) -> &'static str {
+    if state.mounted {
+        "mounted"
+    } else {
+        "unmounted"
+    }
+}
+
+fn print_statuses_json(
+    output: Output,
+    states: &[MountState],
+    outcomes: &[Option<ProbeOutcome>],
+    usage_stats: &HashMap<String, UsageStats>,
+) {
+    let list: Vec<serde_json::Value> = states
+        .iter()
+        .zip(outcomes)
+        .map(|(state, outcome)| {
+            let stats = usage_stats.get(&state.definition.id);
+
+            json!({
+                "id": state.definition.id,
+                "mounted": state.mounted,
+                "health": outcome.as_ref().map(|outcome| outcome.code()),
+                "details": outcome.as_ref().and_then(|outcome| outcome.details()),
+                "lastMountedAt": stats.and_then(|stats| stats.last_mounted_at),
+                "mountCount": stats.map(|stats| stats.mount_count).unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    output.print_json(&list);
+}
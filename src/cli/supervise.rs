@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use clap::{Arg, ArgMatches, Command, value_parser};
+
+use crate::manager::Manager;
+use crate::supervisor::Supervisor;
+
+use super::exit;
+use super::signals::{STOP_REQUESTED, install_stop_signal_handlers};
+
+const ARG_ID: &str = "id";
+const ARG_MAX_BACKOFF: &str = "max-backoff";
+
+pub fn build() -> Command {
+    Command::new("supervise")
+        .about("Mounts the specified SFTP system with sshfs running in the foreground, restarting it whenever it exits unexpectedly. Meant to be run as a service (e.g. via systemd)")
+        .arg(Arg::new(ARG_ID).required(true))
+        .arg(
+            Arg::new(ARG_MAX_BACKOFF)
+                .long(ARG_MAX_BACKOFF)
+                .num_args(1)
+                .value_parser(value_parser!(u64).range(1..))
+                .value_name("SECONDS")
+                .default_value("60")
+                .help("Maximum number of seconds to wait before restarting sshfs after consecutive failures")
+        )
+}
+
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let id = matches.get_one::<String>(ARG_ID).expect("required");
+    let max_backoff = matches.get_one::<u64>(ARG_MAX_BACKOFF).expect("defaulted");
+
+    let definition = match manager.resolve_definition(id) {
+        Ok(definition) => definition,
+        Err(err) => {
+            log::error!("Failed to find or load definition: {0}: {1}", id, err);
+            return exit::Status::DefinitionNotFound;
+        }
+    };
+
+    if let Err(err) = install_stop_signal_handlers() {
+        log::error!("Failed to install signal handlers: {0}", err);
+        return exit::Status::Failure;
+    }
+
+    let supervisor = Supervisor::new(manager.clone(), definition)
+        .with_backoff(Duration::from_secs(1), Duration::from_secs(*max_backoff));
+
+    match supervisor.run(&STOP_REQUESTED) {
+        Ok(()) => exit::Status::Success,
+        Err(err) => {
+            log::error!("Failure supervising {0}: {1:?}", id, err);
+            exit::Status::Failure
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use clap::{ArgMatches, Command};
+
+use crate::errors::SftpManError;
+use crate::manager::Manager;
+
+use super::exit;
+
+pub fn build() -> Command {
+    Command::new("sync")
+        .about("Synchronizes the definitions with a git remote (see `sftpman config set syncGitRemote ..`), so that they can be shared across machines")
+}
+
+pub fn run(manager: &Manager, _matches: &ArgMatches) -> exit::Status {
+    match manager.sync() {
+        Ok(outcome) => {
+            if !outcome.committed && !outcome.received && !outcome.pushed {
+                log::info!("The definitions are already in sync");
+            }
+            if outcome.committed {
+                log::info!("Local changes to the definitions were committed");
+            }
+            if outcome.received {
+                log::info!("Changes to the definitions were received from the remote");
+            }
+            if outcome.pushed {
+                log::info!("The definitions were pushed to the remote");
+            }
+
+            exit::Status::Success
+        }
+
+        Err(SftpManError::SyncNotConfigured) => {
+            log::error!(
+                "No remote to synchronize with is configured. Configure one via: sftpman config set syncGitRemote <git-url>"
+            );
+            exit::Status::Failure
+        }
+
+        Err(err @ SftpManError::SyncConflict(_)) => {
+            log::error!(
+                "{0}. Nothing was changed locally. Resolve the conflict with git in {1} and run sync again",
+                err,
+                manager.definitions_path().display()
+            );
+            exit::Status::Failure
+        }
+
+        Err(err) => {
+            log::error!("Failed to synchronize the definitions: {0:?}", err);
+            exit::Status::Failure
+        }
+    }
+}
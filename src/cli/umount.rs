@@ -1,13 +1,89 @@
-use clap::{Arg, ArgMatches, Command};
+use std::path::{Path, PathBuf};
 
-use crate::{manager::Manager, model::FilesystemMountDefinition};
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
 
+use crate::daemon::DaemonClient;
+use crate::errors::SftpManError;
+use crate::i18n::{self, Language};
+use crate::manager::Manager;
+use crate::model::{
+    DefinitionFilter, FilesystemMountDefinition, KillPolicy, TerminationSignal, UmountOutcome,
+};
+use crate::utils::id_resolution::resolve_id;
+
+use super::bulk::{self, print_summary, skip_remaining, summarize};
+use super::daemon::client_if_running;
 use super::exit;
+use super::output::{OperationResult, Output};
+use super::progress::{self, Operation, Progress};
+
+const ARG_KILL: &str = "kill";
+const ARG_NO_KILL: &str = "no-kill";
+const ARG_KILL_USERS: &str = "kill-users";
+const ARG_SIGNAL: &str = "signal";
+const ARG_PREFIX: &str = "prefix";
 
 pub fn build() -> Command {
-    Command::new("umount")
-        .about("Unmounts the specified SFTP system or systems, unless already unmounted")
-        .arg(Arg::new("id").num_args(1..).required(true))
+    with_kill_policy_args(
+        Command::new("umount")
+            .visible_aliases(["u", "um"])
+            .about("Unmounts the specified SFTP system or systems, unless already unmounted")
+            .arg(Arg::new("id").num_args(1..).required(true)),
+    )
+}
+
+fn with_kill_policy_args(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new(ARG_KILL)
+            .long(ARG_KILL)
+            .action(ArgAction::SetTrue)
+            .conflicts_with(ARG_NO_KILL)
+            .help("Skip fusermount and unmount by killing the sshfs process right away"),
+    )
+    .arg(
+        Arg::new(ARG_NO_KILL)
+            .long(ARG_NO_KILL)
+            .action(ArgAction::SetTrue)
+            .help("Fail instead of killing the sshfs process when the filesystem is busy"),
+    )
+    .arg(
+        Arg::new(ARG_KILL_USERS)
+            .long(ARG_KILL_USERS)
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all([ARG_KILL, ARG_NO_KILL])
+            .help("Terminate the processes using the filesystem (see `sftpman lsof`) and then unmount cleanly, instead of killing the sshfs process. Fails if the filesystem is still busy"),
+    )
+    .arg(
+        Arg::new(ARG_SIGNAL)
+            .long(ARG_SIGNAL)
+            .num_args(1)
+            .requires(ARG_KILL_USERS)
+            .value_parser([
+                TerminationSignal::Term.to_static_str(),
+                TerminationSignal::Int.to_static_str(),
+                TerminationSignal::Hup.to_static_str(),
+                TerminationSignal::Kill.to_static_str(),
+            ])
+            .help("The signal to terminate processes with, when using --kill-users (default: TERM). Example: HUP"),
+    )
+    .arg(progress::arg())
+}
+
+fn kill_policy_from_matches(matches: &ArgMatches) -> KillPolicy {
+    if matches.get_flag(ARG_KILL) {
+        KillPolicy::Immediate
+    } else if matches.get_flag(ARG_NO_KILL) {
+        KillPolicy::Never
+    } else if matches.get_flag(ARG_KILL_USERS) {
+        let signal = matches
+            .get_one::<String>(ARG_SIGNAL)
+            .map(|value| TerminationSignal::from_string(value).expect("validated by clap"))
+            .unwrap_or_default();
+
+        KillPolicy::TerminateUsers(signal)
+    } else {
+        KillPolicy::Fallback
+    }
 }
 
 pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
@@ -17,77 +93,358 @@ pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
         .map(|s| s.as_str())
         .collect();
 
-    umount(manager, &ids)
+    umount(
+        manager,
+        client_if_running(manager, matches).as_ref(),
+        &ids,
+        kill_policy_from_matches(matches),
+        Progress::from_matches(matches),
+        Output::from_matches(matches),
+    )
 }
 
 pub fn build_umount_all() -> Command {
-    Command::new("umount_all").about("Unmounts all known SFTP systems")
+    with_kill_policy_args(
+        Command::new("umount_all")
+            .visible_alias("ua")
+            .about("Unmounts all known SFTP systems (including temporary mounts)")
+            .arg(
+                Arg::new(ARG_PREFIX)
+                    .long(ARG_PREFIX)
+                    .num_args(1)
+                    .value_parser(value_parser!(PathBuf))
+                    .help("Only unmount systems whose local mount path is inside this directory. Example: /mnt/sshfs/work"),
+            )
+            .arg(bulk::fail_fast_arg()),
+    )
+}
+
+pub fn run_umount_all(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    // Mount paths are absolute, so relative prefixes are resolved against the current directory.
+    let prefix = match matches
+        .get_one::<PathBuf>(ARG_PREFIX)
+        .map(std::path::absolute)
+    {
+        None => None,
+        Some(Ok(prefix)) => Some(prefix),
+        Some(Err(err)) => {
+            log::error!("Invalid --{0} path: {1}", ARG_PREFIX, err);
+            return exit::Status::Failure;
+        }
+    };
+
+    umount_all(
+        manager,
+        client_if_running(manager, matches).as_ref(),
+        prefix.as_deref(),
+        kill_policy_from_matches(matches),
+        matches.get_flag(bulk::ARG_FAIL_FAST),
+        Progress::from_matches(matches),
+        Output::from_matches(matches),
+    )
+}
+
+pub fn build_umount_idle() -> Command {
+    Command::new("umount_idle").about(
+        "Unmounts SFTP systems which have been idle for longer than their idle_unmount_minutes setting allows. Meant to be run periodically (e.g. every minute)",
+    )
+}
+
+pub fn run_umount_idle(manager: &Manager) -> exit::Status {
+    match manager.umount_idle() {
+        Ok(ids) => {
+            for id in ids {
+                log::info!("Unmounted idle filesystem: {0}", id);
+            }
+            exit::Status::Success
+        }
+        Err(err) => {
+            log::error!("Failure unmounting idle filesystems: {0:?}", err);
+            exit::Status::Failure
+        }
+    }
+}
+
+pub fn build_gc() -> Command {
+    Command::new("gc").about(
+        "Forgets about temporary mounts (see mount --temporary) which are no longer mounted (e.g. because sshfs died) and removes their leftover mount points",
+    )
 }
 
-pub fn run_umount_all(manager: &Manager) -> exit::Status {
-    umount_all(manager)
+pub fn run_gc(manager: &Manager) -> exit::Status {
+    match manager.gc() {
+        Ok(ids) => {
+            for id in ids {
+                log::info!("Forgot about temporary mount: {0}", id);
+            }
+            exit::Status::Success
+        }
+        Err(err) => {
+            log::error!("Failure cleaning up temporary mounts: {0:?}", err);
+            exit::Status::Failure
+        }
+    }
 }
 
-/// Unmounts the given filesystems by id.
+/// Unmounts the given filesystems by id (which may also be the IDs of temporary mounts, see `Manager::mount_temporary()`).
 /// Returns exit::Status::Success if all unmounting succeeded.
 /// Returns exit::Status::DefinitionNotFound if at least one filesystem was not found.
 /// Returns exit::Status::Failure if at least one filesystem failed to unmount.
-pub fn umount(manager: &Manager, ids: &Vec<&str>) -> exit::Status {
+/// If `daemon_client` is provided, unmounting (of filesystems which are not temporary mounts) is performed by the daemon.
+pub fn umount(
+    manager: &Manager,
+    daemon_client: Option<&DaemonClient>,
+    ids: &Vec<&str>,
+    kill_policy: KillPolicy,
+    progress: Progress,
+    output: Output,
+) -> exit::Status {
     let definitions = manager.definitions().unwrap();
+    let temporary_definitions: Vec<FilesystemMountDefinition> = manager
+        .temporary_state()
+        .unwrap()
+        .into_iter()
+        .map(|state| state.definition)
+        .collect();
 
     let mut exit_status = exit::Status::Success;
 
+    let mut results: Vec<OperationResult> = Vec::new();
+
     let mut definitions_to_work_on: Vec<&FilesystemMountDefinition> = Vec::new();
+    let mut temporary_definitions_to_work_on: Vec<&FilesystemMountDefinition> = Vec::new();
 
-    for id in ids {
-        let definition_or_none = definitions.iter().find(|&x| &x.id == id);
+    let all_ids: Vec<&str> = definitions
+        .iter()
+        .chain(&temporary_definitions)
+        .map(|definition| definition.id.as_str())
+        .collect();
 
-        match definition_or_none {
-            None => {
-                log::error!("Failed to find filesystem with an id of: {0}", id);
+    for id in ids {
+        let id = match resolve_id(id, &all_ids) {
+            Ok(id) => id,
+            Err(err) => {
+                log::error!("Failed to find filesystem: {0}", err);
+                progress.failed(Operation::Umount, id, &err);
+                results.push(OperationResult::failure(id, &err));
                 exit_status = exit::Status::DefinitionNotFound;
-            }
-
-            Some(definition) => {
-                definitions_to_work_on.push(definition);
+                continue;
             }
         };
+
+        if let Some(definition) = definitions.iter().find(|&x| x.id == id) {
+            definitions_to_work_on.push(definition);
+        } else if let Some(definition) = temporary_definitions.iter().find(|&x| x.id == id) {
+            temporary_definitions_to_work_on.push(definition);
+        }
     }
 
-    if !umount_definitions(manager, &definitions_to_work_on) {
+    let mut all_good = umount_definitions(
+        manager,
+        daemon_client,
+        &definitions_to_work_on,
+        kill_policy,
+        false,
+        progress,
+        &mut results,
+    );
+
+    all_good &= umount_temporary_definitions(
+        manager,
+        &temporary_definitions_to_work_on,
+        kill_policy,
+        false,
+        progress,
+        &mut results,
+    );
+
+    if !all_good {
         exit_status = exit::Status::Failure
     }
 
+    // Unmounting filesystems on their own is deliberate, so they don't get mounted again via `mount --restore`
+    // (unlike those unmounted via umount_all).
+    for result in results.iter().filter(|result| result.success) {
+        if let Err(err) = manager.leave_session(&result.id) {
+            log::warn!(
+                "{0}: failed to take out of the session: {1:?}",
+                result.id,
+                err
+            );
+        }
+    }
+
+    output.print_results(&results);
+
     exit_status
 }
 
-/// Unmounts all known filesystems which are currently mounted.
+/// Unmounts all known filesystems (including temporary mounts) which are currently mounted.
+/// If `prefix` is provided, only filesystems whose local mount path is inside it are unmounted.
+/// If `fail_fast` is `true`, nothing else gets unmounted after a failure.
+/// Once done, a summary of what happened to each filesystem is printed (unless printing JSON or progress events).
 /// Returns exit::Status::Success if all unmounting succeeded.
 /// Returns exit::Status::Failure if at least one filesystem failed to unmount.
-pub fn umount_all(manager: &Manager) -> exit::Status {
+pub fn umount_all(
+    manager: &Manager,
+    daemon_client: Option<&DaemonClient>,
+    prefix: Option<&Path>,
+    kill_policy: KillPolicy,
+    fail_fast: bool,
+    progress: Progress,
+    output: Output,
+) -> exit::Status {
+    let mut filter = DefinitionFilter::new().with_mounted(true);
+
+    if let Some(prefix) = prefix {
+        filter = filter.with_mount_path_prefix(prefix);
+    }
+
     let definitions_to_work_on: Vec<FilesystemMountDefinition> = manager
-        .full_state()
+        .definitions_filtered(&filter)
         .unwrap()
         .into_iter()
-        .filter(|state| state.mounted)
         .map(|state| state.definition)
         .collect();
 
-    if umount_definitions(manager, &definitions_to_work_on.iter().collect()) {
+    let temporary_definitions_to_work_on: Vec<FilesystemMountDefinition> = manager
+        .temporary_state()
+        .unwrap()
+        .into_iter()
+        .filter(|state| filter.matches(state))
+        .map(|state| state.definition)
+        .collect();
+
+    let mut results: Vec<OperationResult> = Vec::new();
+
+    let temporary_definitions_to_work_on: Vec<&FilesystemMountDefinition> =
+        temporary_definitions_to_work_on.iter().collect();
+
+    let mut all_good = umount_definitions(
+        manager,
+        daemon_client,
+        &definitions_to_work_on.iter().collect(),
+        kill_policy,
+        fail_fast,
+        progress,
+        &mut results,
+    );
+
+    if all_good || !fail_fast {
+        all_good &= umount_temporary_definitions(
+            manager,
+            &temporary_definitions_to_work_on,
+            kill_policy,
+            fail_fast,
+            progress,
+            &mut results,
+        );
+    } else if let Some(failed) = results.iter().find(|result| !result.success) {
+        let failed_id = failed.id.clone();
+        skip_remaining(&temporary_definitions_to_work_on, &failed_id, &mut results);
+    }
+
+    output.print_results(&results);
+
+    let summary = summarize(&results);
+    if summary.total() > 0 && !output.is_json() && !progress.is_enabled() {
+        print_summary(&summary, "unmounted", "already unmounted");
+    }
+
+    if all_good {
         exit::Status::Success
     } else {
         exit::Status::Failure
     }
 }
 
-/// Unmounts the given filesystems.
-fn umount_definitions(manager: &Manager, definitions: &Vec<&FilesystemMountDefinition>) -> bool {
+/// Unmounts the given filesystems, recording the result for each into `results`.
+/// If `fail_fast` is `true`, the remaining filesystems are skipped after the first failure.
+fn umount_definitions(
+    manager: &Manager,
+    daemon_client: Option<&DaemonClient>,
+    definitions: &Vec<&FilesystemMountDefinition>,
+    kill_policy: KillPolicy,
+    fail_fast: bool,
+    progress: Progress,
+    results: &mut Vec<OperationResult>,
+) -> bool {
+    umount_each(
+        definitions,
+        fail_fast,
+        progress,
+        results,
+        |definition| match daemon_client {
+            Some(client) => client.umount(&definition.id, kill_policy),
+            None => manager.umount_with_kill_policy(definition, kill_policy),
+        },
+    )
+}
+
+/// Unmounts the given temporary mounts (see `Manager::mount_temporary()`), recording the result for each into `results`.
+/// The daemon only knows about stored definitions, so this is never routed through it.
+fn umount_temporary_definitions(
+    manager: &Manager,
+    definitions: &Vec<&FilesystemMountDefinition>,
+    kill_policy: KillPolicy,
+    fail_fast: bool,
+    progress: Progress,
+    results: &mut Vec<OperationResult>,
+) -> bool {
+    umount_each(definitions, fail_fast, progress, results, |definition| {
+        manager.umount_temporary(definition, kill_policy)
+    })
+}
+
+fn umount_each(
+    definitions: &Vec<&FilesystemMountDefinition>,
+    fail_fast: bool,
+    progress: Progress,
+    results: &mut Vec<OperationResult>,
+    umount: impl Fn(&FilesystemMountDefinition) -> Result<UmountOutcome, SftpManError>,
+) -> bool {
     let mut all_good = true;
 
-    for definition in definitions {
-        if let Err(err) = manager.umount(definition) {
-            log::error!("Failure unmounting {0}: {1:?}", definition.id, err);
-            all_good = false
+    for (idx, definition) in definitions.iter().enumerate() {
+        progress.started(Operation::Umount, &definition.id);
+
+        match umount(definition) {
+            Ok(outcome) => {
+                if outcome == UmountOutcome::UnmountedByKill {
+                    log::warn!(
+                        "{0}: got unmounted by killing sshfs, so programs using it may have lost data",
+                        definition.id
+                    );
+                }
+
+                progress.succeeded(Operation::Umount, &definition.id);
+                results.push(OperationResult::success(&definition.id).with_outcome(outcome.code()));
+            }
+            Err(err) => {
+                let failure = i18n::translate_or(
+                    Language::detect(),
+                    "message.umount_failed",
+                    "Failure unmounting",
+                );
+
+                if let SftpManError::MountBusy(_, _) = &err {
+                    log::error!("{0} {1}: {2}", failure, definition.id, err);
+                    log::error!(
+                        "{0}: see `sftpman lsof {0}` for the files they have open",
+                        definition.id
+                    );
+                } else {
+                    log::error!("{0} {1}: {2:?}", failure, definition.id, err);
+                }
+                progress.failed(Operation::Umount, &definition.id, &err);
+                results.push(OperationResult::failure(&definition.id, &err));
+                all_good = false;
+
+                if fail_fast {
+                    skip_remaining(&definitions[idx + 1..], &definition.id, results);
+                    break;
+                }
+            }
         }
     }
 
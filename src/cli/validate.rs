@@ -0,0 +1,77 @@
+use clap::{ArgMatches, Command};
+use serde_json::json;
+
+use crate::manager::Manager;
+use crate::model::DefinitionCheck;
+
+use super::exit;
+use super::output::Output;
+
+pub fn build() -> Command {
+    Command::new("validate").about(
+        "Checks all stored definitions for problems (invalid values, missing key files, conflicting mount paths, unknown mount options, etc.). Useful after editing definitions by hand or syncing them from another machine",
+    )
+}
+
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let checks = match manager.check_definitions() {
+        Ok(checks) => checks,
+        Err(err) => {
+            log::error!("Failed to check the definitions: {0:?}", err);
+            return exit::Status::Failure;
+        }
+    };
+
+    let output = Output::from_matches(matches);
+
+    if output.is_json() {
+        print_checks_json(output, &checks);
+    } else {
+        print_checks(&checks);
+    }
+
+    if checks.iter().any(|check| check.has_errors()) {
+        exit::Status::ValidationFailure
+    } else {
+        exit::Status::Success
+    }
+}
+
+fn print_checks(checks: &[DefinitionCheck]) {
+    for check in checks {
+        if check.problems.is_empty() {
+            println!("{0}: OK", check.path.display());
+            continue;
+        }
+
+        println!("{0}:", check.path.display());
+
+        for problem in &check.problems {
+            let severity = if problem.is_error() {
+                "error"
+            } else {
+                "warning"
+            };
+
+            println!("  - {0}: {1}", severity, problem);
+        }
+    }
+}
+
+fn print_checks_json(output: Output, checks: &[DefinitionCheck]) {
+    let list: Vec<serde_json::Value> = checks
+        .iter()
+        .map(|check| {
+            json!({
+                "path": check.path,
+                "id": check.id,
+                "problems": check.problems.iter().map(|problem| json!({
+                    "severity": if problem.is_error() { "error" } else { "warning" },
+                    "message": problem.to_string(),
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    output.print_json(&list);
+}
@@ -0,0 +1,202 @@
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command, value_parser};
+use serde_json::json;
+
+use crate::manager::Manager;
+use crate::model::{DefinitionFilter, FilesystemMountDefinition, VerificationOutcome};
+
+use super::exit;
+use super::output::Output;
+
+const ARG_ID: &str = "id";
+const ARG_ALL: &str = "all";
+const ARG_JOBS: &str = "jobs";
+const ARG_HOST: &str = "host";
+const ARG_SEARCH: &str = "search";
+const ARG_CREATE_REMOTE_PATH: &str = "create-remote-path";
+
+pub fn build() -> Command {
+    Command::new("verify")
+        .about("Verifies that the specified SFTP systems can be connected to and authenticated with, and that their remote paths exist and can be listed, without mounting them. Prints a summary table")
+        .arg(Arg::new(ARG_ID).num_args(1..))
+        .arg(
+            Arg::new(ARG_ALL)
+                .long(ARG_ALL)
+                .action(ArgAction::SetTrue)
+                .help("Verify all known systems (optionally narrowed down via --host or --search)"),
+        )
+        .group(
+            ArgGroup::new("what")
+                .args([ARG_ID, ARG_ALL])
+                .required(true),
+        )
+        .arg(
+            Arg::new(ARG_JOBS)
+                .long(ARG_JOBS)
+                .num_args(1)
+                .value_parser(value_parser!(u16).range(1..))
+                .default_value("4")
+                .help("Number of systems to verify in parallel"),
+        )
+        .arg(
+            Arg::new(ARG_HOST)
+                .long(ARG_HOST)
+                .num_args(1)
+                // Either IDs or --all are required, so conflicting with IDs means requiring --all.
+                .conflicts_with(ARG_ID)
+                .help("Only verify systems connecting to this host. Example: example.com"),
+        )
+        .arg(
+            Arg::new(ARG_SEARCH)
+                .long(ARG_SEARCH)
+                .num_args(1)
+                .conflicts_with(ARG_ID)
+                .help("Only verify systems whose ID, host, user or remote path contains this text. Example: backup"),
+        )
+        .arg(
+            Arg::new(ARG_CREATE_REMOTE_PATH)
+                .long(ARG_CREATE_REMOTE_PATH)
+                .action(ArgAction::SetTrue)
+                .help("Create remote paths which don't exist yet (along with their parents, like mkdir -p)"),
+        )
+}
+
+/// Verifies the given (or all) filesystems and prints the outcomes.
+/// Returns exit::Status::Success if all verified systems are reachable (systems which can't be verified non-interactively are skipped).
+/// Returns exit::Status::DefinitionNotFound if at least one filesystem was not found.
+/// Returns exit::Status::Failure if at least one filesystem failed verification.
+pub fn run(manager: &Manager, matches: &ArgMatches) -> exit::Status {
+    let jobs = *matches.get_one::<u16>(ARG_JOBS).expect("defaulted");
+
+    let mut exit_status = exit::Status::Success;
+
+    let definitions: Vec<FilesystemMountDefinition> = if matches.get_flag(ARG_ALL) {
+        let mut filter = DefinitionFilter::new();
+
+        if let Some(host) = matches.get_one::<String>(ARG_HOST) {
+            filter = filter.with_host(host);
+        }
+
+        if let Some(text) = matches.get_one::<String>(ARG_SEARCH) {
+            filter = filter.with_text(text);
+        }
+
+        match manager.definitions_filtered(&filter) {
+            Ok(states) => states.into_iter().map(|state| state.definition).collect(),
+            Err(err) => {
+                log::error!("Failed to load the definitions: {0:?}", err);
+                return exit::Status::Failure;
+            }
+        }
+    } else {
+        let mut definitions = Vec::new();
+
+        for id in matches.get_many::<String>(ARG_ID).expect("required") {
+            match manager.resolve_definition(id) {
+                Ok(definition) => definitions.push(definition),
+                Err(err) => {
+                    log::error!("Failed to find or load definition: {0}: {1}", id, err);
+                    exit_status = exit::Status::DefinitionNotFound;
+                }
+            }
+        }
+
+        definitions
+    };
+
+    let outcomes = manager.verify_many(
+        &definitions,
+        jobs as usize,
+        matches.get_flag(ARG_CREATE_REMOTE_PATH),
+    );
+
+    let output = Output::from_matches(matches);
+
+    if output.is_json() {
+        print_outcomes_json(output, &definitions, &outcomes);
+    } else {
+        print_outcomes(&definitions, &outcomes);
+    }
+
+    if matches!(exit_status, exit::Status::Success)
+        && outcomes.iter().any(|outcome| outcome.is_failure())
+    {
+        exit_status = exit::Status::Failure;
+    }
+
+    exit_status
+}
+
+fn print_outcomes(definitions: &[FilesystemMountDefinition], outcomes: &[VerificationOutcome]) {
+    let id_width = definitions
+        .iter()
+        .map(|definition| definition.id.len())
+        .chain(std::iter::once("ID".len()))
+        .max()
+        .unwrap_or_default();
+
+    let result_width = outcomes
+        .iter()
+        .map(|outcome| outcome.to_string().len())
+        .chain(std::iter::once("RESULT".len()))
+        .max()
+        .unwrap_or_default();
+
+    println!("{0:id_width$}  {1:result_width$}  DETAILS", "ID", "RESULT");
+
+    for (definition, outcome) in definitions.iter().zip(outcomes) {
+        let line = format!(
+            "{0:id_width$}  {1:result_width$}  {2}",
+            definition.id,
+            outcome.to_string(),
+            outcome.details().unwrap_or_default()
+        );
+
+        println!("{0}", line.trim_end());
+    }
+
+    // Outcomes are summarized in a fixed order, leaving out the ones that didn't happen.
+    let summary: Vec<String> = [
+        VerificationOutcome::Reachable,
+        VerificationOutcome::PathCreated,
+        VerificationOutcome::AuthFailed(String::new()),
+        VerificationOutcome::PathMissing(String::new()),
+        VerificationOutcome::PathUnreadable(String::new()),
+        VerificationOutcome::Unreachable(String::new()),
+        VerificationOutcome::Skipped(String::new()),
+    ]
+    .iter()
+    .filter_map(|kind| {
+        let count = outcomes
+            .iter()
+            .filter(|outcome| outcome.code() == kind.code())
+            .count();
+
+        (count > 0).then(|| format!("{0} {1}", count, kind))
+    })
+    .collect();
+
+    if !summary.is_empty() {
+        println!();
+        println!("{0}", summary.join(", "));
+    }
+}
+
+fn print_outcomes_json(
+    output: Output,
+    definitions: &[FilesystemMountDefinition],
+    outcomes: &[VerificationOutcome],
+) {
+    let list: Vec<serde_json::Value> = definitions
+        .iter()
+        .zip(outcomes)
+        .map(|(definition, outcome)| {
+            json!({
+                "id": definition.id,
+                "result": outcome.code(),
+                "details": outcome.details(),
+            })
+        })
+        .collect();
+
+    output.print_json(&list);
+}
@@ -0,0 +1,15 @@
+use std::process::{Command, Output};
+
+use crate::errors::SftpManError;
+
+/// Runs the commands a `Manager` mounts and unmounts filesystems with (see `Manager::with_command_runner()`),
+/// instead of them getting executed.
+///
+/// This is the seam for testing mount logic (e.g. via `testing::FakeCommandRunner`) without `sshfs`, FUSE or a remote machine.
+///
+/// Like `std::process::Command::output()`, running a command should only fail with `SftpManError::CommandExecution`
+/// (the command could not be started) or `SftpManError::CommandUnsuccessful` (it exited with a non-zero status),
+/// as that's what the manager makes sense of (e.g. to tell why mounting failed, see `MountFailureKind`).
+pub trait CommandRunner: Send + Sync {
+    fn run(&self, cmd: Command) -> Result<Output, SftpManError>;
+}
@@ -0,0 +1,209 @@
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use serde_json::{Value, json};
+
+use crate::errors::SftpManError;
+use crate::model::{DefinitionFilter, KillPolicy, MountOutcome, UmountOutcome};
+
+use super::protocol::{
+    DaemonEvent, DaemonRequest, DaemonResponse, METHOD_EVENT, METHOD_LIST, METHOD_MOUNT,
+    METHOD_PROBE, METHOD_SUBSCRIBE, METHOD_UMOUNT, MountStatus, OperationStatus, ProbeStatus,
+};
+
+/// A client for talking to the daemon (see `Daemon`) over its unix socket.
+#[derive(Debug, Clone)]
+pub struct DaemonClient {
+    socket_path: PathBuf,
+}
+
+impl DaemonClient {
+    pub fn new(socket_path: PathBuf) -> Self {
+        Self { socket_path }
+    }
+
+    /// Tells if a daemon is currently listening on the socket.
+    pub fn is_running(&self) -> bool {
+        UnixStream::connect(&self.socket_path).is_ok()
+    }
+
+    /// Returns the state of all definitions known to the daemon.
+    pub fn list(&self) -> Result<Vec<MountStatus>, SftpManError> {
+        let result = self.call(METHOD_LIST, Value::Null)?;
+
+        serde_json::from_value(result)
+            .map_err(|err| SftpManError::JSON(self.socket_path.clone(), err))
+    }
+
+    /// Returns the state of the definitions known to the daemon which match the given filter.
+    pub fn list_filtered(
+        &self,
+        filter: &DefinitionFilter,
+    ) -> Result<Vec<MountStatus>, SftpManError> {
+        let mut params = serde_json::Map::new();
+
+        if let Some(host) = &filter.host {
+            params.insert("host".to_owned(), json!(host));
+        }
+        if let Some(auth_type) = &filter.auth_type {
+            params.insert("authType".to_owned(), json!(auth_type.to_static_str()));
+        }
+        if let Some(mounted) = filter.mounted {
+            params.insert("mounted".to_owned(), json!(mounted));
+        }
+        if let Some(enabled) = filter.enabled {
+            params.insert("enabled".to_owned(), json!(enabled));
+        }
+        if let Some(text) = &filter.text {
+            params.insert("search".to_owned(), json!(text));
+        }
+        if let Some(prefix) = &filter.mount_path_prefix {
+            params.insert(
+                "mountPathPrefix".to_owned(),
+                json!(prefix.to_string_lossy()),
+            );
+        }
+
+        let result = self.call(METHOD_LIST, Value::Object(params))?;
+
+        serde_json::from_value(result)
+            .map_err(|err| SftpManError::JSON(self.socket_path.clone(), err))
+    }
+
+    /// Asks the daemon to mount the definition with the given ID.
+    pub fn mount(&self, id: &str) -> Result<MountOutcome, SftpManError> {
+        let result = self.call(METHOD_MOUNT, json!({"id": id}))?;
+
+        let code = self.outcome_code(result)?;
+
+        MountOutcome::from_code(&code).map_err(|_| unknown_outcome(&code))
+    }
+
+    /// Asks the daemon to unmount the definition with the given ID.
+    pub fn umount(&self, id: &str, kill_policy: KillPolicy) -> Result<UmountOutcome, SftpManError> {
+        let mut params = json!({"id": id, "killPolicy": kill_policy.to_static_str()});
+
+        if let KillPolicy::TerminateUsers(signal) = kill_policy {
+            params["signal"] = json!(signal.to_static_str());
+        }
+
+        let result = self.call(METHOD_UMOUNT, params)?;
+
+        let code = self.outcome_code(result)?;
+
+        UmountOutcome::from_code(&code).map_err(|_| unknown_outcome(&code))
+    }
+
+    /// Extracts the outcome code out of the result of a `mount` or `umount` call (see `OperationStatus`).
+    fn outcome_code(&self, result: Value) -> Result<String, SftpManError> {
+        if result.is_null() {
+            return Err(SftpManError::Daemon(
+                "the response lacks the outcome of the operation".to_owned(),
+            ));
+        }
+
+        let status: OperationStatus = serde_json::from_value(result)
+            .map_err(|err| SftpManError::JSON(self.socket_path.clone(), err))?;
+
+        Ok(status.outcome)
+    }
+
+    /// Asks the daemon to check whether the mount of the definition with the given ID actually works (see `Manager::probe()`).
+    pub fn probe(&self, id: &str, write: bool) -> Result<ProbeStatus, SftpManError> {
+        let result = self.call(METHOD_PROBE, json!({"id": id, "write": write}))?;
+
+        serde_json::from_value(result)
+            .map_err(|err| SftpManError::JSON(self.socket_path.clone(), err))
+    }
+
+    /// Subscribes to events and returns an iterator which blocks until the next event arrives.
+    pub fn subscribe(&self) -> Result<DaemonEventStream, SftpManError> {
+        let (_, lines) = self.request(METHOD_SUBSCRIBE, Value::Null)?;
+
+        Ok(DaemonEventStream {
+            socket_path: self.socket_path.clone(),
+            lines,
+        })
+    }
+
+    /// Performs a single request and returns its result.
+    pub fn call(&self, method: &str, params: Value) -> Result<Value, SftpManError> {
+        let (result, _) = self.request(method, params)?;
+        Ok(result)
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<(Value, Lines<BufReader<UnixStream>>), SftpManError> {
+        let io_error = |err| SftpManError::IO(self.socket_path.clone(), err);
+
+        let mut stream = UnixStream::connect(&self.socket_path).map_err(io_error)?;
+
+        let mut serialized = serde_json::to_string(&DaemonRequest::new(1, method, params))
+            .map_err(|err| SftpManError::JSON(self.socket_path.clone(), err))?;
+        serialized.push('\n');
+
+        stream.write_all(serialized.as_bytes()).map_err(io_error)?;
+
+        let mut lines = BufReader::new(stream).lines();
+
+        let line = match lines.next() {
+            Some(line) => line.map_err(io_error)?,
+            None => {
+                return Err(SftpManError::Daemon(
+                    "the connection was closed without a response".to_owned(),
+                ));
+            }
+        };
+
+        let response: DaemonResponse = serde_json::from_str(&line)
+            .map_err(|err| SftpManError::JSON(self.socket_path.clone(), err))?;
+
+        if let Some(error) = response.error {
+            return Err(SftpManError::Daemon(error.message));
+        }
+
+        Ok((response.result.unwrap_or(Value::Null), lines))
+    }
+}
+
+/// Creates the error for an outcome code (see `OperationStatus`) which is not known to this client.
+fn unknown_outcome(code: &str) -> SftpManError {
+    SftpManError::Daemon(format!("the operation had an unknown outcome: {0}", code))
+}
+
+/// Iterates over events received from the daemon (see `DaemonClient::subscribe()`).
+pub struct DaemonEventStream {
+    socket_path: PathBuf,
+    lines: Lines<BufReader<UnixStream>>,
+}
+
+impl Iterator for DaemonEventStream {
+    type Item = Result<DaemonEvent, SftpManError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(SftpManError::IO(self.socket_path.clone(), err))),
+            };
+
+            let notification: DaemonRequest = match serde_json::from_str(&line) {
+                Ok(notification) => notification,
+                Err(err) => return Some(Err(SftpManError::JSON(self.socket_path.clone(), err))),
+            };
+
+            if notification.method != METHOD_EVENT {
+                continue;
+            }
+
+            return Some(
+                serde_json::from_value(notification.params)
+                    .map_err(|err| SftpManError::JSON(self.socket_path.clone(), err)),
+            );
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::manager::Manager;
+use crate::model::MountState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Mount,
+    Umount,
+}
+
+impl Operation {
+    fn label(&self) -> &'static str {
+        match self {
+            Operation::Mount => "mount",
+            Operation::Umount => "umount",
+        }
+    }
+}
+
+#[derive(Default)]
+struct OperationStats {
+    succeeded: u64,
+    failed: u64,
+    duration_seconds_sum: f64,
+}
+
+#[derive(Default)]
+struct State {
+    operations: HashMap<Operation, OperationStats>,
+
+    /// IDs of definitions which have been mounted at some point while the daemon was running.
+    ever_mounted_ids: HashSet<String>,
+
+    remounts: u64,
+}
+
+/// Collects statistics about the operations performed by the daemon
+/// and renders them in the [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+#[derive(Default)]
+pub struct Metrics {
+    state: Mutex<State>,
+}
+
+impl Metrics {
+    pub fn record_operation(&self, operation: Operation, duration: Duration, succeeded: bool) {
+        let mut state = self.state.lock().unwrap();
+
+        let stats = state.operations.entry(operation).or_default();
+        if succeeded {
+            stats.succeeded += 1;
+        } else {
+            stats.failed += 1;
+        }
+        stats.duration_seconds_sum += duration.as_secs_f64();
+    }
+
+    /// Records that a definition was seen mounted.
+    /// Mounting a definition which had already been mounted before (and got unmounted since) counts as a remount.
+    pub fn record_mounted(&self, id: &str) {
+        let mut state = self.state.lock().unwrap();
+
+        if !state.ever_mounted_ids.insert(id.to_owned()) {
+            state.remounts += 1;
+        }
+    }
+
+    /// Renders the metrics for the given mount states.
+    pub fn render(&self, manager: &Manager, states: &[MountState]) -> String {
+        let mounted_count = states.iter().filter(|state| state.mounted).count();
+        let broken_count = states
+            .iter()
+            .filter(|state| state.mounted && is_mount_broken(manager, state))
+            .count();
+
+        let mut out = String::new();
+
+        write_gauge(
+            &mut out,
+            "sftpman_mounts_configured",
+            "Number of configured filesystem definitions.",
+            states.len(),
+        );
+        write_gauge(
+            &mut out,
+            "sftpman_mounts_mounted",
+            "Number of currently mounted filesystems.",
+            mounted_count,
+        );
+        write_gauge(
+            &mut out,
+            "sftpman_mounts_broken",
+            "Number of mounted filesystems whose sshfs process is gone, leaving a dead mount point behind.",
+            broken_count,
+        );
+
+        let state = self.state.lock().unwrap();
+
+        let _ = writeln!(
+            out,
+            "# HELP sftpman_operations_total Number of mount/umount operations performed by the daemon."
+        );
+        let _ = writeln!(out, "# TYPE sftpman_operations_total counter");
+        for operation in [Operation::Mount, Operation::Umount] {
+            let stats = state.operations.get(&operation);
+
+            for (result, value) in [
+                ("success", stats.map_or(0, |stats| stats.succeeded)),
+                ("failure", stats.map_or(0, |stats| stats.failed)),
+            ] {
+                let _ = writeln!(
+                    out,
+                    "sftpman_operations_total{{operation=\"{0}\",result=\"{1}\"}} {2}",
+                    operation.label(),
+                    result,
+                    value
+                );
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP sftpman_operation_duration_seconds How long mount/umount operations performed by the daemon took."
+        );
+        let _ = writeln!(out, "# TYPE sftpman_operation_duration_seconds summary");
+        for operation in [Operation::Mount, Operation::Umount] {
+            let stats = state.operations.get(&operation);
+
+            let _ = writeln!(
+                out,
+                "sftpman_operation_duration_seconds_sum{{operation=\"{0}\"}} {1}",
+                operation.label(),
+                stats.map_or(0.0, |stats| stats.duration_seconds_sum)
+            );
+            let _ = writeln!(
+                out,
+                "sftpman_operation_duration_seconds_count{{operation=\"{0}\"}} {1}",
+                operation.label(),
+                stats.map_or(0, |stats| stats.succeeded + stats.failed)
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP sftpman_remounts_total Number of times a filesystem got mounted again after having been unmounted."
+        );
+        let _ = writeln!(out, "# TYPE sftpman_remounts_total counter");
+        let _ = writeln!(out, "sftpman_remounts_total {0}", state.remounts);
+
+        out
+    }
+
+    /// Writes the metrics to a file (e.g. for the textfile collector of the Prometheus node exporter).
+    ///
+    /// The file is replaced atomically, so that a partially-written file is never read.
+    pub fn write_textfile(
+        &self,
+        path: &Path,
+        manager: &Manager,
+        states: &[MountState],
+    ) -> std::io::Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+
+        fs::write(&tmp_path, self.render(manager, states))?;
+        fs::rename(&tmp_path, path)
+    }
+}
+
+fn write_gauge(out: &mut String, name: &str, help: &str, value: usize) {
+    let _ = writeln!(out, "# HELP {0} {1}", name, help);
+    let _ = writeln!(out, "# TYPE {0} gauge", name);
+    let _ = writeln!(out, "{0} {1}", name, value);
+}
+
+/// A mount is considered broken if its `sshfs` process is gone (e.g. "Transport endpoint is not connected").
+///
+/// The mount point itself is deliberately not accessed, as doing so may hang for as long as `sshfs` does.
+fn is_mount_broken(manager: &Manager, state: &MountState) -> bool {
+    manager
+        .is_definition_disconnected(&state.definition)
+        .unwrap_or_else(|err| {
+            log::debug!(
+                "Failed to check if {0} is disconnected: {1:?}",
+                state.definition.id,
+                err
+            );
+            false
+        })
+}
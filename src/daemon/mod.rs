@@ -0,0 +1,9 @@
+mod client;
+mod metrics;
+pub mod protocol;
+mod server;
+#[cfg(target_os = "linux")]
+mod sleep;
+
+pub use client::{DaemonClient, DaemonEventStream};
+pub use server::Daemon;
@@ -0,0 +1,182 @@
+//! The daemon speaks [JSON-RPC 2.0](https://www.jsonrpc.org/specification) over a unix socket,
+//! with each message (request, response or notification) being a single line of JSON.
+//!
+//! Supported methods:
+//!
+//! - `list` - returns the state of all definitions (an array of `MountStatus` objects).
+//!   The optional params (`{"host": "example.com", "authType": "password", "mounted": true, "enabled": true, "search": "backup",
+//!   "mountPathPrefix": "/mnt/sshfs/work"}`)
+//!   narrow down the list (see `DefinitionFilter`)
+//! - `mount` (params: `{"id": "my-fs"}`) - mounts the given definition and returns an `OperationStatus` object
+//!   (see `MountOutcome::code()`)
+//! - `umount` (params: `{"id": "my-fs", "killPolicy": "fallback"}`) - unmounts the given definition (`killPolicy` is optional)
+//!   and returns an `OperationStatus` object (see `UmountOutcome::code()`). With a `killPolicy` of `terminate_users`,
+//!   an optional `signal` (e.g. `"HUP"`, see `TerminationSignal`) may be given as well
+//! - `probe` (params: `{"id": "my-fs", "write": false}`) - checks that the given definition's mount actually works
+//!   (see `Manager::probe()`; `write` is optional) and returns a `ProbeStatus` object
+//! - `subscribe` - makes the daemon send an `event` notification (see `DaemonEvent`) on the same connection
+//!   whenever a filesystem gets mounted, unmounted or disconnected (its `sshfs` process being gone while still mounted)
+//!   and, when built with the `watch` feature, whenever a definition gets added, changed or removed
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+pub const METHOD_LIST: &str = "list";
+pub const METHOD_MOUNT: &str = "mount";
+pub const METHOD_UMOUNT: &str = "umount";
+pub const METHOD_PROBE: &str = "probe";
+pub const METHOD_SUBSCRIBE: &str = "subscribe";
+pub const METHOD_EVENT: &str = "event";
+
+pub const ERROR_CODE_PARSE: i64 = -32700;
+pub const ERROR_CODE_METHOD_NOT_FOUND: i64 = -32601;
+pub const ERROR_CODE_INVALID_PARAMS: i64 = -32602;
+pub const ERROR_CODE_OPERATION_FAILED: i64 = -32000;
+
+/// A request (or a notification, when `id` is `None`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonRequest {
+    pub jsonrpc: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+
+    pub method: String,
+
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl DaemonRequest {
+    pub fn new(id: u64, method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_owned(),
+            id: Some(Value::from(id)),
+            method: method.to_owned(),
+            params,
+        }
+    }
+
+    pub fn notification(method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_owned(),
+            id: None,
+            method: method.to_owned(),
+            params,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonResponse {
+    pub jsonrpc: String,
+
+    pub id: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<DaemonResponseError>,
+}
+
+impl DaemonResponse {
+    pub fn success(id: Option<Value>, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_owned(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn failure(id: Option<Value>, code: i64, message: String) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_owned(),
+            id,
+            result: None,
+            error: Some(DaemonResponseError { code, message }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonResponseError {
+    pub code: i64,
+    pub message: String,
+}
+
+/// The state of a single definition, as returned by the `list` method.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MountStatus {
+    pub id: String,
+
+    pub mounted: bool,
+
+    #[serde(rename = "localMountPath")]
+    pub local_mount_path: String,
+
+    /// Whether the definition can be mounted (see `FilesystemMountDefinition::enabled`).
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+/// The health of a single definition's mount, as returned by the `probe` method.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProbeStatus {
+    /// The outcome of the probe (see `ProbeOutcome::code()`), e.g. `healthy` or `unresponsive`.
+    pub health: String,
+
+    #[serde(default)]
+    pub details: Option<String>,
+}
+
+/// What a `mount` or `umount` call did.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OperationStatus {
+    /// The outcome of the operation (see `MountOutcome::code()` and `UmountOutcome::code()`), e.g. `unmounted_by_kill`.
+    pub outcome: String,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DaemonEventKind {
+    Mounted,
+    Unmounted,
+
+    /// The filesystem is still mounted, but its `sshfs` process is gone (e.g. it crashed or got killed).
+    Disconnected,
+
+    DefinitionAdded,
+    DefinitionChanged,
+    DefinitionRemoved,
+}
+
+impl DaemonEventKind {
+    /// Returns the name of the event type, as used in the `type` field (e.g. `definitionAdded`).
+    pub fn to_static_str(&self) -> &'static str {
+        match self {
+            Self::Mounted => "mounted",
+            Self::Unmounted => "unmounted",
+            Self::Disconnected => "disconnected",
+            Self::DefinitionAdded => "definitionAdded",
+            Self::DefinitionChanged => "definitionChanged",
+            Self::DefinitionRemoved => "definitionRemoved",
+        }
+    }
+}
+
+/// An event sent (as the params of an `event` notification) to connections which have subscribed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DaemonEvent {
+    #[serde(rename = "type")]
+    pub kind: DaemonEventKind,
+
+    pub id: String,
+}
@@ -0,0 +1,1052 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::auth_type::AuthType;
+use crate::errors::SftpManError;
+use crate::manager::Manager;
+use crate::model::{DefinitionFilter, KillPolicy, TerminationSignal};
+use crate::utils::network::default_route_interfaces;
+
+use super::metrics::{Metrics, Operation};
+use super::protocol::{
+    DaemonEvent, DaemonEventKind, DaemonRequest, DaemonResponse, ERROR_CODE_INVALID_PARAMS,
+    ERROR_CODE_METHOD_NOT_FOUND, ERROR_CODE_OPERATION_FAILED, ERROR_CODE_PARSE, METHOD_EVENT,
+    METHOD_LIST, METHOD_MOUNT, METHOD_PROBE, METHOD_SUBSCRIBE, METHOD_UMOUNT, MountStatus,
+    OperationStatus, ProbeStatus,
+};
+
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How often the mount table is checked for changes made outside of the daemon.
+const STATE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often idle filesystems are unmounted (see `Manager::umount_idle()`).
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the default routes get checked for changes (see `Daemon::with_network_following()`).
+const ROUTE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long there needs to be no default route for the network to be considered down,
+/// so that brief outages (e.g. while roaming between access points) don't cause unmounting.
+const NETWORK_DOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// How often remounting the filesystems unmounted due to the network is retried, while it keeps failing.
+const REMOUNT_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long to wait for the network after resuming from sleep, before remounting (see `Daemon::with_sleep_following()`).
+#[cfg(target_os = "linux")]
+const RESUME_NETWORK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often the metrics textfile (if enabled) gets rewritten.
+const METRICS_TEXTFILE_INTERVAL: Duration = Duration::from_secs(15);
+
+type Connection = Arc<Mutex<UnixStream>>;
+
+/// A long-running process which listens on a unix socket and serves requests (list, mount, umount, probe, subscribe).
+///
+/// Routing all operations through the daemon gives frontends (GUIs, the CLI) a single coordination point,
+/// as operations get performed one at a time.
+/// See the `protocol` module for details about the wire format.
+pub struct Daemon {
+    manager: Manager,
+    socket_path: PathBuf,
+    metrics_address: Option<SocketAddr>,
+    metrics_textfile_path: Option<PathBuf>,
+    follow_network: bool,
+    follow_sleep: bool,
+}
+
+struct Shared {
+    manager: Manager,
+
+    /// Ensures that only one mount/umount operation runs at a time.
+    operation_lock: Mutex<()>,
+
+    /// Connections which have subscribed to events.
+    subscribers: Mutex<Vec<Connection>>,
+
+    /// IDs of the definitions that were last seen mounted.
+    mounted_ids: Mutex<HashSet<String>>,
+
+    /// IDs of the mounted definitions whose `sshfs` process was last seen gone.
+    disconnected_ids: Mutex<HashSet<String>>,
+
+    metrics: Metrics,
+}
+
+/// What the daemon knows about the network, when following it (see `Daemon::with_network_following()`).
+#[derive(Default)]
+struct NetworkState {
+    /// The interfaces default routes went through as of the last check.
+    default_route_interfaces: BTreeSet<String>,
+
+    /// Since when there has been no default route (if there's none).
+    no_default_route_since: Option<Instant>,
+
+    /// Whether the network is considered down (see `NETWORK_DOWN_GRACE_PERIOD`).
+    down: bool,
+
+    /// IDs of the definitions which got unmounted due to the network, to be remounted once it's back.
+    unmounted_ids: BTreeSet<String>,
+
+    last_remount_attempt: Option<Instant>,
+}
+
+impl Daemon {
+    pub fn new(manager: Manager, socket_path: PathBuf) -> Self {
+        Self {
+            manager,
+            socket_path,
+            metrics_address: None,
+            metrics_textfile_path: None,
+            follow_network: true,
+            follow_sleep: true,
+        }
+    }
+
+    /// Makes the daemon follow (the default) or ignore network changes, as seen in the default routes (Linux-only).
+    ///
+    /// When following, mounts which are dead (their `sshfs` process being gone), whose requirements (see `FilesystemMountDefinition::requires`)
+    /// became unmet, or (for definitions without requirements) whose network went down get lazily unmounted (see `Manager::umount_lazily()`),
+    /// so that processes don't hang on them. They get remounted once the network (and their requirements) are back.
+    pub fn with_network_following(mut self, enabled: bool) -> Self {
+        self.follow_network = enabled;
+        self
+    }
+
+    /// Makes the daemon follow (the default) or ignore the system going to sleep (suspending or hibernating) and resuming (Linux-only).
+    ///
+    /// When following, all mounts get unmounted when logind announces sleeping (via its `PrepareForSleep` signal),
+    /// as `sshfs` mounts surviving sleep tend to end up hanging ("Transport endpoint is not connected").
+    /// A delay lock (see `systemd-inhibit`) gives unmounting time to complete. The unmounted filesystems get remounted on resume,
+    /// once the network is available (or after waiting for it for a while).
+    ///
+    /// This relies on `gdbus` (to receive the signal) and `systemd-inhibit` (to delay sleeping).
+    /// Without the latter, the system may go to sleep before unmounting completes.
+    pub fn with_sleep_following(mut self, enabled: bool) -> Self {
+        self.follow_sleep = enabled;
+        self
+    }
+
+    /// Makes the daemon serve metrics (in the Prometheus text format) over HTTP on the given address.
+    ///
+    /// The address should normally be a local one (e.g. `127.0.0.1:9877`), as the metrics are served without authentication.
+    pub fn with_metrics_address(mut self, address: SocketAddr) -> Self {
+        self.metrics_address = Some(address);
+        self
+    }
+
+    /// Makes the daemon periodically write metrics (in the Prometheus text format) to the given file.
+    ///
+    /// This is meant for the textfile collector of the Prometheus node exporter.
+    pub fn with_metrics_textfile(mut self, path: PathBuf) -> Self {
+        self.metrics_textfile_path = Some(path);
+        self
+    }
+
+    /// Serves requests until `stop_requested` becomes `true`.
+    pub fn run(&self, stop_requested: &AtomicBool) -> Result<(), SftpManError> {
+        let listener = self.bind()?;
+
+        let metrics_listener = match self.metrics_address {
+            Some(address) => Some(bind_metrics(address)?),
+            None => None,
+        };
+
+        log::info!("Listening on {0}", self.socket_path.display());
+
+        let shared = Shared {
+            manager: self.manager.clone(),
+            operation_lock: Mutex::new(()),
+            subscribers: Mutex::new(Vec::new()),
+            mounted_ids: Mutex::new(HashSet::new()),
+            disconnected_ids: Mutex::new(HashSet::new()),
+            metrics: Metrics::default(),
+        };
+
+        // Establish the initial state, so that only subsequent changes produce events.
+        shared.refresh_mounted_ids();
+
+        thread::scope(|scope| {
+            scope.spawn(|| shared.poll(stop_requested, self.follow_network));
+
+            #[cfg(all(feature = "watch", target_os = "linux"))]
+            scope.spawn(|| shared.watch_definitions(stop_requested));
+
+            if self.follow_sleep {
+                #[cfg(target_os = "linux")]
+                scope.spawn(|| shared.follow_sleep(stop_requested));
+            }
+
+            if let Some(metrics_listener) = &metrics_listener {
+                scope.spawn(|| shared.serve_metrics(metrics_listener, stop_requested));
+            }
+
+            if let Some(path) = &self.metrics_textfile_path {
+                scope.spawn(|| shared.write_metrics_textfile(path, stop_requested));
+            }
+
+            while !stop_requested.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let shared = &shared;
+                        scope.spawn(move || shared.serve(stream, stop_requested));
+                    }
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_INTERVAL);
+                    }
+                    Err(err) => {
+                        log::error!("Failed to accept connection: {0}", err);
+                        thread::sleep(ACCEPT_INTERVAL);
+                    }
+                }
+            }
+        });
+
+        if let Err(err) = fs::remove_file(&self.socket_path) {
+            log::debug!(
+                "Failed to remove socket {0}: {1}",
+                self.socket_path.display(),
+                err
+            );
+        }
+
+        log::info!("Daemon stopped");
+
+        Ok(())
+    }
+
+    fn bind(&self) -> Result<UnixListener, SftpManError> {
+        let socket_path = &self.socket_path;
+
+        if let Some(dir_path) = socket_path.parent() {
+            fs::create_dir_all(dir_path)
+                .map_err(|err| SftpManError::IO(dir_path.to_path_buf(), err))?;
+        }
+
+        if socket_path.exists() {
+            if UnixStream::connect(socket_path).is_ok() {
+                return Err(SftpManError::Generic(format!(
+                    "another daemon is already listening on {0}",
+                    socket_path.display()
+                )));
+            }
+
+            log::debug!("Removing stale socket {0}", socket_path.display());
+
+            fs::remove_file(socket_path)
+                .map_err(|err| SftpManError::IO(socket_path.clone(), err))?;
+        }
+
+        let listener = UnixListener::bind(socket_path)
+            .map_err(|err| SftpManError::IO(socket_path.clone(), err))?;
+
+        // Only the current user should be able to talk to the daemon.
+        fs::set_permissions(socket_path, fs::Permissions::from_mode(0o600))
+            .map_err(|err| SftpManError::IO(socket_path.clone(), err))?;
+
+        listener
+            .set_nonblocking(true)
+            .map_err(|err| SftpManError::IO(socket_path.clone(), err))?;
+
+        Ok(listener)
+    }
+}
+
+impl Shared {
+    fn poll(&self, stop_requested: &AtomicBool, follow_network: bool) {
+        let mut last_state_poll = Instant::now();
+        let mut last_idle_check = Instant::now();
+        let mut last_route_check = Instant::now();
+
+        let mut network_state = NetworkState {
+            default_route_interfaces: default_route_interfaces().unwrap_or_default(),
+            ..NetworkState::default()
+        };
+
+        while !stop_requested.load(Ordering::SeqCst) {
+            thread::sleep(ACCEPT_INTERVAL);
+
+            if last_idle_check.elapsed() >= IDLE_CHECK_INTERVAL {
+                last_idle_check = Instant::now();
+
+                let _lock = self.operation_lock.lock().unwrap();
+                if let Err(err) = self.manager.umount_idle() {
+                    log::error!("Failed to unmount idle filesystems: {0:?}", err);
+                }
+            }
+
+            if follow_network && last_route_check.elapsed() >= ROUTE_CHECK_INTERVAL {
+                last_route_check = Instant::now();
+
+                self.follow_network(&mut network_state);
+            }
+
+            if last_state_poll.elapsed() >= STATE_POLL_INTERVAL {
+                last_state_poll = Instant::now();
+
+                self.refresh_mounted_ids();
+            }
+        }
+    }
+
+    /// Checks the default routes for changes, lazily unmounting the mounts affected by them and remounting those once the network is back
+    /// (see `Daemon::with_network_following()`).
+    fn follow_network(&self, state: &mut NetworkState) {
+        // The routing tables cannot be read on this system, so there's nothing to follow.
+        let Some(interfaces) = default_route_interfaces() else {
+            return;
+        };
+
+        let changed = interfaces != state.default_route_interfaces;
+
+        if changed {
+            log::info!(
+                "Default routes changed (interfaces: {0:?} -> {1:?})",
+                state.default_route_interfaces,
+                interfaces
+            );
+        }
+
+        let mut went_down = false;
+
+        if interfaces.is_empty() {
+            let since = *state
+                .no_default_route_since
+                .get_or_insert_with(Instant::now);
+
+            if !state.down && since.elapsed() >= NETWORK_DOWN_GRACE_PERIOD {
+                log::warn!("The network is down (no default route)");
+
+                state.down = true;
+                went_down = true;
+            }
+        } else {
+            state.no_default_route_since = None;
+            state.down = false;
+        }
+
+        state.default_route_interfaces = interfaces;
+
+        if changed || went_down {
+            self.umount_affected_by_network(state);
+        }
+
+        let remount_due = changed
+            || state
+                .last_remount_attempt
+                .is_none_or(|at| at.elapsed() >= REMOUNT_RETRY_INTERVAL);
+
+        if !state.down && !state.unmounted_ids.is_empty() && remount_due {
+            state.last_remount_attempt = Some(Instant::now());
+
+            self.remount_after_network(state);
+        }
+    }
+
+    fn umount_affected_by_network(&self, state: &mut NetworkState) {
+        let states = match self.manager.full_state() {
+            Ok(states) => states,
+            Err(err) => {
+                log::error!("Failed to determine the mount state: {0:?}", err);
+                return;
+            }
+        };
+
+        for definition in states
+            .into_iter()
+            .filter(|mount_state| mount_state.mounted)
+            .map(|mount_state| mount_state.definition)
+        {
+            // Definitions with requirements are judged by them, as they may well be reachable without a default route (e.g. over a LAN).
+            let reason = if self
+                .manager
+                .is_definition_disconnected(&definition)
+                .unwrap_or(false)
+            {
+                "its sshfs process is gone".to_owned()
+            } else if let Err(err) = self.manager.check_requirements(&definition) {
+                match err {
+                    SftpManError::RequirementUnmet(_, description) => description,
+                    err => err.to_string(),
+                }
+            } else if state.down && definition.requires.is_empty() {
+                "the network is down".to_owned()
+            } else {
+                continue;
+            };
+
+            log::warn!("{0}: unmounting, as {1}", definition.id, reason);
+
+            let _lock = self.operation_lock.lock().unwrap();
+
+            let started_at = Instant::now();
+            let result = self.manager.umount_lazily(&definition);
+            self.metrics
+                .record_operation(Operation::Umount, started_at.elapsed(), result.is_ok());
+
+            match result {
+                Ok(_) => {
+                    state.unmounted_ids.insert(definition.id);
+                }
+                Err(err) => log::error!("{0}: failed to unmount: {1}", definition.id, err),
+            }
+        }
+
+        self.refresh_mounted_ids();
+    }
+
+    fn remount_after_network(&self, state: &mut NetworkState) {
+        for id in state.unmounted_ids.clone() {
+            // Definitions removed in the meantime are no longer of interest.
+            let Ok(definition) = self.manager.definition(&id) else {
+                state.unmounted_ids.remove(&id);
+                continue;
+            };
+
+            if let Err(err) = self.manager.check_requirements(&definition) {
+                log::debug!("{0}: not remounting yet: {1}", id, err);
+                continue;
+            }
+
+            log::info!("{0}: remounting, as the network is back", id);
+
+            let _lock = self.operation_lock.lock().unwrap();
+
+            let started_at = Instant::now();
+            let result = self.manager.mount_with_report(&definition);
+            self.metrics
+                .record_operation(Operation::Mount, started_at.elapsed(), result.is_ok());
+
+            match result {
+                Ok(_) => {
+                    state.unmounted_ids.remove(&id);
+                }
+                Err(report) => log::warn!(
+                    "{0}: failed to remount (retrying in {1:?}): {2}",
+                    id,
+                    REMOUNT_RETRY_INTERVAL,
+                    report
+                ),
+            }
+        }
+
+        self.refresh_mounted_ids();
+    }
+
+    /// Re-reads the mount state and notifies subscribers about any changes since the last check.
+    fn refresh_mounted_ids(&self) {
+        let states = match self.manager.full_state() {
+            Ok(states) => states,
+            Err(err) => {
+                log::error!("Failed to determine the mount state: {0:?}", err);
+                return;
+            }
+        };
+
+        let mounted_states: Vec<_> = states.into_iter().filter(|state| state.mounted).collect();
+
+        let current_disconnected_ids: HashSet<String> = mounted_states
+            .iter()
+            .filter(
+                |state| match self.manager.is_definition_disconnected(&state.definition) {
+                    Ok(disconnected) => disconnected,
+                    Err(err) => {
+                        log::debug!(
+                            "{0}: failed to check for disconnection: {1:?}",
+                            state.definition.id,
+                            err
+                        );
+                        false
+                    }
+                },
+            )
+            .map(|state| state.definition.id.clone())
+            .collect();
+
+        let current_ids: HashSet<String> = mounted_states
+            .into_iter()
+            .map(|state| state.definition.id)
+            .collect();
+
+        let mut events: Vec<DaemonEvent> = Vec::new();
+
+        {
+            let mut mounted_ids = self.mounted_ids.lock().unwrap();
+
+            for id in current_ids.difference(&mounted_ids) {
+                self.metrics.record_mounted(id);
+
+                events.push(DaemonEvent {
+                    kind: DaemonEventKind::Mounted,
+                    id: id.clone(),
+                });
+            }
+
+            for id in mounted_ids.difference(&current_ids) {
+                events.push(DaemonEvent {
+                    kind: DaemonEventKind::Unmounted,
+                    id: id.clone(),
+                });
+            }
+
+            *mounted_ids = current_ids;
+
+            let mut disconnected_ids = self.disconnected_ids.lock().unwrap();
+
+            for id in current_disconnected_ids.difference(&disconnected_ids) {
+                events.push(DaemonEvent {
+                    kind: DaemonEventKind::Disconnected,
+                    id: id.clone(),
+                });
+            }
+
+            *disconnected_ids = current_disconnected_ids;
+        }
+
+        for event in events {
+            self.broadcast(&event);
+        }
+    }
+
+    /// Notifies subscribers about definitions being added, changed or removed (e.g. by editing them by hand).
+    #[cfg(all(feature = "watch", target_os = "linux"))]
+    fn watch_definitions(&self, stop_requested: &AtomicBool) {
+        use crate::watcher::DefinitionChange;
+
+        let mut watcher = match self.manager.watch() {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                log::error!("Failed to watch for definition changes: {0:?}", err);
+                return;
+            }
+        };
+
+        while !stop_requested.load(Ordering::SeqCst) {
+            let changes = match watcher.wait(Some(ACCEPT_INTERVAL)) {
+                Ok(changes) => changes,
+                Err(err) => {
+                    log::error!("Failed to watch for definition changes: {0:?}", err);
+                    return;
+                }
+            };
+
+            for change in changes {
+                let (kind, id) = match change {
+                    DefinitionChange::Added(id) => (DaemonEventKind::DefinitionAdded, id),
+                    DefinitionChange::Changed(id) => (DaemonEventKind::DefinitionChanged, id),
+                    DefinitionChange::Removed(id) => (DaemonEventKind::DefinitionRemoved, id),
+                };
+
+                self.broadcast(&DaemonEvent { kind, id });
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn follow_sleep(&self, stop_requested: &AtomicBool) {
+        use super::sleep::{SleepInhibitor, SleepMonitor};
+
+        let mut monitor = match SleepMonitor::start() {
+            Ok(monitor) => monitor,
+            Err(err) => {
+                log::warn!("Not following suspend and resume: {0}", err);
+                return;
+            }
+        };
+
+        let take_inhibitor = || match SleepInhibitor::take() {
+            Ok(inhibitor) => Some(inhibitor),
+            Err(err) => {
+                log::warn!(
+                    "Failed to delay sleeping, so it may happen before unmounting completes: {0}",
+                    err
+                );
+                None
+            }
+        };
+
+        let mut inhibitor = take_inhibitor();
+        let mut unmounted_ids: Vec<String> = Vec::new();
+
+        while !stop_requested.load(Ordering::SeqCst) {
+            match monitor.poll() {
+                Ok(Some(true)) => {
+                    log::info!("The system is going to sleep. Unmounting everything..");
+
+                    unmounted_ids = self.umount_before_sleep();
+
+                    // Releasing the lock lets the system go to sleep.
+                    inhibitor = None;
+                }
+                Ok(Some(false)) => {
+                    log::info!("The system resumed");
+
+                    inhibitor = take_inhibitor();
+
+                    self.remount_after_sleep(std::mem::take(&mut unmounted_ids));
+                }
+                Ok(None) => thread::sleep(ACCEPT_INTERVAL),
+                Err(err) => {
+                    log::error!("Stopped following suspend and resume: {0}", err);
+                    break;
+                }
+            }
+        }
+
+        drop(inhibitor);
+    }
+
+    /// Unmounts all mounted filesystems, returning the IDs of those which got unmounted.
+    #[cfg(target_os = "linux")]
+    fn umount_before_sleep(&self) -> Vec<String> {
+        let states = match self.manager.full_state() {
+            Ok(states) => states,
+            Err(err) => {
+                log::error!("Failed to determine the mount state: {0:?}", err);
+                return Vec::new();
+            }
+        };
+
+        let mut unmounted_ids = Vec::new();
+
+        for definition in states
+            .into_iter()
+            .filter(|state| state.mounted)
+            .map(|state| state.definition)
+        {
+            let _lock = self.operation_lock.lock().unwrap();
+
+            // Killing `sshfs` if the filesystem is busy beats leaving a mount behind which will hang after resuming.
+            let started_at = Instant::now();
+            let result = self.manager.umount(&definition);
+            self.metrics
+                .record_operation(Operation::Umount, started_at.elapsed(), result.is_ok());
+
+            match result {
+                Ok(_) => unmounted_ids.push(definition.id),
+                Err(err) => log::error!("{0}: failed to unmount: {1}", definition.id, err),
+            }
+        }
+
+        self.refresh_mounted_ids();
+
+        unmounted_ids
+    }
+
+    #[cfg(target_os = "linux")]
+    fn remount_after_sleep(&self, ids: Vec<String>) {
+        if ids.is_empty() {
+            return;
+        }
+
+        if let Err(err) = self.manager.wait_for_network(None, RESUME_NETWORK_TIMEOUT) {
+            log::warn!("Remounting after resuming regardless: {0}", err);
+        }
+
+        for id in ids {
+            // Definitions removed in the meantime are no longer of interest.
+            let Ok(definition) = self.manager.definition(&id) else {
+                continue;
+            };
+
+            log::info!("{0}: remounting after resuming", id);
+
+            let _lock = self.operation_lock.lock().unwrap();
+
+            let started_at = Instant::now();
+            let result = self.manager.mount_with_report(&definition);
+            self.metrics
+                .record_operation(Operation::Mount, started_at.elapsed(), result.is_ok());
+
+            if let Err(report) = result {
+                log::error!("{0}: failed to remount: {1}", id, report);
+            }
+        }
+
+        self.refresh_mounted_ids();
+    }
+
+    fn broadcast(&self, event: &DaemonEvent) {
+        log::debug!("Broadcasting event: {0:?}", event);
+
+        let notification = DaemonRequest::notification(METHOD_EVENT, json!(event));
+
+        // Subscribers which cannot be written to (e.g. disconnected ones) get dropped.
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|connection| write_line(connection, &notification).is_ok());
+    }
+
+    fn serve(&self, stream: UnixStream, stop_requested: &AtomicBool) {
+        let reader_stream = match prepare_stream(&stream) {
+            Ok(reader_stream) => reader_stream,
+            Err(err) => {
+                log::error!("Failed to prepare connection: {0}", err);
+                return;
+            }
+        };
+
+        let connection: Connection = Arc::new(Mutex::new(stream));
+        let mut reader = BufReader::new(reader_stream);
+        let mut line = String::new();
+
+        while !stop_requested.load(Ordering::SeqCst) {
+            match reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if !line.trim().is_empty()
+                        && let Some(response) = self.handle(line.trim(), &connection)
+                        && write_line(&connection, &response).is_err()
+                    {
+                        break;
+                    }
+
+                    line.clear();
+                }
+                // Partial lines are kept in `line` and completed on subsequent reads.
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(err) => {
+                    log::debug!("Connection closed: {0}", err);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle(&self, line: &str, connection: &Connection) -> Option<DaemonResponse> {
+        let request: DaemonRequest = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(err) => {
+                // The ID of an unparsable request is unknown, so the error gets reported with a null ID.
+                return Some(DaemonResponse::failure(
+                    None,
+                    ERROR_CODE_PARSE,
+                    err.to_string(),
+                ));
+            }
+        };
+
+        log::debug!("Handling request: {0:?}", request);
+
+        let id = request.id.clone();
+
+        let result = match request.method.as_str() {
+            METHOD_LIST => self.list(&request.params),
+            METHOD_MOUNT => self.mount(&request.params),
+            METHOD_UMOUNT => self.umount(&request.params),
+            METHOD_PROBE => self.probe(&request.params),
+            METHOD_SUBSCRIBE => {
+                self.subscribers.lock().unwrap().push(connection.clone());
+                Ok(Value::Bool(true))
+            }
+            method => Err((
+                ERROR_CODE_METHOD_NOT_FOUND,
+                format!("Unknown method: {0}", method),
+            )),
+        };
+
+        // Notifications (requests without an ID) don't get responses, not even for errors.
+        if id.is_none() {
+            if let Err((_, message)) = &result {
+                log::debug!("Failed to handle notification: {0}", message);
+            }
+
+            return None;
+        }
+
+        Some(match result {
+            Ok(result) => DaemonResponse::success(id, result),
+            Err((code, message)) => DaemonResponse::failure(id, code, message),
+        })
+    }
+
+    fn list(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let filter = filter_from_params(params)?;
+
+        let states = self
+            .manager
+            .definitions_filtered(&filter)
+            .map_err(operation_failed)?;
+
+        let list: Vec<MountStatus> = states
+            .into_iter()
+            .map(|state| MountStatus {
+                local_mount_path: state.definition.local_mount_path().to_string(),
+                enabled: state.definition.enabled,
+                id: state.definition.id,
+                mounted: state.mounted,
+            })
+            .collect();
+
+        Ok(json!(list))
+    }
+
+    fn mount(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let definition = self
+            .manager
+            .definition(required_string_param(params, "id")?)
+            .map_err(operation_failed)?;
+
+        let outcome = {
+            let _lock = self.operation_lock.lock().unwrap();
+
+            let started_at = Instant::now();
+            let result = self.manager.mount_with_report(&definition);
+            self.metrics
+                .record_operation(Operation::Mount, started_at.elapsed(), result.is_ok());
+
+            // The whole report is sent, so that clients learn which step failed (and what happened while cleaning up).
+            result.map_err(|report| {
+                (
+                    ERROR_CODE_OPERATION_FAILED,
+                    format!("{0}: {1:?}", report, report.failures),
+                )
+            })?
+        };
+
+        self.refresh_mounted_ids();
+
+        Ok(json!(OperationStatus {
+            outcome: outcome.code().to_owned(),
+        }))
+    }
+
+    fn umount(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let definition = self
+            .manager
+            .definition(required_string_param(params, "id")?)
+            .map_err(operation_failed)?;
+
+        let kill_policy = match params.get("killPolicy").and_then(Value::as_str) {
+            Some(value) => KillPolicy::from_string(value)
+                .map_err(|err| (ERROR_CODE_INVALID_PARAMS, format!("killPolicy: {0}", err)))?,
+            None => KillPolicy::default(),
+        };
+
+        let kill_policy = match (kill_policy, params.get("signal").and_then(Value::as_str)) {
+            (KillPolicy::TerminateUsers(_), Some(value)) => KillPolicy::TerminateUsers(
+                TerminationSignal::from_string(value)
+                    .map_err(|err| (ERROR_CODE_INVALID_PARAMS, format!("signal: {0}", err)))?,
+            ),
+            (kill_policy, _) => kill_policy,
+        };
+
+        let outcome = {
+            let _lock = self.operation_lock.lock().unwrap();
+
+            let started_at = Instant::now();
+            let result = self
+                .manager
+                .umount_with_kill_policy(&definition, kill_policy);
+            self.metrics
+                .record_operation(Operation::Umount, started_at.elapsed(), result.is_ok());
+
+            result.map_err(operation_failed)?
+        };
+
+        self.refresh_mounted_ids();
+
+        Ok(json!(OperationStatus {
+            outcome: outcome.code().to_owned(),
+        }))
+    }
+
+    fn probe(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let definition = self
+            .manager
+            .definition(required_string_param(params, "id")?)
+            .map_err(operation_failed)?;
+
+        let write = params
+            .get("write")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        // Probing doesn't change anything, so it does not wait for other operations to complete.
+        let outcome = self
+            .manager
+            .probe(&definition, write)
+            .map_err(operation_failed)?;
+
+        Ok(json!(ProbeStatus {
+            health: outcome.code().to_owned(),
+            details: outcome.details().map(|details| details.to_owned()),
+        }))
+    }
+
+    fn serve_metrics(&self, listener: &TcpListener, stop_requested: &AtomicBool) {
+        while !stop_requested.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(err) = self.respond_with_metrics(stream) {
+                        log::debug!("Failed to serve metrics: {0}", err);
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(ACCEPT_INTERVAL);
+                }
+                Err(err) => {
+                    log::error!("Failed to accept metrics connection: {0}", err);
+                    thread::sleep(ACCEPT_INTERVAL);
+                }
+            }
+        }
+    }
+
+    /// Responds to a single HTTP request with the metrics.
+    fn respond_with_metrics(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        let mut request_line = String::new();
+        BufReader::new(stream.try_clone()?).read_line(&mut request_line)?;
+
+        // Example: `GET /metrics HTTP/1.1`
+        let path = request_line.split_whitespace().nth(1).unwrap_or("");
+
+        let (status, body) = if path == "/metrics" || path == "/" {
+            ("200 OK", self.render_metrics())
+        } else {
+            ("404 Not Found", String::new())
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {0}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {1}\r\nConnection: close\r\n\r\n{2}",
+            status,
+            body.len(),
+            body
+        )
+    }
+
+    fn write_metrics_textfile(&self, path: &Path, stop_requested: &AtomicBool) {
+        let mut last_written_at: Option<Instant> = None;
+
+        while !stop_requested.load(Ordering::SeqCst) {
+            if last_written_at.is_none_or(|at| at.elapsed() >= METRICS_TEXTFILE_INTERVAL) {
+                last_written_at = Some(Instant::now());
+
+                match self.manager.full_state() {
+                    Ok(states) => {
+                        if let Err(err) = self.metrics.write_textfile(path, &self.manager, &states)
+                        {
+                            log::error!("Failed to write metrics to {0}: {1}", path.display(), err);
+                        }
+                    }
+                    Err(err) => {
+                        log::error!("Failed to determine the mount state: {0:?}", err);
+                    }
+                }
+            }
+
+            thread::sleep(ACCEPT_INTERVAL);
+        }
+    }
+
+    fn render_metrics(&self) -> String {
+        match self.manager.full_state() {
+            Ok(states) => self.metrics.render(&self.manager, &states),
+            Err(err) => {
+                log::error!("Failed to determine the mount state: {0:?}", err);
+                self.metrics.render(&self.manager, &[])
+            }
+        }
+    }
+}
+
+fn bind_metrics(address: SocketAddr) -> Result<TcpListener, SftpManError> {
+    let listener = TcpListener::bind(address).map_err(|err| {
+        SftpManError::Daemon(format!(
+            "failed to listen for metrics requests on {0}: {1}",
+            address, err
+        ))
+    })?;
+
+    listener.set_nonblocking(true).map_err(|err| {
+        SftpManError::Daemon(format!(
+            "failed to listen for metrics requests on {0}: {1}",
+            address, err
+        ))
+    })?;
+
+    log::info!("Serving metrics on http://{0}/metrics", address);
+
+    Ok(listener)
+}
+
+fn prepare_stream(stream: &UnixStream) -> std::io::Result<UnixStream> {
+    // Accepted streams may inherit the listener's non-blocking mode.
+    stream.set_nonblocking(false)?;
+
+    // Reads time out periodically, so that stop requests are noticed.
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    stream.try_clone()
+}
+
+fn required_string_param<'a>(params: &'a Value, name: &str) -> Result<&'a str, (i64, String)> {
+    params.get(name).and_then(Value::as_str).ok_or((
+        ERROR_CODE_INVALID_PARAMS,
+        format!("Missing string parameter: {0}", name),
+    ))
+}
+
+/// Builds a filter from the optional `list` params (e.g. `{"host": "example.com", "authType": "password", "mounted": true, "search": "backup", "mountPathPrefix": "/mnt/sshfs/work"}`).
+fn filter_from_params(params: &Value) -> Result<DefinitionFilter, (i64, String)> {
+    let mut filter = DefinitionFilter::new();
+
+    if let Some(host) = params.get("host").and_then(Value::as_str) {
+        filter = filter.with_host(host);
+    }
+
+    if let Some(value) = params.get("authType").and_then(Value::as_str) {
+        let auth_type = AuthType::from_string(value)
+            .map_err(|err| (ERROR_CODE_INVALID_PARAMS, format!("authType: {0}", err)))?;
+        filter = filter.with_auth_type(auth_type);
+    }
+
+    if let Some(mounted) = params.get("mounted").and_then(Value::as_bool) {
+        filter = filter.with_mounted(mounted);
+    }
+
+    if let Some(enabled) = params.get("enabled").and_then(Value::as_bool) {
+        filter = filter.with_enabled(enabled);
+    }
+
+    if let Some(text) = params.get("search").and_then(Value::as_str) {
+        filter = filter.with_text(text);
+    }
+
+    if let Some(prefix) = params.get("mountPathPrefix").and_then(Value::as_str) {
+        filter = filter.with_mount_path_prefix(Path::new(prefix));
+    }
+
+    Ok(filter)
+}
+
+fn operation_failed(err: SftpManError) -> (i64, String) {
+    (ERROR_CODE_OPERATION_FAILED, format!("{0}: {1:?}", err, err))
+}
+
+fn write_line<T: Serialize>(connection: &Connection, message: &T) -> std::io::Result<()> {
+    let mut serialized = serde_json::to_string(message)?;
+    serialized.push('\n');
+
+    connection.lock().unwrap().write_all(serialized.as_bytes())
+}
@@ -0,0 +1,147 @@
+//! Following suspend and resume via logind's `PrepareForSleep` signal (see `Daemon::with_sleep_following()`).
+//!
+//! The signal is received via `gdbus monitor` and the sleep delay lock is held via `systemd-inhibit`,
+//! so that no D-Bus library is needed.
+
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::os::fd::AsRawFd;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use nix::fcntl::{FcntlArg, OFlag, fcntl};
+
+use crate::errors::SftpManError;
+
+const PREPARE_FOR_SLEEP_SIGNAL: &str = "org.freedesktop.login1.Manager.PrepareForSleep";
+
+/// Receives logind's `PrepareForSleep` signals, by running `gdbus monitor` in the background.
+pub(super) struct SleepMonitor {
+    process: Child,
+    reader: BufReader<ChildStdout>,
+    line: String,
+}
+
+impl SleepMonitor {
+    pub(super) fn start() -> Result<Self, SftpManError> {
+        let mut cmd = Command::new("gdbus");
+        cmd.args([
+            "monitor",
+            "--system",
+            "--dest",
+            "org.freedesktop.login1",
+            "--object-path",
+            "/org/freedesktop/login1",
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
+        let mut process = match cmd.spawn() {
+            Ok(process) => process,
+            Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+        };
+
+        let stdout = process.stdout.take().expect("stdout is piped");
+
+        // Reading must not block, so that the daemon can stop while nothing gets signaled.
+        if let Err(err) = fcntl(stdout.as_raw_fd(), FcntlArg::F_SETFL(OFlag::O_NONBLOCK)) {
+            let _ = process.kill();
+            let _ = process.wait();
+
+            return Err(SftpManError::Generic(format!(
+                "failed to make the output of gdbus non-blocking: {0}",
+                err.desc()
+            )));
+        }
+
+        Ok(Self {
+            process,
+            reader: BufReader::new(stdout),
+            line: String::new(),
+        })
+    }
+
+    /// Returns the next signaled transition (`true` when about to sleep, `false` when resumed), if one was signaled since the last call.
+    pub(super) fn poll(&mut self) -> Result<Option<bool>, SftpManError> {
+        loop {
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => {
+                    return Err(SftpManError::Generic(
+                        "gdbus stopped monitoring logind".to_owned(),
+                    ));
+                }
+                Ok(_) => {
+                    // Lines look like: `/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (true,)`
+                    let transition = self
+                        .line
+                        .split_once(PREPARE_FOR_SLEEP_SIGNAL)
+                        .map(|(_, arguments)| arguments.contains("true"));
+
+                    self.line.clear();
+
+                    if transition.is_some() {
+                        return Ok(transition);
+                    }
+                }
+                // Partial lines are kept in `line` and completed on subsequent reads.
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(None),
+                Err(err) => {
+                    return Err(SftpManError::Generic(format!(
+                        "failed to read from gdbus: {0}",
+                        err
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SleepMonitor {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// A delay lock on sleeping, which gives the daemon time to unmount before the system goes to sleep.
+///
+/// It's held by running `systemd-inhibit --mode=delay .. cat` and gets released when dropped
+/// (closing the standard input of `cat` makes it, and with it `systemd-inhibit`, exit).
+pub(super) struct SleepInhibitor {
+    process: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl SleepInhibitor {
+    pub(super) fn take() -> Result<Self, SftpManError> {
+        let mut cmd = Command::new("systemd-inhibit");
+        cmd.args([
+            "--what=sleep",
+            "--mode=delay",
+            "--who=sftpman",
+            "--why=Unmounting sshfs filesystems",
+            "cat",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+        let mut process = match cmd.spawn() {
+            Ok(process) => process,
+            Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+        };
+
+        let stdin = process.stdin.take();
+
+        Ok(Self { process, stdin })
+    }
+}
+
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        drop(self.stdin.take());
+
+        if let Err(err) = self.process.wait() {
+            log::debug!("Failed to wait for systemd-inhibit to exit: {0}", err);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::errors::SftpManError;
+use crate::model::SyncOutcome;
+use crate::utils::command::run_command;
+
+/// Used as the commit author when git has no identity configured (e.g. on a fresh machine).
+const FALLBACK_AUTHOR_NAME: &str = "sftpman";
+const FALLBACK_AUTHOR_EMAIL: &str = "sftpman@localhost";
+
+/// Synchronizes the definitions directory with a git remote.
+///
+/// The directory is turned into a git repository on first use. Local changes get committed,
+/// changes from the remote get merged in and the result gets pushed back.
+///
+/// If the same definitions were changed both locally and remotely, the merge is aborted (leaving the local definitions as they were)
+/// and `SftpManError::SyncConflict` is returned.
+pub(crate) fn sync_with_git_remote(
+    dir_path: &Path,
+    remote: &str,
+    branch: &str,
+) -> Result<SyncOutcome, SftpManError> {
+    if !dir_path.join(".git").exists() {
+        log::info!(
+            "Initializing a git repository for definitions in {0}",
+            dir_path.display()
+        );
+
+        let mut cmd = git(dir_path);
+        cmd.args(["init", "--quiet", "--initial-branch", branch]);
+        run_command(cmd)?;
+
+        let mut cmd = git(dir_path);
+        cmd.args(["remote", "add", "origin", remote]);
+        run_command(cmd)?;
+    } else {
+        // The remote may have been changed in the configuration since the last sync.
+        let mut cmd = git(dir_path);
+        cmd.args(["remote", "set-url", "origin", remote]);
+        run_command(cmd)?;
+    }
+
+    let committed = commit_local_changes(dir_path)?;
+
+    let mut cmd = git(dir_path);
+    cmd.args(["fetch", "--quiet", "origin"]);
+    run_command(cmd)?;
+
+    let remote_ref = format!("origin/{0}", branch);
+    let head_before = rev_parse(dir_path, "HEAD")?;
+
+    let mut received = false;
+
+    if rev_parse(dir_path, &remote_ref)?.is_some() {
+        let mut cmd = git(dir_path);
+        with_author_fallback(dir_path, &mut cmd)?;
+        cmd.args([
+            "merge",
+            "--quiet",
+            "--no-edit",
+            // The first sync on a new machine joins the history of the local definitions with that of the remote.
+            "--allow-unrelated-histories",
+            &remote_ref,
+        ]);
+
+        if let Err(err) = run_command(cmd) {
+            let conflicting_ids = conflicting_ids(dir_path)?;
+
+            let mut cmd = git(dir_path);
+            cmd.args(["merge", "--abort"]);
+            run_command(cmd)?;
+
+            if conflicting_ids.is_empty() {
+                return Err(err);
+            }
+
+            return Err(SftpManError::SyncConflict(conflicting_ids));
+        }
+
+        received = rev_parse(dir_path, "HEAD")? != head_before;
+    }
+
+    let mut pushed = false;
+
+    if rev_parse(dir_path, "HEAD")? != rev_parse(dir_path, &remote_ref)? {
+        let mut cmd = git(dir_path);
+        cmd.args(["push", "--quiet", "origin", &format!("HEAD:{0}", branch)]);
+        run_command(cmd)?;
+
+        pushed = true;
+    }
+
+    Ok(SyncOutcome {
+        committed,
+        received,
+        pushed,
+    })
+}
+
+fn git(dir_path: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(dir_path);
+    cmd
+}
+
+/// Commits all local changes (if any). Returns `true` if a commit was made.
+fn commit_local_changes(dir_path: &Path) -> Result<bool, SftpManError> {
+    let mut cmd = git(dir_path);
+    cmd.args(["add", "--all"]);
+    run_command(cmd)?;
+
+    let mut cmd = git(dir_path);
+    cmd.args(["status", "--porcelain"]);
+    let output = run_command(cmd)?;
+
+    if output.stdout.is_empty() {
+        return Ok(false);
+    }
+
+    let mut cmd = git(dir_path);
+    with_author_fallback(dir_path, &mut cmd)?;
+    cmd.args(["commit", "--quiet", "--message", "Update definitions"]);
+    run_command(cmd)?;
+
+    Ok(true)
+}
+
+/// Makes the given git command use a fallback identity, unless one is configured.
+fn with_author_fallback(dir_path: &Path, cmd: &mut Command) -> Result<(), SftpManError> {
+    let mut check = git(dir_path);
+    check.args(["config", "user.email"]);
+
+    if run_command(check).is_err() {
+        cmd.args([
+            "-c",
+            &format!("user.name={0}", FALLBACK_AUTHOR_NAME),
+            "-c",
+            &format!("user.email={0}", FALLBACK_AUTHOR_EMAIL),
+        ]);
+    }
+
+    Ok(())
+}
+
+/// Returns the commit the given revision points to, or `None` if it doesn't exist (e.g. `HEAD` in an empty repository).
+fn rev_parse(dir_path: &Path, revision: &str) -> Result<Option<String>, SftpManError> {
+    let mut cmd = git(dir_path);
+    cmd.args(["rev-parse", "--verify", "--quiet", revision]);
+
+    match run_command(cmd) {
+        Ok(output) => Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_owned(),
+        )),
+        Err(SftpManError::CommandUnsuccessful(_, _)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the IDs of the definitions which could not be merged (during a merge).
+fn conflicting_ids(dir_path: &Path) -> Result<Vec<String>, SftpManError> {
+    let mut cmd = git(dir_path);
+    cmd.args(["diff", "--name-only", "--diff-filter=U"]);
+    let output = run_command(cmd)?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|name| name.strip_suffix(".json").unwrap_or(name).to_owned())
+        .collect())
+}
@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use crate::auth_type::AuthType;
+use crate::model::FilesystemMountDefinition;
+
+/// A desktop entry (`.desktop` file) which mounts a filesystem and opens a file manager at it,
+/// making it show up in application menus and docks.
+///
+/// Desktop entries are meant to be installed into the user's applications directory (e.g. `~/.local/share/applications`).
+/// See the [Desktop Entry Specification](https://specifications.freedesktop.org/desktop-entry-spec/latest/).
+#[derive(Debug, Clone)]
+pub struct DesktopEntry {
+    /// The name of the file (e.g. `sftpman-my-fs.desktop`), derived from the definition ID.
+    pub file_name: String,
+
+    pub contents: String,
+}
+
+impl DesktopEntry {
+    /// Creates a desktop entry for the given definition, which runs `program` (the path to the `sftpman` executable)
+    /// as `program mount --open ID`.
+    pub fn new(definition: &FilesystemMountDefinition, program: &Path) -> Self {
+        let name = definition.description.as_deref().unwrap_or(&definition.id);
+
+        // Without a way to obtain the password non-interactively, sshfs prompts for it.
+        let needs_terminal = matches!(
+            definition.auth_type,
+            AuthType::Password | AuthType::KeyboardInteractive
+        ) && !definition.has_password_source();
+
+        let exec = [
+            quote_exec_arg(&program.to_string_lossy()),
+            "mount".to_owned(),
+            "--open".to_owned(),
+            quote_exec_arg(&definition.id),
+        ]
+        .join(" ");
+
+        let lines = [
+            "[Desktop Entry]".to_owned(),
+            "Type=Application".to_owned(),
+            "Version=1.5".to_owned(),
+            format!("Name={0}", escape_value(name)),
+            format!(
+                "Comment=Mount {0} from {1} and open it",
+                escape_value(&definition.remote_path),
+                escape_value(&definition.host)
+            ),
+            "Icon=folder-remote".to_owned(),
+            format!("Exec={0}", escape_value(&exec)),
+            format!("Terminal={0}", needs_terminal),
+            "Categories=Network;FileTransfer;".to_owned(),
+            format!(
+                "Keywords=sftp;sshfs;{0};{1};",
+                escape_list_value(&definition.id),
+                escape_list_value(&definition.host)
+            ),
+        ];
+
+        Self {
+            file_name: Self::file_name_for(&definition.id),
+            contents: format!("{0}\n", lines.join("\n")),
+        }
+    }
+
+    /// Returns the file name that the desktop entry for the definition with the given ID gets (e.g. `sftpman-my-fs.desktop`).
+    pub fn file_name_for(id: &str) -> String {
+        format!("{0}-{1}.desktop", env!("CARGO_PKG_NAME"), id)
+    }
+}
+
+/// Quotes an argument for the `Exec` key, if it contains anything which is reserved there (e.g. spaces).
+fn quote_exec_arg(arg: &str) -> String {
+    // Field codes (e.g. `%f`) get expanded even within quotes, so literal percent signs are always doubled.
+    let arg = arg.replace('%', "%%");
+
+    let is_reserved = |c: char| {
+        c.is_whitespace()
+            || matches!(
+                c,
+                '"' | '\''
+                    | '\\'
+                    | '>'
+                    | '<'
+                    | '~'
+                    | '|'
+                    | '&'
+                    | ';'
+                    | '$'
+                    | '*'
+                    | '?'
+                    | '#'
+                    | '('
+                    | ')'
+                    | '`'
+            )
+    };
+
+    if !arg.contains(is_reserved) {
+        return arg;
+    }
+
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Escapes a string value (e.g. a `Name`), so that backslashes and line breaks survive.
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes an item of a list value (e.g. `Keywords`), whose items are separated by semicolons.
+fn escape_list_value(value: &str) -> String {
+    escape_value(value).replace(';', "\\;")
+}
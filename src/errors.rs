@@ -1,10 +1,24 @@
 use thiserror::Error;
 
+use crate::i18n::{self, Language};
+
 #[derive(Error, Debug)]
 pub enum ManagerInitError {
     /// Happens when `directories::ProjectDirs` cannot retrieve a valid home directory path from the operating system.
     #[error("The config directory could not be determined")]
     NoConfigDirectory,
+
+    /// Happens when the global configuration file (e.g. `~/.config/sftpman/config.json`) exists, but cannot be read.
+    #[error("The global configuration file could not be read")]
+    GlobalConfigRead(std::path::PathBuf, std::io::Error),
+
+    /// Happens when the global configuration file (e.g. `~/.config/sftpman/config.json`) cannot be parsed as JSON.
+    #[error("The global configuration file could not be parsed")]
+    GlobalConfigParse(std::path::PathBuf, serde_json::Error),
+
+    /// Happens when the selected profile (see `Manager::for_profile()`) does not exist.
+    #[error("The profile {0} does not exist")]
+    UnknownProfile(String),
 }
 
 #[derive(Error, Debug)]
@@ -17,6 +31,28 @@ pub enum PreflightCheckError {
     #[error("A required command was executed, but was unsuccessful")]
     CommandUnsuccessful(std::process::Command, std::process::Output),
 
+    /// Happens when some definitions (whose IDs are contained) use a FIDO2 security key (e.g. a `sk-ssh-ed25519` key),
+    /// but `ssh-sk-helper` (which `ssh` needs for using such keys) cannot be found.
+    #[error(
+        "A security key is used, but ssh-sk-helper (part of OpenSSH, usually shipped with libfido2 support) could not be found"
+    )]
+    SecurityKeyHelperMissing(Vec<String>),
+
+    /// Happens when a definition (whose ID is contained) uses `AuthType::AuthenticationAgent`, but there's no usable SSH agent.
+    /// The reason (e.g. the agent having no identities loaded) is contained as well.
+    #[error("An SSH agent is needed, but it cannot be used")]
+    AgentUnavailable(String, String),
+
+    /// Happens when some definitions (whose IDs are contained) limit the bandwidth (see `FilesystemMountDefinition::download_limit`),
+    /// but `trickle` (which enforces the limits) cannot be found.
+    #[error("A bandwidth limit is used, but trickle could not be found")]
+    BandwidthLimiterMissing(Vec<String>),
+
+    /// Happens when some definitions (whose IDs are contained) get bind-mounted at extra mount paths
+    /// (see `FilesystemMountDefinition::extra_mount_path_method`), but `bindfs` (which makes the bind mounts) cannot be found.
+    #[error("Extra mount paths are bind-mounted, but bindfs could not be found")]
+    BindMounterMissing(Vec<String>),
+
     /// Happens when the default mount path (e.g. `/mnt/sshfs`) does not exist and cannot be prepared.
     #[error(
         "The default mount path (/mnt/sshfs) could not be prepared. Mounting there will fail until this is fixed"
@@ -28,6 +64,14 @@ pub enum PreflightCheckError {
         "A test directory under the default mount path (/mnt/sshfs) could not be prepared. Mounting there will fail until this is fixed"
     )]
     TestUnderBasePathIO(std::path::PathBuf, std::io::Error),
+
+    /// Happens when something else (a systemd `.mount`/`.automount` unit, an fstab entry or an autofs map) mounts the same path
+    /// as a definition (whose ID is contained), which makes them fight over it (e.g. unmount/remount loops).
+    /// Where the other mount comes from (e.g. `/etc/auto.master`) is contained as well.
+    #[error(
+        "Another automount manager (systemd unit, fstab entry or autofs map) targets the same path as a definition, so they will interfere with each other"
+    )]
+    ConflictingMountManager(String, String),
 }
 
 #[derive(Error, Debug)]
@@ -41,6 +85,7 @@ pub enum SftpManError {
     NoMountsConfigDirectory,
 
     /// Happens when `mnt::get_submounts` fails to parse the mount list.
+    #[cfg(target_os = "linux")]
     #[error("The current mounts could not be parsed")]
     MountListParse(#[from] mnt::ParseError),
 
@@ -52,6 +97,84 @@ pub enum SftpManError {
     #[error("The mount config definition could not be removed")]
     FilesystemMountDefinitionRemove(std::path::PathBuf, std::io::Error),
 
+    /// Happens when trying to mount a definition which is disabled (see `FilesystemMountDefinition::enabled`).
+    #[error("The definition is disabled")]
+    DefinitionDisabled(String),
+
+    /// Happens when the local mount path of a definition is not empty while mounting
+    /// (unless allowed via `FilesystemMountDefinition::allow_non_empty_mount_path`).
+    #[error("The local mount path {path} is not empty", path = .1.display())]
+    MountPathNotEmpty(String, std::path::PathBuf),
+
+    /// Happens when trying to mount a definition whose requirement (see `FilesystemMountDefinition::requires`) is not met,
+    /// e.g. because the VPN it needs is down.
+    #[error("Requirement not met: {1}")]
+    RequirementUnmet(String, String),
+
+    /// Happens when scanning for a definition's host keys (see `Manager::scan_host_keys()`) yields none,
+    /// e.g. because the host is unreachable. The output of `ssh-keyscan` is contained.
+    #[error("No host keys could be retrieved from the remote machine")]
+    NoHostKeys(String, String),
+
+    /// Happens when generating an SSH key (see `Manager::generate_ssh_key()`) at a path where a file already exists.
+    #[error("A file already exists where the SSH key was to be stored")]
+    SshKeyExists(std::path::PathBuf),
+
+    /// Happens when an ID given by the user (see `Manager::resolve_definition()`) neither is nor starts any definition's ID.
+    /// Close matches (e.g. for typos) are contained, closest first.
+    #[error("No definition has an ID of (or starting with) {0}{hint}", hint = did_you_mean(.1))]
+    UnknownId(String, Vec<String>),
+
+    /// Happens when an ID given by the user (see `Manager::resolve_definition()`) is the start of several definitions' IDs,
+    /// which are contained.
+    #[error("The ID {0} is ambiguous, as it could be any of: {ids}", ids = .1.join(", "))]
+    AmbiguousId(String, Vec<String>),
+
+    /// Happens when persisting a definition which was changed by someone else (e.g. another frontend) since it was loaded.
+    /// See `Manager::persist_with_force()` for overwriting it anyway.
+    #[error("The definition was changed by someone else since it was loaded")]
+    ConflictingEdit(String),
+
+    /// Happens when the password referenced by a definition (see `FilesystemMountDefinition::password_command`) cannot be obtained.
+    #[error("The password could not be obtained: {1}")]
+    PasswordUnavailable(String, String),
+
+    /// Happens when a definition using `AuthType::AuthenticationAgent` is mounted, but there's no usable SSH agent
+    /// (e.g. `SSH_AUTH_SOCK` is not set, or the agent has no identities loaded).
+    #[error("The SSH agent cannot be used: {1}")]
+    AgentUnavailable(String, String),
+
+    /// Happens when a definition (whose ID is contained) limits the bandwidth (see `FilesystemMountDefinition::download_limit`),
+    /// but `trickle` (which enforces the limits) cannot be found.
+    #[error("A bandwidth limit is used, but trickle could not be found")]
+    BandwidthLimiterMissing(String),
+
+    /// Happens when a program needed for mounting a definition (whose ID is contained) cannot be found before mounting
+    /// (e.g. `sshfs`, or a `FilesystemMountDefinition::ssh_binary`). Alternatives are contained as `A or B` (e.g. `fusermount3 or fusermount`).
+    #[error("{1} could not be found, but it is needed for mounting{hint}", hint = install_hint(.1))]
+    ProgramMissing(String, String),
+
+    /// Happens when FUSE cannot be used for mounting a definition (whose ID is contained), as found before mounting.
+    /// The reason (e.g. `/dev/fuse does not exist`) is contained.
+    #[error(
+        "FUSE cannot be used ({1}). Check that FUSE is installed and that the fuse kernel module is loaded (e.g. `modprobe fuse`)"
+    )]
+    FuseUnavailable(String, String),
+
+    /// Happens when an SSH key (or certificate) file of a definition (whose ID is contained) cannot be read before mounting.
+    #[error("The key file {path} cannot be read: {2}", path = .1.display())]
+    KeyFileUnreadable(String, std::path::PathBuf, std::io::Error),
+
+    /// Happens when the host of a definition (whose ID is contained) does not resolve before mounting
+    /// (see `GlobalConfig::resolve_hosts_before_mount`). The host and the reason (e.g. `Name or service not known`) are contained.
+    #[error("DNS resolution failed for {1}: {2}")]
+    HostResolutionFailed(String, String, String),
+
+    /// Happens when something else (e.g. a file, or a symlink pointing elsewhere) is at one of the extra mount paths
+    /// of a definition (whose ID is contained), where the filesystem was to be exposed (see `FilesystemMountDefinition::extra_mount_paths`).
+    #[error("Something else is in the way of exposing the filesystem at {path}", path = .1.display())]
+    ExtraMountPathOccupied(String, std::path::PathBuf),
+
     /// Happens when the mount config definition cannot be parsed as JSON.
     #[error("The mount config definition could not be parsed")]
     JSON(std::path::PathBuf, serde_json::Error),
@@ -79,4 +202,286 @@ pub enum SftpManError {
     /// Happens when the mount directory could not be prepared.
     #[error("The mount directory could not be prepared")]
     IO(std::path::PathBuf, std::io::Error),
+
+    /// Happens when `sshfs` (running with debug output enabled) fails to mount.
+    /// Its debug output can be found in the log file at `log_path`.
+    #[error(
+        "The mount failed ({reason}). See the sshfs debug log for details: {0}",
+        .log_path.display()
+    )]
+    MountFailedWithDebugLog {
+        log_path: std::path::PathBuf,
+        reason: String,
+    },
+
+    /// Happens when the mount command was executed, but was unsuccessful for a recognized reason (see `MountFailureKind`).
+    #[error("The mount failed: {0}")]
+    MountFailure(
+        MountFailureKind,
+        std::process::Command,
+        std::process::Output,
+    ),
+
+    /// Happens when the mount commands succeeded, but the filesystem (whose definition ID is contained) does not show up as mounted.
+    #[error("The mount commands succeeded, but the filesystem did not get mounted")]
+    MountMissing(String),
+
+    /// Happens when unmounting a filesystem (whose definition ID is contained) fails, because processes are using it
+    /// (see `Manager::blocking_processes()`). The processes found to be using it are contained.
+    #[error("The filesystem is busy, as it is used by: {processes}", processes = list_processes(.1))]
+    MountBusy(String, Vec<crate::model::BlockingProcess>),
+
+    /// Happens when the daemon (see `Daemon`) responds to a request with an error.
+    #[error("The daemon reported an error: {0}")]
+    Daemon(String),
+
+    /// Happens when an operation (e.g. detecting mounts or killing processes) is not supported on the current platform.
+    #[error("This operation is not supported on the current platform: {0}")]
+    UnsupportedPlatform(&'static str),
+
+    /// Happens when a global configuration setting which does not exist is referenced (see `GlobalConfig::set()`).
+    #[error("There is no such configuration setting: {0}")]
+    UnknownConfigSetting(String),
+
+    /// Happens when a value which does not fit a global configuration setting is given (see `GlobalConfig::set()`).
+    #[error("The value is not valid for configuration setting {0}")]
+    InvalidConfigValue(String, serde_json::Error),
+
+    /// Happens when synchronizing definitions (see `Manager::sync()`) without a sync remote being configured (see `GlobalConfig::sync_git_remote`).
+    #[error("No remote to synchronize definitions with is configured")]
+    SyncNotConfigured,
+
+    /// Happens when the same definitions (with the given IDs) were changed both locally and remotely since the last sync (see `Manager::sync()`).
+    #[error("The definitions were changed both locally and remotely: {}", .0.join(", "))]
+    SyncConflict(Vec<String>),
+
+    /// Happens when the network does not become available before the timeout expires (see `Manager::wait_for_network()`).
+    #[error("The network did not become available in time")]
+    NetworkWaitTimeout(std::time::Duration),
+
+    /// Happens when a profile which does not exist is referenced (see `Manager::switch_profile()`).
+    #[error("The profile {0} does not exist")]
+    UnknownProfile(String),
+
+    /// Happens when creating a profile (see `Manager::create_profile()`) which already exists.
+    #[error("The profile {0} already exists")]
+    ProfileExists(String),
+
+    /// Happens when creating a profile (see `Manager::create_profile()`) whose name contains something other than
+    /// alphanumeric characters, underscores, dashes or dots.
+    #[error(
+        "Invalid profile name {0}. It must contain only alphanumeric characters, underscores, dashes, or dots"
+    )]
+    InvalidProfileName(String),
+
+    /// Happens when a URL (e.g. `sftp://user@example.com/storage`) cannot be turned into a definition (see `FilesystemMountDefinition::from_url()`).
+    #[error("Invalid URL {0}: {1}")]
+    InvalidUrl(String, String),
+}
+
+/// Recognized reasons for `sshfs` (or the `ssh` command it runs) failing to mount.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MountFailureKind {
+    /// The remote host rejected all authentication attempts (e.g. `Permission denied (publickey)`).
+    AuthenticationFailed,
+
+    /// The remote host's key is unknown or has changed (`Host key verification failed`).
+    HostKeyVerificationFailed,
+
+    /// The hostname could not be resolved (e.g. `Could not resolve hostname`).
+    HostNotResolved,
+
+    /// Nothing is listening on the remote port (`Connection refused`).
+    ConnectionRefused,
+
+    /// The remote host could not be reached in time (e.g. `Connection timed out`).
+    ConnectionTimedOut,
+
+    /// The connection was closed by the remote host (e.g. `read: Connection reset by peer`).
+    ConnectionClosed,
+
+    /// The local mount point is unusable (e.g. `fuse: bad mount point`).
+    BadMountPoint,
+
+    /// The local mount point is not empty (`fuse: mountpoint is not empty`).
+    MountPointNotEmpty,
+
+    /// The remote path does not exist.
+    RemotePathNotFound,
+
+    /// FUSE is not available on this system (e.g. `fuse: device not found`).
+    FuseUnavailable,
+}
+
+impl MountFailureKind {
+    /// Tries to recognize the reason for a mount failure based on the error output (stderr) of `sshfs`.
+    pub fn from_stderr(stderr: &str) -> Option<Self> {
+        // The order matters, as some messages are contained in others
+        // (e.g. `fuse: bad mount point `/mnt/x': No such file or directory`).
+        let patterns: [(&str, Self); 12] = [
+            ("fuse: device not found", Self::FuseUnavailable),
+            ("/dev/fuse", Self::FuseUnavailable),
+            ("fuse: mountpoint is not empty", Self::MountPointNotEmpty),
+            ("fuse: bad mount point", Self::BadMountPoint),
+            (
+                "Host key verification failed",
+                Self::HostKeyVerificationFailed,
+            ),
+            ("Permission denied", Self::AuthenticationFailed),
+            (
+                "Too many authentication failures",
+                Self::AuthenticationFailed,
+            ),
+            ("Could not resolve hostname", Self::HostNotResolved),
+            ("Connection refused", Self::ConnectionRefused),
+            ("Connection timed out", Self::ConnectionTimedOut),
+            ("Connection reset", Self::ConnectionClosed),
+            ("Connection closed", Self::ConnectionClosed),
+        ];
+
+        patterns
+            .into_iter()
+            .find(|(pattern, _)| stderr.contains(pattern))
+            .map(|(_, kind)| kind)
+            .or_else(|| {
+                stderr
+                    .lines()
+                    .any(is_remote_path_not_found_line)
+                    .then_some(Self::RemotePathNotFound)
+            })
+    }
+
+    /// Returns a human-readable hint about what may be done to fix the problem.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::AuthenticationFailed => {
+                "Check that the user is correct and that the SSH key is added to the server (e.g. in ~/.ssh/authorized_keys)."
+            }
+            Self::HostKeyVerificationFailed => {
+                "Verify and accept the host key via `sftpman accept_hostkey ID` (or by connecting once with ssh), or check if it has changed for a legitimate reason."
+            }
+            Self::HostNotResolved => {
+                "Check that the host name is spelled correctly and that DNS works."
+            }
+            Self::ConnectionRefused => {
+                "Is the port correct? Check that an SSH server is running on the remote machine."
+            }
+            Self::ConnectionTimedOut => {
+                "Check that the host is reachable (network, VPN, firewall) and that the port is correct."
+            }
+            Self::ConnectionClosed => {
+                "Check that SFTP is enabled on the SSH server and that the user is allowed to use it."
+            }
+            Self::BadMountPoint => {
+                "Check that the local mount path exists, is a directory and is not in use by a stale mount."
+            }
+            Self::MountPointNotEmpty => {
+                "The local mount path contains files. Empty it or pick another mount path."
+            }
+            Self::RemotePathNotFound => "Check that the remote path exists on the remote machine.",
+            Self::FuseUnavailable => {
+                "Check that FUSE is installed and that the fuse kernel module is loaded (e.g. `modprobe fuse`)."
+            }
+        }
+    }
+
+    /// Returns the hint (see `hint()`) in the given language, falling back to English when there is no translation.
+    pub fn localized_hint(&self, language: Language) -> &'static str {
+        i18n::translate(language, &format!("mount_failure.{0}.hint", self.key()))
+            .unwrap_or(self.hint())
+    }
+
+    /// Returns the description of the failure (as shown via `Display`) in the given language,
+    /// falling back to English when there is no translation.
+    pub fn localized_description(&self, language: Language) -> &'static str {
+        i18n::translate(language, &format!("mount_failure.{0}", self.key()))
+            .unwrap_or(self.description())
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::AuthenticationFailed => "authentication failed",
+            Self::HostKeyVerificationFailed => "host key verification failed",
+            Self::HostNotResolved => "the host name could not be resolved",
+            Self::ConnectionRefused => "the connection was refused",
+            Self::ConnectionTimedOut => "the connection timed out",
+            Self::ConnectionClosed => "the connection was closed by the remote machine",
+            Self::BadMountPoint => "the local mount path is unusable",
+            Self::MountPointNotEmpty => "the local mount path is not empty",
+            Self::RemotePathNotFound => "the remote path does not exist",
+            Self::FuseUnavailable => "FUSE is not available",
+        }
+    }
+
+    /// Returns a stable name for the failure, used for looking up translations (see `i18n`).
+    fn key(&self) -> &'static str {
+        match self {
+            Self::AuthenticationFailed => "authentication_failed",
+            Self::HostKeyVerificationFailed => "host_key_verification_failed",
+            Self::HostNotResolved => "host_not_resolved",
+            Self::ConnectionRefused => "connection_refused",
+            Self::ConnectionTimedOut => "connection_timed_out",
+            Self::ConnectionClosed => "connection_closed",
+            Self::BadMountPoint => "bad_mount_point",
+            Self::MountPointNotEmpty => "mount_point_not_empty",
+            Self::RemotePathNotFound => "remote_path_not_found",
+            Self::FuseUnavailable => "fuse_unavailable",
+        }
+    }
+}
+
+impl std::fmt::Display for MountFailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{0}", self.description())
+    }
+}
+
+/// Tells whether a line of `sshfs` error output says that the remote path does not exist
+/// (e.g. `user@example.com:/storage: No such file or directory`).
+///
+/// Other things which don't exist (e.g. `Warning: Identity file /home/user/.ssh/id_rsa not accessible: No such file or directory`,
+/// reported by `ssh`) are told apart by the `HOST:PATH` form, whose host part contains no whitespace.
+fn is_remote_path_not_found_line(line: &str) -> bool {
+    let Some(target) = line.trim().strip_suffix(": No such file or directory") else {
+        return false;
+    };
+
+    match target.split_once(':') {
+        Some((host, path)) => {
+            !host.is_empty()
+                && !host.contains(char::is_whitespace)
+                && !path.is_empty()
+                && !path.starts_with(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// Suggests where a missing program (see `SftpManError::ProgramMissing`) comes from, for the well-known ones.
+fn install_hint(program: &str) -> &'static str {
+    match program {
+        "sshfs" => ". Install sshfs (e.g. the `sshfs` package)",
+        "fusermount3 or fusermount" => ". Install FUSE (e.g. the `fuse3` package)",
+        "ssh" => ". Install the OpenSSH client (e.g. the `openssh-client` or `openssh` package)",
+        "bindfs" => ". Install bindfs (e.g. the `bindfs` package)",
+        _ => "",
+    }
+}
+
+/// Formats suggestions for an unknown ID (e.g. `. Did you mean: my-fs?`), if there are any.
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+
+    format!(". Did you mean: {0}?", suggestions.join(", "))
+}
+
+/// Formats the processes keeping a filesystem busy (e.g. `vim (PID 1234), bash (PID 5678)`).
+fn list_processes(processes: &[crate::model::BlockingProcess]) -> String {
+    processes
+        .iter()
+        .map(|process| process.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
 }
@@ -0,0 +1,284 @@
+//! Translations for user-facing messages.
+//!
+//! Messages are looked up by stable keys (e.g. validation failure codes) in the built-in catalogs below.
+//! Messages without a translation fall back to English.
+//!
+//! Translated so far: validation failures (see `FieldValidationError::localized_message()`),
+//! recognized mount failures (see `MountFailureKind::localized_description()` and `MountFailureKind::localized_hint()`)
+//! and the CLI's summary lines for failed mounts and unmounts.
+//! Other `SftpManError` messages and the remaining CLI output are still English-only.
+
+/// A language which user-facing messages can be translated to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    /// Detects the language from the environment, the same way gettext does (`LC_ALL`, then `LC_MESSAGES`, then `LANG`).
+    pub fn detect() -> Self {
+        for name in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+            if let Ok(value) = std::env::var(name)
+                && !value.is_empty()
+            {
+                return Self::from_locale(&value);
+            }
+        }
+
+        Self::default()
+    }
+
+    /// Parses a POSIX locale name (e.g. `de_DE.UTF-8`, `de`) into a language, falling back to English for unsupported ones.
+    pub fn from_locale(locale: &str) -> Self {
+        let code = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match code.as_str() {
+            "de" => Self::German,
+            _ => Self::English,
+        }
+    }
+
+    /// Returns the ISO 639-1 code for the language (e.g. `de`).
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::German => "de",
+        }
+    }
+}
+
+/// Returns the translation for a message identified by the given key, if there is one.
+pub(crate) fn translate(language: Language, key: &str) -> Option<&'static str> {
+    let catalog = match language {
+        Language::English => return None,
+        Language::German => CATALOG_DE,
+    };
+
+    catalog
+        .iter()
+        .find(|(entry_key, _)| *entry_key == key)
+        .map(|(_, message)| *message)
+}
+
+/// Returns the translation for the given message, or the message itself (in English) if there is no translation.
+#[cfg(all(feature = "cli", unix))]
+pub(crate) fn translate_or<'a>(language: Language, key: &str, message: &'a str) -> &'a str {
+    translate(language, key).unwrap_or(message)
+}
+
+// Keys are either `{field}.{code}` for validation failures (see `FieldValidationError`),
+// `mount_failure.{kind}` (and `mount_failure.{kind}.hint`) for mount failures (see `MountFailureKind`), or `message.{name}` for other messages.
+const CATALOG_DE: &[(&str, &str)] = &[
+    ("id.length", "Eine ID muss angegeben werden."),
+    (
+        "id.invalid_id",
+        "Die ID darf nur alphanumerische Zeichen, Unterstriche, Bindestriche oder Punkte enthalten.",
+    ),
+    (
+        "description.length",
+        "Die Beschreibung darf nicht leer sein.",
+    ),
+    ("host.length", "Ein Host muss angegeben werden."),
+    (
+        "host.invalid_host",
+        "Der Host ist ungültig. IPv6-Adressen werden ohne Klammern angegeben, optional mit einer Zonen-ID (z. B. fe80::1%eth0).",
+    ),
+    ("user.length", "Ein Benutzer muss angegeben werden."),
+    (
+        "mount_options.invalid_mount_option",
+        "Eine Einhängeoption ist ungültig. Jede Option braucht einen Namen und muss einzeln angegeben werden (nicht durch Kommas getrennt).",
+    ),
+    (
+        "ciphers.invalid_cipher",
+        "Eine Chiffre ist ungültig. Jede Chiffre muss einzeln angegeben werden (nicht durch Kommas getrennt).",
+    ),
+    (
+        "env.invalid_env_var",
+        "Eine Umgebungsvariable ist ungültig. Namen dürfen nicht leer sein und kein = enthalten.",
+    ),
+    (
+        "remote_path.length",
+        "Ein entfernter Pfad muss angegeben werden.",
+    ),
+    (
+        "remote_path.not_absolute_path",
+        "Der entfernte Pfad muss absolut sein.",
+    ),
+    (
+        "mount_dest_path.length",
+        "Ein lokaler Einhängepfad muss angegeben werden.",
+    ),
+    (
+        "mount_dest_path.not_absolute_path",
+        "Der lokale Einhängepfad muss absolut sein.",
+    ),
+    (
+        "mount_dest_path.unknown_placeholder",
+        "Der lokale Einhängepfad enthält unbekannte Platzhalter. Unterstützt werden: {id}, {host}, {user}, {port}.",
+    ),
+    (
+        "extra_mount_paths.not_absolute_path",
+        "Zusätzliche Einhängepfade müssen absolut sein.",
+    ),
+    (
+        "extra_mount_paths.unknown_placeholder",
+        "Ein zusätzlicher Einhängepfad enthält unbekannte Platzhalter. Unterstützt werden: {id}, {host}, {user}, {port}.",
+    ),
+    (
+        "sub_mounts.duplicate_sub_mount_directory",
+        "Ein Verzeichnis wird von mehr als einer Untereinhängung verwendet.",
+    ),
+    (
+        "known_hosts_file.not_absolute_path",
+        "Der Pfad zur known_hosts-Datei muss absolut sein.",
+    ),
+    (
+        "certificate_file.not_absolute_path",
+        "Der Pfad zur Zertifikatsdatei muss absolut sein.",
+    ),
+    (
+        "proxy_command.length",
+        "Der Proxy-Befehl darf nicht leer sein.",
+    ),
+    (
+        "ssh_binary.length",
+        "Das SSH-Programm darf nicht leer sein.",
+    ),
+    (
+        "socks_proxy.invalid_socks_proxy",
+        "Der SOCKS-Proxy ist ungültig. Er muss als host:port angegeben werden (z. B. 127.0.0.1:1080).",
+    ),
+    (
+        "nice.range",
+        "Der nice-Wert muss zwischen -20 und 19 liegen.",
+    ),
+    (
+        "ionice_level.range",
+        "Die ionice-Stufe muss zwischen 0 und 7 liegen.",
+    ),
+    (
+        "download_limit.range",
+        "Das Download-Limit muss mindestens 1 KiB/s betragen.",
+    ),
+    (
+        "upload_limit.range",
+        "Das Upload-Limit muss mindestens 1 KiB/s betragen.",
+    ),
+    (
+        "__all__.no_ssh_key_for_publickey_auth",
+        "Die Authentifizierungsart publickey erfordert einen SSH-Schlüssel.",
+    ),
+    (
+        "__all__.certificate_without_publickey_auth",
+        "Eine Zertifikatsdatei kann nur mit der Authentifizierungsart publickey (zusammen mit dem zugehörigen SSH-Schlüssel) verwendet werden.",
+    ),
+    (
+        "__all__.multiple_password_sources",
+        "Es kann nur ein Passwort-Befehl, eine Passwort-Umgebungsvariable oder eine Passwort-Datei angegeben werden.",
+    ),
+    (
+        "__all__.password_source_without_password_auth",
+        "Eine Passwortquelle kann nur mit der Authentifizierungsart password verwendet werden.",
+    ),
+    (
+        "__all__.gssapi_options_without_gssapi_auth",
+        "GSSAPI-Optionen können nur mit der Authentifizierungsart gssapi-with-mic verwendet werden.",
+    ),
+    (
+        "__all__.multiple_proxies",
+        "Es kann nur ein Proxy-Befehl oder ein SOCKS-Proxy angegeben werden.",
+    ),
+    (
+        "__all__.ionice_level_without_best_effort",
+        "Eine ionice-Stufe kann nur mit der ionice-Klasse best-effort verwendet werden.",
+    ),
+    (
+        "mount_failure.authentication_failed",
+        "die Authentifizierung ist fehlgeschlagen",
+    ),
+    (
+        "mount_failure.authentication_failed.hint",
+        "Prüfen Sie, ob der Benutzer stimmt und ob der SSH-Schlüssel auf dem Server hinterlegt ist (z. B. in ~/.ssh/authorized_keys).",
+    ),
+    (
+        "mount_failure.host_key_verification_failed",
+        "die Überprüfung des Host-Schlüssels ist fehlgeschlagen",
+    ),
+    (
+        "mount_failure.host_key_verification_failed.hint",
+        "Prüfen und akzeptieren Sie den Host-Schlüssel über `sftpman accept_hostkey ID` (oder durch einmaliges Verbinden mit ssh), oder prüfen Sie, ob er sich aus einem legitimen Grund geändert hat.",
+    ),
+    (
+        "mount_failure.host_not_resolved",
+        "der Hostname konnte nicht aufgelöst werden",
+    ),
+    (
+        "mount_failure.host_not_resolved.hint",
+        "Prüfen Sie, ob der Hostname richtig geschrieben ist und ob DNS funktioniert.",
+    ),
+    (
+        "mount_failure.connection_refused",
+        "die Verbindung wurde abgelehnt",
+    ),
+    (
+        "mount_failure.connection_refused.hint",
+        "Stimmt der Port? Prüfen Sie, ob auf dem entfernten Rechner ein SSH-Server läuft.",
+    ),
+    (
+        "mount_failure.connection_timed_out",
+        "die Zeit für den Verbindungsaufbau ist abgelaufen",
+    ),
+    (
+        "mount_failure.connection_timed_out.hint",
+        "Prüfen Sie, ob der Host erreichbar ist (Netzwerk, VPN, Firewall) und ob der Port stimmt.",
+    ),
+    (
+        "mount_failure.connection_closed",
+        "die Verbindung wurde vom entfernten Rechner geschlossen",
+    ),
+    (
+        "mount_failure.connection_closed.hint",
+        "Prüfen Sie, ob SFTP auf dem SSH-Server aktiviert ist und ob der Benutzer es verwenden darf.",
+    ),
+    (
+        "mount_failure.bad_mount_point",
+        "der lokale Einhängepfad ist unbrauchbar",
+    ),
+    (
+        "mount_failure.bad_mount_point.hint",
+        "Prüfen Sie, ob der lokale Einhängepfad existiert, ein Verzeichnis ist und nicht von einer veralteten Einhängung belegt wird.",
+    ),
+    (
+        "mount_failure.mount_point_not_empty",
+        "der lokale Einhängepfad ist nicht leer",
+    ),
+    (
+        "mount_failure.mount_point_not_empty.hint",
+        "Der lokale Einhängepfad enthält Dateien. Leeren Sie ihn oder wählen Sie einen anderen Einhängepfad.",
+    ),
+    (
+        "mount_failure.remote_path_not_found",
+        "der entfernte Pfad existiert nicht",
+    ),
+    (
+        "mount_failure.remote_path_not_found.hint",
+        "Prüfen Sie, ob der entfernte Pfad auf dem entfernten Rechner existiert.",
+    ),
+    ("mount_failure.fuse_unavailable", "FUSE ist nicht verfügbar"),
+    (
+        "mount_failure.fuse_unavailable.hint",
+        "Prüfen Sie, ob FUSE installiert und das Kernelmodul fuse geladen ist (z. B. `modprobe fuse`).",
+    ),
+    (
+        "message.validation_failed",
+        "Die Validierung ist mit folgenden Fehlern fehlgeschlagen:",
+    ),
+    ("message.mount_failed", "Fehler beim Einhängen von"),
+    ("message.umount_failed", "Fehler beim Aushängen von"),
+];
@@ -1,17 +1,58 @@
 mod auth_type;
+mod automount;
+mod command_runner;
 
-#[cfg(feature = "cli")]
+#[cfg(feature = "capi")]
+pub mod capi;
+
+// The CLI, the daemon and the supervisor rely on unix signals and sockets.
+#[cfg(all(feature = "cli", unix))]
 pub mod cli;
 
+#[cfg(unix)]
+pub mod daemon;
+mod definition_sync;
+mod desktop_entry;
 mod errors;
+mod i18n;
 mod manager;
 mod model;
+mod mount_options;
+mod remote_browser;
+#[cfg(unix)]
+mod supervisor;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod utils;
+#[cfg(all(feature = "watch", target_os = "linux"))]
+mod watcher;
 
 pub use auth_type::AuthType;
-pub use errors::{ManagerInitError, PreflightCheckError, SftpManError};
+pub use automount::{AutomountOwner, AutomountUnits};
+pub use command_runner::CommandRunner;
+pub use desktop_entry::DesktopEntry;
+pub use errors::{ManagerInitError, MountFailureKind, PreflightCheckError, SftpManError};
+pub use i18n::Language;
 pub use manager::Manager;
-pub use model::{DEFAULT_MOUNT_PATH_PREFIX, FilesystemMountDefinition, MountState};
+pub use model::{
+    BlockingProcess, BulkSummary, BulkSummaryEntry, Capabilities, DEFAULT_MOUNT_PATH_PREFIX,
+    DEFAULT_PROFILE, DefinitionCheck, DefinitionFilter, DefinitionProblem, ExtraMountPathMethod,
+    FieldValidationError, FilesystemMountDefinition, FilesystemMountDefinitionBuilder,
+    GlobalConfig, HostCandidate, HostKey, IoniceClass, KillPolicy, MOUNT_BACKEND_ENV_VAR,
+    MountBackend, MountFailureReport, MountOutcome, MountPath, MountPlan, MountRequirement,
+    MountState, MountStep, MountStepFailure, PROFILE_ENV_VAR, PersistOutcome, PlannedStep,
+    PlannedStepPurpose, ProbeOutcome, Profile, SubMount, SyncOutcome, TerminationSignal,
+    UmountOutcome, UsageStats, VerificationOutcome,
+};
+pub use mount_options::{
+    KNOWN_MOUNT_OPTIONS, MountOption, MountOptionValueType, MountOptionWarning,
+    check_mount_options, escape_mount_option, find_mount_option, normalize_mount_options,
+};
+pub use remote_browser::{RemoteBrowser, join_remote_path, parent_remote_path};
+#[cfg(unix)]
+pub use supervisor::Supervisor;
+#[cfg(all(feature = "watch", target_os = "linux"))]
+pub use watcher::{DefinitionChange, DefinitionWatcher};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
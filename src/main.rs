@@ -1,11 +1,12 @@
 use std::process;
 
-#[cfg(feature = "cli")]
+#[cfg(all(feature = "cli", unix))]
 use libsftpman::cli;
 
+#[cfg(all(feature = "cli", unix))]
 use libsftpman::Manager;
 
-#[cfg(feature = "cli")]
+#[cfg(all(feature = "cli", unix))]
 fn main() {
     let arg_matches: clap::ArgMatches = cli::build().get_matches();
 
@@ -20,7 +21,18 @@ fn main() {
     builder.filter_level(log_level);
     builder.init();
 
-    let manager = Manager::new().unwrap();
+    let manager = match arg_matches.get_one::<String>(cli::ARG_PROFILE) {
+        Some(profile) => Manager::for_profile(profile),
+        None => Manager::new(),
+    };
+
+    let manager = match manager {
+        Ok(manager) => manager,
+        Err(err) => {
+            log::error!("Failed to initialize: {0:?}", err);
+            process::exit(cli::ExitStatus::Failure.into());
+        }
+    };
 
     process::exit(cli::run(&manager, &arg_matches).into());
 }
@@ -29,3 +41,9 @@ fn main() {
 fn main() {
     println!("This is a library, not an executable.");
 }
+
+#[cfg(all(feature = "cli", not(unix)))]
+fn main() {
+    println!("The sftpman CLI application is only supported on unix-like systems.");
+    process::exit(1);
+}
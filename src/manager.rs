@@ -1,242 +1,3000 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
-use std::time::Duration;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use validator::Validate;
+
+use crate::auth_type::AuthType;
+use crate::model::{
+    BANDWIDTH_LIMITER, BIND_MOUNTER, DEFAULT_MOUNT_PATH_PREFIX, DEFAULT_PROFILE,
+    DEFAULT_SSH_BINARY, MOUNT_BACKEND_ENV_VAR, PROFILE_ENV_VAR, SSH_DEFAULT_TIMEOUT,
+};
+
+use super::command_runner::CommandRunner;
+use super::definition_sync::sync_with_git_remote;
+use super::errors::{ManagerInitError, MountFailureKind, PreflightCheckError, SftpManError};
+use super::model::{
+    BlockingProcess, BulkSummary, Capabilities, DefinitionCheck, DefinitionFilter,
+    DefinitionProblem, ExtraMountPathMethod, FieldValidationError, FilesystemMountDefinition,
+    GlobalConfig, HostCandidate, HostKey, KillPolicy, MountBackend, MountFailureReport,
+    MountOutcome, MountPlan, MountRequirement, MountState, MountStep, PersistOutcome, PlannedStep,
+    PlannedStepPurpose, ProbeOutcome, Profile, SyncOutcome, TerminationSignal, UmountOutcome,
+    UsageStats, VerificationOutcome,
+};
+use super::mount_options::check_mount_options;
+use super::remote_browser::{join_remote_path, quote};
+
+use super::utils::activity::{ActivityRecord, load_activity_records, save_activity_records};
+use super::utils::bookmarks::{parse_gtk_bookmarks, parse_xbel_bookmarks};
+use super::utils::command::{
+    find_program, run_command, run_command_background, run_command_interactive,
+};
+use super::utils::created_directories::{load_created_directories, save_created_directories};
+use super::utils::fs::{
+    MountEntry, ensure_directory_recursively_created, ensure_symlink, get_mounts_under_path_prefix,
+    is_directory_empty, probe_directory, remove_empty_directory, remove_symlink_to,
+};
+use super::utils::fusermount::{create_fusermount_check_command, create_fusermount3_check_command};
+use super::utils::id_resolution::find_definition;
+use super::utils::mount_managers::find_foreign_mounts;
+use super::utils::network::{
+    CONNECT_TIMEOUT, does_host_resolve, is_address_reachable, is_interface_up, resolve_host,
+    wait_for_network,
+};
+use super::utils::paused::{load_paused_ids, save_paused_ids};
+use super::utils::premount::{PremountCheck, load_passed_checks, save_passed_checks};
+use super::utils::process::{
+    ensure_process_killed, process_io_counter, processes_using_paths, sshfs_pid_by_definition,
+    terminate_processes,
+};
+use super::utils::rclone::parse_rclone_sftp_remotes;
+use super::utils::secret::{resolve_password, run_command_with_password, write_password};
+use super::utils::session::{load_session_ids, save_session_ids};
+use super::utils::ssh::{
+    ensure_agent_usable, find_known_host_keys, find_private_keys, find_security_key_helper,
+    fingerprint_known_hosts_lines, is_certificate_for_public_key, parse_keyscan_output,
+    parse_known_hosts, parse_ssh_config_hosts,
+};
+use super::utils::state_cache::StateCache;
+use super::utils::systemd::{is_user_manager_available, wrap_in_scope};
+use super::utils::usage_stats::{load_usage_stats, save_usage_stats};
+
+const VFS_TYPE_SSHFS: &str = "fuse.sshfs";
+
+/// The name of the global configuration file (see `GlobalConfig`), stored in the config directory.
+const GLOBAL_CONFIG_FILE_NAME: &str = "config.json";
+
+/// The directory (in the config, state and runtime directories of the default profile) where other profiles' directories are.
+const PROFILES_DIR_NAME: &str = "profiles";
+
+/// The name of the file (in the config directory of the default profile) naming the profile switched to (see `Manager::switch_profile()`).
+const SWITCHED_PROFILE_FILE_NAME: &str = "switched_profile";
+
+/// How long to wait for the mount to appear when `sshfs` runs in the foreground (with debug output enabled).
+const SSHFS_DEBUG_MOUNT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a passed pre-mount check (see `Manager::with_premount_checks()`) is trusted for, before being performed again.
+const PREMOUNT_CHECK_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// How long to wait for a probe operation (see `Manager::probe()`) before considering the mount unresponsive.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Manages filesystem definitions (stored in the config directory) and their mounts.
+///
+/// A manager is `Send + Sync` and cheap to clone (everything is shared behind reference counting),
+/// so frontends can hand clones to background threads. Clones share the state cache (see `with_state_cache_ttl()`)
+/// and the recorded mounts (see `with_mount_backend()` and `with_command_runner()`).
+#[derive(Default, Clone)]
+pub struct Manager {
+    paths: Arc<ManagerPaths>,
+    config: Arc<GlobalConfig>,
+    state_cache: Arc<StateCache>,
+    mount_backend: MountBackend,
+
+    /// Runs the mount and unmount commands instead of them getting executed (see `with_command_runner()`).
+    command_runner: Option<Arc<dyn CommandRunner>>,
+
+    /// The local mount paths of the filesystems mounted via `MountBackend::Simulate` or via a command runner,
+    /// which the system knows nothing about.
+    recorded_mounts: Arc<Mutex<BTreeSet<PathBuf>>>,
+
+    /// Whether mounting skips the pre-mount checks (see `with_premount_checks()`).
+    skip_premount_checks: bool,
+}
+
+#[derive(Default)]
+struct ManagerPaths {
+    /// The name of the profile in use (see `Manager::for_profile()`), which the config, state and runtime paths belong to.
+    profile: String,
+
+    /// The config directory of the default profile (e.g. `~/.config/sftpman`), where the other profiles' config directories are.
+    base_config_path: PathBuf,
+
+    config_path: PathBuf,
+    state_path: PathBuf,
+    runtime_path: PathBuf,
+    ssh_path: PathBuf,
+    home_path: PathBuf,
+
+    /// The user's configuration directory (e.g. `~/.config`), where other applications' configuration (e.g. rclone's) is found.
+    user_config_path: PathBuf,
+
+    /// The user's data directory (e.g. `~/.local/share`), where other applications' data (e.g. KDE's places) is found.
+    user_data_path: PathBuf,
+}
+
+// Frontends rely on sharing managers across threads, so this is enforced at compile time.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Manager>();
+};
+
+impl Manager {
+    /// Creates a new manager for the profile in use (see `for_profile()`),
+    /// loading the global configuration file (if one exists) from the profile's config directory.
+    ///
+    /// The profile in use is the one selected via the `SFTPMAN_PROFILE` environment variable (e.g. `SFTPMAN_PROFILE=work`),
+    /// or else the one switched to via `switch_profile()`, or else the default one.
+    ///
+    /// The mount backend (see `with_mount_backend()`) can be selected via the `SFTPMAN_MOUNT_BACKEND` environment variable
+    /// (e.g. `SFTPMAN_MOUNT_BACKEND=simulate`).
+    pub fn new() -> Result<Self, ManagerInitError> {
+        let profile = match std::env::var(PROFILE_ENV_VAR) {
+            Ok(profile) if !profile.is_empty() => profile,
+            _ => load_switched_profile(project_dirs()?.config_dir()),
+        };
+
+        Self::for_profile(&profile)
+    }
+
+    /// Creates a new manager for the given profile (e.g. `work`), loading the global configuration file (if one exists)
+    /// from the profile's config directory.
+    ///
+    /// Each profile has its own definitions, global configuration and state (e.g. usage stats, or the daemon's socket),
+    /// kept in a directory of its own (e.g. `~/.config/sftpman/profiles/work`).
+    /// Those of the default profile (`DEFAULT_PROFILE`) are kept directly in the config directory (e.g. `~/.config/sftpman`).
+    ///
+    /// Profiles other than the default one need to be created first (see `create_profile()`).
+    pub fn for_profile(profile: &str) -> Result<Self, ManagerInitError> {
+        let d = project_dirs()?;
+
+        let base_dirs = directories::BaseDirs::new().ok_or(ManagerInitError::NoConfigDirectory)?;
+        let home_path = base_dirs.home_dir().to_path_buf();
+        let ssh_path = home_path.join(".ssh");
+        let user_config_path = base_dirs.config_dir().to_path_buf();
+        let user_data_path = base_dirs.data_dir().to_path_buf();
+
+        let base_config_path = d.config_dir().to_path_buf();
+
+        // The state directory is only available on Linux.
+        let base_state_path = d.state_dir().unwrap_or(d.data_local_dir()).to_path_buf();
+
+        // The runtime directory is only available on Linux, when `$XDG_RUNTIME_DIR` is set.
+        let base_runtime_path = d.runtime_dir().unwrap_or(&base_state_path).to_path_buf();
+
+        let (config_path, state_path, runtime_path) = if profile == DEFAULT_PROFILE {
+            (base_config_path.clone(), base_state_path, base_runtime_path)
+        } else {
+            let config_path = profile_path(&base_config_path, profile);
+
+            if !is_valid_profile_name(profile) || !config_path.is_dir() {
+                return Err(ManagerInitError::UnknownProfile(profile.to_owned()));
+            }
+
+            (
+                config_path,
+                profile_path(&base_state_path, profile),
+                profile_path(&base_runtime_path, profile),
+            )
+        };
+
+        let config =
+            Self::global_config_from_config_path(&config_path.join(GLOBAL_CONFIG_FILE_NAME))?;
+
+        Ok(Self {
+            paths: Arc::new(ManagerPaths {
+                profile: profile.to_owned(),
+                base_config_path,
+                config_path,
+                state_path,
+                runtime_path,
+                ssh_path,
+                home_path,
+                user_config_path,
+                user_data_path,
+            }),
+            config: Arc::new(config),
+            state_cache: Arc::new(StateCache::default()),
+            mount_backend: mount_backend_from_env(),
+            command_runner: None,
+            recorded_mounts: Arc::new(Mutex::new(BTreeSet::new())),
+            skip_premount_checks: false,
+        })
+    }
+
+    /// Creates a manager which keeps everything (definitions, state, SSH configuration, etc.) under the given home directory,
+    /// laid out like in a real one (e.g. `HOME/.config/sftpman`), instead of under the user's actual home directory.
+    ///
+    /// The global configuration file is loaded from there as well (see `testing::TestEnvironment`).
+    #[cfg(feature = "testing")]
+    pub(crate) fn with_home(home_path: &Path) -> Result<Self, ManagerInitError> {
+        let user_config_path = home_path.join(".config");
+        let config_path = user_config_path.join("sftpman");
+        let config =
+            Self::global_config_from_config_path(&config_path.join(GLOBAL_CONFIG_FILE_NAME))?;
+
+        Ok(Self {
+            paths: Arc::new(ManagerPaths {
+                profile: DEFAULT_PROFILE.to_owned(),
+                base_config_path: config_path.clone(),
+                config_path,
+                state_path: home_path.join(".local").join("state").join("sftpman"),
+                runtime_path: home_path.join(".run").join("sftpman"),
+                ssh_path: home_path.join(".ssh"),
+                home_path: home_path.to_path_buf(),
+                user_config_path,
+                user_data_path: home_path.join(".local").join("share"),
+            }),
+            config: Arc::new(config),
+            state_cache: Arc::new(StateCache::default()),
+            mount_backend: MountBackend::default(),
+            command_runner: None,
+            recorded_mounts: Arc::new(Mutex::new(BTreeSet::new())),
+            skip_premount_checks: false,
+        })
+    }
+
+    /// Makes the manager (and clones made from it afterwards) mount and unmount filesystems via the given backend
+    /// (e.g. `MountBackend::Simulate` for demos or for CI environments without FUSE).
+    pub fn with_mount_backend(mut self, mount_backend: MountBackend) -> Self {
+        self.mount_backend = mount_backend;
+        self
+    }
+
+    /// Returns how filesystems get mounted and unmounted (see `with_mount_backend()`).
+    pub fn mount_backend(&self) -> MountBackend {
+        self.mount_backend
+    }
+
+    /// Makes the manager (and clones made from it afterwards) hand the mount and unmount commands (e.g. `sshfs ..`, `fusermount3 -u ..`)
+    /// to the given runner (e.g. `testing::FakeCommandRunner`), instead of executing them.
+    ///
+    /// As the commands don't actually mount anything the system knows about, filesystems get recorded as mounted once their mount commands succeed
+    /// (and as unmounted once their unmount commands do), the way `MountBackend::Simulate` records them.
+    /// The pre-mount checks (see `with_premount_checks()`) get skipped, and unmounting never falls back to killing `sshfs`, as there's no such process.
+    pub fn with_command_runner(mut self, runner: Arc<dyn CommandRunner>) -> Self {
+        self.command_runner = Some(runner);
+        self
+    }
+
+    /// Tells if mounts are recorded by the manager (see `with_command_runner()`), rather than being looked up in the system's mount table.
+    fn records_mounts(&self) -> bool {
+        self.mount_backend == MountBackend::Simulate || self.command_runner.is_some()
+    }
+
+    /// Runs a mount or unmount command, via the command runner if one is set (see `with_command_runner()`).
+    fn execute(&self, cmd: Command) -> Result<(), SftpManError> {
+        match &self.command_runner {
+            Some(runner) => runner.run(cmd).map(|_| ()),
+            None => run_command(cmd).map(|_| ()),
+        }
+    }
+
+    /// Makes the manager (and clones made from it afterwards) perform (the default) or skip the pre-mount checks.
+    ///
+    /// Before mounting, cheap checks verify that what mounting relies on is there (programs like `sshfs` being installed,
+    /// `/dev/fuse` being accessible, SSH keys being readable), so that something missing gets reported clearly,
+    /// instead of surfacing as whatever error `sshfs` happens to fail with.
+    /// Checks which pass are remembered for a while (in the runtime directory), so they're not repeated on each mount.
+    pub fn with_premount_checks(mut self, enabled: bool) -> Self {
+        self.skip_premount_checks = !enabled;
+        self
+    }
+
+    /// Makes the manager (and clones made from it afterwards) cache the mount table and the list of definitions
+    /// for the given amount of time, instead of re-reading them on each call (e.g. when a GUI polls `full_state()`).
+    ///
+    /// Operations performed through the manager (mounting, persisting, etc.) invalidate the cache,
+    /// but changes made elsewhere (e.g. by another process) only become visible once the cache expires
+    /// (or after calling `invalidate_state_cache()`).
+    ///
+    /// Caching is disabled by default (a TTL of zero).
+    pub fn with_state_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.state_cache = Arc::new(StateCache::new(ttl));
+        self
+    }
+
+    /// Returns how long the mount table and the list of definitions are cached for (see `with_state_cache_ttl()`).
+    pub fn state_cache_ttl(&self) -> Duration {
+        self.state_cache.ttl()
+    }
+
+    /// Discards the cached mount table and list of definitions (see `with_state_cache_ttl()`).
+    pub fn invalidate_state_cache(&self) {
+        self.state_cache.invalidate();
+    }
+
+    /// Returns the name of the profile in use (see `for_profile()`).
+    pub fn profile(&self) -> &str {
+        &self.paths.profile
+    }
+
+    /// Returns the existing profiles: the default one, followed by the created ones (see `create_profile()`) by name.
+    pub fn profiles(&self) -> Result<Vec<Profile>, SftpManError> {
+        let dir_path = self.paths.base_config_path.join(PROFILES_DIR_NAME);
+
+        let mut names: Vec<String> = Vec::new();
+
+        if dir_path.is_dir() {
+            let entries =
+                fs::read_dir(&dir_path).map_err(|err| SftpManError::IO(dir_path.clone(), err))?;
+
+            for entry in entries {
+                let entry = entry.map_err(|err| SftpManError::IO(dir_path.clone(), err))?;
+
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.path().is_dir() && is_valid_profile_name(&name) {
+                    names.push(name);
+                }
+            }
+        }
+
+        names.sort();
+
+        Ok(std::iter::once(DEFAULT_PROFILE.to_owned())
+            .chain(names)
+            .map(|name| Profile {
+                current: name == self.profile(),
+                name,
+            })
+            .collect())
+    }
+
+    /// Creates a profile (without definitions, using the default global configuration), to be used via `for_profile()`.
+    pub fn create_profile(&self, name: &str) -> Result<(), SftpManError> {
+        if !is_valid_profile_name(name) {
+            return Err(SftpManError::InvalidProfileName(name.to_owned()));
+        }
+
+        let dir_path = profile_path(&self.paths.base_config_path, name);
+
+        if name == DEFAULT_PROFILE || dir_path.exists() {
+            return Err(SftpManError::ProfileExists(name.to_owned()));
+        }
+
+        let mounts_path = dir_path.join("mounts");
+
+        fs::create_dir_all(&mounts_path).map_err(|err| SftpManError::IO(mounts_path, err))
+    }
+
+    /// Makes managers created via `new()` from now on use the given profile (unless `SFTPMAN_PROFILE` selects another one).
+    ///
+    /// Existing managers (this one included) keep using their profile.
+    pub fn switch_profile(&self, name: &str) -> Result<(), SftpManError> {
+        let path = self.paths.base_config_path.join(SWITCHED_PROFILE_FILE_NAME);
+
+        if name == DEFAULT_PROFILE {
+            return match fs::remove_file(&path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    Err(SftpManError::IO(path, err))
+                }
+                _ => Ok(()),
+            };
+        }
+
+        if !is_valid_profile_name(name)
+            || !profile_path(&self.paths.base_config_path, name).is_dir()
+        {
+            return Err(SftpManError::UnknownProfile(name.to_owned()));
+        }
+
+        fs::write(&path, format!("{0}\n", name)).map_err(|err| SftpManError::IO(path, err))
+    }
+
+    /// Returns the profile switched to via `switch_profile()` (which managers created via `new()` use by default).
+    pub fn switched_profile(&self) -> String {
+        load_switched_profile(&self.paths.base_config_path)
+    }
+
+    /// Returns the global configuration in use.
+    pub fn config(&self) -> &GlobalConfig {
+        &self.config
+    }
+
+    /// Replaces the global configuration in use (for this manager only, without persisting it).
+    pub fn set_config(&mut self, config: GlobalConfig) {
+        self.config = Arc::new(config);
+    }
+
+    /// Persists the given global configuration (to `config.json` in the config directory) and starts using it.
+    pub fn persist_config(&mut self, config: GlobalConfig) -> Result<(), SftpManError> {
+        let path = self.config_path_global();
+
+        fs::create_dir_all(&self.paths.config_path)
+            .map_err(|err| SftpManError::IO(self.paths.config_path.clone(), err))?;
+
+        let serialized = config
+            .to_json_string()
+            .map_err(|err| SftpManError::JSON(path.clone(), err))?;
+
+        fs::write(&path, serialized).map_err(|err| SftpManError::IO(path.clone(), err))?;
+
+        self.set_config(config);
+
+        Ok(())
+    }
+
+    /// Returns the list of all known (stored in the config directory) filesystem definitions.
+    pub fn definitions(&self) -> Result<Vec<FilesystemMountDefinition>, SftpManError> {
+        self.state_cache.definitions(|| self.load_definitions())
+    }
+
+    fn load_definitions(&self) -> Result<Vec<FilesystemMountDefinition>, SftpManError> {
+        let dir_path = self.config_path_mounts();
+
+        if !dir_path.is_dir() {
+            log::debug!(
+                "Mount config directory {0} doesn't exist. Returning an empty definitions list ...",
+                dir_path.display()
+            );
+            return Ok(vec![]);
+        }
+
+        let mut list: Vec<FilesystemMountDefinition> = Vec::new();
+
+        let directory_entries =
+            fs::read_dir(dir_path).map_err(|err| SftpManError::Generic(err.to_string()))?;
+
+        for entry in directory_entries {
+            let entry = entry.map_err(|err| SftpManError::Generic(err.to_string()))?;
+
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+
+            let name = path.file_name();
+            if name.is_none() {
+                continue;
+            }
+            if !name.unwrap().to_string_lossy().ends_with(".json") {
+                continue;
+            }
+
+            match self.definition_from_config_path(&path) {
+                Ok(cfg) => list.push(cfg),
+                Err(err) => return Err(err),
+            }
+        }
+
+        list.sort_by_key(|item| item.id.clone());
+
+        Ok(list)
+    }
+
+    /// Returns the filesystem definition (as stored in the config directory) for the given ID.
+    pub fn definition(&self, id: &str) -> Result<FilesystemMountDefinition, SftpManError> {
+        self.definition_from_config_path(&self.config_path_for_definition_id(id))
+    }
+
+    /// Finds the definition (among `definitions()`) that an ID given by the user refers to.
+    ///
+    /// Besides full IDs, unambiguous prefixes are accepted (e.g. `prod-w` for `prod-webserver`), but an exact match always wins.
+    /// Unknown IDs fail with `SftpManError::UnknownId` (suggesting close matches) and ambiguous ones with `SftpManError::AmbiguousId`.
+    /// As it may pick a different definition than the one given, this is meant for interactive use (unlike `definition()`).
+    pub fn resolve_definition(
+        &self,
+        query: &str,
+    ) -> Result<FilesystemMountDefinition, SftpManError> {
+        // Loading the one definition directly keeps other (possibly broken) definitions from getting in the way.
+        match self.definition(query) {
+            Err(SftpManError::FilesystemMountDefinitionRead(_, err))
+                if err.kind() == std::io::ErrorKind::NotFound => {}
+            result => return result,
+        }
+
+        let definitions = self.definitions()?;
+
+        find_definition(&definitions, query).cloned()
+    }
+
+    /// Starts watching the config directory for definitions being added, changed or removed (see `DefinitionWatcher`).
+    ///
+    /// The mounts configuration directory gets created, if it doesn't exist yet.
+    #[cfg(all(feature = "watch", target_os = "linux"))]
+    pub fn watch(&self) -> Result<crate::watcher::DefinitionWatcher, SftpManError> {
+        let dir_path = self.config_path_mounts();
+
+        fs::create_dir_all(&dir_path).map_err(|err| SftpManError::IO(dir_path.clone(), err))?;
+
+        crate::watcher::DefinitionWatcher::new(self.clone(), dir_path)
+    }
+
+    /// Returns the full state (configuration and mount status) of all known (stored in the config directory) filesystem definitions.
+    pub fn full_state(&self) -> Result<Vec<MountState>, SftpManError> {
+        self.state_of(self.definitions()?)
+    }
+
+    /// Returns the state (configuration and mount status, like `full_state()` does) of temporary mounts (see `mount_temporary()`).
+    pub fn temporary_state(&self) -> Result<Vec<MountState>, SftpManError> {
+        self.state_of(self.temporary_definitions()?)
+    }
+
+    fn state_of(
+        &self,
+        definitions: Vec<FilesystemMountDefinition>,
+    ) -> Result<Vec<MountState>, SftpManError> {
+        let mounted_sshfs_paths: HashSet<PathBuf> = self
+            .mounts()?
+            .into_iter()
+            .filter(|mount| mount.vfstype == VFS_TYPE_SSHFS)
+            .map(|mount| mount.file)
+            .collect();
+
+        let mut list: Vec<MountState> = Vec::new();
+
+        for definition in definitions {
+            let mounted = if definition.sub_mounts.is_empty() {
+                mounted_sshfs_paths.contains(definition.local_mount_path().as_path())
+            } else {
+                definition
+                    .sub_mount_definitions()
+                    .iter()
+                    .any(|sub_definition| {
+                        mounted_sshfs_paths.contains(sub_definition.local_mount_path().as_path())
+                    })
+            };
+            list.push(MountState::new(definition, mounted));
+        }
+
+        Ok(list)
+    }
+
+    /// Returns the full state (configuration and mount status, like `full_state()` does) of the definitions matching the given filter,
+    /// so that frontends (e.g. search boxes) don't need to reimplement filtering.
+    pub fn definitions_filtered(
+        &self,
+        filter: &DefinitionFilter,
+    ) -> Result<Vec<MountState>, SftpManError> {
+        Ok(self
+            .full_state()?
+            .into_iter()
+            .filter(|state| filter.matches(state))
+            .collect())
+    }
+
+    /// Returns the other definitions (with a different ID) which point to the same remote directory
+    /// (host, port and remote path) as the given one.
+    ///
+    /// Mounting the same remote directory more than once usually indicates a mistake
+    /// and makes it ambiguous which `sshfs` process is responsible for which mount.
+    pub fn definitions_with_same_remote(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<Vec<FilesystemMountDefinition>, SftpManError> {
+        Ok(self
+            .definitions()?
+            .into_iter()
+            .filter(|other| other.id != definition.id && have_same_remote(definition, other))
+            .collect())
+    }
+
+    /// Checks all stored definition files for problems (e.g. after editing them by hand or syncing them from another machine).
+    ///
+    /// Unlike `definitions()`, this doesn't stop at files which can't be parsed, but reports them as well.
+    /// Files are checked in alphabetical order.
+    pub fn check_definitions(&self) -> Result<Vec<DefinitionCheck>, SftpManError> {
+        let dir_path = self.config_path_mounts();
+
+        if !dir_path.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let directory_entries =
+            fs::read_dir(&dir_path).map_err(|err| SftpManError::IO(dir_path.clone(), err))?;
+
+        let mut paths: Vec<PathBuf> = directory_entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut checks: Vec<DefinitionCheck> = Vec::new();
+        let mut definitions: Vec<FilesystemMountDefinition> = Vec::new();
+
+        for path in paths {
+            let mut check = DefinitionCheck {
+                path: path.clone(),
+                id: None,
+                problems: Vec::new(),
+            };
+
+            match self.definition_from_config_path(&path) {
+                Ok(definition) => {
+                    check.id = Some(definition.id.clone());
+                    check.problems = check_definition(&path, &definition);
+                    definitions.push(definition);
+                }
+                Err(SftpManError::FilesystemMountDefinitionRead(_, err)) => {
+                    check
+                        .problems
+                        .push(DefinitionProblem::Unreadable(err.to_string()));
+                }
+                Err(SftpManError::JSON(_, err)) => {
+                    check
+                        .problems
+                        .push(DefinitionProblem::Unparseable(err.to_string()));
+                }
+                Err(err) => return Err(err),
+            }
+
+            checks.push(check);
+        }
+
+        // Conflicts between definitions can only be detected once all of them are loaded.
+        for check in checks.iter_mut() {
+            let Some(definition) = definitions
+                .iter()
+                .find(|definition| Some(&definition.id) == check.id.as_ref())
+            else {
+                continue;
+            };
+
+            let others = definitions.iter().filter(|other| other.id != definition.id);
+
+            let same_mount_path: Vec<String> = others
+                .clone()
+                .filter(|other| other.local_mount_path() == definition.local_mount_path())
+                .map(|other| other.id.clone())
+                .collect();
+
+            if !same_mount_path.is_empty() {
+                check
+                    .problems
+                    .push(DefinitionProblem::SameMountPath(same_mount_path));
+            }
+
+            let same_remote: Vec<String> = others
+                .filter(|other| have_same_remote(definition, other))
+                .map(|other| other.id.clone())
+                .collect();
+
+            if !same_remote.is_empty() {
+                check
+                    .problems
+                    .push(DefinitionProblem::SameRemote(same_remote));
+            }
+        }
+
+        Ok(checks)
+    }
+
+    /// Tells if the `sshfs` process serving the given (mounted) filesystem definition is gone (e.g. it crashed or got killed),
+    /// leaving a dead mount point behind.
+    pub fn is_definition_disconnected(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<bool, SftpManError> {
+        if !definition.sub_mounts.is_empty() {
+            for sub_definition in definition.sub_mount_definitions() {
+                if self.is_definition_mounted(&sub_definition)?
+                    && self.is_definition_disconnected(&sub_definition)?
+                {
+                    return Ok(true);
+                }
+            }
+
+            return Ok(false);
+        }
+
+        // Recorded mounts have no `sshfs` process, but never get disconnected either.
+        if self.records_mounts() {
+            return Ok(false);
+        }
+
+        Ok(sshfs_pid_by_definition(definition)?.is_none())
+    }
+
+    /// Tells if the given filesystem definition is currently mounted.
+    ///
+    /// Definitions with sub-mounts (see `FilesystemMountDefinition::sub_mounts`) count as mounted if any of them is.
+    pub fn is_definition_mounted(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<bool, SftpManError> {
+        if !definition.sub_mounts.is_empty() {
+            for sub_definition in definition.sub_mount_definitions() {
+                if self.is_definition_mounted(&sub_definition)? {
+                    return Ok(true);
+                }
+            }
+
+            return Ok(false);
+        }
+
+        let local_mount_path = definition.local_mount_path();
+
+        for mount in self.mounts()? {
+            if mount.file != local_mount_path.as_path() {
+                continue;
+            }
+
+            if mount.vfstype != VFS_TYPE_SSHFS {
+                return Err(SftpManError::MountVfsTypeMismatch {
+                    path: local_mount_path.into_path_buf(),
+                    found_vfs_type: mount.vfstype.to_string(),
+                    expected_vfs_type: VFS_TYPE_SSHFS.to_string(),
+                });
+            }
+
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Returns all current mounts (possibly cached, see `with_state_cache_ttl()`).
+    fn mounts(&self) -> Result<Vec<MountEntry>, SftpManError> {
+        if self.records_mounts() {
+            return Ok(self
+                .recorded_mounts
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|path| MountEntry {
+                    file: path.clone(),
+                    vfstype: VFS_TYPE_SSHFS.to_owned(),
+                })
+                .collect());
+        }
+
+        self.state_cache
+            .mounts(|| get_mounts_under_path_prefix(Path::new("/")))
+    }
+
+    /// Mounts a filesystem definition unless already mounted.
+    ///
+    /// Disabled definitions (see `FilesystemMountDefinition::enabled`) are refused with `SftpManError::DefinitionDisabled`.
+    /// On success, tells whether mounting actually happened (see `MountOutcome`).
+    /// On failure, only the error which made mounting fail is returned. See `mount_with_report()` for the full story.
+    pub fn mount(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<MountOutcome, SftpManError> {
+        self.mount_with_report(definition)
+            .map_err(MountFailureReport::into_error)
+    }
+
+    /// Mounts a filesystem definition like `mount()` does, but on failure reports which step failed (see `MountStep`),
+    /// along with the failures of any steps performed to clean up afterwards.
+    ///
+    /// For definitions with sub-mounts (see `FilesystemMountDefinition::sub_mounts`), the ones not mounted yet get mounted.
+    /// If one of them fails, those mounted so far get unmounted again and the report is about the failed one (e.g. `my-fs:web`).
+    ///
+    /// Actually mounting updates the definition's usage stats (see `usage_stats()`).
+    /// Either way, the definition becomes part of the session (see `session_ids()`).
+    pub fn mount_with_report(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<MountOutcome, MountFailureReport> {
+        let outcome = self.do_mount(definition)?;
+
+        // This also happens when already mounted, in case some of the extra mount paths got removed in the meantime.
+        if !definition.extra_mount_paths.is_empty()
+            && let Err(err) = self.expose_at_extra_mount_paths(definition)
+        {
+            log::error!(
+                "{0}: failed to expose at the extra mount paths: {1}",
+                definition.id,
+                err
+            );
+
+            let mut report =
+                MountFailureReport::new(&definition.id, MountStep::ExtraMountPaths, err);
+
+            // A filesystem which was mounted already is left alone.
+            if outcome == MountOutcome::Mounted
+                && let Err(err) = self.umount(definition)
+            {
+                report.push(MountStep::Cleanup, err);
+            }
+
+            return Err(report);
+        }
+
+        if outcome == MountOutcome::Mounted {
+            self.record_mount(definition);
+        }
+
+        self.join_session(definition);
+
+        Ok(outcome)
+    }
+
+    /// Mounts multiple definitions one after another (see `mount_with_report()`), summarizing what happened to each.
+    ///
+    /// Definitions which are disabled or whose requirements are not met (see `check_requirements()`) count as skipped.
+    /// If `fail_fast` is `true`, the definitions remaining after the first failure are skipped too,
+    /// which is useful when they depend on one another (e.g. one getting mounted inside another).
+    pub fn mount_many(
+        &self,
+        definitions: &[FilesystemMountDefinition],
+        fail_fast: bool,
+    ) -> BulkSummary {
+        let mut summary = BulkSummary::new();
+
+        let mut definitions = definitions.iter();
+
+        for definition in definitions.by_ref() {
+            match self.mount_with_report(definition) {
+                Ok(MountOutcome::Mounted) => summary.succeeded.push(definition.id.clone()),
+                Ok(MountOutcome::AlreadyMounted) => summary.unchanged.push(definition.id.clone()),
+                Err(report) => match &report.cause().error {
+                    SftpManError::DefinitionDisabled(_) => {
+                        summary.push_skipped(&definition.id, &"disabled")
+                    }
+                    SftpManError::RequirementUnmet(_, reason) => {
+                        summary.push_skipped(&definition.id, reason)
+                    }
+                    _ => {
+                        log::error!("{0}: failed to mount: {1}", definition.id, report);
+                        summary.push_failed(&definition.id, &report);
+
+                        if fail_fast {
+                            break;
+                        }
+                    }
+                },
+            }
+        }
+
+        for definition in definitions {
+            summary.push_skipped(&definition.id, &"an earlier definition failed to mount");
+        }
+
+        summary
+    }
+
+    fn do_mount(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<MountOutcome, MountFailureReport> {
+        let _cache_invalidation = self.state_cache.invalidation_guard();
+
+        let preparation_failed =
+            |err| MountFailureReport::new(&definition.id, MountStep::Preparation, err);
+
+        if !definition.enabled {
+            return Err(preparation_failed(SftpManError::DefinitionDisabled(
+                definition.id.clone(),
+            )));
+        }
+
+        if !definition.sub_mounts.is_empty() {
+            return self.mount_sub_mounts(definition);
+        }
+
+        if self
+            .is_definition_mounted(definition)
+            .map_err(preparation_failed)?
+        {
+            log::info!("{0}: already mounted, nothing to do..", definition.id);
+            return Ok(MountOutcome::AlreadyMounted);
+        }
+
+        self.check_requirements(definition)
+            .map_err(preparation_failed)?;
+
+        log::info!("{0}: mounting..", definition.id);
+
+        if self.mount_backend == MountBackend::Simulate {
+            return self.simulate_mount(definition).map_err(preparation_failed);
+        }
+
+        // This happens before obtaining the password, so that nobody gets asked for one in vain.
+        // The checks are about the programs a command runner stands in for, so they're pointless with one.
+        if self.command_runner.is_none() {
+            self.run_premount_checks(definition)
+                .map_err(preparation_failed)?;
+        }
+
+        let password = resolve_password(definition).map_err(preparation_failed)?;
+
+        if definition.auth_type == AuthType::AuthenticationAgent {
+            ensure_agent_usable(definition).map_err(preparation_failed)?;
+        }
+
+        let local_mount_path = definition.local_mount_path();
+
+        self.create_mount_directory(definition)
+            .map_err(preparation_failed)?;
+
+        // Mounting would hide the files in the directory (and sshfs may refuse it with a terse message).
+        if !definition.allow_non_empty_mount_path
+            && !is_directory_empty(&local_mount_path).map_err(preparation_failed)?
+        {
+            return Err(preparation_failed(SftpManError::MountPathNotEmpty(
+                definition.id.clone(),
+                local_mount_path.into_path_buf(),
+            )));
+        }
+
+        let cmds = self
+            .mount_commands_for(definition)
+            .map_err(preparation_failed)?;
+        let cmds_count = cmds.len();
+
+        let mut failure: Option<(MountStep, SftpManError)> = None;
+
+        for (idx, cmd) in cmds.into_iter().enumerate() {
+            log::debug!("{0}: executing mount command: {1:?}", definition.id, cmd);
+
+            // The last command is the sshfs command, which stays in the foreground when running with debug output.
+            let is_sshfs_cmd = idx == cmds_count - 1;
+
+            // This happens after the before-mount command, as it may be what makes the host resolvable (e.g. by connecting to a VPN).
+            if is_sshfs_cmd && let Err(err) = self.ensure_host_resolves(definition) {
+                log::error!("{0}: {1}", definition.id, err);
+
+                failure = Some((MountStep::HostResolution, err));
+                break;
+            }
+
+            let result = if self.command_runner.is_some() {
+                self.execute(cmd)
+            } else if is_sshfs_cmd && definition.debug_sshfs {
+                self.run_sshfs_with_debug_log(definition, cmd, password.as_deref())
+            } else if is_sshfs_cmd && let Some(password) = &password {
+                run_command_with_password(cmd, password).map(|_| ())
+            } else if is_sshfs_cmd && definition.uses_security_key() {
+                // Capturing the output would swallow the prompt asking to confirm with the security key.
+                log::warn!(
+                    "{0}: confirm with your security key (e.g. by touching it) if asked to",
+                    definition.id
+                );
+                run_command_interactive(cmd).map(|_| ())
+            } else {
+                run_command(cmd).map(|_| ())
+            };
+
+            let result = match result {
+                Err(SftpManError::CommandUnsuccessful(cmd, output)) if is_sshfs_cmd => {
+                    Err(classify_mount_failure(cmd, output))
+                }
+                other => other,
+            };
+
+            if let Err(err) = result {
+                log::error!(
+                    "{0}: failed to run mount command: {1:?}",
+                    definition.id,
+                    err
+                );
+
+                let step = if is_sshfs_cmd {
+                    MountStep::Sshfs
+                } else {
+                    MountStep::BeforeMount
+                };
+
+                failure = Some((step, err));
+                break;
+            }
+        }
+
+        if failure.is_none() && self.command_runner.is_some() {
+            self.recorded_mounts
+                .lock()
+                .unwrap()
+                .insert(definition.local_mount_path().into_path_buf());
+        }
+
+        // sshfs only exits successfully once the filesystem is mounted, so this is merely a safety net.
+        if failure.is_none() {
+            self.state_cache.invalidate();
+
+            match self.is_definition_mounted(definition) {
+                Ok(true) => return Ok(MountOutcome::Mounted),
+                Ok(false) => {
+                    failure = Some((
+                        MountStep::Verification,
+                        SftpManError::MountMissing(definition.id.clone()),
+                    ));
+                }
+                Err(err) => failure = Some((MountStep::Verification, err)),
+            }
+        }
+
+        let (step, err) = failure.expect("Mounting should have failed at this point");
+        let mut report = MountFailureReport::new(&definition.id, step, err);
+
+        log::debug!("{0}: performing umount to clean up", definition.id);
+
+        // This will most likely fail, but we should try to do it anyway.
+        if let Err(err) = self.umount(definition) {
+            log::debug!(
+                "{0}: failed to perform cleanup-umount: {1:?}",
+                definition.id,
+                err
+            );
+
+            report.push(MountStep::Cleanup, err);
+        }
+
+        self.clean_up_after_unmount(definition);
+
+        Err(report)
+    }
+
+    /// Pretends to mount a definition (see `MountBackend::Simulate`), by creating its local mount path and recording it as mounted.
+    fn simulate_mount(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<MountOutcome, SftpManError> {
+        self.create_mount_directory(definition)?;
+
+        self.recorded_mounts
+            .lock()
+            .unwrap()
+            .insert(definition.local_mount_path().into_path_buf());
+
+        log::info!("{0}: mounted (simulated)", definition.id);
+
+        Ok(MountOutcome::Mounted)
+    }
+
+    fn mount_sub_mounts(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<MountOutcome, MountFailureReport> {
+        // Creating the common parent first makes it get cleaned up along with the definition, rather than with one of its sub-mounts.
+        self.create_mount_directory(definition)
+            .map_err(|err| MountFailureReport::new(&definition.id, MountStep::Preparation, err))?;
+
+        let mut outcome = MountOutcome::AlreadyMounted;
+        let mut mounted: Vec<FilesystemMountDefinition> = Vec::new();
+
+        for sub_definition in definition.sub_mount_definitions() {
+            match self.do_mount(&sub_definition) {
+                Ok(MountOutcome::AlreadyMounted) => {}
+                Ok(sub_outcome) => {
+                    outcome = sub_outcome;
+                    mounted.push(sub_definition);
+                }
+                Err(mut report) => {
+                    for sub_definition in mounted.iter().rev() {
+                        log::debug!("{0}: performing umount to clean up", sub_definition.id);
+
+                        if let Err(err) = self.umount(sub_definition) {
+                            report.push(MountStep::Cleanup, err);
+                        }
+                    }
+
+                    self.clean_up_after_unmount(definition);
+
+                    return Err(report);
+                }
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Tells which commands `mount()` would run for the given definition (see `MountPlan`), without running anything.
+    ///
+    /// The plan doesn't depend on whether the definition is already mounted (in which case `mount()` wouldn't run anything).
+    /// Disabled definitions are refused with `SftpManError::DefinitionDisabled`, like `mount()` does.
+    pub fn plan_mount(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<MountPlan, SftpManError> {
+        if !definition.enabled {
+            return Err(SftpManError::DefinitionDisabled(definition.id.clone()));
+        }
+
+        if !definition.sub_mounts.is_empty() {
+            let mut steps: Vec<PlannedStep> = Vec::new();
+            for sub_definition in definition.sub_mount_definitions() {
+                steps.extend(self.plan_mount(&sub_definition)?.steps);
+            }
+            steps.extend(self.plan_bind_mounts(definition));
+
+            return Ok(MountPlan {
+                id: definition.id.clone(),
+                steps,
+            });
+        }
+
+        let cmds = self.mount_commands_for(definition)?;
+        let cmds_count = cmds.len();
+
+        let steps = cmds
+            .iter()
+            .enumerate()
+            .map(|(idx, cmd)| {
+                let purpose = if idx == cmds_count - 1 {
+                    PlannedStepPurpose::Mount
+                } else {
+                    PlannedStepPurpose::BeforeMount
+                };
+
+                PlannedStep::new(cmd, purpose)
+            })
+            .chain(self.plan_bind_mounts(definition))
+            .collect();
+
+        Ok(MountPlan {
+            id: definition.id.clone(),
+            steps,
+        })
+    }
+
+    /// Returns the steps bind-mounting the filesystem at its extra mount paths (see `ExtraMountPathMethod::Bind`), if any.
+    ///
+    /// Symlinks (see `ExtraMountPathMethod::Symlink`) get created without running anything, so they're not part of plans.
+    fn plan_bind_mounts(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> impl Iterator<Item = PlannedStep> {
+        self.bind_mount_paths(definition).into_iter().map(|path| {
+            PlannedStep::new(
+                &definition.bind_mount_command(&path),
+                PlannedStepPurpose::BindMount,
+            )
+        })
+    }
+
+    /// Returns the steps undoing the bind mounts made by the steps `plan_bind_mounts()` returns.
+    fn plan_bind_umounts(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> impl Iterator<Item = PlannedStep> {
+        self.bind_mount_paths(definition).into_iter().map(|path| {
+            PlannedStep::new(
+                &definition.bind_umount_command(&path),
+                PlannedStepPurpose::Unmount,
+            )
+        })
+    }
+
+    fn bind_mount_paths(&self, definition: &FilesystemMountDefinition) -> Vec<PathBuf> {
+        match definition.extra_mount_path_method {
+            ExtraMountPathMethod::Symlink => Vec::new(),
+            ExtraMountPathMethod::Bind => definition.local_extra_mount_paths(),
+        }
+    }
+
+    /// Exposes a mounted filesystem at its extra mount paths (see `FilesystemMountDefinition::extra_mount_paths`).
+    ///
+    /// Paths where it's exposed already are left as they are.
+    fn expose_at_extra_mount_paths(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<(), SftpManError> {
+        let local_mount_path = definition.local_mount_path();
+
+        for path in definition.local_extra_mount_paths() {
+            log::debug!(
+                "{0}: exposing at {1} via {2}",
+                definition.id,
+                path.display(),
+                definition.extra_mount_path_method
+            );
+
+            match definition.extra_mount_path_method {
+                ExtraMountPathMethod::Symlink => {
+                    ensure_symlink(&local_mount_path, &path).map_err(|err| {
+                        if err.kind() == std::io::ErrorKind::AlreadyExists {
+                            SftpManError::ExtraMountPathOccupied(
+                                definition.id.clone(),
+                                path.clone(),
+                            )
+                        } else {
+                            SftpManError::IO(path.clone(), err)
+                        }
+                    })?;
+                }
+                ExtraMountPathMethod::Bind => self.bind_mount(definition, &path)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn bind_mount(
+        &self,
+        definition: &FilesystemMountDefinition,
+        path: &Path,
+    ) -> Result<(), SftpManError> {
+        if self.mounts()?.iter().any(|mount| mount.file == path) {
+            return Ok(());
+        }
+
+        if self.mount_backend == MountBackend::Simulate {
+            log::info!(
+                "{0}: bind-mounting at {1} (simulated)",
+                definition.id,
+                path.display()
+            );
+            return Ok(());
+        }
+
+        // Like the local mount path, the directory needs to be empty, so that nothing gets hidden.
+        if path.symlink_metadata().is_ok() && !(path.is_dir() && is_directory_empty(path)?) {
+            return Err(SftpManError::ExtraMountPathOccupied(
+                definition.id.clone(),
+                path.to_path_buf(),
+            ));
+        }
+
+        fs::create_dir_all(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
+
+        let cmd = definition.bind_mount_command(path);
+        log::debug!(
+            "{0}: executing bind-mount command: {1:?}",
+            definition.id,
+            cmd
+        );
+
+        let result = self.execute(cmd);
+
+        self.state_cache.invalidate();
+
+        if result.is_err() {
+            let _ = remove_empty_directory(path);
+        } else if self.command_runner.is_some() {
+            self.recorded_mounts
+                .lock()
+                .unwrap()
+                .insert(path.to_path_buf());
+        }
+
+        result
+    }
+
+    /// Removes what `expose_at_extra_mount_paths()` created (symlinks or bind mounts), leaving anything else at those paths alone.
+    ///
+    /// All paths get handled, even if some fail to. The first failure gets returned.
+    fn unexpose_from_extra_mount_paths(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<(), SftpManError> {
+        let local_mount_path = definition.local_mount_path();
+
+        let mut first_error: Option<SftpManError> = None;
+
+        for path in definition.local_extra_mount_paths() {
+            let result = match definition.extra_mount_path_method {
+                ExtraMountPathMethod::Symlink => remove_symlink_to(&local_mount_path, &path)
+                    .map_err(|err| SftpManError::IO(path.clone(), err)),
+                ExtraMountPathMethod::Bind => self.bind_umount(definition, &path),
+            };
+
+            if let Err(err) = result {
+                first_error.get_or_insert(err);
+            }
+        }
+
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn bind_umount(
+        &self,
+        definition: &FilesystemMountDefinition,
+        path: &Path,
+    ) -> Result<(), SftpManError> {
+        if self.mount_backend != MountBackend::Simulate
+            && self.mounts()?.iter().any(|mount| mount.file == path)
+        {
+            let cmd = definition.bind_umount_command(path);
+            log::debug!(
+                "{0}: executing bind-unmount command: {1:?}",
+                definition.id,
+                cmd
+            );
+
+            self.execute(cmd)?;
+
+            self.recorded_mounts.lock().unwrap().remove(path);
+            self.state_cache.invalidate();
+        }
+
+        // The directory got created for the bind mount, so it's removed along with it (unless something else got put there).
+        if path.is_dir()
+            && let Err(err) = remove_empty_directory(path)
+        {
+            log::debug!(
+                "{0}: not removing extra mount path {1}: {2:?}",
+                definition.id,
+                path.display(),
+                err
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Verifies that what mounting the given definition relies on is there (see `with_premount_checks()`).
+    ///
+    /// Checks which passed within `PREMOUNT_CHECK_TTL` are not repeated.
+    fn run_premount_checks(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<(), SftpManError> {
+        if self.skip_premount_checks {
+            return Ok(());
+        }
+
+        let path = self.premount_checks_path();
+
+        // A broken cache file merely means that the checks are performed again.
+        let mut passed_checks = load_passed_checks(&path).unwrap_or_else(|err| {
+            log::debug!("Failed to load the passed pre-mount checks: {0:?}", err);
+            BTreeMap::new()
+        });
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut result = Ok(());
+        let mut changed = false;
+
+        for check in PremountCheck::for_definition(definition) {
+            let cache_key = check.cache_key();
+
+            if let Some(cache_key) = &cache_key
+                && let Some(passed_at) = passed_checks.get(cache_key)
+                && now.saturating_sub(*passed_at) < PREMOUNT_CHECK_TTL.as_secs()
+            {
+                continue;
+            }
+
+            log::debug!(
+                "{0}: performing pre-mount check: {1:?}",
+                definition.id,
+                check
+            );
+
+            if let Err(err) = check.run(definition) {
+                result = Err(err);
+                break;
+            }
+
+            if let Some(cache_key) = cache_key {
+                passed_checks.insert(cache_key, now);
+                changed = true;
+            }
+        }
+
+        if changed && let Err(err) = save_passed_checks(&path, &passed_checks) {
+            log::debug!("Failed to save the passed pre-mount checks: {0:?}", err);
+        }
+
+        result
+    }
+
+    /// Returns the mount commands for the given definition, with the `sshfs` command (always the last one)
+    /// wrapped in a systemd scope if configured (see `GlobalConfig::use_systemd_scope`).
+    fn mount_commands_for(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<Vec<Command>, SftpManError> {
+        let mut cmds = definition.mount_commands()?;
+
+        if self.config.use_systemd_scope {
+            if is_user_manager_available() {
+                let cmd_sshfs = cmds
+                    .pop()
+                    .expect("The list of mount commands should end with the sshfs command");
+                cmds.push(wrap_in_scope(&definition.id, cmd_sshfs));
+            } else {
+                log::debug!(
+                    "{0}: a systemd user manager is not available, so sshfs won't run in a scope unit",
+                    definition.id
+                );
+            }
+        }
+
+        Ok(cmds)
+    }
+
+    /// Copies a file (or, if `recursive`, a directory) from the remote machine of a definition to the local machine via `scp`,
+    /// without mounting anything.
+    ///
+    /// Relative remote paths are relative to the definition's remote path (see `FilesystemMountDefinition::remote_path`).
+    /// `scp` runs attached to the terminal, so it may ask for a password (or to confirm with a security key)
+    /// and shows its progress.
+    pub fn download(
+        &self,
+        definition: &FilesystemMountDefinition,
+        remote_path: &str,
+        local_path: &Path,
+        recursive: bool,
+    ) -> Result<(), SftpManError> {
+        let remote_path = resolve_remote_path(definition, remote_path);
+
+        self.copy(
+            definition,
+            definition.download_command(&remote_path, local_path, recursive),
+        )
+    }
+
+    /// Copies a file (or, if `recursive`, a directory) from the local machine to the remote machine of a definition,
+    /// like `download()` does.
+    pub fn upload(
+        &self,
+        definition: &FilesystemMountDefinition,
+        local_path: &Path,
+        remote_path: &str,
+        recursive: bool,
+    ) -> Result<(), SftpManError> {
+        let remote_path = resolve_remote_path(definition, remote_path);
+
+        self.copy(
+            definition,
+            definition.upload_command(local_path, &remote_path, recursive),
+        )
+    }
+
+    fn copy(
+        &self,
+        definition: &FilesystemMountDefinition,
+        cmd: Command,
+    ) -> Result<(), SftpManError> {
+        if definition.auth_type == AuthType::AuthenticationAgent {
+            ensure_agent_usable(definition)?;
+        }
+
+        log::debug!("{0}: copying via: {1:?}", definition.id, cmd);
+
+        run_command_interactive(cmd).map(|_| ())
+    }
+
+    /// Verifies that the remote machine of a definition can be connected to and authenticated with,
+    /// and that its remote path exists and can be listed, without mounting anything.
+    ///
+    /// The check runs `sftp` non-interactively, so definitions which require user interaction
+    /// (password or keyboard-interactive authentication, security keys) are skipped.
+    pub fn verify(&self, definition: &FilesystemMountDefinition) -> VerificationOutcome {
+        self.verify_with(definition, false)
+    }
+
+    /// Verifies a definition like `verify()` does, but creates the remote path (along with its parents, like `mkdir -p` does)
+    /// if it's missing, reporting `VerificationOutcome::PathCreated` if that worked out.
+    pub fn verify_creating_remote_path(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> VerificationOutcome {
+        self.verify_with(definition, true)
+    }
+
+    fn verify_with(
+        &self,
+        definition: &FilesystemMountDefinition,
+        create_remote_path: bool,
+    ) -> VerificationOutcome {
+        if matches!(
+            definition.auth_type,
+            AuthType::Password | AuthType::KeyboardInteractive
+        ) {
+            return VerificationOutcome::Skipped(format!(
+                "{0} authentication requires user interaction",
+                definition.auth_type.to_static_str()
+            ));
+        }
+
+        if definition.uses_security_key() {
+            return VerificationOutcome::Skipped(
+                "security keys require user interaction".to_owned(),
+            );
+        }
+
+        if definition.auth_type == AuthType::AuthenticationAgent
+            && let Err(err) = ensure_agent_usable(definition)
+        {
+            return match err {
+                SftpManError::AgentUnavailable(_, reason) => {
+                    VerificationOutcome::AuthFailed(reason)
+                }
+                err => VerificationOutcome::AuthFailed(err.to_string()),
+            };
+        }
+
+        let outcome = self.run_verification(definition, &[]);
+
+        if !create_remote_path || !matches!(outcome, VerificationOutcome::PathMissing(_)) {
+            return outcome;
+        }
+
+        log::info!(
+            "{0}: creating the remote path {1}..",
+            definition.id,
+            definition.remote_path
+        );
+
+        // sftp's mkdir doesn't create parents, so each one is created in turn.
+        // The `-` prefix makes sftp carry on if a directory already exists.
+        let mut commands: Vec<String> = Vec::new();
+        let mut path = String::new();
+        for component in definition.remote_path.split('/') {
+            if component.is_empty() {
+                if path.is_empty() {
+                    path.push('/');
+                }
+                continue;
+            }
+
+            // Relative paths (e.g. `backups/daily`) are relative to the remote user's home directory.
+            path = if path.is_empty() {
+                component.to_owned()
+            } else {
+                join_remote_path(&path, component)
+            };
+            commands.push(format!("-mkdir {0}", quote(&path)));
+        }
+
+        match self.run_verification(definition, &commands) {
+            VerificationOutcome::Reachable => VerificationOutcome::PathCreated,
+            outcome => outcome,
+        }
+    }
+
+    /// Runs the given `sftp` commands, followed by changing to the remote path and listing it.
+    fn run_verification(
+        &self,
+        definition: &FilesystemMountDefinition,
+        commands: &[String],
+    ) -> VerificationOutcome {
+        let mut cmd = definition.sftp_batch_command();
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        log::debug!("{0}: verifying via: {1:?}", definition.id, cmd);
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(err) => {
+                return VerificationOutcome::Unreachable(format!("failed to run sftp: {0}", err));
+            }
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // If sftp exits early (e.g. failing to connect), writing fails, which is reported via its exit status below.
+            for command in commands {
+                let _ = writeln!(stdin, "{0}", command);
+            }
+            let _ = writeln!(stdin, "cd {0}", quote(&definition.remote_path));
+            // Listing makes sure that the directory can be read, not just entered.
+            let _ = writeln!(stdin, "ls");
+        }
+
+        let output = match child.wait_with_output() {
+            Ok(output) => output,
+            Err(err) => {
+                return VerificationOutcome::Unreachable(format!("failed to run sftp: {0}", err));
+            }
+        };
+
+        if output.status.success() {
+            return VerificationOutcome::Reachable;
+        }
+
+        classify_verification_failure(&String::from_utf8_lossy(&output.stderr))
+    }
+
+    /// Verifies multiple definitions (see `verify()`), working on up to `jobs` of them in parallel.
+    /// If `create_remote_path` is `true`, missing remote paths get created (see `verify_creating_remote_path()`).
+    ///
+    /// The outcomes are returned in the order of the given definitions.
+    pub fn verify_many(
+        &self,
+        definitions: &[FilesystemMountDefinition],
+        jobs: usize,
+        create_remote_path: bool,
+    ) -> Vec<VerificationOutcome> {
+        let next_index = AtomicUsize::new(0);
+        let outcomes: Mutex<Vec<Option<VerificationOutcome>>> =
+            Mutex::new(vec![None; definitions.len()]);
+
+        thread::scope(|scope| {
+            for _ in 0..jobs.clamp(1, definitions.len().max(1)) {
+                scope.spawn(|| {
+                    loop {
+                        let index = next_index.fetch_add(1, Ordering::Relaxed);
+                        let Some(definition) = definitions.get(index) else {
+                            break;
+                        };
+
+                        let outcome = self.verify_with(definition, create_remote_path);
+                        outcomes.lock().unwrap()[index] = Some(outcome);
+                    }
+                });
+            }
+        });
+
+        outcomes
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|outcome| outcome.expect("Each definition should have been verified"))
+            .collect()
+    }
+
+    /// Probes a mounted filesystem, to tell a working mount from one which is mounted but dead
+    /// (e.g. because the connection got lost without `sshfs` noticing).
+    ///
+    /// The mount point gets listed and, if `write` is `true`, a temporary file gets created in it and deleted again.
+    /// Operations which don't complete within a few seconds make the mount count as unresponsive.
+    /// Such operations are left running in the background, as operations on a dead mount can't be interrupted.
+    ///
+    /// For definitions with sub-mounts (see `FilesystemMountDefinition::sub_mounts`), each of them gets probed
+    /// and the first problem is reported.
+    pub fn probe(
+        &self,
+        definition: &FilesystemMountDefinition,
+        write: bool,
+    ) -> Result<ProbeOutcome, SftpManError> {
+        if !self.is_definition_mounted(definition)? {
+            return Ok(ProbeOutcome::NotMounted);
+        }
+
+        if !definition.sub_mounts.is_empty() {
+            for sub_definition in definition.sub_mount_definitions() {
+                match self.probe(&sub_definition, write)? {
+                    ProbeOutcome::Healthy => {}
+                    outcome => return Ok(outcome),
+                }
+            }
+
+            return Ok(ProbeOutcome::Healthy);
+        }
+
+        let path = definition.local_mount_path().into_path_buf();
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        thread::spawn(move || {
+            // The receiver is gone if waiting timed out, in which case there's no one to tell.
+            let _ = sender.send(probe_directory(&path, write));
+        });
+
+        Ok(match receiver.recv_timeout(PROBE_TIMEOUT) {
+            Ok(Ok(())) => ProbeOutcome::Healthy,
+            Ok(Err(err)) => ProbeOutcome::Failed(err.to_string()),
+            Err(_) => {
+                log::debug!(
+                    "{0}: probing did not complete within {1:?}",
+                    definition.id,
+                    PROBE_TIMEOUT
+                );
+                ProbeOutcome::Unresponsive
+            }
+        })
+    }
+
+    /// Returns the SSH private keys found in `~/.ssh` (e.g. `~/.ssh/id_ed25519`), as candidates for `ssh_key`.
+    ///
+    /// The default key names used by `ssh` come first (newer key types before older ones),
+    /// followed by any other keys in alphabetical order.
+    pub fn discover_ssh_keys(&self) -> Result<Vec<PathBuf>, SftpManError> {
+        find_private_keys(&self.paths.ssh_path)
+    }
+
+    /// Returns hosts which are likely to be connected to, as candidates for the `host`, `user` and `port` fields.
+    ///
+    /// Hosts defined in `~/.ssh/config` come first (in the order they're defined in),
+    /// followed by those only found in `~/.ssh/known_hosts` (which lack user information).
+    pub fn discover_hosts(&self) -> Result<Vec<HostCandidate>, SftpManError> {
+        let mut list =
+            parse_ssh_config_hosts(&read_optional_file(&self.paths.ssh_path.join("config"))?);
+
+        for candidate in parse_known_hosts(&read_optional_file(
+            &self.paths.ssh_path.join("known_hosts"),
+        )?) {
+            let is_known = list.iter().any(|existing| {
+                (existing.host == candidate.host || existing.name == candidate.name)
+                    && existing.port == candidate.port
+            });
+
+            if !is_known {
+                list.push(candidate);
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Returns the `known_hosts` file which host keys get checked against when connecting for the given definition
+    /// (`known_hosts_file`, falling back to `~/.ssh/known_hosts`).
+    pub fn known_hosts_path(&self, definition: &FilesystemMountDefinition) -> PathBuf {
+        match &definition.known_hosts_file {
+            Some(path) => PathBuf::from(path),
+            None => self.paths.ssh_path.join("known_hosts"),
+        }
+    }
+
+    /// Retrieves the host keys that the definition's SSH server presents (via `ssh-keyscan`), along with their fingerprints,
+    /// telling which ones are already listed in the `known_hosts` file in use (see `known_hosts_path()`).
+    ///
+    /// The keys are retrieved over an unverified connection, so their fingerprints should be checked
+    /// (e.g. against those the server's administrator provides) before accepting them via `accept_host_keys()`.
+    pub fn scan_host_keys(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<Vec<HostKey>, SftpManError> {
+        if definition.effective_proxy_command().is_some() {
+            log::warn!(
+                "{0}: ssh-keyscan connects to {1} directly, bypassing the configured proxy",
+                definition.id,
+                definition.host
+            );
+        }
+
+        let mut cmd = Command::new("ssh-keyscan");
+        cmd.arg("-T")
+            .arg(SSH_DEFAULT_TIMEOUT.to_string())
+            .arg("-p")
+            .arg(definition.port.to_string())
+            .arg(&definition.host);
+
+        let output = match cmd.output() {
+            Ok(output) => output,
+            Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+        };
+
+        let scanned = parse_keyscan_output(&String::from_utf8_lossy(&output.stdout));
+        if scanned.is_empty() {
+            return Err(SftpManError::NoHostKeys(
+                definition.id.clone(),
+                String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            ));
+        }
+
+        let lines: Vec<String> = scanned
+            .iter()
+            .map(|key| key.to_known_hosts_line())
+            .collect();
+        let fingerprints = fingerprint_known_hosts_lines(&lines)?;
+
+        let known_hosts_path = self.known_hosts_path(definition);
+
+        Ok(scanned
+            .into_iter()
+            .zip(fingerprints)
+            .map(|(key, fingerprint)| HostKey {
+                known: find_known_host_keys(&known_hosts_path, &key.host).contains(&key.key),
+                known_hosts_line: key.to_known_hosts_line(),
+                key_type: key.key_type,
+                fingerprint,
+            })
+            .collect())
+    }
+
+    /// Adds the given host keys (see `scan_host_keys()`) to the `known_hosts` file in use (see `known_hosts_path()`),
+    /// so that connecting no longer fails with `Host key verification failed`. Keys which are already known are skipped.
+    ///
+    /// Returns the path to the `known_hosts` file.
+    pub fn accept_host_keys(
+        &self,
+        definition: &FilesystemMountDefinition,
+        keys: &[HostKey],
+    ) -> Result<PathBuf, SftpManError> {
+        let path = self.known_hosts_path(definition);
+
+        let lines: Vec<&str> = keys
+            .iter()
+            .filter(|key| !key.known)
+            .map(|key| key.known_hosts_line.as_str())
+            .collect();
+
+        if lines.is_empty() {
+            return Ok(path);
+        }
+
+        // Like ssh, the SSH directory and the `known_hosts` file are kept private.
+        if let Some(dir_path) = path.parent() {
+            ensure_private_directory(dir_path)?;
+        }
+
+        // The file may not end with a line break, in which case the first new line would get glued to the last one.
+        let needs_line_break = fs::read(&path)
+            .map(|contents| !contents.is_empty() && !contents.ends_with(b"\n"))
+            .unwrap_or(false);
+
+        let mut options = fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = options
+            .open(&path)
+            .map_err(|err| SftpManError::IO(path.clone(), err))?;
+
+        let mut contents = String::new();
+        if needs_line_break {
+            contents.push('\n');
+        }
+        for line in lines {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+
+        file.write_all(contents.as_bytes())
+            .map_err(|err| SftpManError::IO(path.clone(), err))?;
+
+        Ok(path)
+    }
+
+    /// Returns where `generate_ssh_key()` is meant to store a dedicated key for the given definition (e.g. `~/.ssh/sftpman_my-fs`).
+    pub fn default_ssh_key_path(&self, definition: &FilesystemMountDefinition) -> PathBuf {
+        self.paths
+            .ssh_path
+            .join(format!("sftpman_{0}", definition.id))
+    }
+
+    /// Generates a dedicated ed25519 keypair for the given definition (via `ssh-keygen`), storing the private key at the given path
+    /// (see `default_ssh_key_path()`) and the public key next to it (with a `.pub` extension).
+    ///
+    /// The key has no passphrase, as `sshfs` can't ask for one. Existing keys are never overwritten.
+    /// The definition is left as is, so `ssh_key` needs to be pointed to the new key separately.
+    pub fn generate_ssh_key(
+        &self,
+        definition: &FilesystemMountDefinition,
+        path: &Path,
+    ) -> Result<(), SftpManError> {
+        if path.exists() {
+            return Err(SftpManError::SshKeyExists(path.to_path_buf()));
+        }
+
+        if let Some(dir_path) = path.parent() {
+            ensure_private_directory(dir_path)?;
+        }
+
+        let mut cmd = Command::new("ssh-keygen");
+        cmd.args(["-q", "-t", "ed25519", "-N", "", "-C"])
+            .arg(format!("sftpman-{0}", definition.id))
+            .arg("-f")
+            .arg(path);
+
+        log::debug!("{0}: generating an SSH key via: {1:?}", definition.id, cmd);
+
+        run_command(cmd)?;
+
+        Ok(())
+    }
+
+    /// Adds the given public key (e.g. `~/.ssh/sftpman_my-fs.pub`) to the remote user's `~/.ssh/authorized_keys`,
+    /// like `ssh-copy-id` does (see `FilesystemMountDefinition::key_deployment_command()`).
+    ///
+    /// This authenticates with a password, which `ssh` asks for interactively (on the terminal).
+    pub fn deploy_public_key(
+        &self,
+        definition: &FilesystemMountDefinition,
+        public_key_path: &Path,
+    ) -> Result<(), SftpManError> {
+        let public_key = fs::read_to_string(public_key_path)
+            .map_err(|err| SftpManError::IO(public_key_path.to_path_buf(), err))?;
+
+        let mut cmd = definition.key_deployment_command();
+        cmd.stdin(Stdio::piped());
+
+        log::debug!(
+            "{0}: deploying the public key via: {1:?}",
+            definition.id,
+            cmd
+        );
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+        };
+
+        // ssh reads the password from the terminal, so the key can be fed to the remote command via the standard input.
+        if let Some(mut stdin) = child.stdin.take() {
+            // If ssh exits early (e.g. failing to connect), writing fails, which is reported via its exit status below.
+            let _ = writeln!(stdin, "{0}", public_key.trim_end());
+        }
+
+        let output = match child.wait_with_output() {
+            Ok(output) => output,
+            Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+        };
+
+        if !output.status.success() {
+            return Err(SftpManError::CommandUnsuccessful(cmd, output));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the path to rclone's configuration file (`$RCLONE_CONFIG`, falling back to `~/.config/rclone/rclone.conf`).
+    pub fn rclone_config_path(&self) -> PathBuf {
+        match std::env::var_os("RCLONE_CONFIG") {
+            Some(path) => PathBuf::from(path),
+            None => self
+                .paths
+                .user_config_path
+                .join("rclone")
+                .join("rclone.conf"),
+        }
+    }
+
+    /// Creates definitions for the SFTP remotes (`type = sftp`) found in the given rclone configuration file
+    /// (see `rclone_config_path()`), without persisting them.
+    ///
+    /// Definitions are named after their remote. rclone remotes point to the user's home directory, which can't be known
+    /// without connecting, so the remote path is set to `/`. Passwords (which rclone stores obscured) are not imported,
+    /// so remotes using one get password authentication (asking for the password when mounting).
+    pub fn definitions_from_rclone_config(
+        &self,
+        path: &Path,
+    ) -> Result<Vec<FilesystemMountDefinition>, SftpManError> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
+
+        let remotes = parse_rclone_sftp_remotes(&contents).map_err(|err| {
+            SftpManError::Generic(format!("failed to parse {0}: {1}", path.display(), err))
+        })?;
+
+        // Like ssh (and rclone), paths may be given relative to the home directory.
+        let expand_home = |path: String| match path.strip_prefix("~/") {
+            Some(relative) => self.paths.home_path.join(relative).display().to_string(),
+            None => path,
+        };
+
+        Ok(remotes
+            .into_iter()
+            .map(|remote| {
+                let auth_type = if remote.key_file.is_some() {
+                    AuthType::PublicKey
+                } else if remote.has_password {
+                    AuthType::Password
+                } else {
+                    AuthType::AuthenticationAgent
+                };
+
+                FilesystemMountDefinition {
+                    id: sanitize_id(&remote.name),
+                    host: remote.host,
+                    port: remote.port.unwrap_or(22),
+                    user: remote
+                        .user
+                        .unwrap_or_else(|| std::env::var("USER").unwrap_or_default()),
+                    remote_path: "/".to_owned(),
+                    auth_type,
+                    ssh_key: remote.key_file.map(expand_home).unwrap_or_default(),
+                    known_hosts_file: remote.known_hosts_file.map(expand_home),
+                    ..FilesystemMountDefinition::default()
+                }
+            })
+            .collect())
+    }
+
+    /// Returns the paths to the file manager bookmark files that SFTP bookmarks can be imported from
+    /// (see `definitions_from_bookmarks()`): GTK's (`~/.config/gtk-3.0/bookmarks`) and KDE's places (`~/.local/share/user-places.xbel`).
+    pub fn bookmarks_paths(&self) -> Vec<PathBuf> {
+        vec![
+            self.paths
+                .user_config_path
+                .join("gtk-3.0")
+                .join("bookmarks"),
+            self.paths.user_data_path.join("user-places.xbel"),
+        ]
+    }
+
+    /// Creates definitions for the SFTP bookmarks (e.g. `sftp://user@example.com/storage`) found in the given file manager bookmark files
+    /// (see `bookmarks_paths()`), without persisting them. XBEL files (like KDE's places) are recognized by their `.xbel` extension.
+    ///
+    /// Definitions are named after the bookmark's label or, failing that, after the host and the last component of the path.
+    /// Bookmarks without a user get the current one (`$USER`). Authentication is left to the SSH agent (see `AuthType::AuthenticationAgent`).
+    /// Bookmarks whose URL can't be parsed are skipped, as are locations bookmarked more than once (e.g. in both GTK and KDE).
+    pub fn definitions_from_bookmarks(
+        &self,
+        paths: &[PathBuf],
+    ) -> Result<Vec<FilesystemMountDefinition>, SftpManError> {
+        let mut list: Vec<FilesystemMountDefinition> = Vec::new();
+
+        for path in paths {
+            let contents = fs::read_to_string(path)
+                .map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
+
+            let bookmarks = if path.extension().is_some_and(|ext| ext == "xbel") {
+                parse_xbel_bookmarks(&contents)
+            } else {
+                parse_gtk_bookmarks(&contents)
+            };
+
+            for bookmark in bookmarks {
+                let mut definition = match FilesystemMountDefinition::parse_url(&bookmark.url) {
+                    Ok(definition) => definition,
+                    Err(err) => {
+                        log::warn!("Skipping bookmark {0}: {1}", bookmark.url, err);
+                        continue;
+                    }
+                };
+
+                if list
+                    .iter()
+                    .any(|other| have_same_remote(other, &definition))
+                {
+                    continue;
+                }
+
+                let name = bookmark.label.unwrap_or_else(|| {
+                    match definition
+                        .remote_path
+                        .trim_end_matches('/')
+                        .rsplit_once('/')
+                    {
+                        Some((_, last)) if !last.is_empty() => {
+                            format!("{0}-{1}", definition.host, last)
+                        }
+                        _ => definition.host.clone(),
+                    }
+                });
+
+                let id = sanitize_id(&name);
+
+                // Different bookmarks may end up with the same name (e.g. same-named directories on the same host).
+                definition.id = id.clone();
+                let mut suffix = 2;
+                while list.iter().any(|other| other.id == definition.id) {
+                    definition.id = format!("{0}-{1}", id, suffix);
+                    suffix += 1;
+                }
+
+                list.push(definition);
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Returns the path to the unix socket that the daemon (see `Daemon`) listens on by default
+    /// (e.g. `$XDG_RUNTIME_DIR/sftpman/daemon.sock`).
+    pub fn daemon_socket_path(&self) -> PathBuf {
+        self.paths.runtime_path.join("daemon.sock")
+    }
+
+    /// Returns the path to the log file which captures `sshfs` debug output for the given definition
+    /// (e.g. `~/.local/state/sftpman/logs/{id}.log`).
+    ///
+    /// The log file is only written to when mounting with `debug_sshfs` enabled.
+    pub fn sshfs_debug_log_path(&self, definition: &FilesystemMountDefinition) -> PathBuf {
+        self.paths
+            .state_path
+            .join("logs")
+            .join(format!("{0}.log", definition.id))
+    }
+
+    /// Runs `sshfs` (with debug output enabled) in the background, capturing its output to a log file,
+    /// and waits for the mount to appear.
+    ///
+    /// With debug output enabled, `sshfs` doesn't daemonize, so it cannot be run via `run_command()`.
+    fn run_sshfs_with_debug_log(
+        &self,
+        definition: &FilesystemMountDefinition,
+        mut cmd: Command,
+        password: Option<&str>,
+    ) -> Result<(), SftpManError> {
+        let log_path = self.sshfs_debug_log_path(definition);
+
+        if let Some(log_dir_path) = log_path.parent() {
+            fs::create_dir_all(log_dir_path)
+                .map_err(|err| SftpManError::IO(log_dir_path.to_path_buf(), err))?;
+        }
+
+        let log_file =
+            fs::File::create(&log_path).map_err(|err| SftpManError::IO(log_path.clone(), err))?;
+        let log_file_for_stderr = log_file
+            .try_clone()
+            .map_err(|err| SftpManError::IO(log_path.clone(), err))?;
+
+        log::info!(
+            "{0}: capturing sshfs debug output to {1}",
+            definition.id,
+            log_path.display()
+        );
+
+        cmd.stdin(if password.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::from(log_file))
+        .stderr(Stdio::from(log_file_for_stderr));
+
+        // Run in a separate process group, so that it's not affected by signals (e.g. Ctrl+C) sent to us.
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+        };
+
+        if let Some(password) = password
+            && let Err(err) = write_password(&mut child, password)
+        {
+            log::debug!(
+                "{0}: failed to write the password to sshfs: {1}",
+                definition.id,
+                err
+            );
+        }
+
+        let deadline = Instant::now() + SSHFS_DEBUG_MOUNT_TIMEOUT;
+
+        loop {
+            if let Ok(Some(status)) = child.try_wait() {
+                return Err(SftpManError::MountFailedWithDebugLog {
+                    log_path,
+                    reason: format!("sshfs exited with {0}", status),
+                });
+            }
+
+            if self.is_definition_mounted(definition)? {
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                if let Err(err) = child.kill() {
+                    log::debug!("{0}: failed to kill sshfs: {1}", definition.id, err);
+                }
+                let _ = child.wait();
+
+                return Err(SftpManError::MountFailedWithDebugLog {
+                    log_path,
+                    reason: format!(
+                        "the mount did not appear within {0:?}",
+                        SSHFS_DEBUG_MOUNT_TIMEOUT
+                    ),
+                });
+            }
+
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// Unmounts a filesystem definition (unless already unmounted) and removes its mount path from the filesystem hierarchy.
+    ///
+    /// Unmounting is performed via a command call to `fusermount3 -u ..` (preferred) or `fusermount -u ..` (fallback),
+    /// which may fail on filesystems that are currently busy.
+    /// In such cases, a fallback is performed - the `sshfs` process responsible for the mount gets terminated.
+    /// The returned `UmountOutcome` tells which of these happened.
+    pub fn umount(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<UmountOutcome, SftpManError> {
+        self.umount_with_kill_policy(definition, KillPolicy::default())
+    }
+
+    /// Unmounts a filesystem definition (unless already unmounted) like `umount()` does,
+    /// but lets the caller control whether the `sshfs` process responsible for the mount gets killed (see `KillPolicy`).
+    ///
+    /// For definitions with sub-mounts (see `FilesystemMountDefinition::sub_mounts`), all of them get unmounted (in reverse order),
+    /// even if some fail to. The first failure gets returned.
+    pub fn umount_with_kill_policy(
+        &self,
+        definition: &FilesystemMountDefinition,
+        kill_policy: KillPolicy,
+    ) -> Result<UmountOutcome, SftpManError> {
+        let _cache_invalidation = self.state_cache.invalidation_guard();
+
+        // Bind mounts keep the filesystem busy, so they go first. This also happens when not mounted, to clean up stale ones.
+        if !definition.extra_mount_paths.is_empty()
+            && let Err(err) = self.unexpose_from_extra_mount_paths(definition)
+        {
+            log::warn!(
+                "{0}: failed to remove the extra mount paths: {1}",
+                definition.id,
+                err
+            );
+        }
+
+        if !definition.sub_mounts.is_empty() {
+            return self.umount_sub_mounts(definition, kill_policy);
+        }
+
+        if !self.is_definition_mounted(definition)? {
+            log::info!("{0}: not mounted, nothing to do..", definition.id);
+            return Ok(UmountOutcome::AlreadyUnmounted);
+        }
+
+        if self.mount_backend == MountBackend::Simulate {
+            log::info!("{0}: unmounting (simulated)..", definition.id);
+
+            self.recorded_mounts
+                .lock()
+                .unwrap()
+                .remove(definition.local_mount_path().as_path());
+
+            self.clean_up_after_unmount(definition);
+
+            return Ok(UmountOutcome::UnmountedCleanly);
+        }
+
+        // There's no `sshfs` process to kill (or processes to terminate) with a command runner, so only the unmount commands get run.
+        if self.command_runner.is_some() {
+            log::info!("{0}: unmounting..", definition.id);
+
+            self.do_umount(definition)?;
+
+            return Ok(UmountOutcome::UnmountedCleanly);
+        }
+
+        if kill_policy == KillPolicy::Immediate {
+            log::info!("{0}: unmounting by killing sshfs..", definition.id);
+
+            self.kill_sshfs_for_definition(definition)?;
+
+            self.clean_up_after_unmount(definition);
+
+            return Ok(UmountOutcome::UnmountedByKill);
+        }
+
+        let terminated_users = match kill_policy {
+            KillPolicy::TerminateUsers(signal) => {
+                self.terminate_blocking_processes(definition, signal)?
+            }
+            _ => false,
+        };
+
+        log::info!("{0}: unmounting..", definition.id);
+
+        match self.do_umount(definition) {
+            Ok(_) if terminated_users => Ok(UmountOutcome::UnmountedAfterTerminatingUsers),
+            Ok(_) => Ok(UmountOutcome::UnmountedCleanly),
+
+            Err(err) => {
+                // It's likely that this is a "Device is busy" error.
+
+                log::warn!("{0} failed to get unmounted: {1:?}", definition.id, err);
+
+                let blocking_processes = match self.blocking_processes(definition) {
+                    Ok(processes) => processes,
+                    Err(err) => {
+                        log::debug!(
+                            "{0}: failed to determine the processes using it: {1:?}",
+                            definition.id,
+                            err
+                        );
+                        Vec::new()
+                    }
+                };
+
+                if matches!(
+                    kill_policy,
+                    KillPolicy::Never | KillPolicy::TerminateUsers(_)
+                ) {
+                    if !blocking_processes.is_empty() {
+                        return Err(SftpManError::MountBusy(
+                            definition.id.clone(),
+                            blocking_processes,
+                        ));
+                    }
+
+                    return Err(err);
+                }
+
+                if !blocking_processes.is_empty() {
+                    log::warn!(
+                        "{0} is used by: {1}. Killing sshfs regardless..",
+                        definition.id,
+                        blocking_processes
+                            .iter()
+                            .map(|process| process.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    );
+                }
+
+                self.kill_sshfs_for_definition(definition)?;
+
+                // Killing successfully is good enough to unmount.
+                // We don't need to call do_umount() again, as calling `fusermount -u ..` (etc), may fail with:
+                // > CommandUnsuccessfulError("fusermount" "-u" "/home/user/mounts/storage", Output { status: ExitStatus(unix_wait_status(256)), stdout: "", stderr: "fusermount: entry for /path not found in /etc/mtab\n" })
+                // We only need to clean up now.
+
+                self.clean_up_after_unmount(definition);
+
+                Ok(UmountOutcome::UnmountedByKill)
+            }
+        }
+    }
+
+    fn umount_sub_mounts(
+        &self,
+        definition: &FilesystemMountDefinition,
+        kill_policy: KillPolicy,
+    ) -> Result<UmountOutcome, SftpManError> {
+        let mut outcome = UmountOutcome::AlreadyUnmounted;
+        let mut first_error: Option<SftpManError> = None;
+
+        for sub_definition in definition.sub_mount_definitions().iter().rev() {
+            match self.umount_with_kill_policy(sub_definition, kill_policy) {
+                Ok(UmountOutcome::AlreadyUnmounted) => {}
+                Ok(sub_outcome) => {
+                    // Having had to kill (or else, to terminate the processes using) any of them is what's worth reporting.
+                    outcome = match (outcome, sub_outcome) {
+                        (UmountOutcome::UnmountedByKill, _)
+                        | (_, UmountOutcome::UnmountedByKill) => UmountOutcome::UnmountedByKill,
+                        (UmountOutcome::UnmountedAfterTerminatingUsers, _) => {
+                            UmountOutcome::UnmountedAfterTerminatingUsers
+                        }
+                        _ => sub_outcome,
+                    };
+                }
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        if let Some(err) = first_error {
+            return Err(err);
+        }
+
+        self.clean_up_after_unmount(definition);
+
+        Ok(outcome)
+    }
+
+    /// Unmounts a filesystem definition (unless already unmounted) lazily (see `FilesystemMountDefinition::lazy_umount_command()`)
+    /// and removes its mount path from the filesystem hierarchy.
+    ///
+    /// This is meant for mounts whose remote machine went away (e.g. with the network), where regular unmounting
+    /// (or the processes using the mount) could hang. The `sshfs` process is left to exit on its own.
+    pub fn umount_lazily(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<UmountOutcome, SftpManError> {
+        let _cache_invalidation = self.state_cache.invalidation_guard();
+
+        if !definition.extra_mount_paths.is_empty()
+            && let Err(err) = self.unexpose_from_extra_mount_paths(definition)
+        {
+            log::warn!(
+                "{0}: failed to remove the extra mount paths: {1}",
+                definition.id,
+                err
+            );
+        }
+
+        if !definition.sub_mounts.is_empty() {
+            let mut outcome = UmountOutcome::AlreadyUnmounted;
+
+            for sub_definition in definition.sub_mount_definitions().iter().rev() {
+                if self.umount_lazily(sub_definition)? != UmountOutcome::AlreadyUnmounted {
+                    outcome = UmountOutcome::UnmountedCleanly;
+                }
+            }
+
+            self.clean_up_after_unmount(definition);
+
+            return Ok(outcome);
+        }
+
+        if !self.is_definition_mounted(definition)? {
+            return Ok(UmountOutcome::AlreadyUnmounted);
+        }
+
+        // Simulated mounts cannot hang, so they get unmounted the regular way.
+        if self.mount_backend == MountBackend::Simulate {
+            return self.umount(definition);
+        }
+
+        log::info!("{0}: unmounting lazily..", definition.id);
+
+        let cmd = definition.lazy_umount_command();
+        log::debug!("{0}: executing unmount command: {1:?}", definition.id, cmd);
+
+        self.execute(cmd)?;
+
+        self.recorded_mounts
+            .lock()
+            .unwrap()
+            .remove(definition.local_mount_path().as_path());
+
+        self.clean_up_after_unmount(definition);
+
+        Ok(UmountOutcome::UnmountedCleanly)
+    }
+
+    /// Tells which commands `umount()` would run for the given definition (see `MountPlan`), without running anything.
+    ///
+    /// Killing the `sshfs` process (see `KillPolicy`) is a fallback that only happens if these commands fail, so it's not part of the plan.
+    pub fn plan_umount(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<MountPlan, SftpManError> {
+        if !definition.sub_mounts.is_empty() {
+            let mut steps: Vec<PlannedStep> = self.plan_bind_umounts(definition).collect();
+            for sub_definition in definition.sub_mount_definitions().iter().rev() {
+                steps.extend(self.plan_umount(sub_definition)?.steps);
+            }
+
+            return Ok(MountPlan {
+                id: definition.id.clone(),
+                steps,
+            });
+        }
+
+        let steps = self
+            .plan_bind_umounts(definition)
+            .chain(
+                definition
+                    .umount_commands()?
+                    .iter()
+                    .map(|cmd| PlannedStep::new(cmd, PlannedStepPurpose::Unmount)),
+            )
+            .collect();
+
+        Ok(MountPlan {
+            id: definition.id.clone(),
+            steps,
+        })
+    }
+
+    fn do_umount(&self, definition: &FilesystemMountDefinition) -> Result<(), SftpManError> {
+        let cmds = definition.umount_commands().unwrap();
+
+        for cmd in cmds {
+            log::debug!("{0}: executing unmount command: {1:?}", definition.id, cmd);
+
+            if let Err(err) = self.execute(cmd) {
+                log::error!(
+                    "{0}: failed to run unmount command: {1:?}",
+                    definition.id,
+                    err
+                );
+
+                // We weren't successful to unmount, but it may be because the mount point already got unmounted.
+                // It doesn't hurt to try and clean up.
+                self.clean_up_after_unmount(definition);
+
+                return Err(err);
+            }
+        }
+
+        self.recorded_mounts
+            .lock()
+            .unwrap()
+            .remove(definition.local_mount_path().as_path());
+
+        self.clean_up_after_unmount(definition);
+
+        Ok(())
+    }
+
+    /// Unmounts filesystems which have been idle for longer than their `idle_unmount_minutes` setting allows.
+    ///
+    /// Activity is determined based on the I/O counters of the `sshfs` process responsible for each mount.
+    /// The counters are remembered in the state directory between calls,
+    /// so this is meant to be called periodically (e.g. every minute).
+    /// The idle time of a filesystem is counted from the first call which noticed it.
+    ///
+    /// Idle filesystems which are still in use (e.g. by a shell whose working directory is in them) are never force-unmounted
+    /// (see `KillPolicy::Never`), but left mounted until a later call finds them unused.
+    ///
+    /// Returns the IDs of the definitions that got unmounted.
+    pub fn umount_idle(&self) -> Result<Vec<String>, SftpManError> {
+        let activity_path = self.paths.state_path.join("activity.json");
+        let mut records = load_activity_records(&activity_path)?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut unmounted_ids: Vec<String> = Vec::new();
+
+        let states = self.full_state()?;
+
+        // Forget about definitions which no longer exist.
+        records.retain(|id, _| states.iter().any(|state| state.definition.id == *id));
+
+        let paused_ids = self.paused_ids()?;
+
+        for state in states {
+            let definition = state.definition;
+
+            // Paused definitions are left alone, so their idle time starts over once they get resumed.
+            if paused_ids.contains(&definition.id) {
+                records.remove(&definition.id);
+                continue;
+            }
+
+            let Some(idle_unmount_minutes) = definition.idle_unmount_minutes else {
+                records.remove(&definition.id);
+                continue;
+            };
+
+            if !state.mounted {
+                records.remove(&definition.id);
+                continue;
+            }
+
+            // Definitions with sub-mounts are idle when all of their sshfs processes are.
+            let sshfs_definitions = if definition.sub_mounts.is_empty() {
+                vec![definition.clone()]
+            } else {
+                definition.sub_mount_definitions()
+            };
+
+            let mut pids: Vec<i32> = Vec::new();
+            for sshfs_definition in &sshfs_definitions {
+                if let Some(pid) = sshfs_pid_by_definition(sshfs_definition)? {
+                    pids.push(pid);
+                }
+            }
+
+            let Some(&pid) = pids.first() else {
+                log::debug!(
+                    "{0}: could not determine the sshfs process, skipping idle check..",
+                    definition.id
+                );
+                continue;
+            };
+
+            let io_counter = match pids
+                .iter()
+                .map(|pid| process_io_counter(*pid))
+                .sum::<Result<u64, SftpManError>>()
+            {
+                Ok(io_counter) => io_counter,
+                Err(err) => {
+                    log::warn!("{0}: skipping idle check: {1:?}", definition.id, err);
+                    continue;
+                }
+            };
+
+            let record = records
+                .entry(definition.id.clone())
+                .or_insert(ActivityRecord {
+                    pid,
+                    io_counter,
+                    last_active_at: now,
+                });
+
+            if record.pid != pid || record.io_counter != io_counter {
+                *record = ActivityRecord {
+                    pid,
+                    io_counter,
+                    last_active_at: now,
+                };
+                continue;
+            }
+
+            let idle_seconds = now.saturating_sub(record.last_active_at);
+
+            log::debug!("{0}: idle for {1}s", definition.id, idle_seconds);
+
+            if idle_seconds < u64::from(idle_unmount_minutes) * 60 {
+                continue;
+            }
+
+            log::info!(
+                "{0}: idle for more than {1} minute(s), unmounting..",
+                definition.id,
+                idle_unmount_minutes
+            );
 
-use crate::model::DEFAULT_MOUNT_PATH_PREFIX;
+            // Something may still be using the filesystem without doing I/O (e.g. a shell whose working directory is in it),
+            // which killing sshfs would pull the rug from under.
+            match self.umount_with_kill_policy(&definition, KillPolicy::Never) {
+                Ok(_) => {
+                    records.remove(&definition.id);
+                    unmounted_ids.push(definition.id);
+                }
+                Err(SftpManError::MountBusy(_, processes)) => {
+                    log::info!(
+                        "{0}: idle, but still in use by {1}, so not unmounting it for now",
+                        definition.id,
+                        processes
+                            .iter()
+                            .map(|process| process.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    );
+                }
+                Err(err) => {
+                    log::error!(
+                        "{0}: failed to unmount idle filesystem: {1:?}",
+                        definition.id,
+                        err
+                    );
+                }
+            }
+        }
 
-use super::errors::{ManagerInitError, PreflightCheckError, SftpManError};
-use super::model::{FilesystemMountDefinition, MountState};
+        save_activity_records(&activity_path, &records)?;
 
-use super::utils::command::{run_command, run_command_background};
-use super::utils::fs::{
-    ensure_directory_recursively_created, get_mounts_under_path_prefix, remove_empty_directory,
-};
-use super::utils::fusermount::{create_fusermount_check_command, create_fusermount3_check_command};
-use super::utils::process::{ensure_process_killed, sshfs_pid_by_definition};
+        Ok(unmounted_ids)
+    }
 
-const VFS_TYPE_SSHFS: &str = "fuse.sshfs";
+    /// Pauses the automatic management of a definition, until `resume()` gets called.
+    ///
+    /// Paused definitions are not touched by anything that acts on its own (e.g. `umount_idle()` run by the daemon,
+    /// or `mount_all --auto`), but can still be mounted and unmounted explicitly.
+    /// Unlike disabling a definition (see `FilesystemMountDefinition::enabled`), this doesn't change the definition itself.
+    ///
+    /// Returns `false` if the definition was already paused.
+    pub fn pause(&self, definition: &FilesystemMountDefinition) -> Result<bool, SftpManError> {
+        let mut ids = self.paused_ids()?;
 
-#[derive(Default, Clone)]
-pub struct Manager {
-    config_path: PathBuf,
-}
+        if !ids.insert(definition.id.clone()) {
+            return Ok(false);
+        }
 
-impl Manager {
-    pub fn new() -> Result<Self, ManagerInitError> {
-        let d = directories::ProjectDirs::from("sftpman", "Devture Ltd", "sftpman")
-            .ok_or(ManagerInitError::NoConfigDirectory)?;
+        save_paused_ids(&self.paused_ids_path(), &ids)?;
 
-        Ok(Self {
-            config_path: d.config_dir().to_path_buf().to_owned(),
-        })
+        Ok(true)
     }
 
-    /// Returns the list of all known (stored in the config directory) filesystem definitions.
-    pub fn definitions(&self) -> Result<Vec<FilesystemMountDefinition>, SftpManError> {
-        let dir_path = self.config_path_mounts();
+    /// Resumes the automatic management of a definition paused via `pause()`.
+    ///
+    /// Returns `false` if the definition was not paused.
+    pub fn resume(&self, definition: &FilesystemMountDefinition) -> Result<bool, SftpManError> {
+        let mut ids = self.paused_ids()?;
 
-        if !dir_path.is_dir() {
-            log::debug!(
-                "Mount config directory {0} doesn't exist. Returning an empty definitions list ...",
-                dir_path.display()
-            );
-            return Ok(vec![]);
+        if !ids.remove(&definition.id) {
+            return Ok(false);
         }
 
-        let mut list: Vec<FilesystemMountDefinition> = Vec::new();
+        save_paused_ids(&self.paused_ids_path(), &ids)?;
 
-        let directory_entries =
-            fs::read_dir(dir_path).map_err(|err| SftpManError::Generic(err.to_string()))?;
+        Ok(true)
+    }
 
-        for entry in directory_entries {
-            let entry = entry.map_err(|err| SftpManError::Generic(err.to_string()))?;
+    /// Tells whether the automatic management of a definition is paused (see `pause()`).
+    pub fn is_paused(&self, definition: &FilesystemMountDefinition) -> Result<bool, SftpManError> {
+        Ok(self.paused_ids()?.contains(&definition.id))
+    }
 
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
+    /// Returns the IDs of the definitions whose automatic management is paused (see `pause()`).
+    pub fn paused_ids(&self) -> Result<BTreeSet<String>, SftpManError> {
+        load_paused_ids(&self.paused_ids_path())
+    }
 
-            let name = path.file_name();
-            if name.is_none() {
-                continue;
-            }
-            if !name.unwrap().to_string_lossy().ends_with(".json") {
+    /// Returns the usage stats (when they were last mounted and how often) of the definitions, by ID.
+    ///
+    /// Definitions which never got mounted (since usage started being tracked) are missing.
+    pub fn usage_stats(&self) -> Result<HashMap<String, UsageStats>, SftpManError> {
+        load_usage_stats(&self.usage_stats_path())
+    }
+
+    /// Returns the definitions which haven't been used for at least the given duration (mounted ones are never included).
+    ///
+    /// Being used means getting mounted (see `usage_stats()`). For definitions which were never mounted,
+    /// the last change to their configuration file counts instead, so that freshly created ones are spared.
+    pub fn unused_definitions(
+        &self,
+        unused_for: Duration,
+    ) -> Result<Vec<FilesystemMountDefinition>, SftpManError> {
+        let usage_stats = self.usage_stats()?;
+        let cutoff = SystemTime::now()
+            .checked_sub(unused_for)
+            .unwrap_or(UNIX_EPOCH);
+
+        let mut list: Vec<FilesystemMountDefinition> = Vec::new();
+
+        for state in self.full_state()? {
+            if state.mounted {
                 continue;
             }
 
-            match Self::definition_from_config_path(&path) {
-                Ok(cfg) => list.push(cfg),
-                Err(err) => return Err(err),
+            let last_used_at = match usage_stats
+                .get(&state.definition.id)
+                .and_then(|stats| stats.last_mounted_at)
+            {
+                Some(last_mounted_at) => UNIX_EPOCH + Duration::from_secs(last_mounted_at),
+                None => {
+                    let path = self.config_path_for_definition_id(&state.definition.id);
+                    fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .map_err(|err| SftpManError::IO(path, err))?
+                }
+            };
+
+            if last_used_at < cutoff {
+                list.push(state.definition);
             }
         }
 
-        list.sort_by_key(|item| item.id.clone());
-
         Ok(list)
     }
 
-    /// Returns the filesystem definition (as stored in the config directory) for the given ID.
-    pub fn definition(&self, id: &str) -> Result<FilesystemMountDefinition, SftpManError> {
-        Self::definition_from_config_path(&self.config_path_for_definition_id(id))
-    }
-
-    /// Returns the full state (configuration and mount status) of all known (stored in the config directory) filesystem definitions.
-    pub fn full_state(&self) -> Result<Vec<MountState>, SftpManError> {
-        let mut mounted_sshfs_paths_map: HashMap<String, bool> = HashMap::new();
+    /// Tells whether the host of a definition still resolves to at least one address.
+    ///
+    /// Like with ssh, `Host` aliases defined in `~/.ssh/config` get resolved via their `HostName`.
+    pub fn does_host_resolve(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<bool, SftpManError> {
+        let host = self
+            .ssh_config_host(definition)?
+            .map(|candidate| candidate.host)
+            .unwrap_or_else(|| definition.host.clone());
 
-        for mount in get_mounts_under_path_prefix("/")? {
-            if mount.vfstype != VFS_TYPE_SSHFS {
-                continue;
-            }
+        Ok(does_host_resolve(&host))
+    }
 
-            mounted_sshfs_paths_map
-                .insert(mount.file.as_os_str().to_str().unwrap().to_owned(), true);
+    /// Makes sure that the host of a definition resolves, so that mounting can fail right away if it doesn't
+    /// (see `GlobalConfig::resolve_hosts_before_mount`).
+    ///
+    /// Hosts which ssh connects to through a proxy (the definition's own or one from `~/.ssh/config`) are not resolved,
+    /// as they may only be resolvable by the proxy. Neither are those of definitions opting out (see `FilesystemMountDefinition::skip_host_resolution`).
+    fn ensure_host_resolves(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<(), SftpManError> {
+        if !self.config.resolve_hosts_before_mount
+            || definition.skip_host_resolution
+            || definition.effective_proxy_command().is_some()
+        {
+            return Ok(());
         }
 
-        let mut list: Vec<MountState> = Vec::new();
+        let host = match self.ssh_config_host(definition)? {
+            Some(candidate) if candidate.proxied => {
+                log::debug!(
+                    "{0}: not resolving {1}, as ~/.ssh/config connects to it through a proxy",
+                    definition.id,
+                    definition.host
+                );
+                return Ok(());
+            }
+            Some(candidate) => candidate.host,
+            None => definition.host.clone(),
+        };
 
-        for definition in self.definitions()? {
-            let mounted = mounted_sshfs_paths_map.contains_key(&definition.local_mount_path());
-            list.push(MountState::new(definition, mounted));
-        }
+        log::debug!("{0}: resolving {1}..", definition.id, host);
 
-        Ok(list)
+        resolve_host(&host).map_err(|reason| {
+            SftpManError::HostResolutionFailed(definition.id.clone(), host, reason)
+        })
     }
 
-    /// Tells if the given filesystem definition is currently mounted.
-    pub fn is_definition_mounted(
+    /// Returns the `Host` entry in `~/.ssh/config` (if any) which the host of a definition is an alias for.
+    fn ssh_config_host(
         &self,
         definition: &FilesystemMountDefinition,
-    ) -> Result<bool, SftpManError> {
-        let local_mount_path = definition.local_mount_path();
+    ) -> Result<Option<HostCandidate>, SftpManError> {
+        Ok(
+            parse_ssh_config_hosts(&read_optional_file(&self.paths.ssh_path.join("config"))?)
+                .into_iter()
+                .find(|candidate| candidate.name == definition.host),
+        )
+    }
 
-        for mount in get_mounts_under_path_prefix(local_mount_path.as_str())? {
-            if *mount.file.as_os_str().to_str().unwrap() != local_mount_path {
-                continue;
-            }
+    /// Updates the usage stats (see `usage_stats()`) after a definition got mounted.
+    ///
+    /// Failing to do so doesn't make mounting fail, so it only gets logged.
+    fn record_mount(&self, definition: &FilesystemMountDefinition) {
+        let path = self.usage_stats_path();
 
-            if mount.vfstype != VFS_TYPE_SSHFS {
-                return Err(SftpManError::MountVfsTypeMismatch {
-                    path: std::path::Path::new(&local_mount_path).to_path_buf(),
-                    found_vfs_type: mount.vfstype.to_string(),
-                    expected_vfs_type: VFS_TYPE_SSHFS.to_string(),
-                });
-            }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
 
-            return Ok(true);
+        let result = load_usage_stats(&path).and_then(|mut all| {
+            let stats = all.entry(definition.id.clone()).or_default();
+            stats.last_mounted_at = Some(now);
+            stats.mount_count += 1;
+
+            save_usage_stats(&path, &all)
+        });
+
+        if let Err(err) = result {
+            log::warn!(
+                "{0}: failed to update the usage stats: {1:?}",
+                definition.id,
+                err
+            );
         }
+    }
 
-        Ok(false)
+    /// Returns the IDs of the definitions in the session: those mounted (see `mount()`) and not taken out of it since
+    /// (see `leave_session()`).
+    ///
+    /// Unmounting doesn't change the session by itself, so it tells which filesystems to mount again
+    /// to get back to the previous working set (e.g. after a reboot, or after unmounting everything).
+    pub fn session_ids(&self) -> Result<BTreeSet<String>, SftpManError> {
+        load_session_ids(&self.session_path())
     }
 
-    /// Mounts a filesystem definition unless already mounted.
-    pub fn mount(&self, definition: &FilesystemMountDefinition) -> Result<(), SftpManError> {
-        if self.is_definition_mounted(definition)? {
-            log::info!("{0}: already mounted, nothing to do..", definition.id);
-            return Ok(());
+    /// Takes a definition out of the session (see `session_ids()`), e.g. because it was unmounted on its own
+    /// (as opposed to along with everything else).
+    ///
+    /// Returns `false` if the definition was not in the session.
+    pub fn leave_session(&self, id: &str) -> Result<bool, SftpManError> {
+        let mut ids = self.session_ids()?;
+
+        if !ids.remove(id) {
+            return Ok(false);
         }
 
-        log::info!("{0}: mounting..", definition.id);
+        save_session_ids(&self.session_path(), &ids)?;
 
-        ensure_directory_recursively_created(&definition.local_mount_path())?;
+        Ok(true)
+    }
 
-        let cmds = definition.mount_commands().unwrap();
+    /// Adds a definition to the session (see `session_ids()`) after it got mounted.
+    ///
+    /// Failing to do so doesn't make mounting fail, so it only gets logged.
+    fn join_session(&self, definition: &FilesystemMountDefinition) {
+        let path = self.session_path();
 
-        for cmd in cmds {
-            log::debug!("{0}: executing mount command: {1:?}", definition.id, cmd);
+        let result = load_session_ids(&path).and_then(|mut ids| {
+            if ids.insert(definition.id.clone()) {
+                save_session_ids(&path, &ids)?;
+            }
 
-            if let Err(err) = run_command(cmd) {
-                log::error!(
-                    "{0}: failed to run mount command: {1:?}",
-                    definition.id,
-                    err
-                );
+            Ok(())
+        });
 
-                log::debug!("{0}: performing umount to clean up", definition.id);
+        if let Err(err) = result {
+            log::warn!(
+                "{0}: failed to add to the session: {1:?}",
+                definition.id,
+                err
+            );
+        }
+    }
 
-                // This will most likely fail, but we should try to do it anyway.
-                if let Err(err) = self.umount(definition) {
-                    log::debug!(
-                        "{0}: failed to perform cleanup-umount: {1:?}",
-                        definition.id,
-                        err
-                    );
-                }
+    fn session_path(&self) -> PathBuf {
+        // The state directory (unlike the runtime one) survives reboots, which the session needs to as well.
+        self.paths.state_path.join("session.json")
+    }
 
-                self.clean_up_after_unmount(definition);
+    fn usage_stats_path(&self) -> PathBuf {
+        self.paths.state_path.join("usage.json")
+    }
 
-                return Err(err);
-            }
-        }
+    fn premount_checks_path(&self) -> PathBuf {
+        self.paths.runtime_path.join("premount_checks.json")
+    }
 
-        Ok(())
+    fn paused_ids_path(&self) -> PathBuf {
+        self.paths.state_path.join("paused.json")
     }
 
-    /// Unmounts a filesystem definition (unless already unmounted) and removes its mount path from the filesystem hierarchy.
+    /// Mounts a definition which is not stored in the config directory (e.g. one made via `FilesystemMountDefinition::from_url()`),
+    /// like `mount_with_report()` does.
     ///
-    /// Unmounting is performed via a command call to `fusermount3 -u ..` (preferred) or `fusermount -u ..` (fallback),
-    /// which may fail on filesystems that are currently busy.
-    /// In such cases, a fallback is performed - the `sshfs` process responsible for the mount gets terminated.
-    pub fn umount(&self, definition: &FilesystemMountDefinition) -> Result<(), SftpManError> {
-        if !self.is_definition_mounted(definition)? {
-            log::info!("{0}: not mounted, nothing to do..", definition.id);
-            return Ok(());
-        }
+    /// While mounted, the definition is remembered in the runtime directory, so that it shows up in `temporary_state()`
+    /// and can be unmounted via `umount_temporary()`. Should it get unmounted some other way (e.g. by `sshfs` dying),
+    /// it lingers until `gc()` gets called.
+    pub fn mount_temporary(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<MountOutcome, MountFailureReport> {
+        // The definition is remembered before mounting, so that it can be found even if we get interrupted while mounting.
+        self.remember_temporary(definition)
+            .map_err(|err| MountFailureReport::new(&definition.id, MountStep::Preparation, err))?;
 
-        log::info!("{0}: unmounting..", definition.id);
+        // Temporary mounts are one-offs, so they're left out of the usage stats.
+        let result = self.do_mount(definition);
 
-        match self.do_umount(definition) {
-            Ok(_) => Ok(()),
+        if result.is_err() {
+            self.forget_temporary(&definition.id);
+        }
 
-            Err(err) => {
-                // It's likely that this is a "Device is busy" error.
+        result
+    }
 
-                log::warn!("{0} failed to get unmounted: {1:?}", definition.id, err);
+    /// Unmounts a temporary mount (see `mount_temporary()`) like `umount_with_kill_policy()` does, and forgets about it.
+    pub fn umount_temporary(
+        &self,
+        definition: &FilesystemMountDefinition,
+        kill_policy: KillPolicy,
+    ) -> Result<UmountOutcome, SftpManError> {
+        let outcome = self.umount_with_kill_policy(definition, kill_policy)?;
 
-                self.kill_sshfs_for_definition(definition)?;
+        self.forget_temporary(&definition.id);
 
-                // Killing successfully is good enough to unmount.
-                // We don't need to call do_umount() again, as calling `fusermount -u ..` (etc), may fail with:
-                // > CommandUnsuccessfulError("fusermount" "-u" "/home/user/mounts/storage", Output { status: ExitStatus(unix_wait_status(256)), stdout: "", stderr: "fusermount: entry for /path not found in /etc/mtab\n" })
-                // We only need to clean up now.
+        Ok(outcome)
+    }
 
-                self.clean_up_after_unmount(definition);
+    /// Forgets about temporary mounts (see `mount_temporary()`) which are no longer mounted (e.g. because `sshfs` died),
+    /// removing their leftover local mount paths.
+    ///
+    /// Returns the IDs of the temporary mounts that got forgotten.
+    pub fn gc(&self) -> Result<Vec<String>, SftpManError> {
+        let mut forgotten_ids: Vec<String> = Vec::new();
 
-                Ok(())
+        for state in self.temporary_state()? {
+            if state.mounted {
+                continue;
             }
+
+            self.clean_up_after_unmount(&state.definition);
+            self.forget_temporary(&state.definition.id);
+
+            forgotten_ids.push(state.definition.id);
         }
+
+        Ok(forgotten_ids)
     }
 
-    fn do_umount(&self, definition: &FilesystemMountDefinition) -> Result<(), SftpManError> {
-        let cmds = definition.umount_commands().unwrap();
+    fn temporary_definitions(&self) -> Result<Vec<FilesystemMountDefinition>, SftpManError> {
+        let dir_path = self.runtime_path_temporary();
 
-        for cmd in cmds {
-            log::debug!("{0}: executing unmount command: {1:?}", definition.id, cmd);
+        if !dir_path.is_dir() {
+            return Ok(vec![]);
+        }
 
-            if let Err(err) = run_command(cmd) {
-                log::error!(
-                    "{0}: failed to run unmount command: {1:?}",
-                    definition.id,
-                    err
-                );
+        let mut list: Vec<FilesystemMountDefinition> = Vec::new();
 
-                // We weren't successful to unmount, but it may be because the mount point already got unmounted.
-                // It doesn't hurt to try and clean up.
-                self.clean_up_after_unmount(definition);
+        let directory_entries =
+            fs::read_dir(&dir_path).map_err(|err| SftpManError::IO(dir_path.clone(), err))?;
 
-                return Err(err);
+        for entry in directory_entries {
+            let path = entry
+                .map_err(|err| SftpManError::IO(dir_path.clone(), err))?
+                .path();
+
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                list.push(self.definition_from_config_path(&path)?);
             }
         }
 
-        self.clean_up_after_unmount(definition);
+        list.sort_by_key(|item| item.id.clone());
 
-        Ok(())
+        Ok(list)
+    }
+
+    fn remember_temporary(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<(), SftpManError> {
+        let dir_path = self.runtime_path_temporary();
+        fs::create_dir_all(&dir_path).map_err(|err| SftpManError::IO(dir_path, err))?;
+
+        let path = self.temporary_path_for_definition_id(&definition.id);
+
+        let contents = definition
+            .to_json_string()
+            .map_err(|err| SftpManError::JSON(path.clone(), err))?;
+
+        fs::write(&path, contents).map_err(|err| SftpManError::IO(path, err))
+    }
+
+    fn forget_temporary(&self, id: &str) {
+        let path = self.temporary_path_for_definition_id(id);
+
+        if let Err(err) = fs::remove_file(&path)
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            log::warn!("{0}: failed to remove {1}: {2}", id, path.display(), err);
+        }
     }
 
     /// Unmounts the given filesystem (if mounted) and removes the configuration file for it.
     pub fn remove(&self, definition: &FilesystemMountDefinition) -> Result<(), SftpManError> {
+        let _cache_invalidation = self.state_cache.invalidation_guard();
+
         log::info!("{0}: removing..", definition.id);
 
         self.umount(definition)?;
@@ -253,9 +3011,92 @@ impl Manager {
             SftpManError::FilesystemMountDefinitionRemove(definition_config_path, err)
         })?;
 
+        // A definition created later with the same ID should not inherit the paused state (or the usage stats, or a place in the session).
+        if let Err(err) = self.resume(definition) {
+            log::warn!(
+                "{0}: failed to forget paused state: {1:?}",
+                definition.id,
+                err
+            );
+        }
+
+        if let Err(err) = self.leave_session(&definition.id) {
+            log::warn!(
+                "{0}: failed to take out of the session: {1:?}",
+                definition.id,
+                err
+            );
+        }
+
+        let usage_stats_path = self.usage_stats_path();
+        let result = load_usage_stats(&usage_stats_path).and_then(|mut all| {
+            if all.remove(&definition.id).is_some() {
+                save_usage_stats(&usage_stats_path, &all)?;
+            }
+            Ok(())
+        });
+
+        if let Err(err) = result {
+            log::warn!(
+                "{0}: failed to forget usage stats: {1:?}",
+                definition.id,
+                err
+            );
+        }
+
         Ok(())
     }
 
+    /// Reports what this build and the system it runs on support (e.g. which external programs are available),
+    /// so that frontends can hide options which can't work, instead of letting users run into errors.
+    ///
+    /// Unlike `preflight_check()`, this doesn't run any commands, so it's cheap enough to call when building a UI.
+    pub fn capabilities(&self) -> Capabilities {
+        let programs = [
+            "sshfs",
+            "ssh",
+            "ssh-add",
+            "sftp",
+            "scp",
+            "fusermount3",
+            "fusermount",
+            BANDWIDTH_LIMITER,
+            BIND_MOUNTER,
+            // Used for SOCKS proxies (see `FilesystemMountDefinition::socks_proxy`).
+            "nc",
+            "git",
+            "systemd-run",
+        ]
+        .into_iter()
+        .map(|name| (name.to_owned(), find_program(name).is_some()))
+        .collect::<BTreeMap<String, bool>>();
+
+        let fusermount = ["fusermount3", "fusermount"]
+            .into_iter()
+            .find(|name| programs.get(*name).copied().unwrap_or(false))
+            .map(str::to_owned);
+
+        let features = [
+            ("cli", cfg!(feature = "cli")),
+            ("capi", cfg!(feature = "capi")),
+            ("watch", cfg!(all(feature = "watch", target_os = "linux"))),
+            ("jsonc", cfg!(feature = "jsonc")),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name.to_owned())
+        .collect();
+
+        Capabilities {
+            platform: std::env::consts::OS.to_owned(),
+            fusermount,
+            programs,
+            security_keys: find_security_key_helper().is_some(),
+            systemd_user_manager: is_user_manager_available(),
+            features,
+        }
+    }
+
     /// Checks if we have everything needed to mount/unmount sshfs/SFTP filesystems.
     pub fn preflight_check(&self) -> Result<(), Vec<PreflightCheckError>> {
         let mut cmd_alternative_groups: Vec<Vec<Command>> = Vec::new();
@@ -264,9 +3105,27 @@ impl Manager {
         cmd_sshfs.arg("-h");
         cmd_alternative_groups.push(vec![cmd_sshfs]);
 
-        let mut cmd_ssh = Command::new("ssh");
-        cmd_ssh.arg("-V");
-        cmd_alternative_groups.push(vec![cmd_ssh]);
+        let definitions = self.definitions().unwrap_or_default();
+
+        // Each `ssh` program in use (the default one and those of definitions overriding it) gets checked.
+        let ssh_binaries: BTreeSet<&str> = std::iter::once(
+            self.config
+                .ssh_binary
+                .as_deref()
+                .unwrap_or(DEFAULT_SSH_BINARY),
+        )
+        .chain(
+            definitions
+                .iter()
+                .map(|definition| definition.effective_ssh_binary()),
+        )
+        .collect();
+
+        for binary in ssh_binaries {
+            let mut cmd_ssh = Command::new(binary);
+            cmd_ssh.arg("-V");
+            cmd_alternative_groups.push(vec![cmd_ssh]);
+        }
 
         // We favor `fusermount3`, but will also make do with `fusermount` if `fusermount3` is not available.
         // See: https://github.com/spantaleev/sftpman-rs/issues/3
@@ -311,11 +3170,95 @@ impl Manager {
                 }
             }
 
-            if !cmd_group_successful {
-                errors.extend(cmd_group_errors);
+            if !cmd_group_successful {
+                errors.extend(cmd_group_errors);
+            }
+        }
+
+        for definition in definitions
+            .iter()
+            .filter(|definition| definition.auth_type == AuthType::AuthenticationAgent)
+        {
+            if let Err(SftpManError::AgentUnavailable(id, reason)) = ensure_agent_usable(definition)
+            {
+                log::error!("{0}: the SSH agent cannot be used: {1}", id, reason);
+                errors.push(PreflightCheckError::AgentUnavailable(id, reason));
+            }
+        }
+
+        // Other automounters mounting (or unmounting) the same paths cause mysterious unmount/remount loops.
+        let foreign_mounts = find_foreign_mounts();
+        for definition in &definitions {
+            let local_mount_path = definition.local_mount_path();
+
+            for foreign_mount in foreign_mounts
+                .iter()
+                .filter(|foreign_mount| foreign_mount.conflicts_with(&local_mount_path))
+            {
+                log::error!(
+                    "{0}: {1} is also mounted automatically by {2}",
+                    definition.id,
+                    local_mount_path,
+                    foreign_mount.source
+                );
+                errors.push(PreflightCheckError::ConflictingMountManager(
+                    definition.id.clone(),
+                    foreign_mount.source.clone(),
+                ));
             }
         }
 
+        // trickle is optional, so it's only checked for when bandwidth limits are used.
+        let bandwidth_limited_ids: Vec<String> = definitions
+            .iter()
+            .filter(|definition| definition.has_bandwidth_limit())
+            .map(|definition| definition.id.clone())
+            .collect();
+
+        if !bandwidth_limited_ids.is_empty() && find_program(BANDWIDTH_LIMITER).is_none() {
+            log::error!(
+                "Some definitions ({0}) limit the bandwidth, but {1} could not be found",
+                bandwidth_limited_ids.join(", "),
+                BANDWIDTH_LIMITER
+            );
+            errors.push(PreflightCheckError::BandwidthLimiterMissing(
+                bandwidth_limited_ids,
+            ));
+        }
+
+        // bindfs is optional, so it's only checked for when extra mount paths get bind-mounted.
+        let bind_mounted_ids: Vec<String> = definitions
+            .iter()
+            .filter(|definition| !self.bind_mount_paths(definition).is_empty())
+            .map(|definition| definition.id.clone())
+            .collect();
+
+        if !bind_mounted_ids.is_empty() && find_program(BIND_MOUNTER).is_none() {
+            log::error!(
+                "Some definitions ({0}) bind-mount extra mount paths, but {1} could not be found",
+                bind_mounted_ids.join(", "),
+                BIND_MOUNTER
+            );
+            errors.push(PreflightCheckError::BindMounterMissing(bind_mounted_ids));
+        }
+
+        // Security keys only work if OpenSSH was built with support for them, so this is only checked when they're used.
+        let security_key_ids: Vec<String> = definitions
+            .into_iter()
+            .filter(|definition| definition.uses_security_key())
+            .map(|definition| definition.id)
+            .collect();
+
+        if !security_key_ids.is_empty() && find_security_key_helper().is_none() {
+            log::error!(
+                "Some definitions ({0}) use a security key, but ssh-sk-helper could not be found",
+                security_key_ids.join(", ")
+            );
+            errors.push(PreflightCheckError::SecurityKeyHelperMissing(
+                security_key_ids,
+            ));
+        }
+
         let default_mount_path = PathBuf::from(DEFAULT_MOUNT_PATH_PREFIX);
         let mut default_mount_path_ok = false;
         let random_test_path = default_mount_path.join(format!(
@@ -324,7 +3267,13 @@ impl Manager {
             rand::random::<u32>()
         ));
 
-        if default_mount_path.exists() {
+        if self.config.mount_path_template.is_some() {
+            // Definitions without a mount path of their own get mounted according to the template instead.
+            log::debug!(
+                "Not checking the default mount path {}, as a mount path template is configured",
+                DEFAULT_MOUNT_PATH_PREFIX
+            );
+        } else if default_mount_path.exists() {
             log::debug!(
                 "Default mount path {} already exists",
                 DEFAULT_MOUNT_PATH_PREFIX
@@ -388,15 +3337,113 @@ impl Manager {
         }
     }
 
+    /// Waits (up to the given timeout) for the network to become available.
+    ///
+    /// If `address` (e.g. `nas.local:22`) is provided, waits until a TCP connection to it can be established.
+    /// Otherwise, waits until a default route is present.
+    pub fn wait_for_network(
+        &self,
+        address: Option<&str>,
+        timeout: Duration,
+    ) -> Result<(), SftpManError> {
+        log::info!(
+            "Waiting (up to {0:?}) for the network to become available..",
+            timeout
+        );
+
+        wait_for_network(address, timeout)
+    }
+
+    /// Checks that the requirements of a definition (see `FilesystemMountDefinition::requires`) are met,
+    /// failing with `SftpManError::RequirementUnmet` for the first one which isn't.
+    ///
+    /// `mount()` does this too, but callers which mount many definitions (e.g. `mount_all`) can use it to skip those that can't be mounted.
+    pub fn check_requirements(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<(), SftpManError> {
+        for requirement in &definition.requires {
+            let met = match requirement {
+                MountRequirement::InterfaceUp(name) => is_interface_up(name),
+                MountRequirement::Reachable(address) => {
+                    is_address_reachable(address, CONNECT_TIMEOUT)
+                }
+            };
+
+            log::debug!(
+                "{0}: requirement {1} met: {2}",
+                definition.id,
+                requirement,
+                met
+            );
+
+            if !met {
+                return Err(SftpManError::RequirementUnmet(
+                    definition.id.clone(),
+                    requirement.unmet_description(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Persists (creates or updates) a filesystem definition.
     ///
     /// If the definition already exists, it will be unmounted before persisting and will be remounted after.
-    pub fn persist(&self, definition: &FilesystemMountDefinition) -> Result<(), SftpManError> {
-        let mut is_existing_and_mounted = false;
+    /// Failures to do so don't prevent the definition from being written, but are reported in the returned `PersistOutcome`.
+    ///
+    /// If the definition was loaded via this manager and has been changed by someone else since (see `FilesystemMountDefinition::revision`),
+    /// persisting fails with `SftpManError::ConflictingEdit`. To keep editing a definition after persisting it,
+    /// update its revision from the returned `PersistOutcome` (or load it anew).
+    pub fn persist(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<PersistOutcome, SftpManError> {
+        self.persist_with_force(definition, false)
+    }
+
+    /// Persists a filesystem definition like `persist()` does,
+    /// but lets the caller overwrite changes made by someone else in the meantime (when `force` is `true`).
+    pub fn persist_with_force(
+        &self,
+        definition: &FilesystemMountDefinition,
+        force: bool,
+    ) -> Result<PersistOutcome, SftpManError> {
+        let _cache_invalidation = self.state_cache.invalidation_guard();
+
+        if !force && let Some(revision) = definition.revision {
+            let path = self.config_path_for_definition_id(&definition.id);
+
+            let current_revision = match fs::read_to_string(&path) {
+                Ok(contents) => Some(FilesystemMountDefinition::revision_of(&contents)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                Err(err) => return Err(SftpManError::FilesystemMountDefinitionRead(path, err)),
+            };
+
+            if current_revision != Some(revision) {
+                return Err(SftpManError::ConflictingEdit(definition.id.clone()));
+            }
+        }
+
+        // Definitions get written anew, so any comments in the file can't be kept.
+        #[cfg(feature = "jsonc")]
+        if let Ok(contents) = fs::read_to_string(self.config_path_for_definition_id(&definition.id))
+            && crate::utils::jsonc::has_comments(&contents)
+        {
+            log::warn!(
+                "{0}: the definition file contains comments, which get dropped when saving it",
+                definition.id
+            );
+        }
+
+        let mut was_mounted = false;
+        let mut umount_error: Option<SftpManError> = None;
+
         if let Ok(old) = self.definition(&definition.id) {
-            is_existing_and_mounted = self.is_definition_mounted(&old)?;
+            was_mounted = self.is_definition_mounted(&old)?;
 
-            if is_existing_and_mounted {
+            if was_mounted {
                 log::debug!(
                     "{0} was found to be an existing and currently mounted definition. Unmounting..",
                     definition.id
@@ -404,6 +3451,7 @@ impl Manager {
 
                 if let Err(err) = self.umount(&old) {
                     log::error!("{0} failed to be unmounted: {1:?}", definition.id, err);
+                    umount_error = Some(err);
                 }
             }
         }
@@ -434,24 +3482,70 @@ impl Manager {
             .to_json_string()
             .map_err(|err| SftpManError::JSON(path.clone(), err))?;
 
-        fs::write(&path, serialized).map_err(|err| SftpManError::IO(path.clone(), err))?;
+        fs::write(&path, &serialized).map_err(|err| SftpManError::IO(path.clone(), err))?;
 
-        if is_existing_and_mounted {
+        let mut was_remounted = false;
+        let mut remount_error: Option<SftpManError> = None;
+
+        if was_mounted && umount_error.is_none() && !definition.enabled {
+            log::debug!(
+                "{0} is not being mounted again, because it got disabled..",
+                definition.id
+            );
+        } else if was_mounted && umount_error.is_none() {
             log::debug!(
                 "{0} is being mounted, because it was before updating..",
                 definition.id
             );
 
-            if let Err(err) = self.mount(definition) {
-                log::error!(
-                    "{0} failed get re-mounted afte rupdating: {1:?}",
-                    definition.id,
-                    err
-                );
+            match self.mount(definition) {
+                Ok(_) => was_remounted = true,
+                Err(err) => {
+                    log::error!(
+                        "{0} failed to get re-mounted after updating: {1:?}",
+                        definition.id,
+                        err
+                    );
+                    remount_error = Some(err);
+                }
             }
         }
 
-        Ok(())
+        Ok(PersistOutcome {
+            revision: FilesystemMountDefinition::revision_of(&serialized),
+            was_mounted,
+            was_remounted,
+            umount_error,
+            remount_error,
+        })
+    }
+
+    /// Synchronizes the definitions with the git remote configured in `GlobalConfig::sync_git_remote`,
+    /// so that they can be shared across machines.
+    ///
+    /// Local changes get committed, changes from the remote get merged in and the result gets pushed back.
+    /// The definitions directory is turned into a git repository on first use.
+    ///
+    /// If the same definitions were changed both locally and remotely since the last sync, nothing is merged
+    /// and `SftpManError::SyncConflict` is returned. Such conflicts can be resolved with git in the definitions directory.
+    pub fn sync(&self) -> Result<SyncOutcome, SftpManError> {
+        let remote = self
+            .config
+            .sync_git_remote
+            .as_deref()
+            .ok_or(SftpManError::SyncNotConfigured)?;
+
+        let _cache_invalidation = self.state_cache.invalidation_guard();
+
+        let dir_path = self.config_path_mounts();
+        fs::create_dir_all(&dir_path).map_err(|err| SftpManError::IO(dir_path.clone(), err))?;
+
+        sync_with_git_remote(&dir_path, remote, &self.config.sync_git_branch)
+    }
+
+    /// Returns the directory where the definitions are stored (e.g. `~/.config/sftpman/mounts`).
+    pub fn definitions_path(&self) -> PathBuf {
+        self.config_path_mounts()
     }
 
     /// Opens the directory where the given filesystem definition is mounted.
@@ -462,6 +3556,64 @@ impl Manager {
         Ok(())
     }
 
+    /// Returns the processes using the given filesystem definition's mount (having files open or their working directory within it),
+    /// which keep it busy and make unmounting it (without killing sshfs) fail.
+    ///
+    /// Sub-mounts (see `FilesystemMountDefinition::sub_mounts`) and bind-mounted extra mount paths are covered as well.
+    pub fn blocking_processes(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<Vec<BlockingProcess>, SftpManError> {
+        let mut paths = vec![definition.local_mount_path().into_path_buf()];
+        paths.extend(self.bind_mount_paths(definition));
+
+        let paths: Vec<&Path> = paths.iter().map(|path| path.as_path()).collect();
+
+        processes_using_paths(&paths)
+    }
+
+    /// Sends the given signal to the processes using the given filesystem definition's mount (see `blocking_processes()`)
+    /// and waits (up to `GlobalConfig::kill_grace_period()`) for them to exit.
+    ///
+    /// Returns whether there were any such processes.
+    fn terminate_blocking_processes(
+        &self,
+        definition: &FilesystemMountDefinition,
+        signal: TerminationSignal,
+    ) -> Result<bool, SftpManError> {
+        let processes = self.blocking_processes(definition)?;
+
+        if processes.is_empty() {
+            return Ok(false);
+        }
+
+        log::warn!(
+            "{0}: sending {1} to the processes using it: {2}",
+            definition.id,
+            signal,
+            processes
+                .iter()
+                .map(|process| process.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+
+        let pids: Vec<i32> = processes.iter().map(|process| process.pid).collect();
+
+        let remaining_pids = terminate_processes(&pids, signal, self.config.kill_grace_period())?;
+
+        if !remaining_pids.is_empty() {
+            log::warn!(
+                "{0}: processes still running after {1}: {2:?}",
+                definition.id,
+                signal,
+                remaining_pids
+            );
+        }
+
+        Ok(true)
+    }
+
     fn kill_sshfs_for_definition(
         &self,
         definition: &FilesystemMountDefinition,
@@ -481,7 +3633,7 @@ impl Manager {
                     pid
                 );
 
-                ensure_process_killed(pid, Duration::from_millis(500), Duration::from_millis(2000))
+                ensure_process_killed(pid, self.config.kill_grace_period())
             }
 
             None => Err(SftpManError::Generic(format!(
@@ -491,27 +3643,160 @@ impl Manager {
         }
     }
 
-    fn clean_up_after_unmount(&self, definition: &FilesystemMountDefinition) {
+    /// Creates the local mount point (and its parents) for the given definition, if missing.
+    ///
+    /// The directories which get created are remembered in the state directory,
+    /// so that `clean_up_after_unmount()` can remove them (and only them) again.
+    pub(crate) fn create_mount_directory(
+        &self,
+        definition: &FilesystemMountDefinition,
+    ) -> Result<(), SftpManError> {
+        let created = ensure_directory_recursively_created(
+            &definition.local_mount_path(),
+            self.config.mount_dir_mode,
+        )?;
+
+        if created.is_empty() {
+            return Ok(());
+        }
+
+        let path = self.created_directories_path();
+
+        let result = load_created_directories(&path).and_then(|mut all| {
+            let list = all.entry(definition.id.clone()).or_default();
+
+            for dir in created {
+                if !list.contains(&dir) {
+                    list.push(dir);
+                }
+            }
+
+            // Parents are kept first, so that removal (in reverse) starts with the deepest directories.
+            list.sort_by_key(|dir| dir.components().count());
+
+            save_created_directories(&path, &all)
+        });
+
+        if let Err(err) = result {
+            log::warn!(
+                "{0}: failed to remember the created mount directories, so they won't be cleaned up: {1:?}",
+                definition.id,
+                err
+            );
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn clean_up_after_unmount(&self, definition: &FilesystemMountDefinition) {
         log::debug!("{0}: cleaning up after unmounting", definition.id);
 
-        if let Err(err) = remove_empty_directory(&definition.local_mount_path()) {
+        let local_mount_path = definition.local_mount_path();
+
+        if let Err(err) = remove_empty_directory(&local_mount_path) {
             log::debug!(
                 "{0}: failed to remove local mount point: {1:?}",
                 definition.id,
                 err
             );
+            return;
+        }
+
+        let path = self.created_directories_path();
+
+        let mut all = match load_created_directories(&path) {
+            Ok(all) => all,
+            Err(err) => {
+                log::debug!(
+                    "{0}: failed to load the created mount directories: {1:?}",
+                    definition.id,
+                    err
+                );
+                return;
+            }
+        };
+
+        let Some(created) = all.remove(&definition.id) else {
+            return;
+        };
+
+        // Only parents which sftpman created are removed (deepest first), stopping at the first one still in use.
+        for dir in created.iter().rev() {
+            if !local_mount_path.starts_with(dir) || dir == local_mount_path.as_path() {
+                continue;
+            }
+
+            if let Err(err) = remove_empty_directory(dir) {
+                log::debug!(
+                    "{0}: not removing created directory {1}: {2:?}",
+                    definition.id,
+                    dir.display(),
+                    err
+                );
+                break;
+            }
+        }
+
+        if let Err(err) = save_created_directories(&path, &all) {
+            log::debug!(
+                "{0}: failed to forget the created mount directories: {1:?}",
+                definition.id,
+                err
+            );
         }
     }
 
+    fn created_directories_path(&self) -> PathBuf {
+        self.paths.state_path.join("created_directories.json")
+    }
+
+    fn config_path_global(&self) -> PathBuf {
+        self.paths.config_path.join(GLOBAL_CONFIG_FILE_NAME)
+    }
+
     fn config_path_mounts(&self) -> PathBuf {
-        self.config_path.join("mounts")
+        self.paths.config_path.join("mounts")
     }
 
     fn config_path_for_definition_id(&self, id: &str) -> PathBuf {
         self.config_path_mounts().join(format!("{0}.json", id))
     }
 
+    fn runtime_path_temporary(&self) -> PathBuf {
+        self.paths.runtime_path.join("temporary")
+    }
+
+    fn temporary_path_for_definition_id(&self, id: &str) -> PathBuf {
+        self.runtime_path_temporary().join(format!("{0}.json", id))
+    }
+
+    fn global_config_from_config_path(path: &PathBuf) -> Result<GlobalConfig, ManagerInitError> {
+        if !path.exists() {
+            log::debug!(
+                "Global config file {0} doesn't exist. Using the default configuration ...",
+                path.display()
+            );
+            return Ok(GlobalConfig::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .map_err(|err| ManagerInitError::GlobalConfigRead(path.clone(), err))?;
+
+        GlobalConfig::from_json_string(&contents)
+            .map_err(|err| ManagerInitError::GlobalConfigParse(path.clone(), err))
+    }
+
+    /// Sets the defaults which come from the global config (e.g. `mount_path_template`) on a definition,
+    /// as is done for the definitions loaded via the manager.
+    ///
+    /// This is for definitions coming from elsewhere (e.g. `FilesystemMountDefinition::from_url()`).
+    pub fn apply_global_defaults(&self, definition: &mut FilesystemMountDefinition) {
+        definition.mount_path_template = self.config.mount_path_template.clone();
+        definition.default_ssh_binary = self.config.ssh_binary.clone();
+    }
+
     fn definition_from_config_path(
+        &self,
         path: &PathBuf,
     ) -> Result<FilesystemMountDefinition, SftpManError> {
         let contents = fs::read_to_string(path)
@@ -520,8 +3805,232 @@ impl Manager {
         let mount_config_result = FilesystemMountDefinition::from_json_string(&contents);
 
         match mount_config_result {
-            Ok(cfg) => Ok(cfg),
+            Ok(mut cfg) => {
+                cfg.revision = Some(FilesystemMountDefinition::revision_of(&contents));
+                self.apply_global_defaults(&mut cfg);
+                Ok(cfg)
+            }
             Err(err) => Err(SftpManError::JSON(path.clone(), err)),
         }
     }
 }
+
+/// Returns the directories of the default profile (see `Manager::for_profile()`), as determined by the operating system.
+fn project_dirs() -> Result<directories::ProjectDirs, ManagerInitError> {
+    directories::ProjectDirs::from("sftpman", "Devture Ltd", "sftpman")
+        .ok_or(ManagerInitError::NoConfigDirectory)
+}
+
+/// Returns where the given profile's files are, under the given directory of the default profile
+/// (e.g. `~/.config/sftpman/profiles/work` for `~/.config/sftpman`).
+fn profile_path(base_path: &Path, profile: &str) -> PathBuf {
+    base_path.join(PROFILES_DIR_NAME).join(profile)
+}
+
+/// Profile names become directory names, so they're restricted like definition IDs are.
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// Loads the name of the profile switched to (see `Manager::switch_profile()`) from the given config directory of the default profile.
+fn load_switched_profile(base_config_path: &Path) -> String {
+    let path = base_config_path.join(SWITCHED_PROFILE_FILE_NAME);
+
+    match fs::read_to_string(&path) {
+        Ok(contents) if !contents.trim().is_empty() => contents.trim().to_owned(),
+        Ok(_) => DEFAULT_PROFILE.to_owned(),
+        Err(err) => {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                log::warn!(
+                    "Failed to read the switched-to profile from {0}, using the default one: {1}",
+                    path.display(),
+                    err
+                );
+            }
+
+            DEFAULT_PROFILE.to_owned()
+        }
+    }
+}
+
+/// Returns the mount backend selected via the `SFTPMAN_MOUNT_BACKEND` environment variable, falling back to the default one.
+fn mount_backend_from_env() -> MountBackend {
+    let Ok(value) = std::env::var(MOUNT_BACKEND_ENV_VAR) else {
+        return MountBackend::default();
+    };
+
+    MountBackend::from_string(&value).unwrap_or_else(|_| {
+        log::warn!(
+            "Ignoring {0}={1}, as it's neither sshfs nor simulate",
+            MOUNT_BACKEND_ENV_VAR,
+            value
+        );
+        MountBackend::default()
+    })
+}
+
+/// Turns a name (e.g. of an rclone remote) into a valid definition ID, by replacing disallowed characters (e.g. spaces) with dashes.
+fn sanitize_id(name: &str) -> String {
+    name.replace(
+        |c: char| !c.is_alphanumeric() && c != '_' && c != '-' && c != '.',
+        "-",
+    )
+}
+
+/// Resolves a remote path given for copying (see `Manager::download()`) against the definition's remote path.
+fn resolve_remote_path(definition: &FilesystemMountDefinition, path: &str) -> String {
+    if path.starts_with('/') {
+        path.to_owned()
+    } else if path.is_empty() {
+        definition.remote_path.clone()
+    } else {
+        join_remote_path(&definition.remote_path, path)
+    }
+}
+
+/// Checks a single definition (stored at the given path) for problems which don't concern other definitions.
+fn check_definition(path: &Path, definition: &FilesystemMountDefinition) -> Vec<DefinitionProblem> {
+    let mut problems: Vec<DefinitionProblem> = Vec::new();
+
+    if let Some(file_id) = path.file_stem().map(|stem| stem.to_string_lossy())
+        && file_id != definition.id
+    {
+        problems.push(DefinitionProblem::IdMismatch {
+            file_id: file_id.to_string(),
+        });
+    }
+
+    if let Err(errors) = definition.validate() {
+        problems.push(DefinitionProblem::Invalid(FieldValidationError::list_from(
+            &errors,
+        )));
+    }
+
+    if definition.auth_type == AuthType::PublicKey {
+        for key in definition.ssh_keys() {
+            if !Path::new(key).is_file() {
+                problems.push(DefinitionProblem::MissingSshKey(key.to_owned()));
+            }
+        }
+    }
+
+    if let Some(certificate_file) = &definition.certificate_file {
+        problems.extend(check_certificate_file(
+            certificate_file,
+            &definition.ssh_key,
+        ));
+    }
+
+    if let Some(password_file) = &definition.password_file
+        && !Path::new(password_file).is_file()
+    {
+        problems.push(DefinitionProblem::MissingPasswordFile(
+            password_file.clone(),
+        ));
+    }
+
+    problems.extend(
+        check_mount_options(&definition.mount_options)
+            .into_iter()
+            .map(DefinitionProblem::MountOption),
+    );
+
+    problems
+}
+
+/// Checks that the certificate file exists and (if the key's public part is next to it, e.g. `id_ed25519.pub`) that it was issued for the key.
+fn check_certificate_file(certificate_file: &str, ssh_key: &str) -> Option<DefinitionProblem> {
+    if !Path::new(certificate_file).is_file() {
+        return Some(DefinitionProblem::MissingCertificateFile(
+            certificate_file.to_owned(),
+        ));
+    }
+
+    let certificate = fs::read_to_string(certificate_file).ok()?;
+    let public_key = fs::read_to_string(format!("{0}.pub", ssh_key)).ok()?;
+
+    match is_certificate_for_public_key(&certificate, &public_key) {
+        Some(false) => Some(DefinitionProblem::CertificateKeyMismatch {
+            certificate: certificate_file.to_owned(),
+            ssh_key: ssh_key.to_owned(),
+        }),
+        _ => None,
+    }
+}
+
+/// Tells whether both definitions point to the same remote path (on the same host and port).
+fn have_same_remote(a: &FilesystemMountDefinition, b: &FilesystemMountDefinition) -> bool {
+    a.host == b.host
+        && a.port == b.port
+        && normalize_remote_path(&a.remote_path) == normalize_remote_path(&b.remote_path)
+}
+
+/// Strips trailing slashes from a remote path (e.g. `/srv/http/` -> `/srv/http`), so that equivalent paths can be compared.
+fn normalize_remote_path(path: &str) -> &str {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() { path } else { trimmed }
+}
+
+/// Creates the given directory (and its parents) if missing, making it accessible only to the current user (like `~/.ssh` is).
+fn ensure_private_directory(path: &Path) -> Result<(), SftpManError> {
+    if path.exists() {
+        return Ok(());
+    }
+
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+
+    builder
+        .create(path)
+        .map_err(|err| SftpManError::IO(path.to_path_buf(), err))
+}
+
+/// Reads a file which may not exist (in which case, the contents are considered empty).
+fn read_optional_file(path: &Path) -> Result<String, SftpManError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok("".to_owned()),
+        Err(err) => Err(SftpManError::IO(path.to_path_buf(), err)),
+    }
+}
+
+/// Turns an unsuccessful `sshfs` command into a `SftpManError::MountFailure`, if the reason for the failure can be recognized.
+fn classify_mount_failure(cmd: Command, output: std::process::Output) -> SftpManError {
+    match MountFailureKind::from_stderr(&String::from_utf8_lossy(&output.stderr)) {
+        Some(kind) => SftpManError::MountFailure(kind, cmd, output),
+        None => SftpManError::CommandUnsuccessful(cmd, output),
+    }
+}
+
+/// Tells what went wrong when verifying a definition (see `Manager::verify()`), based on the error output (stderr) of `sftp`.
+fn classify_verification_failure(stderr: &str) -> VerificationOutcome {
+    // The first line usually tells the cause (e.g. `Permission denied (publickey).`), followed by `Connection closed`.
+    let details = stderr
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or("sftp failed without reporting an error")
+        .to_owned();
+
+    // These come from sftp itself (as opposed to ssh), so the connection and authentication worked.
+    if stderr.contains("Couldn't canonicalize") || stderr.contains("Can't change directory") {
+        return VerificationOutcome::PathMissing(details);
+    }
+    if stderr.contains("Couldn't read directory") || stderr.contains("readdir(") {
+        return VerificationOutcome::PathUnreadable(details);
+    }
+
+    match MountFailureKind::from_stderr(stderr) {
+        Some(
+            MountFailureKind::AuthenticationFailed | MountFailureKind::HostKeyVerificationFailed,
+        ) => VerificationOutcome::AuthFailed(details),
+        Some(MountFailureKind::RemotePathNotFound) => VerificationOutcome::PathMissing(details),
+        _ => VerificationOutcome::Unreachable(details),
+    }
+}
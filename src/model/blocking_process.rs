@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+/// A process using a mounted filesystem (see `Manager::blocking_processes()`), which keeps it busy and prevents it from being unmounted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockingProcess {
+    pub pid: i32,
+
+    /// The command line the process runs (e.g. `vim notes.txt`), or its name if the command line cannot be read.
+    pub command: String,
+
+    /// The paths (within the mount) which the process has open, including its working directory if it's within the mount.
+    pub paths: Vec<PathBuf>,
+}
+
+impl std::fmt::Display for BlockingProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{0} (PID {1})", self.command, self.pid)
+    }
+}
@@ -0,0 +1,69 @@
+use serde::Serialize;
+
+/// A definition which was skipped or failed during an operation on multiple definitions, along with why (see `BulkSummary`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BulkSummaryEntry {
+    pub id: String,
+
+    /// Why the definition was skipped (e.g. `interface wg0 is down`) or what went wrong.
+    pub reason: String,
+}
+
+/// What an operation on multiple definitions (e.g. `Manager::mount_many()`) did, to be reported once it's over.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BulkSummary {
+    /// The IDs of the definitions which got worked on successfully (e.g. got mounted).
+    pub succeeded: Vec<String>,
+
+    /// The IDs of the definitions which needed nothing done (e.g. because they were mounted already).
+    pub unchanged: Vec<String>,
+
+    /// The definitions which were not worked on (e.g. because their requirements are not met).
+    pub skipped: Vec<BulkSummaryEntry>,
+
+    /// The definitions which failed.
+    pub failed: Vec<BulkSummaryEntry>,
+}
+
+impl BulkSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tells whether nothing failed.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Returns the number of definitions covered.
+    pub fn total(&self) -> usize {
+        self.succeeded.len() + self.unchanged.len() + self.skipped.len() + self.failed.len()
+    }
+
+    pub(crate) fn push_skipped(&mut self, id: &str, reason: &dyn std::fmt::Display) {
+        self.skipped.push(BulkSummaryEntry {
+            id: id.to_owned(),
+            reason: reason.to_string(),
+        });
+    }
+
+    pub(crate) fn push_failed(&mut self, id: &str, reason: &dyn std::fmt::Display) {
+        self.failed.push(BulkSummaryEntry {
+            id: id.to_owned(),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+impl std::fmt::Display for BulkSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{0} succeeded, {1} failed, {2} skipped, {3} unchanged",
+            self.succeeded.len(),
+            self.failed.len(),
+            self.skipped.len(),
+            self.unchanged.len()
+        )
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// What this build of sftpman (and the system it runs on) supports (see `Manager::capabilities()`).
+///
+/// Frontends can use this to hide options which can't work (e.g. bandwidth limits without `trickle`),
+/// instead of letting users run into errors when mounting.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    /// The operating system (e.g. `linux`), as reported by `std::env::consts::OS`.
+    pub platform: String,
+
+    /// The `fusermount` flavor found (`fusermount3` or `fusermount`), if any.
+    /// `fusermount` means that only the old FUSE version (2) is available.
+    pub fusermount: Option<String>,
+
+    /// The external programs that sftpman can make use of (e.g. `sshfs`, `trickle`), telling whether each one was found on `PATH`.
+    pub programs: BTreeMap<String, bool>,
+
+    /// Tells whether FIDO2 security keys can be used (`ssh-sk-helper` was found).
+    #[serde(rename = "securityKeys")]
+    pub security_keys: bool,
+
+    /// Tells whether a systemd user manager is running, which `GlobalConfig::use_systemd_scope` needs.
+    #[serde(rename = "systemdUserManager")]
+    pub systemd_user_manager: bool,
+
+    /// The optional features (e.g. `watch`) this build was compiled with.
+    pub features: Vec<String>,
+}
+
+impl Capabilities {
+    /// Tells whether the given program (e.g. `trickle`) was found.
+    pub fn has_program(&self, name: &str) -> bool {
+        self.programs.get(name).copied().unwrap_or(false)
+    }
+
+    /// Tells whether this build was compiled with the given feature (e.g. `watch`).
+    pub fn has_feature(&self, name: &str) -> bool {
+        self.features.iter().any(|feature| feature == name)
+    }
+}
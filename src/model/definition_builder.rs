@@ -0,0 +1,223 @@
+use validator::{Validate, ValidationErrors};
+
+use crate::auth_type::AuthType;
+
+use super::{
+    ExtraMountPathMethod, FilesystemMountDefinition, IoniceClass, MountRequirement, SubMount,
+};
+
+/// Builds a `FilesystemMountDefinition` (see `FilesystemMountDefinition::builder()`).
+///
+/// The fields every definition needs are asked for upfront, while the rest start out with their defaults
+/// (see `FilesystemMountDefinition::default()`) and can be set via the `with_*` methods.
+#[derive(Debug, Clone)]
+pub struct FilesystemMountDefinitionBuilder {
+    definition: FilesystemMountDefinition,
+}
+
+impl FilesystemMountDefinitionBuilder {
+    pub(crate) fn new(id: &str, host: &str, user: &str, remote_path: &str) -> Self {
+        Self {
+            definition: FilesystemMountDefinition {
+                id: id.to_owned(),
+                host: host.to_owned(),
+                user: user.to_owned(),
+                remote_path: remote_path.to_owned(),
+                ..FilesystemMountDefinition::default()
+            },
+        }
+    }
+
+    /// Sets a human-friendly name for the filesystem (e.g. `Company NAS`).
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.definition.description = Some(description.to_owned());
+        self
+    }
+
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.definition.port = port;
+        self
+    }
+
+    /// Adds an option to pass to `sshfs` (e.g. `follow_symlinks`). Can be called multiple times.
+    pub fn with_mount_option(mut self, option: &str) -> Self {
+        self.definition.mount_options.push(option.to_owned());
+        self
+    }
+
+    pub fn with_mount_dest_path(mut self, path: &str) -> Self {
+        self.definition.mount_dest_path = Some(path.to_owned());
+        self
+    }
+
+    /// Adds a remote path to mount into a subdirectory of the local mount path. Can be called multiple times.
+    pub fn with_sub_mount(mut self, sub_mount: SubMount) -> Self {
+        self.definition.sub_mounts.push(sub_mount);
+        self
+    }
+
+    /// Adds a local path to expose the filesystem at once mounted (e.g. `~/projects/website/uploads`). Can be called multiple times.
+    pub fn with_extra_mount_path(mut self, path: &str) -> Self {
+        self.definition.extra_mount_paths.push(path.to_owned());
+        self
+    }
+
+    pub fn with_extra_mount_path_method(mut self, method: ExtraMountPathMethod) -> Self {
+        self.definition.extra_mount_path_method = method;
+        self
+    }
+
+    pub fn with_allow_non_empty_mount_path(mut self, allow: bool) -> Self {
+        self.definition.allow_non_empty_mount_path = allow;
+        self
+    }
+
+    pub fn with_cmd_before_mount(mut self, command: &str) -> Self {
+        self.definition.cmd_before_mount = command.to_owned();
+        self
+    }
+
+    pub fn with_auth_type(mut self, auth_type: AuthType) -> Self {
+        self.definition.auth_type = auth_type;
+        self
+    }
+
+    pub fn with_ssh_key(mut self, path: &str) -> Self {
+        self.definition.ssh_key = path.to_owned();
+        self
+    }
+
+    /// Adds an SSH key to try after `ssh_key`. Can be called multiple times.
+    pub fn with_fallback_ssh_key(mut self, path: &str) -> Self {
+        self.definition.fallback_ssh_keys.push(path.to_owned());
+        self
+    }
+
+    /// Sets the OpenSSH certificate (e.g. `/home/user/.ssh/id_ed25519-cert.pub`) issued for `ssh_key` by an SSH certificate authority.
+    pub fn with_certificate_file(mut self, path: &str) -> Self {
+        self.definition.certificate_file = Some(path.to_owned());
+        self
+    }
+
+    pub fn with_known_hosts_file(mut self, path: &str) -> Self {
+        self.definition.known_hosts_file = Some(path.to_owned());
+        self
+    }
+
+    pub fn with_proxy_command(mut self, command: &str) -> Self {
+        self.definition.proxy_command = Some(command.to_owned());
+        self
+    }
+
+    pub fn with_socks_proxy(mut self, address: &str) -> Self {
+        self.definition.socks_proxy = Some(address.to_owned());
+        self
+    }
+
+    pub fn with_skip_host_resolution(mut self, skip: bool) -> Self {
+        self.definition.skip_host_resolution = skip;
+        self
+    }
+
+    /// Adds a cipher for `ssh` to use (e.g. `aes128-gcm@openssh.com`). Can be called multiple times, in order of preference.
+    pub fn with_cipher(mut self, cipher: &str) -> Self {
+        self.definition.ciphers.push(cipher.to_owned());
+        self
+    }
+
+    /// Sets the `ssh` program to connect with (e.g. `/opt/openssh/bin/ssh`), instead of the `ssh` found in `PATH`.
+    pub fn with_ssh_binary(mut self, binary: &str) -> Self {
+        self.definition.ssh_binary = Some(binary.to_owned());
+        self
+    }
+
+    pub fn with_password_command(mut self, command: &str) -> Self {
+        self.definition.password_command = Some(command.to_owned());
+        self
+    }
+
+    pub fn with_password_env(mut self, name: &str) -> Self {
+        self.definition.password_env = Some(name.to_owned());
+        self
+    }
+
+    pub fn with_password_file(mut self, path: &str) -> Self {
+        self.definition.password_file = Some(path.to_owned());
+        self
+    }
+
+    pub fn with_gssapi_delegate_credentials(mut self, delegate: bool) -> Self {
+        self.definition.gssapi_delegate_credentials = delegate;
+        self
+    }
+
+    pub fn with_gssapi_key_exchange(mut self, key_exchange: bool) -> Self {
+        self.definition.gssapi_key_exchange = key_exchange;
+        self
+    }
+
+    /// Sets an environment variable for `sshfs` (e.g. `SSH_AUTH_SOCK`). Can be called multiple times.
+    pub fn with_env(mut self, name: &str, value: &str) -> Self {
+        self.definition
+            .env
+            .insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    pub fn with_nice(mut self, nice: i32) -> Self {
+        self.definition.nice = Some(nice);
+        self
+    }
+
+    /// Sets the I/O scheduling class, along with the priority within it (only applies to `IoniceClass::BestEffort`).
+    pub fn with_ionice(mut self, class: IoniceClass, level: Option<u8>) -> Self {
+        self.definition.ionice_class = Some(class);
+        self.definition.ionice_level = level;
+        self
+    }
+
+    pub fn with_download_limit(mut self, kib_per_second: u32) -> Self {
+        self.definition.download_limit = Some(kib_per_second);
+        self
+    }
+
+    pub fn with_upload_limit(mut self, kib_per_second: u32) -> Self {
+        self.definition.upload_limit = Some(kib_per_second);
+        self
+    }
+
+    pub fn with_debug_sshfs(mut self, debug: bool) -> Self {
+        self.definition.debug_sshfs = debug;
+        self
+    }
+
+    pub fn with_idle_unmount_minutes(mut self, minutes: u32) -> Self {
+        self.definition.idle_unmount_minutes = Some(minutes);
+        self
+    }
+
+    pub fn with_auto_mount(mut self, auto_mount: bool) -> Self {
+        self.definition.auto_mount = auto_mount;
+        self
+    }
+
+    /// Adds a condition which needs to be met for mounting. Can be called multiple times.
+    pub fn with_requirement(mut self, requirement: MountRequirement) -> Self {
+        self.definition.requires.push(requirement);
+        self
+    }
+
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.definition.enabled = enabled;
+        self
+    }
+
+    /// Validates the definition (see `FilesystemMountDefinition::validate()`) and returns it, if valid.
+    ///
+    /// `FieldValidationError::list_from()` turns the errors into a list suitable for showing to users.
+    pub fn build(self) -> Result<FilesystemMountDefinition, ValidationErrors> {
+        self.definition.validate()?;
+
+        Ok(self.definition)
+    }
+}
@@ -0,0 +1,123 @@
+use std::path::PathBuf;
+
+use crate::mount_options::MountOptionWarning;
+
+use super::field_validation_error::FieldValidationError;
+
+/// The result of checking a stored definition file (see `Manager::check_definitions()`).
+#[derive(Debug, Clone)]
+pub struct DefinitionCheck {
+    /// The path to the definition file (e.g. `~/.config/sftpman/mounts/my-fs.json`).
+    pub path: PathBuf,
+
+    /// The ID of the definition, if the file could be parsed.
+    pub id: Option<String>,
+
+    /// The problems found with the definition (empty if there are none).
+    pub problems: Vec<DefinitionProblem>,
+}
+
+impl DefinitionCheck {
+    /// Tells whether any of the problems is an error (see `DefinitionProblem::is_error()`).
+    pub fn has_errors(&self) -> bool {
+        self.problems.iter().any(|problem| problem.is_error())
+    }
+}
+
+/// A problem found with a stored definition (see `DefinitionCheck`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefinitionProblem {
+    /// The file could not be read.
+    Unreadable(String),
+
+    /// The file could not be parsed as a definition.
+    Unparseable(String),
+
+    /// The file name (e.g. `my-fs.json`) does not match the definition's ID, so the definition can't be found by its ID.
+    IdMismatch { file_id: String },
+
+    /// The definition does not pass validation (see `FilesystemMountDefinition::validate()`).
+    Invalid(Vec<FieldValidationError>),
+
+    /// An SSH private key (see `FilesystemMountDefinition::ssh_key`) does not exist.
+    MissingSshKey(String),
+
+    /// The certificate file (see `FilesystemMountDefinition::certificate_file`) does not exist.
+    MissingCertificateFile(String),
+
+    /// The certificate (see `FilesystemMountDefinition::certificate_file`) was not issued for the SSH key,
+    /// as determined by comparing it to the key's public part (e.g. `id_ed25519.pub`), so `ssh` won't use it.
+    CertificateKeyMismatch {
+        certificate: String,
+        ssh_key: String,
+    },
+
+    /// The password file (see `FilesystemMountDefinition::password_file`) does not exist.
+    MissingPasswordFile(String),
+
+    /// Other definitions (whose IDs are contained) are mounted at the same local path.
+    SameMountPath(Vec<String>),
+
+    /// Other definitions (whose IDs are contained) point to the same remote path (on the same host and port).
+    SameRemote(Vec<String>),
+
+    /// A mount option is likely a mistake (e.g. unknown).
+    MountOption(MountOptionWarning),
+}
+
+impl DefinitionProblem {
+    /// Tells whether the problem prevents the definition from working (as opposed to likely being a mistake).
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Self::SameRemote(_) | Self::MountOption(_))
+    }
+}
+
+impl std::fmt::Display for DefinitionProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unreadable(err) => write!(f, "The file could not be read: {0}", err),
+            Self::Unparseable(err) => write!(f, "The file could not be parsed: {0}", err),
+            Self::IdMismatch { file_id } => write!(
+                f,
+                "The file name does not match the ID, so it can't be found as {0}",
+                file_id
+            ),
+            Self::Invalid(errors) => {
+                let messages: Vec<String> = errors
+                    .iter()
+                    .map(|err| {
+                        err.message
+                            .clone()
+                            .unwrap_or_else(|| format!("{0}: {1}", err.field, err.code))
+                    })
+                    .collect();
+
+                write!(f, "Validation failed: {0}", messages.join(" "))
+            }
+            Self::MissingSshKey(path) => write!(f, "The SSH key {0} does not exist", path),
+            Self::MissingCertificateFile(path) => {
+                write!(f, "The certificate file {0} does not exist", path)
+            }
+            Self::CertificateKeyMismatch {
+                certificate,
+                ssh_key,
+            } => write!(
+                f,
+                "The certificate {0} was not issued for the SSH key {1}",
+                certificate, ssh_key
+            ),
+            Self::MissingPasswordFile(path) => {
+                write!(f, "The password file {0} does not exist", path)
+            }
+            Self::SameMountPath(ids) => write!(
+                f,
+                "The local mount path is also used by: {0}",
+                ids.join(", ")
+            ),
+            Self::SameRemote(ids) => {
+                write!(f, "The remote path is also used by: {0}", ids.join(", "))
+            }
+            Self::MountOption(warning) => write!(f, "{0}", warning),
+        }
+    }
+}
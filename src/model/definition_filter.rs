@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use crate::auth_type::AuthType;
+
+use super::mount_state::MountState;
+
+/// Criteria for picking definitions (see `Manager::definitions_filtered()`).
+///
+/// Criteria which are `None` match all definitions. A definition needs to match all the other criteria to be picked.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DefinitionFilter {
+    /// Text to look for (case-insensitively) in the ID, host, user or remote path (e.g. for search boxes).
+    pub text: Option<String>,
+
+    /// The host (e.g. `example.com`) which definitions need to connect to (compared case-insensitively).
+    pub host: Option<String>,
+
+    /// The authentication type which definitions need to use.
+    pub auth_type: Option<AuthType>,
+
+    /// Whether definitions need to be mounted (`true`) or unmounted (`false`).
+    pub mounted: Option<bool>,
+
+    /// Whether definitions need to be enabled (`true`) or disabled (`false`).
+    pub enabled: Option<bool>,
+
+    /// A directory (e.g. `/mnt/sshfs/work`) which the local mount path of definitions needs to be in (or be equal to).
+    ///
+    /// Paths are compared component-wise, so `/mnt/sshfs/work` does not match `/mnt/sshfs/workshop`.
+    pub mount_path_prefix: Option<PathBuf>,
+}
+
+impl DefinitionFilter {
+    /// Creates a filter which matches all definitions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = Some(text.to_owned());
+        self
+    }
+
+    pub fn with_host(mut self, host: &str) -> Self {
+        self.host = Some(host.to_owned());
+        self
+    }
+
+    pub fn with_auth_type(mut self, auth_type: AuthType) -> Self {
+        self.auth_type = Some(auth_type);
+        self
+    }
+
+    pub fn with_mounted(mut self, mounted: bool) -> Self {
+        self.mounted = Some(mounted);
+        self
+    }
+
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    pub fn with_mount_path_prefix(mut self, prefix: &Path) -> Self {
+        self.mount_path_prefix = Some(prefix.to_path_buf());
+        self
+    }
+
+    /// Tells if the given definition (and its mount status) matches all criteria.
+    pub fn matches(&self, state: &MountState) -> bool {
+        let definition = &state.definition;
+
+        if let Some(text) = &self.text {
+            let text = text.to_lowercase();
+
+            let found = [
+                &definition.id,
+                &definition.host,
+                &definition.user,
+                &definition.remote_path,
+            ]
+            .iter()
+            .any(|value| value.to_lowercase().contains(&text));
+
+            if !found {
+                return false;
+            }
+        }
+
+        if let Some(host) = &self.host
+            && !definition.host.eq_ignore_ascii_case(host)
+        {
+            return false;
+        }
+
+        if let Some(auth_type) = &self.auth_type
+            && definition.auth_type != *auth_type
+        {
+            return false;
+        }
+
+        if let Some(mounted) = self.mounted
+            && state.mounted != mounted
+        {
+            return false;
+        }
+
+        if let Some(enabled) = self.enabled
+            && definition.enabled != enabled
+        {
+            return false;
+        }
+
+        if let Some(prefix) = &self.mount_path_prefix
+            && !definition.local_mount_path().starts_with(prefix)
+        {
+            return false;
+        }
+
+        true
+    }
+}
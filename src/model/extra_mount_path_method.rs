@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// How a mounted definition gets exposed at its extra mount paths (see `FilesystemMountDefinition::extra_mount_paths`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ExtraMountPathMethod {
+    /// A symlink pointing to the local mount path.
+    ///
+    /// This needs nothing extra, but tools resolving symlinks end up seeing (and working with) the local mount path.
+    #[default]
+    #[serde(rename = "symlink")]
+    Symlink,
+
+    /// A bind mount of the local mount path, made via `bindfs` (a FUSE filesystem, so no elevated privileges are needed).
+    #[serde(rename = "bind")]
+    Bind,
+}
+
+impl ExtraMountPathMethod {
+    pub fn to_static_str(&self) -> &'static str {
+        match &self {
+            Self::Symlink => "symlink",
+            Self::Bind => "bind",
+        }
+    }
+
+    pub fn from_string(s: &str) -> Result<Self, &'static str> {
+        match s {
+            "symlink" => Ok(Self::Symlink),
+            "bind" => Ok(Self::Bind),
+            _ => Err("Unexpected string value"),
+        }
+    }
+}
+
+impl std::fmt::Display for ExtraMountPathMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{0}", self.to_static_str())
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use validator::ValidationErrors;
+
+use crate::i18n::{self, Language};
+
+/// A machine-readable validation failure for a single field,
+/// so that frontends can highlight the offending input and localize the message themselves.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldValidationError {
+    /// The name of the offending field (e.g. `remote_path`).
+    ///
+    /// Failures which concern multiple fields (e.g. a missing SSH key for `publickey` authentication) are reported under `__all__`.
+    pub field: String,
+
+    /// A stable code identifying the failure (e.g. `length`, `not_absolute_path`, `invalid_id`, `no_ssh_key_for_publickey_auth`).
+    pub code: String,
+
+    /// Parameters describing the failure (e.g. `min` and `value` for `length`).
+    pub params: BTreeMap<String, serde_json::Value>,
+
+    /// A human-readable (English) description of the failure, if one is available.
+    /// See `localized_message()` for obtaining it in another language.
+    pub message: Option<String>,
+}
+
+impl FieldValidationError {
+    /// Flattens the errors returned by `FilesystemMountDefinition::validate()` into a list (ordered by field name).
+    pub fn list_from(errors: &ValidationErrors) -> Vec<Self> {
+        let mut list: Vec<Self> = Vec::new();
+
+        for (field_name, field_errors) in errors.field_errors() {
+            for field_error in field_errors {
+                list.push(Self {
+                    field: field_name.to_string(),
+                    code: field_error.code.to_string(),
+                    params: field_error
+                        .params
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.clone()))
+                        .collect(),
+                    message: field_error
+                        .message
+                        .as_ref()
+                        .map(|message| message.to_string()),
+                });
+            }
+        }
+
+        // `field_errors()` is backed by a hash map, so its order is not stable.
+        list.sort_by(|a, b| a.field.cmp(&b.field));
+
+        list
+    }
+
+    /// Returns the human-readable description of the failure in the given language,
+    /// falling back to the English `message` when there is no translation.
+    pub fn localized_message(&self, language: Language) -> Option<String> {
+        let key = format!("{0}.{1}", self.field, self.code);
+
+        match i18n::translate(language, &key) {
+            Some(message) => Some(message.to_owned()),
+            None => self.message.clone(),
+        }
+    }
+}
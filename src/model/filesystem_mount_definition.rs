@@ -1,11 +1,20 @@
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use serde::{Deserialize, Serialize};
 
 use validator::{Validate, ValidationError};
 
-use crate::utils::command::command_to_string;
-use crate::utils::fusermount::get_fusermount_command;
+use crate::mount_options::escape_mount_option;
+use crate::utils::command::{command_to_escaped_string, wrap_command};
+use crate::utils::fusermount::{get_fusermount_command, is_legacy_fuse};
+use crate::utils::path_template::{
+    PLACEHOLDERS, PathTemplateValues, expand_path_template, is_absolute_path_template,
+    unknown_placeholders,
+};
+use crate::utils::ssh::is_security_key;
 
 use crate::auth_type::{
     AuthType, deserialize_auth_type_from_string, serialize_auth_type_to_string,
@@ -13,13 +22,15 @@ use crate::auth_type::{
 
 use crate::errors::SftpManError;
 
+use super::{
+    ExtraMountPathMethod, FilesystemMountDefinitionBuilder, IoniceClass, MountPath,
+    MountRequirement, SubMount,
+};
+
 pub const DEFAULT_MOUNT_PATH_PREFIX: &str = "/mnt/sshfs";
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
-#[validate(schema(
-    function = "validate_ssh_key_for_publickey_auth",
-    skip_on_field_errors = false
-))]
+#[validate(schema(function = "validate_schema", skip_on_field_errors = false))]
 pub struct FilesystemMountDefinition {
     /// Unique identifier for this definition.
     /// If `mount_dest_path` is `None`, this will also influence where the filesystem gets mounted locally (see `local_mount_path()`).
@@ -32,8 +43,18 @@ pub struct FilesystemMountDefinition {
     )]
     pub id: String,
 
-    /// Hostname or IP address of the remote machine
-    #[validate(length(min = 1, message = "A host must be provided."))]
+    /// A human-friendly name for the filesystem (e.g. `Company NAS`), used instead of the ID where there's room for it
+    /// (e.g. in desktop launchers, see `DesktopEntry`).
+    #[serde(default)]
+    #[validate(length(min = 1, message = "The description must not be empty."))]
+    pub description: Option<String>,
+
+    /// Hostname or IP address of the remote machine.
+    /// IPv6 addresses are given without brackets and may have a zone ID (e.g. `fe80::1%eth0` for link-local addresses).
+    #[validate(
+        length(min = 1, message = "A host must be provided."),
+        custom(function = "validate_host")
+    )]
     pub host: String,
 
     /// Port number of the remote machine (e.g. `22`).
@@ -45,7 +66,9 @@ pub struct FilesystemMountDefinition {
 
     /// Mount options to pass to sshfs (-o).
     /// Example: [`follow_symlinks`, `rename`]
+    /// Each option goes into its own entry. Commas within values (e.g. `ProxyCommand=..`) get escaped when mounting.
     #[serde(rename = "mountOptions")]
+    #[validate(custom(function = "validate_mount_options"))]
     pub mount_options: Vec<String>,
 
     /// Path on the remote server that will be mounted locally (e.g. `/storage`).
@@ -60,17 +83,48 @@ pub struct FilesystemMountDefinition {
     pub remote_path: String,
 
     /// Path where the filesystem will be mounted locally (e.g. `/home/user/storage`).
-    /// If not provided, it defaults to `{DEFAULT_MOUNT_PATH_PREFIX}/{id}`.
+    /// If not provided, it defaults to `mount_path_template` or (if that's not set either) to `{DEFAULT_MOUNT_PATH_PREFIX}/{id}`.
+    ///
+    /// It may contain placeholders (e.g. `~/mounts/{host}/{id}`), which get expanded by `local_mount_path()`.
     #[serde(rename = "mountDestPath")]
     #[validate(
         length(min = 1, message = "A local mount destination path must be provided."),
-        custom(
-            function = "validate_absolute_path",
-            message = "The local mount destination path must be absolute."
-        )
+        custom(function = "validate_mount_dest_path")
     )]
     pub mount_dest_path: Option<String>,
 
+    /// Remote paths to mount into subdirectories of the local mount path (e.g. `/var/www` into `web` and `/var/log` into `logs`),
+    /// instead of mounting `remote_path` there.
+    ///
+    /// Each one is mounted by its own `sshfs` process (see `sub_mount_definitions()`), but they all get mounted and unmounted together.
+    /// `remote_path` is still used by whatever doesn't mount (e.g. `Manager::verify()` or `Manager::download()`).
+    #[serde(rename = "subMounts")]
+    #[serde(default)]
+    #[validate(custom(function = "validate_sub_mounts"))]
+    pub sub_mounts: Vec<SubMount>,
+
+    /// Additional local paths (e.g. `~/projects/website/uploads`) where the filesystem gets exposed once mounted
+    /// (see `extra_mount_path_method`), for tools with hardcoded paths, without running another `sshfs` for them.
+    ///
+    /// Like `mount_dest_path`, they may contain placeholders (see `local_extra_mount_paths()`). They get removed when unmounting.
+    #[serde(rename = "extraMountPaths")]
+    #[serde(default)]
+    #[validate(custom(function = "validate_extra_mount_paths"))]
+    pub extra_mount_paths: Vec<String>,
+
+    /// How the filesystem gets exposed at `extra_mount_paths`.
+    #[serde(rename = "extraMountPathMethod")]
+    #[serde(default)]
+    pub extra_mount_path_method: ExtraMountPathMethod,
+
+    /// Tells whether mounting should proceed when the local mount path (see `local_mount_path()`) is not empty.
+    ///
+    /// By default, mounting fails (see `SftpManError::MountPathNotEmpty`), as the files in the directory would be hidden while mounted.
+    /// With old FUSE versions (without `fusermount3`), this passes `-o nonempty` to `sshfs`, as they refuse such mounts otherwise.
+    #[serde(rename = "allowNonEmptyMountPath")]
+    #[serde(default)]
+    pub allow_non_empty_mount_path: bool,
+
     /// Command to run before mounting (e.g. `/bin/true`)
     #[serde(rename = "beforeMount")]
     #[serde(default)]
@@ -88,29 +142,296 @@ pub struct FilesystemMountDefinition {
     /// Path to an SSH private key (e.g. `/home/user/.ssh/id_ed25519`) for authentication types (like `AuthType::PublicKey`) that use a key.
     #[serde(rename = "sshKey")]
     pub ssh_key: String,
+
+    /// Paths to additional SSH private keys, which get tried (in order) after `ssh_key` if the remote machine doesn't accept it
+    /// (e.g. for users with different keys for different realms, who don't know in advance which one a given server accepts).
+    #[serde(rename = "fallbackSshKeys")]
+    #[serde(default)]
+    pub fallback_ssh_keys: Vec<String>,
+
+    /// Path to an OpenSSH certificate (e.g. `/home/user/.ssh/id_ed25519-cert.pub`) signed by an SSH certificate authority,
+    /// for remote machines trusting the authority instead of listing each key in `authorized_keys`.
+    ///
+    /// It's passed to `ssh` (as `CertificateFile`) alongside the private key (`ssh_key`) it was issued for,
+    /// so it requires the `AuthType::PublicKey` authentication type.
+    #[serde(rename = "certificateFile")]
+    #[serde(default)]
+    #[validate(custom(
+        function = "validate_absolute_path",
+        message = "The certificate file path must be absolute."
+    ))]
+    pub certificate_file: Option<String>,
+
+    /// Path to a `known_hosts` file to use for this filesystem, instead of the default one (`~/.ssh/known_hosts`).
+    ///
+    /// This keeps host keys of short-lived machines (e.g. VMs, or hosts behind NAT with recycled addresses)
+    /// from polluting (or conflicting with) the main `known_hosts` file.
+    #[serde(rename = "knownHostsFile")]
+    #[serde(default)]
+    #[validate(custom(
+        function = "validate_absolute_path",
+        message = "The known hosts file path must be absolute."
+    ))]
+    pub known_hosts_file: Option<String>,
+
+    /// A command (`ProxyCommand`) whose standard input and output `ssh` talks to instead of connecting to the remote machine directly,
+    /// for networks where it's only reachable through a custom tunnel (e.g. `ssh -W %h:%p bastion.example.com`).
+    /// `%h` and `%p` get replaced with the host and port by `ssh`. At most one of this and `socks_proxy` may be set.
+    #[serde(rename = "proxyCommand")]
+    #[serde(default)]
+    #[validate(length(min = 1, message = "The proxy command must not be empty."))]
+    pub proxy_command: Option<String>,
+
+    /// Address (`host:port`) of a SOCKS5 proxy to connect to the remote machine through (e.g. `127.0.0.1:1080`).
+    ///
+    /// This is a shortcut for a `proxy_command` of `nc -X 5 -x host:port %h %p` (see `effective_proxy_command()`),
+    /// so a `nc` supporting `-X` (OpenBSD netcat) needs to be installed.
+    #[serde(rename = "socksProxy")]
+    #[serde(default)]
+    #[validate(custom(function = "validate_socks_proxy"))]
+    pub socks_proxy: Option<String>,
+
+    /// Tells whether the host should not be resolved before mounting, even though that's enabled globally
+    /// (see `GlobalConfig::resolve_hosts_before_mount`), e.g. because it's only resolvable by a proxy configured elsewhere.
+    #[serde(rename = "skipHostResolution")]
+    #[serde(default)]
+    pub skip_host_resolution: bool,
+
+    /// The ciphers (`Ciphers`) `ssh` may use, in order of preference (e.g. `aes128-gcm@openssh.com`).
+    /// If empty, `ssh`'s defaults are used.
+    ///
+    /// On fast networks, the cipher is often what limits the throughput (making a 2-3x difference), which `sftpman bench` helps compare.
+    #[serde(default)]
+    #[validate(custom(function = "validate_ciphers"))]
+    pub ciphers: Vec<String>,
+
+    /// The `ssh` program (e.g. `/opt/openssh/bin/ssh`, `dbclient` or a wrapper script) to connect with,
+    /// instead of the `ssh` found in `PATH` (see `effective_ssh_binary()`).
+    ///
+    /// It's used by `sshfs` (via `-o ssh_command=..`), `sftp` and `scp` (via `-S ..`) and for deploying keys,
+    /// so it needs to accept the same arguments as OpenSSH's `ssh`.
+    #[serde(rename = "sshBinary")]
+    #[serde(default)]
+    #[validate(length(min = 1, message = "The SSH binary must not be empty."))]
+    pub ssh_binary: Option<String>,
+
+    /// A command whose output (first line) is the password to authenticate with (e.g. `pass show nas`).
+    ///
+    /// Like `password_env` and `password_file`, this is a reference to a secret stored elsewhere.
+    /// It gets resolved when mounting (see `Manager::mount()`) and the password is passed to `sshfs` via `-o password_stdin`,
+    /// so that it never needs to be stored in the definition. At most one reference may be set and only for `AuthType::Password`.
+    #[serde(rename = "passwordCommand")]
+    #[serde(default)]
+    pub password_command: Option<String>,
+
+    /// The name of an environment variable holding the password to authenticate with (see `password_command`).
+    #[serde(rename = "passwordEnv")]
+    #[serde(default)]
+    pub password_env: Option<String>,
+
+    /// Path to a file holding the password to authenticate with (see `password_command`).
+    #[serde(rename = "passwordFile")]
+    #[serde(default)]
+    pub password_file: Option<String>,
+
+    /// Tells whether Kerberos credentials should be forwarded to the remote machine (`GSSAPIDelegateCredentials`).
+    /// Only applies to `AuthType::GSSAPIWithMic`.
+    #[serde(rename = "gssapiDelegateCredentials")]
+    #[serde(default)]
+    pub gssapi_delegate_credentials: bool,
+
+    /// Tells whether GSSAPI key exchange (`GSSAPIKeyExchange`) should be used, which verifies the remote machine via Kerberos instead of its host key.
+    /// Only applies to `AuthType::GSSAPIWithMic` and requires an `ssh` build with GSSAPI key exchange support (e.g. the one shipped by Debian or Fedora).
+    #[serde(rename = "gssapiKeyExchange")]
+    #[serde(default)]
+    pub gssapi_key_exchange: bool,
+
+    /// Environment variables to set for `sshfs` (and the `ssh` process it starts), as well as for `sftp` (see `sftp_command()`).
+    ///
+    /// This is mostly useful for pointing `SSH_AUTH_SOCK` to a specific SSH agent (e.g. `gpg-agent` instead of `ssh-agent`) for this filesystem.
+    #[serde(default)]
+    #[validate(custom(function = "validate_env"))]
+    pub env: BTreeMap<String, String>,
+
+    /// Scheduling priority (niceness, from -20 to 19) to run `sshfs` (and the `ssh` process it starts) with, via `nice`.
+    ///
+    /// Raising it (e.g. to `10`) keeps background mounts (e.g. for backups) from starving interactive ones of CPU time.
+    /// Lowering it below `0` requires elevated privileges. If `None`, the default priority is used.
+    #[serde(default)]
+    #[validate(range(
+        min = -20,
+        max = 19,
+        message = "The nice value must be between -20 and 19."
+    ))]
+    pub nice: Option<i32>,
+
+    /// I/O scheduling class to run `sshfs` (and the `ssh` process it starts) with, via `ionice`.
+    /// If `None`, the default class is used.
+    #[serde(rename = "ioniceClass")]
+    #[serde(default)]
+    pub ionice_class: Option<IoniceClass>,
+
+    /// Priority within the I/O scheduling class (from 0, the highest, to 7, the lowest).
+    /// Only applies to `IoniceClass::BestEffort`.
+    #[serde(rename = "ioniceLevel")]
+    #[serde(default)]
+    #[validate(range(max = 7, message = "The ionice level must be between 0 and 7."))]
+    pub ionice_level: Option<u8>,
+
+    /// Maximum rate (in KiB/s) at which data is downloaded from the remote machine, enforced by running `ssh` through `trickle`.
+    ///
+    /// This keeps mounts from saturating slow or metered links (e.g. LTE). If `None`, downloading is not limited.
+    #[serde(rename = "downloadLimit")]
+    #[serde(default)]
+    #[validate(range(min = 1, message = "The download limit must be at least 1 KiB/s."))]
+    pub download_limit: Option<u32>,
+
+    /// Maximum rate (in KiB/s) at which data is uploaded to the remote machine (see `download_limit`).
+    /// If `None`, uploading is not limited.
+    #[serde(rename = "uploadLimit")]
+    #[serde(default)]
+    #[validate(range(min = 1, message = "The upload limit must be at least 1 KiB/s."))]
+    pub upload_limit: Option<u32>,
+
+    /// Tells whether `sshfs` should run with debug output enabled (`-o debug,sshfs_debug`).
+    /// The output gets captured to a per-mount log file (see `Manager::sshfs_debug_log_path()`).
+    #[serde(rename = "debugSshfs")]
+    #[serde(default)]
+    pub debug_sshfs: bool,
+
+    /// Number of minutes without filesystem activity, after which the filesystem gets unmounted automatically
+    /// (see `Manager::umount_idle()`).
+    /// If `None`, the filesystem is never unmounted for being idle.
+    #[serde(rename = "idleUnmountMinutes")]
+    #[serde(default)]
+    pub idle_unmount_minutes: Option<u32>,
+
+    /// Tells whether the filesystem should be mounted automatically when a session starts (e.g. by the `pam_helper` command).
+    #[serde(rename = "autoMount")]
+    #[serde(default)]
+    pub auto_mount: bool,
+
+    /// Conditions which need to be met for the filesystem to be mounted (e.g. a VPN interface being up),
+    /// checked before mounting (see `Manager::check_requirements()`).
+    /// Mounting everything (e.g. `mount_all`) skips filesystems whose requirements are not met, instead of failing (possibly after a timeout).
+    #[serde(default)]
+    pub requires: Vec<MountRequirement>,
+
+    /// Tells whether the filesystem can be mounted.
+    /// Disabled definitions are kept (and listed), but are skipped when mounting everything (e.g. `mount_all`, the `pam_helper` command)
+    /// and refused by `Manager::mount()` (see `SftpManError::DefinitionDisabled`).
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+
+    /// Fields which this version doesn't know about (e.g. ones written by a newer version or by a frontend).
+    /// They're kept as they are, so that loading and persisting a definition doesn't drop them.
+    #[serde(flatten)]
+    pub extra_fields: BTreeMap<String, serde_json::Value>,
+
+    /// Identifies the stored contents this definition was loaded from (not stored itself).
+    ///
+    /// `Manager::persist()` uses it to detect that the definition was changed by someone else in the meantime
+    /// (see `SftpManError::ConflictingEdit`). It's `None` for definitions which were not loaded via a `Manager`.
+    #[serde(skip)]
+    pub revision: Option<u64>,
+
+    /// The template for the local mount path of definitions without a `mount_dest_path` (not stored itself).
+    ///
+    /// `Manager` sets it from the global config (see `GlobalConfig::mount_path_template`) for the definitions it loads.
+    #[serde(skip)]
+    pub mount_path_template: Option<String>,
+
+    /// The `ssh` program to use when `ssh_binary` is not set (not stored itself).
+    ///
+    /// `Manager` sets it from the global config (see `GlobalConfig::ssh_binary`) for the definitions it loads.
+    #[serde(skip)]
+    pub default_ssh_binary: Option<String>,
 }
 
-const SSH_DEFAULT_TIMEOUT: u32 = 10;
+pub(crate) const SSH_DEFAULT_TIMEOUT: u32 = 10;
+
+/// The `ssh` program used when neither the definition nor the global config specify one (see `FilesystemMountDefinition::effective_ssh_binary()`).
+pub(crate) const DEFAULT_SSH_BINARY: &str = "ssh";
+
+/// The program used for enforcing bandwidth limits (see `FilesystemMountDefinition::download_limit`).
+pub(crate) const BANDWIDTH_LIMITER: &str = "trickle";
+
+/// The program used for bind-mounting the local mount path at extra mount paths (see `ExtraMountPathMethod::Bind`).
+pub(crate) const BIND_MOUNTER: &str = "bindfs";
+
+/// Definitions stored before `enabled` was introduced are enabled.
+fn enabled_by_default() -> bool {
+    true
+}
 
 impl Default for FilesystemMountDefinition {
     fn default() -> Self {
         FilesystemMountDefinition {
             id: String::new(),
+            description: None,
             host: String::new(),
             port: 22,
             user: String::new(),
             mount_options: Vec::new(),
             remote_path: String::new(),
             mount_dest_path: None,
+            sub_mounts: Vec::new(),
+            extra_mount_paths: Vec::new(),
+            extra_mount_path_method: ExtraMountPathMethod::default(),
+            allow_non_empty_mount_path: false,
             cmd_before_mount: String::new(),
             auth_type: AuthType::PublicKey,
             ssh_key: String::new(),
+            fallback_ssh_keys: Vec::new(),
+            certificate_file: None,
+            known_hosts_file: None,
+            proxy_command: None,
+            socks_proxy: None,
+            skip_host_resolution: false,
+            ciphers: Vec::new(),
+            ssh_binary: None,
+            password_command: None,
+            password_env: None,
+            password_file: None,
+            gssapi_delegate_credentials: false,
+            gssapi_key_exchange: false,
+            env: BTreeMap::new(),
+            nice: None,
+            ionice_class: None,
+            ionice_level: None,
+            download_limit: None,
+            upload_limit: None,
+            debug_sshfs: false,
+            idle_unmount_minutes: None,
+            auto_mount: false,
+            requires: Vec::new(),
+            enabled: true,
+            extra_fields: BTreeMap::new(),
+            revision: None,
+            mount_path_template: None,
+            default_ssh_binary: None,
         }
     }
 }
 
 impl FilesystemMountDefinition {
+    /// Starts building a definition out of the fields which every definition needs, validating it once built
+    /// (e.g. `FilesystemMountDefinition::builder("nas", "nas.local", "me", "/storage").with_ssh_key("/home/me/.ssh/id_ed25519").build()`).
+    ///
+    /// Unlike filling in the struct directly, this doesn't let required fields be forgotten.
+    pub fn builder(
+        id: &str,
+        host: &str,
+        user: &str,
+        remote_path: &str,
+    ) -> FilesystemMountDefinitionBuilder {
+        FilesystemMountDefinitionBuilder::new(id, host, user, remote_path)
+    }
+
     pub fn from_json_string(contents: &str) -> Result<Self, serde_json::Error> {
+        #[cfg(feature = "jsonc")]
+        let contents = &crate::utils::jsonc::to_plain_json(contents);
+
         let deserialized: Self = serde_json::from_str(contents)?;
         Ok(deserialized)
     }
@@ -119,19 +440,200 @@ impl FilesystemMountDefinition {
         serde_json::to_string_pretty(self)
     }
 
+    /// Calculates the revision (see `revision`) for the given stored contents.
+    pub(crate) fn revision_of(contents: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        contents.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Creates a definition for a one-off mount from an SFTP URL (e.g. `sftp://user@example.com:2222/storage`).
+    ///
+    /// The ID is generated (e.g. `tmp-example.com-1a2b3c4d`), so that it doesn't clash with stored definitions.
+    /// If the URL contains no user, the current one (`$USER`) is used. If it contains no path, the root directory is mounted.
+    /// Authentication is left to the SSH agent (see `AuthType::AuthenticationAgent`).
+    pub fn from_url(url: &str) -> Result<Self, SftpManError> {
+        let mut definition = Self::parse_url(url)?;
+
+        definition.id = format!(
+            "tmp-{0}-{1:08x}",
+            definition
+                .host
+                .replace(|c: char| !c.is_alphanumeric() && c != '.' && c != '-', "-"),
+            rand::random::<u32>()
+        );
+
+        Ok(definition)
+    }
+
+    /// Creates a definition (with an empty ID) from an SFTP URL, like `from_url()` does.
+    pub(crate) fn parse_url(url: &str) -> Result<Self, SftpManError> {
+        let invalid = |reason: &str| SftpManError::InvalidUrl(url.to_owned(), reason.to_owned());
+
+        let rest = url
+            .strip_prefix("sftp://")
+            .ok_or_else(|| invalid("only sftp:// URLs are supported"))?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => rest.split_at(idx),
+            None => (rest, "/"),
+        };
+
+        let (user, host_and_port) = match authority.rsplit_once('@') {
+            Some((user, host_and_port)) => (percent_decode(user).map_err(invalid)?, host_and_port),
+            None => (
+                std::env::var("USER").map_err(|_| invalid("no user given and $USER is not set"))?,
+                authority,
+            ),
+        };
+
+        // IPv6 addresses are enclosed in brackets (e.g. `[::1]:22`), as they contain colons themselves.
+        // Their zone ID has its `%` encoded (e.g. `[fe80::1%25eth0]`, see RFC 6874).
+        let (host, port) = match host_and_port.strip_prefix('[') {
+            Some(bracketed) => {
+                let (host, after) = bracketed
+                    .split_once(']')
+                    .ok_or_else(|| invalid("unterminated IPv6 address"))?;
+                (
+                    percent_decode(host).map_err(invalid)?,
+                    after.strip_prefix(':'),
+                )
+            }
+            None => match host_and_port.split_once(':') {
+                Some((host, port)) => (host.to_owned(), Some(port)),
+                None => (host_and_port.to_owned(), None),
+            },
+        };
+
+        if host.is_empty() {
+            return Err(invalid("no host given"));
+        }
+
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| invalid("invalid port"))?,
+            None => 22,
+        };
+
+        Ok(Self {
+            host,
+            port,
+            user,
+            remote_path: percent_decode(path).map_err(invalid)?,
+            auth_type: AuthType::AuthenticationAgent,
+            ..Self::default()
+        })
+    }
+
+    /// Tells whether the password is obtained from a reference (see `password_command`), so mounting requires no interaction.
+    pub fn has_password_source(&self) -> bool {
+        self.password_command.is_some()
+            || self.password_env.is_some()
+            || self.password_file.is_some()
+    }
+
+    /// Tells whether the bandwidth is limited (see `download_limit` and `upload_limit`), which requires `trickle`.
+    pub fn has_bandwidth_limit(&self) -> bool {
+        self.download_limit.is_some() || self.upload_limit.is_some()
+    }
+
+    /// Returns the paths to the SSH private keys to try, in order (`ssh_key`, followed by `fallback_ssh_keys`).
+    pub fn ssh_keys(&self) -> Vec<&str> {
+        std::iter::once(&self.ssh_key)
+            .chain(&self.fallback_ssh_keys)
+            .filter(|key| !key.is_empty())
+            .map(|key| key.as_str())
+            .collect()
+    }
+
+    /// Tells whether authentication uses a FIDO2 security key (e.g. a `sk-ssh-ed25519` key), which may need to be touched when mounting.
+    pub fn uses_security_key(&self) -> bool {
+        self.auth_type == AuthType::PublicKey
+            && self
+                .ssh_keys()
+                .into_iter()
+                .any(|key| is_security_key(Path::new(key)))
+    }
+
     /// Returns the local mount path for this definition.
     /// If `mount_dest_path` is not `None` for this definition, it will be used.
-    /// Otherwise, the default mount path (`DEFAULT_MOUNT_PATH_PREFIX`) will be used (e.g. `/mnt/sshfs/{id}`).
-    pub fn local_mount_path(&self) -> String {
-        match &self.mount_dest_path {
-            Some(path) => path.clone(),
-            None => format!("{0}/{1}", DEFAULT_MOUNT_PATH_PREFIX, self.id),
+    /// Otherwise, `mount_path_template` or the default mount path (`DEFAULT_MOUNT_PATH_PREFIX`) will be used (e.g. `/mnt/sshfs/{id}`).
+    ///
+    /// Placeholders (e.g. `{host}`), environment variables and a leading `~` get expanded (see `expand_path_template()`).
+    pub fn local_mount_path(&self) -> MountPath {
+        let template = match (&self.mount_dest_path, &self.mount_path_template) {
+            (Some(path), _) => path,
+            (None, Some(template)) => template,
+            (None, None) => {
+                return MountPath::new(Path::new(DEFAULT_MOUNT_PATH_PREFIX).join(&self.id));
+            }
+        };
+
+        MountPath::new(expand_path_template(template, &self.path_template_values()))
+    }
+
+    /// Returns the extra local paths the filesystem gets exposed at once mounted (see `extra_mount_paths`),
+    /// with placeholders, environment variables and a leading `~` expanded (like for `local_mount_path()`).
+    pub fn local_extra_mount_paths(&self) -> Vec<PathBuf> {
+        let values = self.path_template_values();
+
+        self.extra_mount_paths
+            .iter()
+            .map(|path| PathBuf::from(expand_path_template(path, &values)))
+            .collect()
+    }
+
+    fn path_template_values(&self) -> PathTemplateValues<'_> {
+        PathTemplateValues {
+            id: &self.id,
+            host: &self.host,
+            user: &self.user,
+            port: self.port,
         }
     }
 
+    /// Returns a definition for each of the sub-mounts (see `sub_mounts`), which mounts its remote path
+    /// into its subdirectory of the local mount path, but is otherwise the same as this one.
+    ///
+    /// Their IDs (e.g. `my-fs:web`) only serve for telling them apart (e.g. in logs), so they're not valid definition IDs.
+    pub fn sub_mount_definitions(&self) -> Vec<FilesystemMountDefinition> {
+        let local_mount_path = self.local_mount_path();
+
+        self.sub_mounts
+            .iter()
+            .map(|sub_mount| FilesystemMountDefinition {
+                id: format!("{0}:{1}", self.id, sub_mount.directory),
+                remote_path: sub_mount.remote_path.clone(),
+                // Both parts come from strings, so the conversion back is lossless.
+                mount_dest_path: Some(
+                    local_mount_path
+                        .join(&sub_mount.directory)
+                        .to_string_lossy()
+                        .into_owned(),
+                ),
+                sub_mounts: Vec::new(),
+                // The filesystem as a whole (not each sub-mount) gets exposed there.
+                extra_mount_paths: Vec::new(),
+                revision: None,
+                ..self.clone()
+            })
+            .collect()
+    }
+
     /// Returns a list of commands for mounting the filesystem definition.
     /// Mounting is performed via `sshfs` and `ssh` commands.
     pub fn mount_commands(&self) -> Result<Vec<Command>, SftpManError> {
+        self.build_mount_commands(false)
+    }
+
+    /// Returns a list of commands for mounting the filesystem definition, like `mount_commands()` does,
+    /// but with `sshfs` staying in the foreground (`sshfs -f ..`) instead of daemonizing.
+    ///
+    /// The last command in the list is the `sshfs` command, which keeps running for as long as the filesystem is mounted.
+    pub fn foreground_mount_commands(&self) -> Result<Vec<Command>, SftpManError> {
+        self.build_mount_commands(true)
+    }
+
+    fn build_mount_commands(&self, foreground: bool) -> Result<Vec<Command>, SftpManError> {
         log::debug!("{0}: building list of mount commands", self.id);
 
         let mut list: Vec<Command> = Vec::new();
@@ -176,52 +678,347 @@ impl FilesystemMountDefinition {
             }
         }
 
-        let mut cmd_ssh = Command::new("ssh");
+        let mut cmd_ssh = Command::new(self.effective_ssh_binary());
         cmd_ssh
             .arg("-p")
             .arg(self.port.to_string())
+            .args(self.ssh_options());
+        let cmd_ssh = self.wrap_with_bandwidth_limit(cmd_ssh);
+
+        let mut cmd_sshfs = Command::new("sshfs");
+        // sshfs starts ssh, which inherits these.
+        cmd_sshfs.envs(&self.env);
+        if foreground {
+            cmd_sshfs.arg("-f");
+        }
+        if self.debug_sshfs {
+            // Debug output implies that sshfs stays in the foreground.
+            cmd_sshfs.arg("-o").arg("debug,sshfs_debug");
+        }
+        if self.has_password_source() {
+            // The resolved password gets written to the standard input of sshfs (see `Manager::mount()`).
+            cmd_sshfs.arg("-o").arg("password_stdin");
+        }
+        if self.allow_non_empty_mount_path && is_legacy_fuse() {
+            // FUSE 3 allows mounting over non-empty directories (and doesn't know this option anymore).
+            cmd_sshfs.arg("-o").arg("nonempty");
+        }
+        cmd_sshfs
+            // Add mount options prefixed with "-o" (ignored if empty).
+            .args(
+                self.mount_options
+                    .iter()
+                    .flat_map(|opt| ["-o".to_owned(), escape_mount_option(opt)]),
+            )
+            // Add the formatted SSH command as an sshfs option.
             .arg("-o")
-            .arg(format!("ConnectTimeout={0}", SSH_DEFAULT_TIMEOUT));
+            .arg(escape_mount_option(&format!(
+                "ssh_command={0}",
+                command_to_escaped_string(&cmd_ssh)
+            )))
+            .arg(self.remote_target(&self.remote_path))
+            // Set the local mount point for the remote directory.
+            .arg(self.local_mount_path());
+
+        list.push(self.wrap_with_priority(cmd_sshfs));
+
+        Ok(list)
+    }
+
+    /// Wraps the given command with `ionice` and `nice` (in that order), as configured via `nice`, `ionice_class` and `ionice_level`.
+    ///
+    /// Both programs replace themselves with the wrapped command, so the resulting process is still the `sshfs` one
+    /// (which matters for finding it later, e.g. for killing it when unmounting).
+    fn wrap_with_priority(&self, cmd: Command) -> Command {
+        let mut cmd = cmd;
+
+        if let Some(class) = &self.ionice_class {
+            let class_number = class.number().to_string();
+            let mut wrapper = vec!["ionice", "-c", &class_number];
+
+            let level = self.ionice_level.map(|level| level.to_string());
+            if *class == IoniceClass::BestEffort
+                && let Some(level) = &level
+            {
+                wrapper.push("-n");
+                wrapper.push(level);
+            }
+
+            cmd = wrap_command(&wrapper, cmd);
+        }
+
+        if let Some(nice) = self.nice {
+            cmd = wrap_command(&["nice", "-n", &nice.to_string()], cmd);
+        }
+
+        cmd
+    }
+
+    /// Wraps the given command with `trickle`, as configured via `download_limit` and `upload_limit`.
+    ///
+    /// `trickle` limits the sockets of the process it runs (and of its children), so it needs to wrap
+    /// the program which actually talks to the remote machine (e.g. `ssh`, rather than `sshfs`).
+    fn wrap_with_bandwidth_limit(&self, cmd: Command) -> Command {
+        match self.bandwidth_limiter() {
+            Some(wrapper) => {
+                let wrapper: Vec<&str> = wrapper.iter().map(String::as_str).collect();
+                wrap_command(&wrapper, cmd)
+            }
+            None => cmd,
+        }
+    }
+
+    /// Returns the `trickle` command line (e.g. `["trickle", "-s", "-d", "500"]`) enforcing the bandwidth limits, if there are any.
+    pub(crate) fn bandwidth_limiter(&self) -> Option<Vec<String>> {
+        if !self.has_bandwidth_limit() {
+            return None;
+        }
+
+        // Standalone mode (`-s`) doesn't need the `trickled` daemon.
+        let mut wrapper: Vec<String> = vec![BANDWIDTH_LIMITER.to_owned(), "-s".to_owned()];
+
+        if let Some(limit) = self.download_limit {
+            wrapper.push("-d".to_owned());
+            wrapper.push(limit.to_string());
+        }
+
+        if let Some(limit) = self.upload_limit {
+            wrapper.push("-u".to_owned());
+            wrapper.push(limit.to_string());
+        }
+
+        Some(wrapper)
+    }
+
+    /// Returns a command which starts an interactive `sftp` session with the remote machine.
+    ///
+    /// Commands (e.g. `ls`) are meant to be fed to its standard input.
+    pub fn sftp_command(&self) -> Command {
+        self.build_sftp_command(false)
+    }
+
+    /// Returns a command which runs `sftp` non-interactively (in batch mode), executing the commands fed to its standard input.
+    ///
+    /// Prompts (e.g. for a password or for accepting an unknown host key) are disabled and make the command fail,
+    /// as does the first failing command.
+    pub fn sftp_batch_command(&self) -> Command {
+        self.build_sftp_command(true)
+    }
+
+    fn build_sftp_command(&self, batch: bool) -> Command {
+        let mut cmd = Command::new("sftp");
+        cmd.envs(&self.env);
+
+        if batch {
+            cmd.arg("-b").arg("-");
+        }
+
+        if let Some(binary) = self.custom_ssh_binary() {
+            cmd.arg("-S").arg(binary);
+        }
+
+        cmd.arg("-q")
+            .arg("-P")
+            .arg(self.port.to_string())
+            .args(self.ssh_options())
+            .arg(self.ssh_destination());
+
+        cmd
+    }
+
+    /// Returns a command which copies a file (or, if `recursive`, a directory) from the remote machine to the local one via `scp`.
+    ///
+    /// The remote path is passed as is, so relative paths are relative to the remote user's home directory.
+    pub fn download_command(
+        &self,
+        remote_path: &str,
+        local_path: &Path,
+        recursive: bool,
+    ) -> Command {
+        let mut cmd = self.build_scp_command(recursive);
+        cmd.arg(self.scp_remote_operand(remote_path))
+            .arg(scp_local_operand(local_path));
+
+        cmd
+    }
+
+    /// Returns a command which copies a file (or, if `recursive`, a directory) from the local machine to the remote one via `scp`
+    /// (see `download_command()`).
+    pub fn upload_command(&self, local_path: &Path, remote_path: &str, recursive: bool) -> Command {
+        let mut cmd = self.build_scp_command(recursive);
+        cmd.arg(scp_local_operand(local_path))
+            .arg(self.scp_remote_operand(remote_path));
+
+        cmd
+    }
+
+    fn build_scp_command(&self, recursive: bool) -> Command {
+        let mut cmd = Command::new("scp");
+        cmd.envs(&self.env);
+
+        if recursive {
+            cmd.arg("-r");
+        }
+
+        if let Some(binary) = self.custom_ssh_binary() {
+            cmd.arg("-S").arg(binary);
+        }
+
+        cmd.arg("-P")
+            .arg(self.port.to_string())
+            .args(self.ssh_options())
+            .arg("--");
+
+        // scp runs ssh, which inherits the limits.
+        self.wrap_with_bandwidth_limit(cmd)
+    }
+
+    fn scp_remote_operand(&self, remote_path: &str) -> String {
+        self.remote_target(remote_path)
+    }
+
+    /// Returns the destination (e.g. `user@[fe80::1%eth0]`) to pass to `sftp` (and, with a path appended, to `sshfs` and `scp`).
+    ///
+    /// We use `[]` around the host to avoid issues with hostnames (IPv6 addresses) containing `:`.
+    /// This also works well for IPv4 addresses, name-based hostnames and IPv6 addresses with a zone ID (kept within the brackets).
+    fn ssh_destination(&self) -> String {
+        format!("{0}@[{1}]", self.user, self.host)
+    }
+
+    /// Returns the remote location (e.g. `user@[example.com]:/storage`) of the given path, as passed to `sshfs` and `scp`.
+    ///
+    /// For mounts, this is what identifies the `sshfs` process (see `sshfs_pid_by_definition()`) and what the mount table lists as the source.
+    pub(crate) fn remote_target(&self, remote_path: &str) -> String {
+        format!("{0}:{1}", self.ssh_destination(), remote_path)
+    }
+
+    /// Returns the command (`ProxyCommand`) that `ssh` should connect to the remote machine through, if any.
+    ///
+    /// This is either `proxy_command` or the one that `socks_proxy` translates to.
+    pub fn effective_proxy_command(&self) -> Option<String> {
+        if let Some(command) = &self.proxy_command {
+            return Some(command.clone());
+        }
+
+        self.socks_proxy
+            .as_ref()
+            .map(|address| format!("nc -X 5 -x {0} %h %p", address))
+    }
+
+    /// Returns the `ssh` program to connect with: `ssh_binary`, else `default_ssh_binary`, else `ssh` (found in `PATH`).
+    pub fn effective_ssh_binary(&self) -> &str {
+        self.custom_ssh_binary().unwrap_or(DEFAULT_SSH_BINARY)
+    }
+
+    /// Returns the `ssh` program to connect with, if it's not the default one (so `sftp` and `scp` need to be told about it).
+    pub(crate) fn custom_ssh_binary(&self) -> Option<&str> {
+        self.ssh_binary
+            .as_deref()
+            .or(self.default_ssh_binary.as_deref())
+    }
+
+    /// Returns a command which adds a public key (fed to its standard input) to the remote user's `~/.ssh/authorized_keys`,
+    /// like `ssh-copy-id` does.
+    ///
+    /// It authenticates with a password (which `ssh` asks for interactively), regardless of `auth_type`,
+    /// as it's meant for setting up key-based authentication in the first place.
+    pub fn key_deployment_command(&self) -> Command {
+        let mut cmd = Command::new(self.effective_ssh_binary());
+        cmd.envs(&self.env);
+
+        cmd.arg("-p")
+            .arg(self.port.to_string())
+            .args(self.connection_options())
+            .args([
+                "-o",
+                "PreferredAuthentications=password,keyboard-interactive",
+                "-o",
+                "PubkeyAuthentication=no",
+            ])
+            .arg("-l")
+            .arg(&self.user)
+            .arg(&self.host)
+            .arg("umask 077 && mkdir -p ~/.ssh && cat >> ~/.ssh/authorized_keys");
+
+        cmd
+    }
+
+    /// Returns the options (e.g. `-o ConnectTimeout=10`) to pass to `ssh` (or `sftp`) when connecting to the remote machine.
+    fn ssh_options(&self) -> Vec<String> {
+        let mut options = self.connection_options();
 
         match &self.auth_type {
             AuthType::PublicKey => {
-                cmd_ssh.arg(format!(
-                    "-o PreferredAuthentications={0}",
+                options.push("-o".to_owned());
+                options.push(format!(
+                    "PreferredAuthentications={0}",
                     AuthType::PublicKey.to_static_str()
                 ));
-                cmd_ssh.arg(format!("-i {0}", self.ssh_key));
+                // ssh tries the keys in the order they're given.
+                for key in self.ssh_keys() {
+                    options.push("-i".to_owned());
+                    options.push(key.to_owned());
+                }
+                if let Some(path) = &self.certificate_file {
+                    options.push("-o".to_owned());
+                    options.push(format!("CertificateFile={0}", path));
+                }
             }
             AuthType::AuthenticationAgent => {
                 // By not specifying a key and preferred authentication type,
                 // we're hoping to delegate all this to an already running SSH agent, if available.
             }
             any_other => {
-                cmd_ssh.arg(format!(
-                    "-o PreferredAuthentications={0}",
+                options.push("-o".to_owned());
+                options.push(format!(
+                    "PreferredAuthentications={0}",
                     any_other.to_static_str()
                 ));
             }
         };
 
-        let mut cmd_sshfs = Command::new("sshfs");
-        cmd_sshfs
-            // Add mount options prefixed with "-o" (ignored if empty).
-            .args(self.mount_options.iter().flat_map(|opt| ["-o", opt]))
-            // Add the formatted SSH command as an sshfs option.
-            .arg("-o")
-            .arg(format!("ssh_command={0}", command_to_string(&cmd_ssh)))
-            // We use `[]` around the host to avoid issues with hostnames (IPv6 addresses) containing `:`.
-            // This also works well for IPv4 addresses and name-based hostnames.
-            .arg(format!(
-                "{0}@[{1}]:{2}",
-                self.user, self.host, self.remote_path
-            ))
-            // Set the local mount point for the remote directory.
-            .arg(self.local_mount_path());
+        if self.auth_type == AuthType::GSSAPIWithMic {
+            // ssh does not try GSSAPI authentication by default.
+            options.push("-o".to_owned());
+            options.push("GSSAPIAuthentication=yes".to_owned());
 
-        list.push(cmd_sshfs);
+            if self.gssapi_delegate_credentials {
+                options.push("-o".to_owned());
+                options.push("GSSAPIDelegateCredentials=yes".to_owned());
+            }
 
-        Ok(list)
+            if self.gssapi_key_exchange {
+                options.push("-o".to_owned());
+                options.push("GSSAPIKeyExchange=yes".to_owned());
+            }
+        }
+
+        options
+    }
+
+    /// Returns the `ssh` options for reaching the remote machine (timeouts, host keys, proxies), which don't depend on how authentication happens.
+    fn connection_options(&self) -> Vec<String> {
+        let mut options: Vec<String> = vec![
+            "-o".to_owned(),
+            format!("ConnectTimeout={0}", SSH_DEFAULT_TIMEOUT),
+        ];
+
+        if let Some(path) = &self.known_hosts_file {
+            options.push("-o".to_owned());
+            options.push(format!("UserKnownHostsFile={0}", path));
+        }
+
+        if let Some(command) = self.effective_proxy_command() {
+            options.push("-o".to_owned());
+            options.push(format!("ProxyCommand={0}", command));
+        }
+
+        if !self.ciphers.is_empty() {
+            options.push("-o".to_owned());
+            options.push(format!("Ciphers={0}", self.ciphers.join(",")));
+        }
+
+        options
     }
 
     /// Returns a list of commands for unmounting the filesystem definition.
@@ -239,6 +1036,32 @@ impl FilesystemMountDefinition {
         Ok(vec![cmd])
     }
 
+    /// Returns a command which lazily unmounts the filesystem (`fusermount3 -u -z ..`):
+    /// it gets detached right away, even if busy or unresponsive, and gets cleaned up once nothing uses it anymore.
+    pub fn lazy_umount_command(&self) -> Command {
+        let mut cmd = Command::new(get_fusermount_command());
+        cmd.arg("-u").arg("-z").arg(self.local_mount_path());
+
+        cmd
+    }
+
+    /// Returns a command which bind-mounts the local mount path at the given extra mount path via `bindfs`
+    /// (see `ExtraMountPathMethod::Bind`).
+    pub fn bind_mount_command(&self, path: &Path) -> Command {
+        let mut cmd = Command::new(BIND_MOUNTER);
+        cmd.arg(self.local_mount_path()).arg(path);
+
+        cmd
+    }
+
+    /// Returns a command which undoes a bind mount made via `bind_mount_command()`.
+    pub fn bind_umount_command(&self, path: &Path) -> Command {
+        let mut cmd = Command::new(get_fusermount_command());
+        cmd.arg("-u").arg(path);
+
+        cmd
+    }
+
     /// Returns a command that opens a file manager (via `xdg-open`) at the local mount path (see `local_mount_path()`).
     ///
     /// Opening requires that the filesystem is already mounted.
@@ -250,6 +1073,47 @@ impl FilesystemMountDefinition {
     }
 }
 
+/// Turns a local path into an `scp` operand which cannot be mistaken for a remote one
+/// (`scp` treats anything with a colon before the first slash, like `notes:2024.txt`, as `host:path`).
+fn scp_local_operand(path: &Path) -> std::path::PathBuf {
+    let looks_remote = path
+        .to_string_lossy()
+        .split('/')
+        .next()
+        .is_some_and(|first| first.contains(':'));
+
+    if looks_remote {
+        Path::new(".").join(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Decodes `%XX` escapes (e.g. `%20` for a space) in a part of a URL.
+fn percent_decode(value: &str) -> Result<String, &'static str> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+
+    while let Some((&byte, after)) = rest.split_first() {
+        if byte != b'%' {
+            bytes.push(byte);
+            rest = after;
+            continue;
+        }
+
+        let decoded = after
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or("invalid percent-encoding")?;
+
+        bytes.push(decoded);
+        rest = &after[2..];
+    }
+
+    String::from_utf8(bytes).map_err(|_| "invalid percent-encoding")
+}
+
 fn validate_id(id: &str) -> Result<(), ValidationError> {
     if !id
         .chars()
@@ -263,6 +1127,55 @@ fn validate_id(id: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+fn validate_host(host: &str) -> Result<(), ValidationError> {
+    let invalid =
+        |message: String| ValidationError::new("invalid_host").with_message(message.into());
+
+    if host.starts_with('[') || host.ends_with(']') {
+        return Err(invalid(format!(
+            "The host {0} is to be given without brackets (e.g. fe80::1%eth0).",
+            host
+        )));
+    }
+
+    if host
+        .chars()
+        .any(|c| c.is_whitespace() || c == '@' || c == '/')
+    {
+        return Err(invalid(format!(
+            "The host {0} contains characters which are not allowed in hosts.",
+            host
+        )));
+    }
+
+    // Only IPv6 addresses contain colons, and only they may have a zone ID (e.g. the `eth0` of `fe80::1%eth0`).
+    let (address, zone_id) = match host.split_once('%') {
+        Some((address, zone_id)) => (address, Some(zone_id)),
+        None => (host, None),
+    };
+
+    if (host.contains(':') || zone_id.is_some()) && address.parse::<std::net::Ipv6Addr>().is_err() {
+        return Err(invalid(format!(
+            "The host {0} is not a valid IPv6 address. Zone IDs (e.g. %eth0) can only follow IPv6 addresses.",
+            host
+        )));
+    }
+
+    if let Some(zone_id) = zone_id
+        && (zone_id.is_empty()
+            || !zone_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        return Err(invalid(format!(
+            "The zone ID of {0} is invalid. It needs to be an interface name or index (e.g. fe80::1%eth0).",
+            host
+        )));
+    }
+
+    Ok(())
+}
+
 fn validate_absolute_path(path: &str) -> Result<(), ValidationError> {
     if !path.starts_with('/') {
         return Err(ValidationError::new("not_absolute_path")
@@ -272,6 +1185,183 @@ fn validate_absolute_path(path: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+fn validate_mount_dest_path(path: &str) -> Result<(), ValidationError> {
+    if !is_absolute_path_template(path) {
+        return Err(ValidationError::new("not_absolute_path").with_message(
+            format!(
+                "The local mount destination path {0} is not absolute (it may also start with ~/ or an environment variable).",
+                path
+            )
+            .into(),
+        ));
+    }
+
+    let unknown = unknown_placeholders(path);
+    if !unknown.is_empty() {
+        return Err(ValidationError::new("unknown_placeholder").with_message(
+            format!(
+                "The local mount destination path contains unknown placeholders ({0}). Supported ones: {1}.",
+                unknown.join(", "),
+                PLACEHOLDERS.map(|name| format!("{{{0}}}", name)).join(", ")
+            )
+            .into(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_extra_mount_paths(paths: &Vec<String>) -> Result<(), ValidationError> {
+    for path in paths {
+        if !is_absolute_path_template(path) {
+            let mut err = ValidationError::new("not_absolute_path").with_message(
+                format!(
+                    "The extra mount path {0} is not absolute (it may also start with ~/ or an environment variable).",
+                    path
+                )
+                .into(),
+            );
+            err.add_param("path".into(), path);
+
+            return Err(err);
+        }
+
+        let unknown = unknown_placeholders(path);
+        if !unknown.is_empty() {
+            let mut err = ValidationError::new("unknown_placeholder").with_message(
+                format!(
+                    "The extra mount path {0} contains unknown placeholders ({1}). Supported ones: {2}.",
+                    path,
+                    unknown.join(", "),
+                    PLACEHOLDERS.map(|name| format!("{{{0}}}", name)).join(", ")
+                )
+                .into(),
+            );
+            err.add_param("path".into(), path);
+
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_mount_options(options: &Vec<String>) -> Result<(), ValidationError> {
+    for option in options {
+        let name = option
+            .split_once('=')
+            .map_or(option.as_str(), |(name, _)| name);
+
+        if name.is_empty() || name.contains(',') {
+            let mut err = ValidationError::new("invalid_mount_option").with_message(
+                format!(
+                    "The mount option {0} is invalid. Each option needs a name and needs to be provided separately (not separated by commas).",
+                    option
+                )
+                .into(),
+            );
+            err.add_param("value".into(), option);
+
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_socks_proxy(address: &str) -> Result<(), ValidationError> {
+    let is_valid = address.rsplit_once(':').is_some_and(|(host, port)| {
+        !host.is_empty()
+            && !host.contains(char::is_whitespace)
+            && port.parse::<u16>().is_ok_and(|port| port != 0)
+    });
+
+    if !is_valid {
+        let mut err = ValidationError::new("invalid_socks_proxy").with_message(
+            format!(
+                "The SOCKS proxy {0} is invalid. It needs to be provided as host:port (e.g. 127.0.0.1:1080).",
+                address
+            )
+            .into(),
+        );
+        err.add_param("value".into(), &address);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_env(env: &BTreeMap<String, String>) -> Result<(), ValidationError> {
+    for (name, value) in env {
+        if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+            let mut err = ValidationError::new("invalid_env_var").with_message(
+                format!(
+                    "The environment variable {0} is invalid. Names must not be empty or contain =.",
+                    name
+                )
+                .into(),
+            );
+            err.add_param("name".into(), name);
+
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_ciphers(ciphers: &Vec<String>) -> Result<(), ValidationError> {
+    for cipher in ciphers {
+        if cipher.is_empty() || cipher.contains(|c: char| c == ',' || c.is_whitespace()) {
+            let mut err = ValidationError::new("invalid_cipher").with_message(
+                format!(
+                    "The cipher {0} is invalid. Each cipher needs to be provided separately (not separated by commas).",
+                    cipher
+                )
+                .into(),
+            );
+            err.add_param("cipher".into(), cipher);
+
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_sub_mounts(sub_mounts: &[SubMount]) -> Result<(), ValidationError> {
+    for (idx, sub_mount) in sub_mounts.iter().enumerate() {
+        if sub_mounts[..idx]
+            .iter()
+            .any(|other| other.directory == sub_mount.directory)
+        {
+            let mut err = ValidationError::new("duplicate_sub_mount_directory").with_message(
+                format!(
+                    "The directory {0} is used by more than one sub-mount.",
+                    sub_mount.directory
+                )
+                .into(),
+            );
+            err.add_param("directory".into(), &sub_mount.directory);
+
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates the rules which concern multiple fields.
+fn validate_schema(entity: &&FilesystemMountDefinition) -> Result<(), ValidationError> {
+    validate_ssh_key_for_publickey_auth(entity)?;
+    validate_certificate_file(entity)?;
+    validate_password_source(entity)?;
+    validate_gssapi_options(entity)?;
+    validate_proxy(entity)?;
+    validate_ionice_level(entity)
+}
+
 fn validate_ssh_key_for_publickey_auth(
     entity: &&FilesystemMountDefinition,
 ) -> Result<(), ValidationError> {
@@ -294,3 +1384,96 @@ fn validate_ssh_key_for_publickey_auth(
         _ => Ok(()),
     }
 }
+
+fn validate_certificate_file(entity: &&FilesystemMountDefinition) -> Result<(), ValidationError> {
+    if entity.certificate_file.is_some() && entity.auth_type != AuthType::PublicKey {
+        return Err(
+            ValidationError::new("certificate_without_publickey_auth").with_message(
+                format!(
+                    "A certificate file can only be used with the {0} authentication type (along with the SSH key it was issued for).",
+                    AuthType::PublicKey,
+                )
+                .into(),
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+fn validate_password_source(entity: &&FilesystemMountDefinition) -> Result<(), ValidationError> {
+    let sources_count = [
+        &entity.password_command,
+        &entity.password_env,
+        &entity.password_file,
+    ]
+    .iter()
+    .filter(|source| source.is_some())
+    .count();
+
+    if sources_count > 1 {
+        return Err(
+            ValidationError::new("multiple_password_sources").with_message(
+                "Only one of the password command, password environment variable or password file can be provided."
+                    .into(),
+            ),
+        );
+    }
+
+    if sources_count == 1 && entity.auth_type != AuthType::Password {
+        return Err(
+            ValidationError::new("password_source_without_password_auth").with_message(
+                format!(
+                    "A password source can only be used with the {0} authentication type.",
+                    AuthType::Password,
+                )
+                .into(),
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+fn validate_gssapi_options(entity: &&FilesystemMountDefinition) -> Result<(), ValidationError> {
+    if (entity.gssapi_delegate_credentials || entity.gssapi_key_exchange)
+        && entity.auth_type != AuthType::GSSAPIWithMic
+    {
+        return Err(
+            ValidationError::new("gssapi_options_without_gssapi_auth").with_message(
+                format!(
+                    "GSSAPI options can only be used with the {0} authentication type.",
+                    AuthType::GSSAPIWithMic,
+                )
+                .into(),
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+fn validate_proxy(entity: &&FilesystemMountDefinition) -> Result<(), ValidationError> {
+    if entity.proxy_command.is_some() && entity.socks_proxy.is_some() {
+        return Err(ValidationError::new("multiple_proxies")
+            .with_message("Only one of the proxy command or SOCKS proxy can be provided.".into()));
+    }
+
+    Ok(())
+}
+
+fn validate_ionice_level(entity: &&FilesystemMountDefinition) -> Result<(), ValidationError> {
+    if entity.ionice_level.is_some() && entity.ionice_class != Some(IoniceClass::BestEffort) {
+        return Err(
+            ValidationError::new("ionice_level_without_best_effort").with_message(
+                format!(
+                    "An ionice level can only be used with the {0} ionice class.",
+                    IoniceClass::BestEffort,
+                )
+                .into(),
+            ),
+        );
+    }
+
+    Ok(())
+}
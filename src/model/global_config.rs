@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::errors::SftpManError;
+use crate::utils::path_template::{PLACEHOLDERS, is_absolute_path_template, unknown_placeholders};
+
+/// Global (not specific to a filesystem definition) configuration.
+///
+/// It's stored in the config directory (e.g. `~/.config/sftpman/config.json`).
+/// All fields are optional in the stored file and fall back to their default values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlobalConfig {
+    /// How long to wait (in milliseconds) after asking the `sshfs` process to terminate (`SIGTERM`),
+    /// before checking if it's gone.
+    ///
+    /// The process is actually checked frequently, so unmounting completes as soon as it exits.
+    /// This only counts towards the time it gets before being killed forcefully (see `kill_grace_period()`).
+    #[serde(rename = "killWaitBeforeDeadCheckMillis")]
+    pub kill_wait_before_dead_check_millis: u64,
+
+    /// How long to wait (in milliseconds) before killing the `sshfs` process forcefully (`SIGKILL`),
+    /// if it's still alive after asking it to terminate (and after `kill_wait_before_dead_check_millis`).
+    #[serde(rename = "killWaitBeforeForceKillMillis")]
+    pub kill_wait_before_force_kill_millis: u64,
+
+    /// Tells whether `sshfs` should be launched in a transient systemd scope unit (via `systemd-run --user --scope`),
+    /// so that each mount gets tracked as its own unit (e.g. `sftpman-my-fs.scope`).
+    ///
+    /// This only takes effect when a systemd user manager is available.
+    #[serde(rename = "useSystemdScope")]
+    pub use_systemd_scope: bool,
+
+    /// A git remote (e.g. `git@example.com:me/sftpman-definitions.git`) to synchronize the definitions with (see `Manager::sync()`).
+    #[serde(rename = "syncGitRemote")]
+    pub sync_git_remote: Option<String>,
+
+    /// The branch of `sync_git_remote` to synchronize the definitions with.
+    #[serde(rename = "syncGitBranch")]
+    pub sync_git_branch: String,
+
+    /// The permissions (e.g. `0o700`) to give to mount directories (and their parents) which get created when mounting.
+    ///
+    /// This is stored as an octal string (e.g. `"0700"`). When not set, directories get created according to the umask.
+    #[serde(rename = "mountDirMode", with = "octal_mode")]
+    pub mount_dir_mode: Option<u32>,
+
+    /// The template for the local mount path of definitions which don't specify one (see `FilesystemMountDefinition::mount_dest_path`),
+    /// so that a convention (e.g. `~/mounts/{host}/{id}`) can be set once, instead of for each definition.
+    ///
+    /// When not set, definitions get mounted under `DEFAULT_MOUNT_PATH_PREFIX` (e.g. `/mnt/sshfs/{id}`).
+    #[serde(
+        rename = "mountPathTemplate",
+        deserialize_with = "deserialize_mount_path_template"
+    )]
+    pub mount_path_template: Option<String>,
+
+    /// Tells whether the host of a definition should be resolved right before running `sshfs`, so that mounting fails right away
+    /// with a clear error (see `SftpManError::HostResolutionFailed`), instead of `sshfs` failing after the connection timeout.
+    ///
+    /// Definitions connecting through a proxy (their own or one from `~/.ssh/config`) are not resolved,
+    /// as their host may only be resolvable by the proxy. Others can opt out via `FilesystemMountDefinition::skip_host_resolution`.
+    #[serde(rename = "resolveHostsBeforeMount")]
+    pub resolve_hosts_before_mount: bool,
+
+    /// The `ssh` program (e.g. `/opt/openssh/bin/ssh`) to connect with for definitions which don't specify one
+    /// (see `FilesystemMountDefinition::ssh_binary`).
+    ///
+    /// When not set, the `ssh` found in `PATH` is used.
+    #[serde(rename = "sshBinary", deserialize_with = "deserialize_ssh_binary")]
+    pub ssh_binary: Option<String>,
+}
+
+impl Default for GlobalConfig {
+    fn default() -> Self {
+        Self {
+            kill_wait_before_dead_check_millis: 500,
+            kill_wait_before_force_kill_millis: 2000,
+            use_systemd_scope: false,
+            sync_git_remote: None,
+            sync_git_branch: "main".to_owned(),
+            mount_dir_mode: None,
+            mount_path_template: None,
+            resolve_hosts_before_mount: false,
+            ssh_binary: None,
+        }
+    }
+}
+
+impl GlobalConfig {
+    pub fn from_json_string(contents: &str) -> Result<Self, serde_json::Error> {
+        #[cfg(feature = "jsonc")]
+        let contents = &crate::utils::jsonc::to_plain_json(contents);
+
+        let deserialized: Self = serde_json::from_str(contents)?;
+        Ok(deserialized)
+    }
+
+    pub fn to_json_string(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Returns all settings (keyed by their name in the stored file, e.g. `useSystemdScope`), in alphabetical order.
+    pub fn settings(&self) -> Vec<(String, serde_json::Value)> {
+        let serde_json::Value::Object(map) =
+            serde_json::to_value(self).expect("GlobalConfig should serialize to a JSON object")
+        else {
+            unreachable!("GlobalConfig should serialize to a JSON object");
+        };
+
+        let mut list: Vec<(String, serde_json::Value)> = map.into_iter().collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+
+        list
+    }
+
+    /// Returns the value of the setting with the given name (e.g. `useSystemdScope`).
+    pub fn get(&self, key: &str) -> Result<serde_json::Value, SftpManError> {
+        self.settings()
+            .into_iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+            .ok_or_else(|| SftpManError::UnknownConfigSetting(key.to_owned()))
+    }
+
+    /// Changes the setting with the given name (e.g. `useSystemdScope`).
+    ///
+    /// The value is parsed as JSON (e.g. `true`, `500`), falling back to treating it as a plain string,
+    /// and is rejected if it doesn't match the setting's type.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), SftpManError> {
+        let mut settings: serde_json::Map<String, serde_json::Value> =
+            self.settings().into_iter().collect();
+
+        if !settings.contains_key(key) {
+            return Err(SftpManError::UnknownConfigSetting(key.to_owned()));
+        }
+
+        let value = serde_json::from_str(value)
+            .unwrap_or_else(|_| serde_json::Value::String(value.to_owned()));
+
+        settings.insert(key.to_owned(), value);
+
+        *self = serde_json::from_value(serde_json::Value::Object(settings))
+            .map_err(|err| SftpManError::InvalidConfigValue(key.to_owned(), err))?;
+
+        Ok(())
+    }
+
+    pub fn kill_wait_before_dead_check(&self) -> Duration {
+        Duration::from_millis(self.kill_wait_before_dead_check_millis)
+    }
+
+    pub fn kill_wait_before_force_kill(&self) -> Duration {
+        Duration::from_millis(self.kill_wait_before_force_kill_millis)
+    }
+
+    /// Returns how long a process asked to terminate (`SIGTERM`) gets to exit, before being killed forcefully (`SIGKILL`).
+    ///
+    /// This is `kill_wait_before_dead_check_millis` and `kill_wait_before_force_kill_millis` combined.
+    pub fn kill_grace_period(&self) -> Duration {
+        self.kill_wait_before_dead_check() + self.kill_wait_before_force_kill()
+    }
+}
+
+/// Deserializes `GlobalConfig::mount_path_template`, rejecting templates which would not lead to an absolute path
+/// or which contain unknown placeholders.
+fn deserialize_mount_path_template<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let Some(template) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    if !is_absolute_path_template(&template) {
+        return Err(serde::de::Error::custom(format!(
+            "expected an absolute path (or one starting with ~/ or an environment variable), got: {0}",
+            template
+        )));
+    }
+
+    let unknown = unknown_placeholders(&template);
+    if !unknown.is_empty() {
+        return Err(serde::de::Error::custom(format!(
+            "unknown placeholders: {0} (supported: {1})",
+            unknown.join(", "),
+            PLACEHOLDERS.map(|name| format!("{{{0}}}", name)).join(", ")
+        )));
+    }
+
+    Ok(Some(template))
+}
+
+/// Deserializes `GlobalConfig::ssh_binary`, rejecting empty values.
+fn deserialize_ssh_binary<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let binary = Option::<String>::deserialize(deserializer)?;
+
+    if binary.as_deref() == Some("") {
+        return Err(serde::de::Error::custom(
+            "expected a program name or path, got an empty string",
+        ));
+    }
+
+    Ok(binary)
+}
+
+/// (De)serializes file permissions as octal strings (e.g. `"0700"`), the way they're usually written.
+///
+/// Plain numbers (e.g. `700`, as `config set mountDirMode 700` gives) are accepted as well and are read as octal too.
+mod octal_mode {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(mode: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+        match mode {
+            Some(mode) => serializer.serialize_str(&format!("{0:04o}", mode)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u32>, D::Error> {
+        let value = match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Null => return Ok(None),
+            serde_json::Value::String(value) => value,
+            serde_json::Value::Number(value) => value.to_string(),
+            other => {
+                return Err(serde::de::Error::custom(format!(
+                    "expected an octal mode (e.g. \"0700\"), got: {0}",
+                    other
+                )));
+            }
+        };
+
+        u32::from_str_radix(&value, 8)
+            .ok()
+            .filter(|mode| *mode <= 0o7777)
+            .map(Some)
+            .ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "expected an octal mode (e.g. \"0700\"), got: {0}",
+                    value
+                ))
+            })
+    }
+}
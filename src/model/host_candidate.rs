@@ -0,0 +1,21 @@
+use serde::Serialize;
+
+/// A host which is likely to be connected to, as discovered from the SSH configuration (see `Manager::discover_hosts()`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HostCandidate {
+    /// The name the host is known by (e.g. a `Host` alias in `~/.ssh/config` or a host name in `~/.ssh/known_hosts`).
+    pub name: String,
+
+    /// The host name or IP address to connect to (`HostName` in `~/.ssh/config`, falling back to `name`).
+    pub host: String,
+
+    /// The user to authenticate as, if one is configured.
+    pub user: Option<String>,
+
+    /// The SSH port, if one other than the default is known.
+    pub port: Option<u16>,
+
+    /// Tells whether ssh connects to the host through a proxy (`ProxyCommand` or `ProxyJump` in `~/.ssh/config`),
+    /// in which case the host may only be resolvable by the proxy.
+    pub proxied: bool,
+}
@@ -0,0 +1,19 @@
+use serde::Serialize;
+
+/// A public key presented by a remote SSH server (see `Manager::scan_host_keys()`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HostKey {
+    /// The key type (e.g. `ssh-ed25519`).
+    #[serde(rename = "keyType")]
+    pub key_type: String,
+
+    /// The key's fingerprint (e.g. `SHA256:...`), as `ssh-keygen -l` shows it.
+    pub fingerprint: String,
+
+    /// The line to add to a `known_hosts` file (e.g. `[example.com]:2222 ssh-ed25519 AAAA...`).
+    #[serde(rename = "knownHostsLine")]
+    pub known_hosts_line: String,
+
+    /// Tells whether the key is already listed in the `known_hosts` file in use.
+    pub known: bool,
+}
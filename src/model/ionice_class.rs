@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// An I/O scheduling class to run `sshfs` with (see `FilesystemMountDefinition::ionice_class`).
+///
+/// The realtime class is not offered, as it requires elevated privileges.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum IoniceClass {
+    /// The default class, whose priority can be tuned (see `FilesystemMountDefinition::ionice_level`).
+    #[serde(rename = "best-effort")]
+    BestEffort,
+
+    /// Only gets disk time when no other process needs it.
+    #[serde(rename = "idle")]
+    Idle,
+}
+
+impl IoniceClass {
+    pub fn to_static_str(&self) -> &'static str {
+        match &self {
+            Self::BestEffort => "best-effort",
+            Self::Idle => "idle",
+        }
+    }
+
+    pub fn from_string(s: &str) -> Result<Self, &'static str> {
+        match s {
+            "best-effort" => Ok(Self::BestEffort),
+            "idle" => Ok(Self::Idle),
+            _ => Err("Unexpected string value"),
+        }
+    }
+
+    /// Returns the number of the class, as expected by `ionice -c`.
+    pub(crate) fn number(&self) -> u8 {
+        match &self {
+            Self::BestEffort => 2,
+            Self::Idle => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for IoniceClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{0}", self.to_static_str())
+    }
+}
@@ -0,0 +1,51 @@
+use super::TerminationSignal;
+
+/// Controls whether the `sshfs` process responsible for a mount (or the processes using the mount) get killed while unmounting.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum KillPolicy {
+    /// Unmounting is attempted via `fusermount3 -u ..` (or `fusermount -u ..`) first.
+    /// If that fails (e.g. the filesystem is busy), the `sshfs` process gets killed.
+    #[default]
+    Fallback,
+
+    /// Unmounting is only attempted via `fusermount3 -u ..` (or `fusermount -u ..`).
+    /// If that fails (e.g. the filesystem is busy), unmounting fails.
+    Never,
+
+    /// Unmounting is performed by killing the `sshfs` process right away, without trying `fusermount3 -u ..` first.
+    Immediate,
+
+    /// The processes using the filesystem (see `Manager::blocking_processes()`) get sent the given signal
+    /// and are waited for to exit, so that unmounting via `fusermount3 -u ..` (or `fusermount -u ..`) can succeed.
+    /// If it still fails, unmounting fails (the `sshfs` process is not killed).
+    TerminateUsers(TerminationSignal),
+}
+
+impl KillPolicy {
+    pub fn to_static_str(&self) -> &'static str {
+        match &self {
+            Self::Fallback => "fallback",
+            Self::Never => "never",
+            Self::Immediate => "immediate",
+            Self::TerminateUsers(_) => "terminate_users",
+        }
+    }
+
+    /// Parses a policy name (see `to_static_str()`). The signal is not part of the name,
+    /// so `terminate_users` results in `TerminateUsers` with the default signal (`TerminationSignal::Term`).
+    pub fn from_string(s: &str) -> Result<Self, &'static str> {
+        match s {
+            "fallback" => Ok(Self::Fallback),
+            "never" => Ok(Self::Never),
+            "immediate" => Ok(Self::Immediate),
+            "terminate_users" => Ok(Self::TerminateUsers(TerminationSignal::default())),
+            _ => Err("Unexpected string value"),
+        }
+    }
+}
+
+impl std::fmt::Display for KillPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{0}", self.to_static_str())
+    }
+}
@@ -1,5 +1,64 @@
+mod blocking_process;
+mod bulk_summary;
+mod capabilities;
+mod definition_builder;
+mod definition_check;
+mod definition_filter;
+mod extra_mount_path_method;
+mod field_validation_error;
 mod filesystem_mount_definition;
+mod global_config;
+mod host_candidate;
+mod host_key;
+mod ionice_class;
+mod kill_policy;
+mod mount_backend;
+mod mount_failure_report;
+mod mount_outcome;
+mod mount_path;
+mod mount_plan;
+mod mount_requirement;
 mod mount_state;
+mod persist_outcome;
+mod probe_outcome;
+mod profile;
+mod sub_mount;
+mod sync_outcome;
+mod termination_signal;
+mod umount_outcome;
+mod usage_stats;
+mod verification_outcome;
 
+pub use blocking_process::BlockingProcess;
+pub use bulk_summary::{BulkSummary, BulkSummaryEntry};
+pub use capabilities::Capabilities;
+pub use definition_builder::FilesystemMountDefinitionBuilder;
+pub use definition_check::{DefinitionCheck, DefinitionProblem};
+pub use definition_filter::DefinitionFilter;
+pub use extra_mount_path_method::ExtraMountPathMethod;
+pub use field_validation_error::FieldValidationError;
+pub(crate) use filesystem_mount_definition::{
+    BANDWIDTH_LIMITER, BIND_MOUNTER, DEFAULT_SSH_BINARY, SSH_DEFAULT_TIMEOUT,
+};
 pub use filesystem_mount_definition::{DEFAULT_MOUNT_PATH_PREFIX, FilesystemMountDefinition};
+pub use global_config::GlobalConfig;
+pub use host_candidate::HostCandidate;
+pub use host_key::HostKey;
+pub use ionice_class::IoniceClass;
+pub use kill_policy::KillPolicy;
+pub use mount_backend::{MOUNT_BACKEND_ENV_VAR, MountBackend};
+pub use mount_failure_report::{MountFailureReport, MountStep, MountStepFailure};
+pub use mount_outcome::MountOutcome;
+pub use mount_path::MountPath;
+pub use mount_plan::{MountPlan, PlannedStep, PlannedStepPurpose};
+pub use mount_requirement::MountRequirement;
 pub use mount_state::MountState;
+pub use persist_outcome::PersistOutcome;
+pub use probe_outcome::ProbeOutcome;
+pub use profile::{DEFAULT_PROFILE, PROFILE_ENV_VAR, Profile};
+pub use sub_mount::SubMount;
+pub use sync_outcome::SyncOutcome;
+pub use termination_signal::TerminationSignal;
+pub use umount_outcome::UmountOutcome;
+pub use usage_stats::UsageStats;
+pub use verification_outcome::VerificationOutcome;
@@ -0,0 +1,39 @@
+/// The environment variable selecting the mount backend (e.g. `SFTPMAN_MOUNT_BACKEND=simulate`) for managers created via `Manager::new()`.
+pub const MOUNT_BACKEND_ENV_VAR: &str = "SFTPMAN_MOUNT_BACKEND";
+
+/// Controls how a `Manager` mounts and unmounts filesystems (see `Manager::with_mount_backend()`).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MountBackend {
+    /// Mounting runs `sshfs` and unmounting runs `fusermount3 -u ..` (or `fusermount -u ..`).
+    #[default]
+    Sshfs,
+
+    /// Mounting is merely pretended (for demos, GUI development and CI environments without FUSE or a remote machine):
+    /// the local mount path gets created and the filesystem is recorded as mounted, without running anything.
+    ///
+    /// The recorded mounts are kept in memory, so they're only known to the manager (and its clones) which made them.
+    Simulate,
+}
+
+impl MountBackend {
+    pub fn to_static_str(&self) -> &'static str {
+        match &self {
+            Self::Sshfs => "sshfs",
+            Self::Simulate => "simulate",
+        }
+    }
+
+    pub fn from_string(s: &str) -> Result<Self, &'static str> {
+        match s {
+            "sshfs" => Ok(Self::Sshfs),
+            "simulate" => Ok(Self::Simulate),
+            _ => Err("Unexpected string value"),
+        }
+    }
+}
+
+impl std::fmt::Display for MountBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{0}", self.to_static_str())
+    }
+}
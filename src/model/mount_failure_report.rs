@@ -0,0 +1,103 @@
+use crate::errors::SftpManError;
+
+/// A step performed while mounting (see `Manager::mount_with_report()`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MountStep {
+    /// Getting ready to mount (e.g. obtaining the password, checking the SSH agent, creating the local mount path).
+    Preparation,
+
+    /// Running the before-mount command (see `FilesystemMountDefinition::cmd_before_mount`).
+    BeforeMount,
+
+    /// Resolving the host right before running `sshfs` (see `GlobalConfig::resolve_hosts_before_mount`).
+    HostResolution,
+
+    /// Running `sshfs`.
+    Sshfs,
+
+    /// Checking that the filesystem actually got mounted after `sshfs` reported success.
+    Verification,
+
+    /// Exposing the mounted filesystem at its extra mount paths (see `FilesystemMountDefinition::extra_mount_paths`).
+    ExtraMountPaths,
+
+    /// Unmounting and cleaning up after one of the other steps failed.
+    Cleanup,
+}
+
+impl std::fmt::Display for MountStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::Preparation => "preparation",
+            Self::BeforeMount => "before-mount command",
+            Self::HostResolution => "host resolution",
+            Self::Sshfs => "sshfs",
+            Self::Verification => "verification",
+            Self::ExtraMountPaths => "extra mount paths",
+            Self::Cleanup => "cleanup",
+        };
+
+        write!(f, "{0}", description)
+    }
+}
+
+/// A step which failed while mounting, along with its error.
+#[derive(Debug)]
+pub struct MountStepFailure {
+    pub step: MountStep,
+
+    pub error: SftpManError,
+}
+
+/// Tells what went wrong when mounting a definition (see `Manager::mount_with_report()`).
+///
+/// The first failure is what made mounting fail. Any others happened while cleaning up after it.
+#[derive(Debug)]
+pub struct MountFailureReport {
+    /// The ID of the definition that failed to mount.
+    pub id: String,
+
+    /// The failed steps, in the order they happened (never empty).
+    pub failures: Vec<MountStepFailure>,
+}
+
+impl MountFailureReport {
+    pub(crate) fn new(id: &str, step: MountStep, error: SftpManError) -> Self {
+        Self {
+            id: id.to_owned(),
+            failures: vec![MountStepFailure { step, error }],
+        }
+    }
+
+    pub(crate) fn push(&mut self, step: MountStep, error: SftpManError) {
+        self.failures.push(MountStepFailure { step, error });
+    }
+
+    /// Returns the failure which made mounting fail.
+    pub fn cause(&self) -> &MountStepFailure {
+        self.failures
+            .first()
+            .expect("A report should contain at least one failure")
+    }
+
+    /// Returns the error which made mounting fail, dropping the rest of the report.
+    pub fn into_error(self) -> SftpManError {
+        self.failures
+            .into_iter()
+            .next()
+            .expect("A report should contain at least one failure")
+            .error
+    }
+}
+
+impl std::fmt::Display for MountFailureReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self
+            .failures
+            .iter()
+            .map(|failure| format!("{0} failed: {1}", failure.step, failure.error))
+            .collect();
+
+        write!(f, "{0}", parts.join("; "))
+    }
+}
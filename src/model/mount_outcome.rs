@@ -0,0 +1,38 @@
+/// What mounting a definition did (see `Manager::mount()`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MountOutcome {
+    /// The filesystem was mounted already, so nothing was done.
+    AlreadyMounted,
+
+    /// The filesystem got mounted.
+    Mounted,
+}
+
+impl MountOutcome {
+    /// Returns a stable, machine-readable name for the outcome (e.g. `already_mounted`).
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::AlreadyMounted => "already_mounted",
+            Self::Mounted => "mounted",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Result<Self, &'static str> {
+        match code {
+            "already_mounted" => Ok(Self::AlreadyMounted),
+            "mounted" => Ok(Self::Mounted),
+            _ => Err("Unexpected string value"),
+        }
+    }
+}
+
+impl std::fmt::Display for MountOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::AlreadyMounted => "already mounted",
+            Self::Mounted => "mounted",
+        };
+
+        write!(f, "{0}", description)
+    }
+}
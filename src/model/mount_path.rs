@@ -0,0 +1,65 @@
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// The local path a definition gets mounted at (see `FilesystemMountDefinition::local_mount_path()`).
+///
+/// It dereferences to `Path`, so it can be passed wherever a path is expected (e.g. to `Command::arg()`).
+/// Being a path, it compares equal to other forms of the same path (e.g. `/mnt/sshfs/my-fs/` and `/mnt/sshfs/my-fs`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct MountPath(PathBuf);
+
+impl MountPath {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self(path.into())
+    }
+
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn into_path_buf(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl Deref for MountPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for MountPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<OsStr> for MountPath {
+    fn as_ref(&self) -> &OsStr {
+        self.0.as_os_str()
+    }
+}
+
+impl From<PathBuf> for MountPath {
+    fn from(value: PathBuf) -> Self {
+        Self(value)
+    }
+}
+
+impl From<MountPath> for PathBuf {
+    fn from(value: MountPath) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for MountPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{0}", self.0.display())
+    }
+}
@@ -0,0 +1,65 @@
+use std::process::Command;
+
+/// Tells what a planned step is for (see `PlannedStep`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlannedStepPurpose {
+    /// Running the before-mount command (see `FilesystemMountDefinition::cmd_before_mount`).
+    BeforeMount,
+
+    /// Running `sshfs` (possibly wrapped, e.g. in a systemd scope), which mounts the filesystem.
+    Mount,
+
+    /// Bind-mounting the filesystem at an extra mount path (see `ExtraMountPathMethod::Bind`).
+    BindMount,
+
+    /// Unmounting the filesystem (e.g. via `fusermount3 -u ..`).
+    Unmount,
+}
+
+impl std::fmt::Display for PlannedStepPurpose {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::BeforeMount => "before-mount command",
+            Self::Mount => "mount",
+            Self::BindMount => "bind mount",
+            Self::Unmount => "unmount",
+        };
+
+        write!(f, "{0}", description)
+    }
+}
+
+/// A command which would be run, as part of a `MountPlan`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedStep {
+    pub program: String,
+
+    pub args: Vec<String>,
+
+    pub purpose: PlannedStepPurpose,
+}
+
+impl PlannedStep {
+    pub(crate) fn new(cmd: &Command, purpose: PlannedStepPurpose) -> Self {
+        Self {
+            program: cmd.get_program().to_string_lossy().to_string(),
+            args: cmd
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect(),
+            purpose,
+        }
+    }
+}
+
+/// The commands that mounting (see `Manager::plan_mount()`) or unmounting (see `Manager::plan_umount()`)
+/// a definition would run, in order.
+///
+/// Planning doesn't execute anything, so plans are suitable for showing to the user before doing the real thing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MountPlan {
+    /// The ID of the definition the plan is for.
+    pub id: String,
+
+    pub steps: Vec<PlannedStep>,
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// A condition which needs to be met for a filesystem to be mounted (see `FilesystemMountDefinition::requires`),
+/// like a VPN being connected.
+///
+/// Requirements are stored as strings (e.g. `interface:wg0` or `reachable:10.0.0.1:22`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum MountRequirement {
+    /// The network interface with the given name (e.g. `wg0`) needs to be up.
+    InterfaceUp(String),
+
+    /// A TCP connection needs to be possible to the given address (e.g. `10.0.0.1:22`).
+    Reachable(String),
+}
+
+impl MountRequirement {
+    pub fn from_string(s: &str) -> Result<Self, &'static str> {
+        let (kind, value) = s
+            .split_once(':')
+            .ok_or("Expected interface:NAME or reachable:HOST:PORT")?;
+
+        if value.is_empty() {
+            return Err("Expected interface:NAME or reachable:HOST:PORT");
+        }
+
+        match kind {
+            "interface" => Ok(Self::InterfaceUp(value.to_owned())),
+            "reachable" => match value.rsplit_once(':') {
+                Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                    Ok(Self::Reachable(value.to_owned()))
+                }
+                _ => Err("Expected reachable:HOST:PORT"),
+            },
+            _ => Err("Expected interface:NAME or reachable:HOST:PORT"),
+        }
+    }
+
+    /// Describes what's wrong when the requirement is not met (e.g. `interface wg0 is down`).
+    pub fn unmet_description(&self) -> String {
+        match self {
+            Self::InterfaceUp(name) => format!("interface {0} is down", name),
+            Self::Reachable(address) => format!("{0} is unreachable", address),
+        }
+    }
+}
+
+impl TryFrom<String> for MountRequirement {
+    type Error = &'static str;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_string(&value)
+    }
+}
+
+impl From<MountRequirement> for String {
+    fn from(value: MountRequirement) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for MountRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InterfaceUp(name) => write!(f, "interface:{0}", name),
+            Self::Reachable(address) => write!(f, "reachable:{0}", address),
+        }
+    }
+}
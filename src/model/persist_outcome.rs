@@ -0,0 +1,26 @@
+use crate::errors::SftpManError;
+
+/// The outcome of persisting a definition (see `Manager::persist()`).
+///
+/// Getting an outcome means that the definition was written. A definition which was mounted gets unmounted
+/// before writing and remounted after, both of which may fail without preventing the definition from being written.
+#[derive(Debug)]
+pub struct PersistOutcome {
+    /// The revision of the written definition (see `FilesystemMountDefinition::revision`),
+    /// for persisting further edits to the same definition.
+    pub revision: u64,
+
+    /// Tells whether the definition was mounted when it got persisted.
+    pub was_mounted: bool,
+
+    /// Tells whether the definition was unmounted and then successfully mounted again (with the new settings).
+    pub was_remounted: bool,
+
+    /// The error which prevented unmounting before writing (if any).
+    /// In this case, remounting is not attempted and the filesystem stays mounted with the old settings.
+    pub umount_error: Option<SftpManError>,
+
+    /// The error which prevented remounting after writing (if any).
+    /// In this case, the filesystem is no longer mounted.
+    pub remount_error: Option<SftpManError>,
+}
@@ -0,0 +1,54 @@
+/// The outcome of probing a mounted filesystem for health (see `Manager::probe()`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbeOutcome {
+    /// The mount responded to all probe operations.
+    Healthy,
+
+    /// The filesystem is not mounted, so there was nothing to probe.
+    NotMounted,
+
+    /// A probe operation failed (e.g. with "Transport endpoint is not connected").
+    Failed(String),
+
+    /// A probe operation did not complete in time, which usually means that the connection is dead
+    /// but `sshfs` hasn't noticed yet.
+    Unresponsive,
+}
+
+impl ProbeOutcome {
+    /// Returns a stable, machine-readable name for the outcome (e.g. `unresponsive`).
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::NotMounted => "not_mounted",
+            Self::Failed(_) => "failed",
+            Self::Unresponsive => "unresponsive",
+        }
+    }
+
+    /// Returns the details (e.g. the error of the failing operation), if there are any.
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            Self::Failed(details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Tells whether the filesystem is mounted, but does not work.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Failed(_) | Self::Unresponsive)
+    }
+}
+
+impl std::fmt::Display for ProbeOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::Healthy => "healthy",
+            Self::NotMounted => "not mounted",
+            Self::Failed(_) => "failed",
+            Self::Unresponsive => "unresponsive",
+        };
+
+        write!(f, "{0}", description)
+    }
+}
@@ -0,0 +1,18 @@
+use serde::Serialize;
+
+/// The environment variable selecting the profile (e.g. `SFTPMAN_PROFILE=work`) for managers created via `Manager::new()`,
+/// taking precedence over the profile switched to via `Manager::switch_profile()`.
+pub const PROFILE_ENV_VAR: &str = "SFTPMAN_PROFILE";
+
+/// The name of the profile whose definitions and global configuration are stored directly in the config directory
+/// (e.g. `~/.config/sftpman/mounts`), which is used unless another profile is selected.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A profile: a separate set of definitions, with its own global configuration (see `Manager::profiles()`).
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    pub name: String,
+
+    /// Whether this is the profile of the manager which listed it.
+    pub current: bool,
+}
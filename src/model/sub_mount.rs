@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// A remote path which gets mounted into a subdirectory of a definition's local mount path
+/// (see `FilesystemMountDefinition::sub_mounts`).
+///
+/// Sub-mounts are stored as strings (e.g. `/var/www:web`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SubMount {
+    /// Path on the remote server (e.g. `/var/www`).
+    pub remote_path: String,
+
+    /// Name of the subdirectory of the local mount path to mount it at (e.g. `web`).
+    pub directory: String,
+}
+
+impl SubMount {
+    pub fn from_string(s: &str) -> Result<Self, &'static str> {
+        let (remote_path, directory) =
+            s.rsplit_once(':').ok_or("Expected REMOTE_PATH:DIRECTORY")?;
+
+        if !remote_path.starts_with('/') {
+            return Err("The remote path must be absolute");
+        }
+
+        if directory.is_empty() || directory == "." || directory == ".." || directory.contains('/')
+        {
+            return Err("The directory must be a plain directory name (e.g. web)");
+        }
+
+        Ok(Self {
+            remote_path: remote_path.to_owned(),
+            directory: directory.to_owned(),
+        })
+    }
+}
+
+impl TryFrom<String> for SubMount {
+    type Error = &'static str;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_string(&value)
+    }
+}
+
+impl From<SubMount> for String {
+    fn from(value: SubMount) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for SubMount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{0}:{1}", self.remote_path, self.directory)
+    }
+}
@@ -0,0 +1,12 @@
+/// The outcome of synchronizing the definitions with a remote (see `Manager::sync()`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncOutcome {
+    /// Tells whether local changes to the definitions were recorded (committed).
+    pub committed: bool,
+
+    /// Tells whether changes made elsewhere were received (and merged into the local definitions).
+    pub received: bool,
+
+    /// Tells whether the resulting definitions were sent (pushed) to the remote.
+    pub pushed: bool,
+}
@@ -0,0 +1,44 @@
+/// The signal sent to the processes using a filesystem, when terminating them before unmounting (see `KillPolicy::TerminateUsers`).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TerminationSignal {
+    /// `SIGTERM`, which asks processes to exit (giving them a chance to save their work).
+    #[default]
+    Term,
+
+    /// `SIGINT`, like pressing Ctrl+C in the terminal a process runs in.
+    Int,
+
+    /// `SIGHUP`, like closing the terminal a process runs in.
+    Hup,
+
+    /// `SIGKILL`, which ends processes right away (they may lose data they were writing).
+    Kill,
+}
+
+impl TerminationSignal {
+    pub fn to_static_str(&self) -> &'static str {
+        match &self {
+            Self::Term => "TERM",
+            Self::Int => "INT",
+            Self::Hup => "HUP",
+            Self::Kill => "KILL",
+        }
+    }
+
+    /// Parses a signal name, with or without the `SIG` prefix (e.g. `TERM` or `SIGTERM`).
+    pub fn from_string(s: &str) -> Result<Self, &'static str> {
+        match s.strip_prefix("SIG").unwrap_or(s) {
+            "TERM" => Ok(Self::Term),
+            "INT" => Ok(Self::Int),
+            "HUP" => Ok(Self::Hup),
+            "KILL" => Ok(Self::Kill),
+            _ => Err("Unexpected string value"),
+        }
+    }
+}
+
+impl std::fmt::Display for TerminationSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SIG{0}", self.to_static_str())
+    }
+}
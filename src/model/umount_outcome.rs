@@ -0,0 +1,54 @@
+/// What unmounting a definition did (see `Manager::umount()`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UmountOutcome {
+    /// The filesystem was not mounted, so nothing was done.
+    AlreadyUnmounted,
+
+    /// The filesystem got unmounted (e.g. via `fusermount3 -u ..`).
+    UnmountedCleanly,
+
+    /// The filesystem got unmounted by killing its `sshfs` process (see `KillPolicy`),
+    /// either because it was requested or because unmounting cleanly failed (e.g. as the filesystem was busy).
+    /// Programs which were using the filesystem may have lost data they were writing.
+    UnmountedByKill,
+
+    /// The filesystem got unmounted cleanly, after terminating the processes which were using it (see `KillPolicy::TerminateUsers`).
+    UnmountedAfterTerminatingUsers,
+}
+
+impl UmountOutcome {
+    /// Returns a stable, machine-readable name for the outcome (e.g. `unmounted_by_kill`).
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::AlreadyUnmounted => "already_unmounted",
+            Self::UnmountedCleanly => "unmounted_cleanly",
+            Self::UnmountedByKill => "unmounted_by_kill",
+            Self::UnmountedAfterTerminatingUsers => "unmounted_after_terminating_users",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Result<Self, &'static str> {
+        match code {
+            "already_unmounted" => Ok(Self::AlreadyUnmounted),
+            "unmounted_cleanly" => Ok(Self::UnmountedCleanly),
+            "unmounted_by_kill" => Ok(Self::UnmountedByKill),
+            "unmounted_after_terminating_users" => Ok(Self::UnmountedAfterTerminatingUsers),
+            _ => Err("Unexpected string value"),
+        }
+    }
+}
+
+impl std::fmt::Display for UmountOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::AlreadyUnmounted => "already unmounted",
+            Self::UnmountedCleanly => "unmounted cleanly",
+            Self::UnmountedByKill => "unmounted by killing sshfs",
+            Self::UnmountedAfterTerminatingUsers => {
+                "unmounted after terminating the programs using it"
+            }
+        };
+
+        write!(f, "{0}", description)
+    }
+}
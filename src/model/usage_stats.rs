@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// How much a definition gets used (see `Manager::usage_stats()`).
+///
+/// This is tracked in the state directory (not in the definition itself), so it's local to the machine
+/// and doesn't make definitions change with every mount (e.g. when syncing them, see `Manager::sync()`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Unix timestamp (in seconds) of when the filesystem last got mounted.
+    #[serde(rename = "lastMountedAt")]
+    #[serde(default)]
+    pub last_mounted_at: Option<u64>,
+
+    /// Number of times the filesystem got mounted.
+    #[serde(rename = "mountCount")]
+    #[serde(default)]
+    pub mount_count: u64,
+}
@@ -0,0 +1,72 @@
+/// The outcome of verifying that a definition's remote machine can be connected to (see `Manager::verify()`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationOutcome {
+    /// Connecting, authenticating, changing to the remote path and listing it all worked.
+    Reachable,
+
+    /// The remote path was missing, but got created (see `Manager::verify_creating_remote_path()`), after which everything worked.
+    PathCreated,
+
+    /// The remote machine could not be connected to (e.g. the host name could not be resolved or the connection timed out).
+    Unreachable(String),
+
+    /// The remote machine was reached, but authentication (or host key verification) failed.
+    AuthFailed(String),
+
+    /// Authentication worked, but the remote path does not exist (or can't be entered).
+    PathMissing(String),
+
+    /// The remote path exists, but can't be listed (e.g. due to its permissions).
+    PathUnreadable(String),
+
+    /// The definition can't be verified non-interactively (e.g. because it uses password authentication).
+    Skipped(String),
+}
+
+impl VerificationOutcome {
+    /// Returns a stable, machine-readable name for the outcome (e.g. `auth_failed`).
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Reachable => "reachable",
+            Self::PathCreated => "path_created",
+            Self::Unreachable(_) => "unreachable",
+            Self::AuthFailed(_) => "auth_failed",
+            Self::PathMissing(_) => "path_missing",
+            Self::PathUnreadable(_) => "path_unreadable",
+            Self::Skipped(_) => "skipped",
+        }
+    }
+
+    /// Returns the details (e.g. the error reported by `sftp`), if there are any.
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            Self::Reachable | Self::PathCreated => None,
+            Self::Unreachable(details)
+            | Self::AuthFailed(details)
+            | Self::PathMissing(details)
+            | Self::PathUnreadable(details)
+            | Self::Skipped(details) => Some(details),
+        }
+    }
+
+    /// Tells whether the outcome indicates a problem with the definition (as opposed to it working or not being verified).
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, Self::Reachable | Self::PathCreated | Self::Skipped(_))
+    }
+}
+
+impl std::fmt::Display for VerificationOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::Reachable => "reachable",
+            Self::PathCreated => "path created",
+            Self::Unreachable(_) => "unreachable",
+            Self::AuthFailed(_) => "auth failed",
+            Self::PathMissing(_) => "path missing",
+            Self::PathUnreadable(_) => "path unreadable",
+            Self::Skipped(_) => "skipped",
+        };
+
+        write!(f, "{0}", description)
+    }
+}
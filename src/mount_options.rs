@@ -0,0 +1,492 @@
+/// The kind of value that a mount option (see `MountOption`) takes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MountOptionValueType {
+    /// The option takes no value (e.g. `reconnect`).
+    Flag,
+
+    /// The option takes a non-negative integer (e.g. `max_conns=4`).
+    Integer,
+
+    /// The option takes arbitrary text (e.g. `fsname=my-fs`).
+    Text,
+
+    /// The option takes one of the given values (e.g. `idmap=user`).
+    OneOf(&'static [&'static str]),
+
+    /// The option takes a colon-separated list of the given values (e.g. `workaround=rename:truncate`).
+    ListOf(&'static [&'static str]),
+}
+
+/// A known `sshfs` (or FUSE) mount option, as passed via `-o` (see `FilesystemMountDefinition::mount_options`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MountOption {
+    pub name: &'static str,
+    pub value_type: MountOptionValueType,
+    pub description: &'static str,
+}
+
+const YES_NO: &[&str] = &["yes", "no"];
+
+/// Known `sshfs` and FUSE mount options.
+///
+/// Options starting with an uppercase letter (e.g. `ServerAliveInterval=15`) are passed on to `ssh` by `sshfs`.
+/// Only a few common ones are listed here, but any such option is considered valid (see `check_mount_options()`).
+pub const KNOWN_MOUNT_OPTIONS: &[MountOption] = &[
+    // sshfs
+    MountOption {
+        name: "reconnect",
+        value_type: MountOptionValueType::Flag,
+        description: "Reconnect to the server if the connection is interrupted",
+    },
+    MountOption {
+        name: "delay_connect",
+        value_type: MountOptionValueType::Flag,
+        description: "Delay connecting to the server until the filesystem is first accessed",
+    },
+    MountOption {
+        name: "follow_symlinks",
+        value_type: MountOptionValueType::Flag,
+        description: "Follow symlinks on the server (showing their targets instead)",
+    },
+    MountOption {
+        name: "transform_symlinks",
+        value_type: MountOptionValueType::Flag,
+        description: "Transform absolute symlinks into relative ones",
+    },
+    MountOption {
+        name: "no_check_root",
+        value_type: MountOptionValueType::Flag,
+        description: "Don't check for the existence of the remote path when mounting",
+    },
+    MountOption {
+        name: "disable_hardlink",
+        value_type: MountOptionValueType::Flag,
+        description: "Make link(2) return an error, instead of creating hard links",
+    },
+    MountOption {
+        name: "workaround",
+        value_type: MountOptionValueType::ListOf(&[
+            "none",
+            "all",
+            "rename",
+            "renamexdev",
+            "truncate",
+            "buflimit",
+            "fstat",
+            "createmode",
+        ]),
+        description: "Enable workarounds for server bugs (e.g. rename, truncate)",
+    },
+    MountOption {
+        name: "idmap",
+        value_type: MountOptionValueType::OneOf(&["none", "user", "file"]),
+        description: "How to translate user/group ids between the server and the local machine",
+    },
+    MountOption {
+        name: "uidfile",
+        value_type: MountOptionValueType::Text,
+        description: "File containing `username:uid` mappings (for idmap=file)",
+    },
+    MountOption {
+        name: "gidfile",
+        value_type: MountOptionValueType::Text,
+        description: "File containing `groupname:gid` mappings (for idmap=file)",
+    },
+    MountOption {
+        name: "nomap",
+        value_type: MountOptionValueType::OneOf(&["ignore", "error"]),
+        description: "What to do with ids which cannot be mapped (for idmap=file)",
+    },
+    MountOption {
+        name: "dir_cache",
+        value_type: MountOptionValueType::OneOf(YES_NO),
+        description: "Enable caching of directory contents",
+    },
+    MountOption {
+        name: "dcache_timeout",
+        value_type: MountOptionValueType::Integer,
+        description: "Seconds to cache directory contents and attributes for",
+    },
+    MountOption {
+        name: "dcache_max_size",
+        value_type: MountOptionValueType::Integer,
+        description: "Maximum number of entries in the directory cache",
+    },
+    MountOption {
+        name: "max_conns",
+        value_type: MountOptionValueType::Integer,
+        description: "Maximum number of SSH connections to use",
+    },
+    MountOption {
+        name: "compression",
+        value_type: MountOptionValueType::OneOf(YES_NO),
+        description: "Enable SSH compression",
+    },
+    MountOption {
+        name: "ssh_command",
+        value_type: MountOptionValueType::Text,
+        description: "Command to run instead of ssh (set by sftpman itself)",
+    },
+    MountOption {
+        name: "sftp_server",
+        value_type: MountOptionValueType::Text,
+        description: "Path to the SFTP server on the remote machine",
+    },
+    MountOption {
+        name: "directport",
+        value_type: MountOptionValueType::Integer,
+        description: "Connect directly to this port, bypassing ssh",
+    },
+    MountOption {
+        name: "password_stdin",
+        value_type: MountOptionValueType::Flag,
+        description: "Read the password from stdin (only for password authentication)",
+    },
+    MountOption {
+        name: "sshfs_sync",
+        value_type: MountOptionValueType::Flag,
+        description: "Make writes synchronous",
+    },
+    MountOption {
+        name: "no_readahead",
+        value_type: MountOptionValueType::Flag,
+        description: "Don't read ahead of what's being requested",
+    },
+    MountOption {
+        name: "sync_readdir",
+        value_type: MountOptionValueType::Flag,
+        description: "Read directories synchronously",
+    },
+    MountOption {
+        name: "sshfs_debug",
+        value_type: MountOptionValueType::Flag,
+        description: "Print sshfs debugging information",
+    },
+    // FUSE
+    MountOption {
+        name: "allow_other",
+        value_type: MountOptionValueType::Flag,
+        description: "Allow access by other users (requires `user_allow_other` in /etc/fuse.conf)",
+    },
+    MountOption {
+        name: "allow_root",
+        value_type: MountOptionValueType::Flag,
+        description: "Allow access by root",
+    },
+    MountOption {
+        name: "default_permissions",
+        value_type: MountOptionValueType::Flag,
+        description: "Let the kernel enforce permissions based on the file modes",
+    },
+    MountOption {
+        name: "auto_unmount",
+        value_type: MountOptionValueType::Flag,
+        description: "Unmount automatically when sshfs exits",
+    },
+    MountOption {
+        name: "uid",
+        value_type: MountOptionValueType::Integer,
+        description: "Report this user id as the owner of all files",
+    },
+    MountOption {
+        name: "gid",
+        value_type: MountOptionValueType::Integer,
+        description: "Report this group id as the group of all files",
+    },
+    MountOption {
+        name: "umask",
+        value_type: MountOptionValueType::Integer,
+        description: "Apply this (octal) umask to the permissions of all files",
+    },
+    MountOption {
+        name: "kernel_cache",
+        value_type: MountOptionValueType::Flag,
+        description: "Keep file contents cached in the kernel across opens",
+    },
+    MountOption {
+        name: "auto_cache",
+        value_type: MountOptionValueType::Flag,
+        description: "Invalidate cached file contents when the file's size or modification time changes",
+    },
+    MountOption {
+        name: "entry_timeout",
+        value_type: MountOptionValueType::Integer,
+        description: "Seconds to cache file names for",
+    },
+    MountOption {
+        name: "attr_timeout",
+        value_type: MountOptionValueType::Integer,
+        description: "Seconds to cache file attributes for",
+    },
+    MountOption {
+        name: "negative_timeout",
+        value_type: MountOptionValueType::Integer,
+        description: "Seconds to cache the absence of file names for",
+    },
+    MountOption {
+        name: "max_read",
+        value_type: MountOptionValueType::Integer,
+        description: "Maximum size (in bytes) of read requests",
+    },
+    MountOption {
+        name: "max_write",
+        value_type: MountOptionValueType::Integer,
+        description: "Maximum size (in bytes) of write requests",
+    },
+    MountOption {
+        name: "fsname",
+        value_type: MountOptionValueType::Text,
+        description: "Filesystem name to show in the mount table",
+    },
+    MountOption {
+        name: "subtype",
+        value_type: MountOptionValueType::Text,
+        description: "Filesystem subtype to show in the mount table",
+    },
+    MountOption {
+        name: "ro",
+        value_type: MountOptionValueType::Flag,
+        description: "Mount read-only",
+    },
+    MountOption {
+        name: "rw",
+        value_type: MountOptionValueType::Flag,
+        description: "Mount read-write",
+    },
+    MountOption {
+        name: "noatime",
+        value_type: MountOptionValueType::Flag,
+        description: "Don't update access times",
+    },
+    MountOption {
+        name: "noexec",
+        value_type: MountOptionValueType::Flag,
+        description: "Don't allow executing files",
+    },
+    MountOption {
+        name: "nosuid",
+        value_type: MountOptionValueType::Flag,
+        description: "Ignore set-user-id and set-group-id bits",
+    },
+    MountOption {
+        name: "nodev",
+        value_type: MountOptionValueType::Flag,
+        description: "Don't interpret device files",
+    },
+    // ssh (passed on by sshfs)
+    MountOption {
+        name: "ServerAliveInterval",
+        value_type: MountOptionValueType::Integer,
+        description: "Seconds of inactivity after which ssh checks if the server is still alive",
+    },
+    MountOption {
+        name: "ServerAliveCountMax",
+        value_type: MountOptionValueType::Integer,
+        description: "Number of unanswered alive checks after which ssh disconnects",
+    },
+    MountOption {
+        name: "IdentitiesOnly",
+        value_type: MountOptionValueType::OneOf(YES_NO),
+        description: "Only use the configured identity (SSH key), even if the SSH agent offers others",
+    },
+    MountOption {
+        name: "StrictHostKeyChecking",
+        value_type: MountOptionValueType::OneOf(&["yes", "accept-new", "no", "ask", "off"]),
+        description: "Whether to accept unknown or changed host keys",
+    },
+    MountOption {
+        name: "ProxyJump",
+        value_type: MountOptionValueType::Text,
+        description: "Connect via the given jump host(s)",
+    },
+];
+
+/// A likely problem with a mount option (see `check_mount_options()`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum MountOptionWarning {
+    /// The option is not known. A similarly named known option may be suggested (e.g. `follow_symlinks` for `follow_symlink`).
+    Unknown {
+        option: String,
+        suggestion: Option<&'static str>,
+    },
+
+    /// The option requires a value, but none was given (e.g. `idmap`).
+    MissingValue { option: String },
+
+    /// The option takes no value, but one was given (e.g. `reconnect=yes`).
+    UnexpectedValue { option: String },
+
+    /// The option's value is not valid for it (e.g. `idmap=nobody`).
+    InvalidValue { option: String },
+}
+
+impl std::fmt::Display for MountOptionWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown {
+                option,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "Unknown mount option {0} (did you mean {1}?)",
+                option, suggestion
+            ),
+            Self::Unknown {
+                option,
+                suggestion: None,
+            } => write!(f, "Unknown mount option {0}", option),
+            Self::MissingValue { option } => {
+                write!(f, "The mount option {0} requires a value", option)
+            }
+            Self::UnexpectedValue { option } => {
+                write!(f, "The mount option {0} does not take a value", option)
+            }
+            Self::InvalidValue { option } => {
+                write!(f, "The mount option {0} has an invalid value", option)
+            }
+        }
+    }
+}
+
+/// Returns the known mount option with the given name.
+pub fn find_mount_option(name: &str) -> Option<&'static MountOption> {
+    KNOWN_MOUNT_OPTIONS
+        .iter()
+        .find(|option| option.name == name)
+}
+
+/// Checks the given mount options (e.g. `["follow_symlinks", "idmap=user"]`) against the known ones.
+///
+/// Unknown options are not necessarily wrong (`sshfs` and FUSE evolve), so these are warnings rather than validation errors.
+pub fn check_mount_options(options: &[String]) -> Vec<MountOptionWarning> {
+    let mut warnings: Vec<MountOptionWarning> = Vec::new();
+
+    for option in options {
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (option.as_str(), None),
+        };
+
+        let Some(known) = find_mount_option(name) else {
+            // sshfs passes these on to ssh, which knows many more options than are listed here.
+            if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                continue;
+            }
+
+            warnings.push(MountOptionWarning::Unknown {
+                option: option.clone(),
+                suggestion: suggest_mount_option(name),
+            });
+            continue;
+        };
+
+        let is_valid = match (known.value_type, value) {
+            (MountOptionValueType::Flag, None) => true,
+            (MountOptionValueType::Flag, Some(_)) => {
+                warnings.push(MountOptionWarning::UnexpectedValue {
+                    option: option.clone(),
+                });
+                continue;
+            }
+            (_, None) => {
+                warnings.push(MountOptionWarning::MissingValue {
+                    option: option.clone(),
+                });
+                continue;
+            }
+            (MountOptionValueType::Integer, Some(value)) => value.parse::<u64>().is_ok(),
+            (MountOptionValueType::Text, Some(value)) => !value.is_empty(),
+            (MountOptionValueType::OneOf(allowed), Some(value)) => allowed.contains(&value),
+            (MountOptionValueType::ListOf(allowed), Some(value)) => {
+                value.split(':').all(|item| allowed.contains(&item))
+            }
+        };
+
+        if !is_valid {
+            warnings.push(MountOptionWarning::InvalidValue {
+                option: option.clone(),
+            });
+        }
+    }
+
+    warnings
+}
+
+/// Escapes a mount option (e.g. `ProxyCommand=ssh -W %h:%p jump.example.com`), so that it can be passed to sshfs via `-o` as is.
+///
+/// FUSE splits `-o` values by commas, unless escaped with a backslash (which in turn needs escaping itself).
+/// Only the value part (after `=`) gets escaped, because commas before it separate options (e.g. `reconnect,idmap=user`)
+/// in definitions created before options were stored one per entry.
+pub fn escape_mount_option(option: &str) -> String {
+    match option.split_once('=') {
+        Some((name, value)) => format!(
+            "{0}={1}",
+            name,
+            value.replace('\\', "\\\\").replace(',', "\\,")
+        ),
+        None => option.to_owned(),
+    }
+}
+
+/// Splits mount options which contain multiple options (e.g. `reconnect,idmap=user`) into one option per entry,
+/// dropping empty ones.
+///
+/// Commas within values (e.g. `ProxyCommand=ssh -o Ciphers=aes128-ctr,aes256-ctr jump`) are left alone.
+pub fn normalize_mount_options(options: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+
+    for option in options {
+        let (names, value) = match option.split_once('=') {
+            Some((names, value)) => (names, Some(value)),
+            None => (option.as_str(), None),
+        };
+
+        let mut names: Vec<&str> = names.split(',').collect();
+        let last = names.pop().unwrap_or_default();
+
+        normalized.extend(names.into_iter().map(|name| name.to_owned()));
+        normalized.push(match value {
+            Some(value) => format!("{0}={1}", last, value),
+            None => last.to_owned(),
+        });
+    }
+
+    normalized.retain(|option| !option.is_empty());
+
+    normalized
+}
+
+/// Suggests a known option with a similar name (likely a typo), if there is one.
+fn suggest_mount_option(name: &str) -> Option<&'static str> {
+    KNOWN_MOUNT_OPTIONS
+        .iter()
+        .map(|option| (option.name, edit_distance(name, option.name)))
+        .filter(|(_, distance)| *distance <= 2)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(name, _)| name)
+}
+
+/// Calculates the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, char_a) in a.chars().enumerate() {
+        let mut current: Vec<usize> = vec![i + 1];
+
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution_cost = if char_a == *char_b { 0 } else { 1 };
+
+            current.push(
+                (previous[j] + substitution_cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
@@ -0,0 +1,192 @@
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Stdio};
+
+use crate::errors::SftpManError;
+use crate::model::FilesystemMountDefinition;
+
+/// `sftp` reports the current directory (in response to `pwd`) with this prefix.
+const PWD_PREFIX: &str = "Remote working directory: ";
+
+/// When reading commands from a non-terminal, `sftp` echoes each command back, prefixed with this.
+const ECHO_PREFIX: &str = "sftp> ";
+
+/// Lists directories on the remote machine (over SFTP) without mounting the filesystem.
+///
+/// A single `sftp` session is kept open for the lifetime of the browser,
+/// so that authentication (e.g. a password prompt) only happens once.
+pub struct RemoteBrowser {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Lines<BufReader<ChildStdout>>,
+    current_directory: String,
+}
+
+impl RemoteBrowser {
+    /// Connects to the remote machine of the given definition.
+    ///
+    /// Depending on the authentication type, this may prompt for a password on the terminal.
+    pub fn open(definition: &FilesystemMountDefinition) -> Result<Self, SftpManError> {
+        let mut cmd = definition.sftp_command();
+        cmd.stdin(Stdio::piped()).stdout(Stdio::piped());
+
+        log::debug!("{0}: starting sftp session: {1:?}", definition.id, cmd);
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+        };
+
+        let stdin = child.stdin.take().expect("stdin should be piped");
+        let stdout = child.stdout.take().expect("stdout should be piped");
+
+        let mut browser = Self {
+            child,
+            stdin,
+            lines: BufReader::new(stdout).lines(),
+            current_directory: String::new(),
+        };
+
+        // The session starts in the remote user's home directory.
+        browser.send("pwd")?;
+        let (_, pwd) = browser.read_until_pwd()?;
+        browser.current_directory = pwd;
+
+        Ok(browser)
+    }
+
+    /// Returns the (absolute) path of the current remote directory.
+    pub fn current_directory(&self) -> &str {
+        &self.current_directory
+    }
+
+    /// Changes the current remote directory and returns its (absolute, normalized) path.
+    pub fn change_directory(&mut self, path: &str) -> Result<&str, SftpManError> {
+        let (_, pwd) = self.run(&format!("cd {0}", quote(path)))?;
+
+        // `sftp` only reports failures on stderr, so a failure is detected by the directory not changing.
+        let is_already_there =
+            path.trim_end_matches('/') == self.current_directory.trim_end_matches('/');
+        if pwd == self.current_directory && !is_already_there {
+            return Err(SftpManError::Generic(format!(
+                "cannot change to remote directory {0} (see the sftp output above)",
+                path
+            )));
+        }
+
+        self.current_directory = pwd;
+
+        Ok(&self.current_directory)
+    }
+
+    /// Returns the names of the directories contained in the current remote directory, sorted alphabetically.
+    pub fn list_directories(&mut self) -> Result<Vec<String>, SftpManError> {
+        let (lines, _) = self.run("ls -l")?;
+
+        let mut directories: Vec<String> = lines
+            .iter()
+            .filter_map(|line| parse_directory_name(line))
+            .filter(|name| name != "." && name != "..")
+            .collect();
+
+        directories.sort();
+
+        Ok(directories)
+    }
+
+    /// Runs a command and returns its output lines along with the current remote directory.
+    ///
+    /// Since `sftp` does not signal the end of a command's output, each command is followed by `pwd`,
+    /// whose (recognizable) output marks the end.
+    fn run(&mut self, command: &str) -> Result<(Vec<String>, String), SftpManError> {
+        self.send(&format!("{0}\npwd", command))?;
+        self.read_until_pwd()
+    }
+
+    fn send(&mut self, commands: &str) -> Result<(), SftpManError> {
+        writeln!(self.stdin, "{0}", commands).map_err(|err| self.session_error(err))?;
+        self.stdin.flush().map_err(|err| self.session_error(err))
+    }
+
+    /// Reads output lines until the output of a `pwd` command.
+    fn read_until_pwd(&mut self) -> Result<(Vec<String>, String), SftpManError> {
+        let mut output: Vec<String> = Vec::new();
+
+        loop {
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => return Err(self.session_error(err)),
+                None => {
+                    return Err(SftpManError::Generic(
+                        "the sftp session ended unexpectedly (see the sftp output above)"
+                            .to_owned(),
+                    ));
+                }
+            };
+
+            if let Some(pwd) = line.strip_prefix(PWD_PREFIX) {
+                return Ok((output, pwd.to_owned()));
+            }
+
+            if line.starts_with(ECHO_PREFIX) {
+                continue;
+            }
+
+            output.push(line);
+        }
+    }
+
+    fn session_error(&self, err: std::io::Error) -> SftpManError {
+        SftpManError::Generic(format!("failed to talk to sftp: {0}", err))
+    }
+}
+
+impl Drop for RemoteBrowser {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, "bye");
+        let _ = self.child.wait();
+    }
+}
+
+/// Extracts the name of a directory from a line of `ls -l` output.
+///
+/// Example: `drwxr-xr-x    5 user     group        4096 Jan  1 12:00 storage`
+fn parse_directory_name(line: &str) -> Option<String> {
+    if !line.starts_with('d') {
+        return None;
+    }
+
+    // Skip the first 8 columns (permissions, links, owner, group, size, month, day, time/year).
+    let mut rest = line;
+    for _ in 0..8 {
+        rest = rest.trim_start();
+        rest = &rest[rest.find(char::is_whitespace)?..];
+    }
+
+    let name = rest.trim_start();
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(name.to_owned())
+}
+
+pub(crate) fn quote(path: &str) -> String {
+    format!("\"{0}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Joins a remote directory path and the name of an entry in it.
+pub fn join_remote_path(directory: &str, name: &str) -> String {
+    if directory.ends_with('/') {
+        format!("{0}{1}", directory, name)
+    } else {
+        format!("{0}/{1}", directory, name)
+    }
+}
+
+/// Returns the parent of a remote directory path (the root directory is its own parent).
+pub fn parent_remote_path(directory: &str) -> String {
+    match directory.trim_end_matches('/').rsplit_once('/') {
+        Some(("", _)) | None => "/".to_owned(),
+        Some((parent, _)) => parent.to_owned(),
+    }
+}
@@ -0,0 +1,268 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nix::sys::signal::{Signal, kill};
+use nix::unistd::Pid;
+
+use crate::errors::SftpManError;
+use crate::manager::Manager;
+use crate::model::{FilesystemMountDefinition, KillPolicy};
+use crate::utils::command::run_command;
+use crate::utils::secret::{resolve_password, write_password};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// If `sshfs` stays alive for at least this long, it's considered to have been running stably
+/// and the restart backoff starts over from its initial value.
+const STABLE_RUN_DURATION: Duration = Duration::from_secs(60);
+
+/// Supervises a single filesystem definition, by running `sshfs` in the foreground (`sshfs -f ..`) as a child process.
+///
+/// Unlike a regular mount (see `Manager::mount()`), where `sshfs` daemonizes and is left on its own,
+/// the supervisor monitors the `sshfs` process, forwards its output to the log
+/// and restarts it (with an exponential backoff) whenever it exits unexpectedly.
+///
+/// This is meant for running a single (critical) mount under a service manager like systemd.
+pub struct Supervisor {
+    manager: Manager,
+    definition: FilesystemMountDefinition,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Supervisor {
+    pub fn new(manager: Manager, definition: FilesystemMountDefinition) -> Self {
+        Self {
+            manager,
+            definition,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+
+    /// Adjusts how long to wait before restarting `sshfs` after it exits unexpectedly.
+    ///
+    /// The wait time starts at `initial` and doubles after each consecutive failure, up to `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Runs (and restarts) `sshfs` until `stop_requested` becomes `true`.
+    ///
+    /// When a stop is requested, the filesystem is unmounted and the `sshfs` process is waited for.
+    pub fn run(&self, stop_requested: &AtomicBool) -> Result<(), SftpManError> {
+        let definition = &self.definition;
+
+        if !definition.enabled {
+            return Err(SftpManError::DefinitionDisabled(definition.id.clone()));
+        }
+
+        // Supervision revolves around a single sshfs process.
+        if !definition.sub_mounts.is_empty() {
+            return Err(SftpManError::Generic(format!(
+                "{0} has sub-mounts and cannot be supervised",
+                definition.id
+            )));
+        }
+
+        if self.manager.is_definition_mounted(definition)? {
+            return Err(SftpManError::Generic(format!(
+                "{0} is already mounted and cannot be supervised",
+                definition.id
+            )));
+        }
+
+        let mut backoff = self.initial_backoff;
+
+        while !stop_requested.load(Ordering::SeqCst) {
+            let started_at = Instant::now();
+
+            match self.run_once(stop_requested) {
+                Ok(()) => {
+                    if stop_requested.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    log::warn!("{0}: sshfs exited unexpectedly", definition.id);
+                }
+                Err(err) => {
+                    log::error!("{0}: sshfs failed: {1:?}", definition.id, err);
+                }
+            }
+
+            // The process is gone, but the mount point may still be around (e.g. "Transport endpoint is not connected").
+            if let Err(err) = self
+                .manager
+                .umount_with_kill_policy(definition, KillPolicy::Never)
+            {
+                log::debug!(
+                    "{0}: failed to unmount after sshfs exited: {1:?}",
+                    definition.id,
+                    err
+                );
+            }
+            self.manager.clean_up_after_unmount(definition);
+
+            if started_at.elapsed() >= STABLE_RUN_DURATION {
+                backoff = self.initial_backoff;
+            }
+
+            log::info!("{0}: restarting sshfs in {1:?}..", definition.id, backoff);
+
+            sleep_unless_stopped(backoff, stop_requested);
+
+            backoff = std::cmp::min(backoff * 2, self.max_backoff);
+        }
+
+        log::info!("{0}: supervision stopped", definition.id);
+
+        Ok(())
+    }
+
+    /// Runs `sshfs` once and waits for it to exit (or for a stop to be requested).
+    fn run_once(&self, stop_requested: &AtomicBool) -> Result<(), SftpManError> {
+        let definition = &self.definition;
+
+        self.manager.create_mount_directory(definition)?;
+
+        let password = resolve_password(definition)?;
+
+        let mut cmds = definition.foreground_mount_commands()?;
+        let cmd_sshfs = cmds
+            .pop()
+            .expect("The list of mount commands should end with the sshfs command");
+
+        for cmd in cmds {
+            log::debug!("{0}: executing mount command: {1:?}", definition.id, cmd);
+            run_command(cmd)?;
+        }
+
+        let mut child =
+            spawn_with_forwarded_output(&definition.id, cmd_sshfs, password.as_deref())?;
+
+        log::info!(
+            "{0}: sshfs running in the foreground (pid {1})",
+            definition.id,
+            child.id()
+        );
+
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    log::debug!("{0}: sshfs exited with: {1}", definition.id, status);
+                    return Ok(());
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    return Err(SftpManError::Generic(format!(
+                        "failed to check on sshfs: {0}",
+                        err
+                    )));
+                }
+            }
+
+            if stop_requested.load(Ordering::SeqCst) {
+                self.stop_child(&mut child);
+                return Ok(());
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn stop_child(&self, child: &mut Child) {
+        let definition = &self.definition;
+
+        log::info!("{0}: stopping sshfs..", definition.id);
+
+        // Unmounting makes `sshfs -f` exit on its own.
+        if let Err(err) = self
+            .manager
+            .umount_with_kill_policy(definition, KillPolicy::Never)
+        {
+            log::warn!(
+                "{0}: failed to unmount, terminating sshfs: {1:?}",
+                definition.id,
+                err
+            );
+
+            if let Err(err) = kill(Pid::from_raw(child.id() as i32), Signal::SIGTERM) {
+                log::debug!("{0}: failed to terminate sshfs: {1}", definition.id, err);
+            }
+        }
+
+        let deadline = Instant::now() + self.manager.config().kill_grace_period();
+
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = child.try_wait() {
+                self.manager.clean_up_after_unmount(definition);
+                return;
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        log::warn!("{0}: sshfs did not exit in time, killing it", definition.id);
+
+        if let Err(err) = child.kill() {
+            log::debug!("{0}: failed to kill sshfs: {1}", definition.id, err);
+        }
+        let _ = child.wait();
+
+        self.manager.clean_up_after_unmount(definition);
+    }
+}
+
+fn spawn_with_forwarded_output(
+    id: &str,
+    mut cmd: Command,
+    password: Option<&str>,
+) -> Result<Child, SftpManError> {
+    log::debug!("{0}: spawning: {1:?}", id, cmd);
+
+    if password.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
+
+    let mut child = match cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+    };
+
+    if let Some(password) = password
+        && let Err(err) = write_password(&mut child, password)
+    {
+        log::debug!("{0}: failed to write the password to sshfs: {1}", id, err);
+    }
+
+    if let Some(stdout) = child.stdout.take() {
+        forward_output(id.to_owned(), stdout, log::Level::Info);
+    }
+
+    if let Some(stderr) = child.stderr.take() {
+        forward_output(id.to_owned(), stderr, log::Level::Warn);
+    }
+
+    Ok(child)
+}
+
+fn forward_output<R: Read + Send + 'static>(id: String, reader: R, level: log::Level) {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            log::log!(level, "{0}: sshfs: {1}", id, line);
+        }
+    });
+}
+
+fn sleep_unless_stopped(duration: Duration, stop_requested: &AtomicBool) {
+    let deadline = Instant::now() + duration;
+
+    while Instant::now() < deadline && !stop_requested.load(Ordering::SeqCst) {
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,274 @@
+//! Fixtures for testing code built on the library (e.g. GUIs), available via the `testing` feature.
+//!
+//! `TestEnvironment` provides a throwaway home directory (with definitions, a global configuration and an SSH configuration)
+//! and a `Manager` working with it, so that tests neither depend on nor touch the user's actual configuration.
+//!
+//! `FakeCommandRunner` stands in for `sshfs`, `fusermount3`, etc. (see `Manager::with_command_runner()`),
+//! so that mounting and unmounting can be tested end-to-end without FUSE, network access, root or a remote machine.
+//!
+//! No SSH/SFTP server is provided: the one talking to it would be `sshfs` (which needs FUSE),
+//! so mount tests are better served by the fake command runner, which can also make mounting fail in specific ways (see `FakeCommandRunner::fail()`).
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output};
+use std::sync::Mutex;
+
+use crate::command_runner::CommandRunner;
+use crate::errors::{ManagerInitError, SftpManError};
+use crate::manager::Manager;
+use crate::model::{FilesystemMountDefinition, GlobalConfig};
+
+/// A throwaway home directory (e.g. `/tmp/sftpman-test-1a2b3c4d`), laid out like a real one (e.g. `HOME/.config/sftpman/mounts`).
+///
+/// It's created via `TestEnvironment::builder()` and gets removed (along with everything in it) when dropped.
+#[derive(Debug)]
+pub struct TestEnvironment {
+    home_path: PathBuf,
+}
+
+impl TestEnvironment {
+    pub fn builder() -> TestEnvironmentBuilder {
+        TestEnvironmentBuilder::default()
+    }
+
+    /// Returns the home directory.
+    pub fn home_path(&self) -> &Path {
+        &self.home_path
+    }
+
+    /// Returns the directory definitions are stored in (e.g. `HOME/.config/sftpman/mounts`).
+    pub fn definitions_path(&self) -> PathBuf {
+        self.home_path
+            .join(".config")
+            .join("sftpman")
+            .join("mounts")
+    }
+
+    /// Returns a directory within the environment to use as a local mount path (see `FilesystemMountDefinition::mount_dest_path`),
+    /// as the default one (`DEFAULT_MOUNT_PATH_PREFIX`) is usually not writable.
+    pub fn mount_path(&self, id: &str) -> PathBuf {
+        self.home_path.join("mnt").join(id)
+    }
+
+    /// Returns a manager working with the environment (its definitions, state, SSH configuration, etc.).
+    ///
+    /// Each call loads the global configuration anew, so changes made to it in the meantime are picked up.
+    /// To mount without running anything, combine it with a fake command runner:
+    /// `environment.manager()?.with_command_runner(runner.clone())`, where `runner` is an `Arc<FakeCommandRunner>`.
+    pub fn manager(&self) -> Result<Manager, ManagerInitError> {
+        Manager::with_home(&self.home_path)
+    }
+}
+
+impl Drop for TestEnvironment {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.home_path) {
+            log::warn!(
+                "Failed to remove the test environment at {0}: {1}",
+                self.home_path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// Builds a `TestEnvironment` (see `TestEnvironment::builder()`).
+#[derive(Debug, Default)]
+pub struct TestEnvironmentBuilder {
+    definitions: Vec<FilesystemMountDefinition>,
+    global_config: Option<GlobalConfig>,
+    ssh_config: Option<String>,
+    known_hosts: Option<String>,
+}
+
+impl TestEnvironmentBuilder {
+    /// Adds a definition to store. Can be called multiple times.
+    ///
+    /// Definitions are stored as they are (without validation), so that the handling of invalid ones can be tested as well.
+    pub fn with_definition(mut self, definition: FilesystemMountDefinition) -> Self {
+        self.definitions.push(definition);
+        self
+    }
+
+    /// Sets the global configuration to store (e.g. `HOME/.config/sftpman/config.json`).
+    pub fn with_global_config(mut self, config: GlobalConfig) -> Self {
+        self.global_config = Some(config);
+        self
+    }
+
+    /// Sets the contents of the SSH configuration (`HOME/.ssh/config`), e.g. for testing `Host` aliases.
+    pub fn with_ssh_config(mut self, contents: &str) -> Self {
+        self.ssh_config = Some(contents.to_owned());
+        self
+    }
+
+    /// Sets the contents of the `known_hosts` file (`HOME/.ssh/known_hosts`).
+    pub fn with_known_hosts(mut self, contents: &str) -> Self {
+        self.known_hosts = Some(contents.to_owned());
+        self
+    }
+
+    /// Creates the home directory (in the system's temporary directory) and stores everything in it.
+    pub fn build(self) -> io::Result<TestEnvironment> {
+        let home_path = std::env::temp_dir().join(format!(
+            "{0}-test-{1:08x}",
+            env!("CARGO_PKG_NAME"),
+            rand::random::<u32>()
+        ));
+
+        fs::create_dir(&home_path)?;
+
+        // From here on, the directory gets removed if something fails.
+        let environment = TestEnvironment { home_path };
+
+        let definitions_path = environment.definitions_path();
+        fs::create_dir_all(&definitions_path)?;
+
+        for definition in &self.definitions {
+            let contents = definition.to_json_string().map_err(io::Error::other)?;
+            fs::write(
+                definitions_path.join(format!("{0}.json", definition.id)),
+                contents,
+            )?;
+        }
+
+        if let Some(config) = &self.global_config {
+            let contents = config.to_json_string().map_err(io::Error::other)?;
+            fs::write(
+                environment
+                    .home_path
+                    .join(".config")
+                    .join("sftpman")
+                    .join("config.json"),
+                contents,
+            )?;
+        }
+
+        let ssh_path = environment.home_path.join(".ssh");
+        fs::create_dir(&ssh_path)?;
+
+        if let Some(contents) = &self.ssh_config {
+            fs::write(ssh_path.join("config"), contents)?;
+        }
+
+        if let Some(contents) = &self.known_hosts {
+            fs::write(ssh_path.join("known_hosts"), contents)?;
+        }
+
+        Ok(environment)
+    }
+}
+
+/// A command runner (see `Manager::with_command_runner()`) which executes nothing,
+/// but records the commands it's given and makes them succeed (with no output), unless told to fail (see `fail()`).
+///
+/// Keep a clone of the `Arc` the manager gets, to inspect the commands afterwards (see `commands()`).
+#[derive(Debug, Default)]
+pub struct FakeCommandRunner {
+    commands: Mutex<Vec<Vec<String>>>,
+    failures: Mutex<Vec<FakeFailure>>,
+}
+
+#[derive(Debug)]
+struct FakeFailure {
+    program: String,
+    exit_code: i32,
+    stderr: String,
+}
+
+impl FakeCommandRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes commands running the given program (e.g. `sshfs`, also when run through a wrapper like `trickle`)
+    /// exit with the given code and error output (e.g. `read: Connection reset by peer`), from now on.
+    ///
+    /// Programs are matched by file name, so `sshfs` matches `/usr/bin/sshfs` as well.
+    pub fn fail(&self, program: &str, exit_code: i32, stderr: &str) {
+        self.failures.lock().unwrap().push(FakeFailure {
+            program: program.to_owned(),
+            exit_code,
+            stderr: stderr.to_owned(),
+        });
+    }
+
+    /// Makes commands succeed again, undoing `fail()`.
+    pub fn succeed(&self) {
+        self.failures.lock().unwrap().clear();
+    }
+
+    /// Returns the commands given to the runner so far (each as its program followed by its arguments), in order.
+    pub fn commands(&self) -> Vec<Vec<String>> {
+        self.commands.lock().unwrap().clone()
+    }
+
+    /// Tells if a command running the given program (matched like `fail()` does) was given to the runner so far.
+    pub fn ran(&self, program: &str) -> bool {
+        self.commands
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|parts| runs_program(parts, program))
+    }
+}
+
+impl CommandRunner for FakeCommandRunner {
+    fn run(&self, cmd: Command) -> Result<Output, SftpManError> {
+        let parts: Vec<String> = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|part| part.to_string_lossy().into_owned())
+            .collect();
+
+        let failure = self
+            .failures
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|failure| runs_program(&parts, &failure.program))
+            .map(|failure| (failure.exit_code, failure.stderr.clone()));
+
+        self.commands.lock().unwrap().push(parts);
+
+        match failure {
+            None => Ok(Output {
+                status: exit_status(0),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            }),
+            Some((exit_code, stderr)) => Err(SftpManError::CommandUnsuccessful(
+                cmd,
+                Output {
+                    status: exit_status(exit_code),
+                    stdout: Vec::new(),
+                    stderr: stderr.into_bytes(),
+                },
+            )),
+        }
+    }
+}
+
+fn runs_program(parts: &[String], program: &str) -> bool {
+    parts.iter().any(|part| {
+        Path::new(part)
+            .file_name()
+            .is_some_and(|name| name == program)
+    })
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+
+    // The raw value is a wait status, in which the exit code is in the second byte.
+    ExitStatus::from_raw((code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+
+    ExitStatus::from_raw(code as u32)
+}
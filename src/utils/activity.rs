@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::SftpManError;
+
+/// Tracks filesystem activity for a mounted definition, based on the I/O counters of its `sshfs` process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityRecord {
+    /// Process id of the `sshfs` process that the counter belongs to.
+    pub pid: i32,
+
+    /// Sum of the bytes read and written by the `sshfs` process.
+    #[serde(rename = "ioCounter")]
+    pub io_counter: u64,
+
+    /// Unix timestamp (in seconds) of when the counter was last seen changing.
+    #[serde(rename = "lastActiveAt")]
+    pub last_active_at: u64,
+}
+
+pub fn load_activity_records(path: &Path) -> Result<HashMap<String, ActivityRecord>, SftpManError> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents =
+        fs::read_to_string(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
+
+    serde_json::from_str(&contents).map_err(|err| SftpManError::JSON(path.to_path_buf(), err))
+}
+
+pub fn save_activity_records(
+    path: &Path,
+    records: &HashMap<String, ActivityRecord>,
+) -> Result<(), SftpManError> {
+    if let Some(dir_path) = path.parent() {
+        fs::create_dir_all(dir_path)
+            .map_err(|err| SftpManError::IO(dir_path.to_path_buf(), err))?;
+    }
+
+    let serialized = serde_json::to_string_pretty(records)
+        .map_err(|err| SftpManError::JSON(path.to_path_buf(), err))?;
+
+    fs::write(path, serialized).map_err(|err| SftpManError::IO(path.to_path_buf(), err))
+}
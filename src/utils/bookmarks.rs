@@ -0,0 +1,88 @@
+/// A bookmark of an SFTP location (e.g. `sftp://user@example.com/storage`), as found in a file manager's bookmarks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SftpBookmark {
+    pub url: String,
+
+    /// The name the bookmark was given, if any.
+    pub label: Option<String>,
+}
+
+/// Parses the SFTP bookmarks out of the contents of a GTK bookmarks file (`~/.config/gtk-3.0/bookmarks`),
+/// which contains one `URL [label]` entry per line.
+pub fn parse_gtk_bookmarks(contents: &str) -> Vec<SftpBookmark> {
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| line.starts_with("sftp://"))
+        .map(|line| match line.split_once(' ') {
+            Some((url, label)) => SftpBookmark {
+                url: url.to_owned(),
+                label: Some(label.trim().to_owned()).filter(|label| !label.is_empty()),
+            },
+            None => SftpBookmark {
+                url: line.to_owned(),
+                label: None,
+            },
+        })
+        .collect()
+}
+
+/// Parses the SFTP bookmarks out of the contents of an XBEL file, like KDE's places (`~/.local/share/user-places.xbel`).
+///
+/// Only what's needed is extracted (the `href` attribute and the `<title>` of each `<bookmark>`),
+/// so this is not a general-purpose XML parser.
+pub fn parse_xbel_bookmarks(contents: &str) -> Vec<SftpBookmark> {
+    let mut list: Vec<SftpBookmark> = Vec::new();
+
+    for element in contents.split("<bookmark").skip(1) {
+        // Only `<bookmark ...>` elements are of interest (not `<bookmark:icon ...>` and such).
+        if !element.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        let element = match element.find("</bookmark>") {
+            Some(idx) => &element[..idx],
+            None => element,
+        };
+
+        let Some(url) = xml_attribute(element, "href") else {
+            continue;
+        };
+
+        if !url.starts_with("sftp://") {
+            continue;
+        }
+
+        let label = element
+            .split_once("<title>")
+            .and_then(|(_, rest)| rest.split_once("</title>"))
+            .map(|(title, _)| decode_xml_entities(title.trim()))
+            .filter(|title| !title.is_empty());
+
+        list.push(SftpBookmark { url, label });
+    }
+
+    list
+}
+
+fn xml_attribute(element: &str, name: &str) -> Option<String> {
+    for quote in ['"', '\''] {
+        let prefix = format!("{0}={1}", name, quote);
+
+        if let Some((_, rest)) = element.split_once(&prefix) {
+            let (value, _) = rest.split_once(quote)?;
+            return Some(decode_xml_entities(value));
+        }
+    }
+
+    None
+}
+
+fn decode_xml_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
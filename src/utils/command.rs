@@ -1,17 +1,60 @@
-use std::process::{Command, Output};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
 
 use crate::errors::SftpManError;
 
-pub fn command_to_string(cmd: &Command) -> String {
-    let mut combined: Vec<String> = Vec::new();
+/// Converts a command to a string, the way sshfs expects its `ssh_command` option to be (see `FilesystemMountDefinition::mount_commands()`).
+///
+/// sshfs splits the command by spaces, so spaces (and backslashes) within arguments (e.g. a key path like `/home/user/my keys/id_ed25519`)
+/// are escaped with a backslash.
+pub fn command_to_escaped_string(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|part| {
+            part.to_str()
+                .unwrap()
+                .replace('\\', "\\\\")
+                .replace(' ', "\\ ")
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Creates a new command, which runs the given command through a wrapper program (e.g. `["nice", "-n", "10"]`).
+///
+/// The environment and working directory configured for the given command carry over.
+pub fn wrap_command(wrapper: &[&str], cmd: Command) -> Command {
+    let (program, wrapper_args) = wrapper
+        .split_first()
+        .expect("The wrapper should contain at least the program name");
 
-    combined.push(cmd.get_program().to_str().unwrap().to_owned());
+    let mut wrapped = Command::new(program);
+    wrapped
+        .args(wrapper_args)
+        .arg(cmd.get_program())
+        .args(cmd.get_args());
 
-    for arg in cmd.get_args() {
-        combined.push(arg.to_str().unwrap().to_owned());
+    for (key, value) in cmd.get_envs() {
+        match value {
+            Some(value) => wrapped.env(key, value),
+            None => wrapped.env_remove(key),
+        };
     }
 
-    combined.join(" ")
+    if let Some(dir) = cmd.get_current_dir() {
+        wrapped.current_dir(dir);
+    }
+
+    wrapped
+}
+
+/// Returns the path to the given program (e.g. `trickle`), if it can be found in one of the `PATH` directories.
+pub fn find_program(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+
+    std::env::split_paths(&paths)
+        .map(|directory| directory.join(name))
+        .find(|path| path.is_file())
 }
 
 pub fn run_command(mut cmd: Command) -> Result<Output, SftpManError> {
@@ -32,6 +75,32 @@ pub fn run_command(mut cmd: Command) -> Result<Output, SftpManError> {
     }
 }
 
+/// Runs a command like `run_command()` does, but lets it interact with the user via the standard input and error streams
+/// (e.g. for `ssh` asking to confirm with a security key).
+///
+/// Since the standard error stream isn't captured, the output returned on failure is empty.
+pub fn run_command_interactive(mut cmd: Command) -> Result<Output, SftpManError> {
+    cmd.stdin(Stdio::inherit()).stderr(Stdio::inherit());
+
+    match cmd.status() {
+        Err(err) => Err(SftpManError::CommandExecution(cmd, err)),
+
+        Ok(status) => {
+            let output = Output {
+                status,
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            };
+
+            if output.status.success() {
+                Ok(output)
+            } else {
+                Err(SftpManError::CommandUnsuccessful(cmd, output))
+            }
+        }
+    }
+}
+
 pub fn run_command_background(mut cmd: Command) -> Result<(), SftpManError> {
     match cmd.spawn() {
         Err(err) => Err(SftpManError::CommandExecution(cmd, err)),
@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::SftpManError;
+
+/// Loads the directories which got created while mounting (keyed by definition ID), so that they can be removed after unmounting.
+pub fn load_created_directories(
+    path: &Path,
+) -> Result<HashMap<String, Vec<PathBuf>>, SftpManError> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents =
+        fs::read_to_string(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
+
+    serde_json::from_str(&contents).map_err(|err| SftpManError::JSON(path.to_path_buf(), err))
+}
+
+pub fn save_created_directories(
+    path: &Path,
+    directories: &HashMap<String, Vec<PathBuf>>,
+) -> Result<(), SftpManError> {
+    if let Some(dir_path) = path.parent() {
+        fs::create_dir_all(dir_path)
+            .map_err(|err| SftpManError::IO(dir_path.to_path_buf(), err))?;
+    }
+
+    let serialized = serde_json::to_string_pretty(directories)
+        .map_err(|err| SftpManError::JSON(path.to_path_buf(), err))?;
+
+    fs::write(path, serialized).map_err(|err| SftpManError::IO(path.to_path_buf(), err))
+}
@@ -1,24 +1,171 @@
 use std::fs;
-use std::path::Path;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use nix::unistd::{Gid, Uid, chown, geteuid};
 
 use crate::errors::SftpManError;
 
-pub fn ensure_directory_recursively_created(path_str: &str) -> Result<(), SftpManError> {
-    let path = Path::new(&path_str);
+/// Creates the given directory, along with any missing parents.
+///
+/// The directories which get created are given the specified permissions (e.g. `0o700`) regardless of the umask, if any.
+/// When running as root on behalf of another user (via `sudo`), they're handed over to that user,
+/// so that mount points don't end up owned by root.
+///
+/// Returns the directories which got created (parents first).
+pub fn ensure_directory_recursively_created(
+    path: &Path,
+    mode: Option<u32>,
+) -> Result<Vec<PathBuf>, SftpManError> {
+    // Ancestors are listed starting from the path itself, so they get reversed to have parents first.
+    let mut missing: Vec<PathBuf> = path
+        .ancestors()
+        .take_while(|ancestor| !ancestor.as_os_str().is_empty() && !ancestor.exists())
+        .map(Path::to_path_buf)
+        .collect();
+    missing.reverse();
 
     fs::create_dir_all(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
 
+    adjust_created_directories(&missing, mode)?;
+
+    Ok(missing)
+}
+
+#[cfg(unix)]
+fn adjust_created_directories(dirs: &[PathBuf], mode: Option<u32>) -> Result<(), SftpManError> {
+    let owner = sudo_invoking_user();
+
+    for dir in dirs {
+        if let Some((uid, gid)) = owner {
+            chown(dir, Some(uid), Some(gid))
+                .map_err(|err| SftpManError::IO(dir.clone(), err.into()))?;
+        }
+
+        if let Some(mode) = mode {
+            fs::set_permissions(dir, fs::Permissions::from_mode(mode))
+                .map_err(|err| SftpManError::IO(dir.clone(), err))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn adjust_created_directories(_dirs: &[PathBuf], _mode: Option<u32>) -> Result<(), SftpManError> {
     Ok(())
 }
 
-pub fn remove_empty_directory(path_str: &str) -> Result<(), SftpManError> {
-    let path = Path::new(&path_str);
+/// Returns the user (and group) that root is running on behalf of via `sudo`, if that's the case.
+#[cfg(unix)]
+fn sudo_invoking_user() -> Option<(Uid, Gid)> {
+    if !geteuid().is_root() {
+        return None;
+    }
 
+    let uid = std::env::var("SUDO_UID").ok()?.parse().ok()?;
+    let gid = std::env::var("SUDO_GID").ok()?.parse().ok()?;
+
+    Some((Uid::from_raw(uid), Gid::from_raw(gid)))
+}
+
+/// Tells whether the given directory contains no entries.
+pub fn is_directory_empty(path: &Path) -> Result<bool, SftpManError> {
+    let mut entries =
+        fs::read_dir(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
+
+    Ok(entries.next().is_none())
+}
+
+pub fn remove_empty_directory(path: &Path) -> Result<(), SftpManError> {
     fs::remove_dir(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
 
     Ok(())
 }
 
-pub fn get_mounts_under_path_prefix(prefix: &str) -> Result<Vec<mnt::MountEntry>, SftpManError> {
-    mnt::get_submounts::<&str>(prefix).map_err(SftpManError::from)
+/// Creates a symlink at `path` pointing to `target`, along with any missing parents of `path`.
+///
+/// Nothing is done if such a symlink is already there, while anything else being there fails with `ErrorKind::AlreadyExists`.
+pub fn ensure_symlink(target: &Path, path: &Path) -> std::io::Result<()> {
+    match fs::read_link(path) {
+        Ok(existing) if existing == target => return Ok(()),
+        Ok(_) => return Err(std::io::ErrorKind::AlreadyExists.into()),
+        Err(_) if path.symlink_metadata().is_ok() => {
+            return Err(std::io::ErrorKind::AlreadyExists.into());
+        }
+        Err(_) => {}
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    create_symlink(target, path)
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &Path, _path: &Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// Removes the symlink at `path`, but only if it points to `target` (so that whatever else may be there is left alone).
+pub fn remove_symlink_to(target: &Path, path: &Path) -> std::io::Result<()> {
+    match fs::read_link(path) {
+        Ok(existing) if existing == target => fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that a directory (usually a mount point) responds, by listing it and optionally by creating (and deleting) a temporary file in it.
+///
+/// On a dead mount, this may block for a long time, so callers should not wait for it indefinitely.
+pub fn probe_directory(path: &Path, write: bool) -> std::io::Result<()> {
+    // Reading a single entry is enough to make the filesystem talk to the remote machine.
+    if let Some(entry) = fs::read_dir(path)?.next() {
+        entry?;
+    }
+
+    if write {
+        let file_path = path.join(format!(".sftpman-probe-{0}", rand::random::<u32>()));
+
+        fs::write(&file_path, b"sftpman probe")?;
+        fs::remove_file(&file_path)?;
+    }
+
+    Ok(())
+}
+
+/// A currently mounted filesystem.
+#[derive(Debug, Clone)]
+pub struct MountEntry {
+    /// The local path the filesystem is mounted at.
+    pub file: PathBuf,
+
+    /// The filesystem type (e.g. `fuse.sshfs`).
+    pub vfstype: String,
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_mounts_under_path_prefix(prefix: &Path) -> Result<Vec<MountEntry>, SftpManError> {
+    let mounts = mnt::get_submounts(prefix).map_err(SftpManError::from)?;
+
+    Ok(mounts
+        .into_iter()
+        .map(|mount| MountEntry {
+            file: mount.file,
+            vfstype: mount.vfstype,
+        })
+        .collect())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_mounts_under_path_prefix(_prefix: &Path) -> Result<Vec<MountEntry>, SftpManError> {
+    Err(SftpManError::UnsupportedPlatform("listing mounts"))
 }
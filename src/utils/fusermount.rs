@@ -27,6 +27,11 @@ pub fn create_fusermount_check_command() -> Command {
     create_check_command(FUSERMOUNT_COMMAND)
 }
 
+/// Tells whether only the old FUSE version (2) is available (`fusermount`, but not `fusermount3`).
+pub fn is_legacy_fuse() -> bool {
+    !FUSERMOUNT3_AVAILABLE.load(Ordering::Relaxed)
+}
+
 // Determines the fusermount command to use.
 // We favor `fusermount3`, but will also make do with `fusermount` if `fusermount3` is not available.
 // See: https://github.com/spantaleev/sftpman-rs/issues/3
@@ -0,0 +1,99 @@
+use crate::errors::SftpManError;
+use crate::model::FilesystemMountDefinition;
+
+/// At most this many close matches get suggested for an unknown ID.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Resolves an ID as typed by the user (e.g. `prod-w`) to one of the given IDs (e.g. `prod-webserver`).
+///
+/// An exact match always wins. Otherwise, the query needs to be a prefix of exactly one ID
+/// (see `SftpManError::AmbiguousId`). If it's not a prefix of any, close matches (e.g. for typos) get suggested
+/// via `SftpManError::UnknownId`.
+pub fn resolve_id<'a>(query: &str, ids: &[&'a str]) -> Result<&'a str, SftpManError> {
+    if let Some(id) = ids.iter().find(|id| **id == query) {
+        return Ok(id);
+    }
+
+    let mut matching: Vec<&str> = ids
+        .iter()
+        .filter(|id| !query.is_empty() && id.starts_with(query))
+        .copied()
+        .collect();
+
+    match matching.len() {
+        0 => Err(SftpManError::UnknownId(
+            query.to_owned(),
+            suggestions(query, ids),
+        )),
+        1 => Ok(matching[0]),
+        _ => {
+            matching.sort();
+            Err(SftpManError::AmbiguousId(
+                query.to_owned(),
+                matching.into_iter().map(str::to_owned).collect(),
+            ))
+        }
+    }
+}
+
+/// Finds the definition that an ID typed by the user refers to (see `resolve_id()`).
+pub fn find_definition<'a>(
+    definitions: &'a [FilesystemMountDefinition],
+    query: &str,
+) -> Result<&'a FilesystemMountDefinition, SftpManError> {
+    let ids: Vec<&str> = definitions
+        .iter()
+        .map(|definition| definition.id.as_str())
+        .collect();
+
+    let id = resolve_id(query, &ids)?;
+
+    Ok(definitions
+        .iter()
+        .find(|definition| definition.id == id)
+        .expect("The resolved ID should belong to one of the definitions"))
+}
+
+/// Returns the IDs which are close enough to the query to likely be what was meant (closest first).
+fn suggestions(query: &str, ids: &[&str]) -> Vec<String> {
+    // Longer IDs leave room for more typos.
+    let max_distance = (query.chars().count() / 3).max(2);
+
+    let mut candidates: Vec<(usize, &str)> = ids
+        .iter()
+        .map(|id| (edit_distance(query, id), *id))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+
+    candidates.sort();
+
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, id)| id.to_owned())
+        .collect()
+}
+
+/// Calculates the Levenshtein distance (the number of single-character insertions, deletions and substitutions) between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = usize::from(a_char != *b_char);
+
+            current[j + 1] = (previous[j] + substitution_cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
@@ -0,0 +1,90 @@
+//! Support for JSON with comments (`//` and `/* */`) and trailing commas, as is handy in hand-edited files.
+
+/// Turns JSON with comments and trailing commas into plain JSON.
+///
+/// Comments and trailing commas are replaced with whitespace (keeping line breaks),
+/// so that positions in parse errors (e.g. `line 5 column 3`) still point to the right place in the original.
+pub fn to_plain_json(contents: &str) -> String {
+    strip(contents).0
+}
+
+/// Tells whether the given JSON contains comments.
+pub fn has_comments(contents: &str) -> bool {
+    strip(contents).1
+}
+
+fn strip(contents: &str) -> (String, bool) {
+    let mut output = String::with_capacity(contents.len());
+    let mut has_comments = false;
+
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        output.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+
+            ('/', Some('/')) => {
+                has_comments = true;
+                output.push(' ');
+
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                    output.push(' ');
+                }
+            }
+
+            ('/', Some('*')) => {
+                has_comments = true;
+                chars.next();
+                output.push_str("  ");
+
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    output.push(if next == '\n' { '\n' } else { ' ' });
+
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+
+            ('}' | ']', _) => {
+                // Comments have been blanked out already, so a trailing comma is the last non-whitespace character.
+                let trimmed_len = output.trim_end().len();
+                if output[..trimmed_len].ends_with(',') {
+                    output.replace_range(trimmed_len - 1..trimmed_len, " ");
+                }
+
+                output.push(c);
+            }
+
+            _ => output.push(c),
+        }
+    }
+
+    (output, has_comments)
+}
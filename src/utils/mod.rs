@@ -1,7 +1,22 @@
+pub mod activity;
+pub mod bookmarks;
 pub mod command;
+pub mod created_directories;
 pub mod fs;
 pub mod fusermount;
+pub mod id_resolution;
+#[cfg(feature = "jsonc")]
+pub mod jsonc;
+pub mod mount_managers;
+pub mod network;
+pub mod path_template;
+pub mod paused;
+pub mod premount;
 pub mod process;
-
-#[cfg(feature = "cli")]
-pub mod validation;
+pub mod rclone;
+pub mod secret;
+pub mod session;
+pub mod ssh;
+pub mod state_cache;
+pub mod systemd;
+pub mod usage_stats;
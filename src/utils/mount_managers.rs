@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories that systemd loads system units from.
+const SYSTEMD_UNIT_DIRS: &[&str] = &[
+    "/etc/systemd/system",
+    "/run/systemd/system",
+    "/usr/lib/systemd/system",
+    "/lib/systemd/system",
+];
+
+const FSTAB_PATH: &str = "/etc/fstab";
+
+const AUTOFS_MASTER_PATH: &str = "/etc/auto.master";
+const AUTOFS_MASTER_DIR_PATH: &str = "/etc/auto.master.d";
+
+/// A path which something other than sftpman (a systemd unit, an fstab entry or an autofs map) mounts automatically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignMount {
+    /// The path being mounted.
+    pub path: PathBuf,
+
+    /// Tells whether everything under `path` is managed as well (like it is for autofs indirect maps).
+    pub covers_children: bool,
+
+    /// Where the mount comes from (e.g. `/etc/systemd/system/mnt-sshfs-my\x2dfs.automount`).
+    pub source: String,
+}
+
+impl ForeignMount {
+    /// Tells whether this mount would compete with a mount at the given path.
+    pub fn conflicts_with(&self, path: &Path) -> bool {
+        path == self.path || (self.covers_children && path.starts_with(&self.path))
+    }
+}
+
+/// Finds the paths mounted automatically by systemd units, fstab entries and autofs maps.
+///
+/// Units generated by sftpman itself (see `AutomountUnits`) are left out, as they're meant to manage the definition's mount.
+pub fn find_foreign_mounts() -> Vec<ForeignMount> {
+    let mut list: Vec<ForeignMount> = Vec::new();
+
+    for dir in SYSTEMD_UNIT_DIRS {
+        list.extend(find_systemd_mount_units(Path::new(dir)));
+    }
+
+    if let Ok(contents) = fs::read_to_string(FSTAB_PATH) {
+        list.extend(parse_fstab(&contents).into_iter().map(|path| ForeignMount {
+            path,
+            covers_children: false,
+            source: FSTAB_PATH.to_owned(),
+        }));
+    }
+
+    let mut master_paths = vec![PathBuf::from(AUTOFS_MASTER_PATH)];
+    if let Ok(entries) = fs::read_dir(AUTOFS_MASTER_DIR_PATH) {
+        master_paths.extend(
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "autofs")),
+        );
+    }
+
+    for master_path in master_paths {
+        list.extend(find_autofs_mounts(&master_path));
+    }
+
+    list
+}
+
+fn find_systemd_mount_units(dir: &Path) -> Vec<ForeignMount> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "mount" || ext == "automount")
+        })
+        .filter_map(|path| {
+            let contents = fs::read_to_string(&path).ok()?;
+            let where_ = parse_systemd_unit_where(&contents)?;
+
+            Some(ForeignMount {
+                path: PathBuf::from(where_),
+                covers_children: false,
+                source: path.display().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the path (`Where=`) of a `.mount` or `.automount` unit, unless sftpman generated the unit.
+fn parse_systemd_unit_where(contents: &str) -> Option<String> {
+    let mut where_: Option<String> = None;
+
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        match key.trim() {
+            "Description" if value.trim().starts_with("sftpman:") => return None,
+            "Where" => where_ = Some(value.trim().to_owned()),
+            _ => {}
+        }
+    }
+
+    where_
+}
+
+/// Parses the mount points of the fstab entries which get mounted automatically
+/// (those not marked `noauto`, unless they're automounted via `x-systemd.automount`).
+fn parse_fstab(contents: &str) -> Vec<PathBuf> {
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _spec = fields.next()?;
+            let file = fields.next()?;
+            let _vfstype = fields.next()?;
+            let options: Vec<&str> = fields.next().unwrap_or_default().split(',').collect();
+
+            if options.contains(&"noauto") && !options.contains(&"x-systemd.automount") {
+                return None;
+            }
+
+            if !file.starts_with('/') {
+                // Swap entries and such.
+                return None;
+            }
+
+            Some(PathBuf::from(decode_fstab_escapes(file)))
+        })
+        .collect()
+}
+
+/// Decodes the octal escapes (e.g. `\040` for a space) used in fstab fields.
+fn decode_fstab_escapes(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(idx) = rest.find('\\') {
+        decoded.push_str(&rest[..idx]);
+
+        let escape = rest.get(idx + 1..idx + 4).unwrap_or_default();
+        match u8::from_str_radix(escape, 8) {
+            Ok(byte) if escape.len() == 3 => {
+                decoded.push(byte as char);
+                rest = &rest[idx + 4..];
+            }
+            _ => {
+                decoded.push('\\');
+                rest = &rest[idx + 1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Finds the paths managed by the maps listed in an autofs master map (e.g. `/etc/auto.master`).
+///
+/// Indirect maps (e.g. `/mnt/remote /etc/auto.remote`) manage everything under their mount point,
+/// while direct maps (`/- /etc/auto.direct`) list the paths they manage.
+fn find_autofs_mounts(master_path: &Path) -> Vec<ForeignMount> {
+    let Ok(contents) = fs::read_to_string(master_path) else {
+        return Vec::new();
+    };
+
+    let mut list: Vec<ForeignMount> = Vec::new();
+
+    for (mount_point, map) in parse_autofs_master(&contents) {
+        if mount_point != "/-" {
+            list.push(ForeignMount {
+                path: PathBuf::from(mount_point),
+                covers_children: true,
+                source: master_path.display().to_string(),
+            });
+            continue;
+        }
+
+        // Only map files can be inspected (not `-hosts`, LDAP and such).
+        let Ok(map_contents) = fs::read_to_string(&map) else {
+            continue;
+        };
+
+        list.extend(
+            parse_autofs_direct_map(&map_contents)
+                .into_iter()
+                .map(|path| ForeignMount {
+                    path,
+                    covers_children: false,
+                    source: map.clone(),
+                }),
+        );
+    }
+
+    list
+}
+
+/// Parses the `mount-point map` entries out of an autofs master map, skipping includes (`+auto.master`).
+fn parse_autofs_master(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('+'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.next()?;
+            let map = fields.next()?;
+
+            if !mount_point.starts_with('/') {
+                return None;
+            }
+
+            // Maps may be prefixed with their type (e.g. `file:/etc/auto.direct`).
+            let map = map.strip_prefix("file:").unwrap_or(map);
+
+            Some((mount_point.to_owned(), map.to_owned()))
+        })
+        .collect()
+}
+
+fn parse_autofs_direct_map(contents: &str) -> Vec<PathBuf> {
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|key| key.starts_with('/'))
+        .map(PathBuf::from)
+        .collect()
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::errors::SftpManError;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub(crate) const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The flag (in `/sys/class/net/NAME/flags`) telling that an interface is up.
+const IFF_UP: u32 = 0x1;
+
+/// Tells if there's a default route (IPv4 or IPv6) going through a non-loopback interface.
+pub fn has_default_route() -> bool {
+    default_route_interfaces().is_some_and(|interfaces| !interfaces.is_empty())
+}
+
+/// Returns the non-loopback interfaces (e.g. `wlan0`, `wg0`) which default routes (IPv4 or IPv6) go through,
+/// or `None` if the routing tables cannot be read (e.g. on non-Linux systems).
+pub fn default_route_interfaces() -> Option<BTreeSet<String>> {
+    let ipv4_interfaces = ipv4_default_route_interfaces();
+    let ipv6_interfaces = ipv6_default_route_interfaces();
+
+    if ipv4_interfaces.is_none() && ipv6_interfaces.is_none() {
+        return None;
+    }
+
+    Some(
+        ipv4_interfaces
+            .into_iter()
+            .chain(ipv6_interfaces)
+            .flatten()
+            .collect(),
+    )
+}
+
+fn ipv4_default_route_interfaces() -> Option<BTreeSet<String>> {
+    // Columns: Iface Destination Gateway Flags RefCnt Use Metric Mask MTU Window IRTT
+    let contents = fs::read_to_string("/proc/net/route").ok()?;
+
+    Some(
+        contents
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let columns: Vec<&str> = line.split_whitespace().collect();
+
+                (columns.len() > 7
+                    && columns[0] != "lo"
+                    && columns[1] == "00000000"
+                    && columns[7] == "00000000")
+                    .then(|| columns[0].to_owned())
+            })
+            .collect(),
+    )
+}
+
+fn ipv6_default_route_interfaces() -> Option<BTreeSet<String>> {
+    // Columns: Destination DestinationPrefixLength Source SourcePrefixLength NextHop Metric RefCnt Use Flags Iface
+    let contents = fs::read_to_string("/proc/net/ipv6_route").ok()?;
+
+    Some(
+        contents
+            .lines()
+            .filter_map(|line| {
+                let columns: Vec<&str> = line.split_whitespace().collect();
+
+                (columns.len() > 9
+                    && columns[9] != "lo"
+                    && columns[0] == "00000000000000000000000000000000"
+                    && columns[1] == "00")
+                    .then(|| columns[9].to_owned())
+            })
+            .collect(),
+    )
+}
+
+/// Tells if the network interface with the given name (e.g. `wg0`) exists and is up.
+///
+/// The administrative state is checked (like `ip link` shows `UP`), as some interfaces (e.g. WireGuard ones)
+/// don't report an operational state.
+pub fn is_interface_up(name: &str) -> bool {
+    // Names like `../..` would escape the directory.
+    if name.contains('/') {
+        return false;
+    }
+
+    let Ok(contents) = fs::read_to_string(format!("/sys/class/net/{0}/flags", name)) else {
+        return false;
+    };
+
+    u32::from_str_radix(contents.trim().trim_start_matches("0x"), 16)
+        .is_ok_and(|flags| flags & IFF_UP != 0)
+}
+
+/// Tells if a TCP connection can be established to the given address (e.g. `nas.local:22`).
+pub fn is_address_reachable(address: &str, timeout: Duration) -> bool {
+    let Ok(socket_addresses) = address.to_socket_addrs() else {
+        return false;
+    };
+
+    socket_addresses
+        .into_iter()
+        .any(|socket_address| TcpStream::connect_timeout(&socket_address, timeout).is_ok())
+}
+
+/// Tells if the given host name (or IP address) resolves to at least one address.
+pub fn does_host_resolve(host: &str) -> bool {
+    resolve_host(host).is_ok()
+}
+
+/// Resolves the given host name (or IP address) the way the system does (e.g. consulting `/etc/hosts`, DNS and mDNS, as configured in `/etc/nsswitch.conf`).
+///
+/// Returns the reason (e.g. `Name or service not known`) if it doesn't resolve to at least one address.
+pub fn resolve_host(host: &str) -> Result<(), String> {
+    let mut socket_addresses = (host, 0).to_socket_addrs().map_err(|err| err.to_string())?;
+
+    match socket_addresses.next() {
+        Some(_) => Ok(()),
+        None => Err("no addresses found".to_owned()),
+    }
+}
+
+/// Waits until the network is available or the timeout expires.
+///
+/// If `address` is provided, the network is considered available when a TCP connection to it can be established.
+/// Otherwise, it's considered available when a default route is present.
+pub fn wait_for_network(address: Option<&str>, timeout: Duration) -> Result<(), SftpManError> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let available = match address {
+            Some(address) => is_address_reachable(address, CONNECT_TIMEOUT),
+            None => has_default_route(),
+        };
+
+        if available {
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            return Err(SftpManError::NetworkWaitTimeout(timeout));
+        }
+
+        log::debug!(
+            "Network not available yet. Checking again in {0:?}..",
+            POLL_INTERVAL
+        );
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,130 @@
+/// The placeholders which may be used in mount path templates (e.g. `~/mounts/{host}/{id}`).
+pub const PLACEHOLDERS: [&str; 4] = ["id", "host", "user", "port"];
+
+/// The values which the placeholders (see `PLACEHOLDERS`) in a mount path template get replaced with.
+pub struct PathTemplateValues<'a> {
+    pub id: &'a str,
+    pub host: &'a str,
+    pub user: &'a str,
+    pub port: u16,
+}
+
+impl PathTemplateValues<'_> {
+    fn get(&self, placeholder: &str) -> Option<String> {
+        match placeholder {
+            "id" => Some(self.id.to_owned()),
+            "host" => Some(self.host.to_owned()),
+            "user" => Some(self.user.to_owned()),
+            "port" => Some(self.port.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Expands a mount path template (e.g. `~/mounts/{host}/{id}`) into a path.
+///
+/// Besides the placeholders (see `PLACEHOLDERS`), environment variables (`$NAME` or `${NAME}`) get expanded,
+/// as does a leading `~` (into the home directory).
+/// Environment variables which are not set and unknown placeholders are left as they are.
+pub fn expand_path_template(template: &str, values: &PathTemplateValues) -> String {
+    let mut expanded = String::with_capacity(template.len());
+
+    let mut rest = template;
+
+    if rest == "~" || rest.starts_with("~/") {
+        match directories::BaseDirs::new() {
+            Some(base_dirs) => {
+                expanded.push_str(&base_dirs.home_dir().to_string_lossy());
+                rest = &rest[1..];
+            }
+            None => log::warn!(
+                "Cannot expand ~ in {0}, as the home directory is unknown",
+                template
+            ),
+        }
+    }
+
+    while let Some(idx) = rest.find(['{', '$']) {
+        expanded.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+
+        let (name, len) = if let Some(after) = rest.strip_prefix("${") {
+            match after.find('}') {
+                Some(end) => (&after[..end], end + 3),
+                None => ("", 0),
+            }
+        } else if let Some(after) = rest.strip_prefix('$') {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end + 1)
+        } else {
+            match rest[1..].find('}') {
+                Some(end) => (&rest[1..end + 1], end + 2),
+                None => ("", 0),
+            }
+        };
+
+        let value = if len == 0 || name.is_empty() {
+            None
+        } else if rest.starts_with('$') {
+            std::env::var(name).ok()
+        } else {
+            values.get(name)
+        };
+
+        match value {
+            Some(value) => {
+                expanded.push_str(&value);
+                rest = &rest[len..];
+            }
+            None => {
+                // Left as is, including whatever follows (e.g. the name of a variable which is not set).
+                let len = len.max(1);
+                expanded.push_str(&rest[..len]);
+                rest = &rest[len..];
+            }
+        }
+    }
+
+    expanded.push_str(rest);
+
+    expanded
+}
+
+/// Returns the placeholders (e.g. `{hostname}`) in a mount path template which are not among `PLACEHOLDERS`.
+///
+/// Environment variables (`${NAME}`) are not placeholders, so they're never reported.
+pub fn unknown_placeholders(template: &str) -> Vec<String> {
+    let mut unknown: Vec<String> = Vec::new();
+
+    let mut offset = 0;
+
+    while let Some(idx) = template[offset..].find('{') {
+        let start = offset + idx;
+        let is_env_var = template[..start].ends_with('$');
+
+        let Some(end) = template[start..].find('}') else {
+            break;
+        };
+
+        let name = &template[start + 1..start + end];
+
+        if !is_env_var && !PLACEHOLDERS.contains(&name) {
+            unknown.push(format!("{{{0}}}", name));
+        }
+
+        offset = start + end + 1;
+    }
+
+    unknown
+}
+
+/// Tells whether a mount path template leads to an absolute path once expanded
+/// (e.g. `/mnt/{host}`, `~/mounts/{id}` or `$XDG_RUNTIME_DIR/sftpman/{id}`).
+pub fn is_absolute_path_template(template: &str) -> bool {
+    template.starts_with('/')
+        || template == "~"
+        || template.starts_with("~/")
+        || template.starts_with('$')
+}
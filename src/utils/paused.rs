@@ -0,0 +1,29 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::errors::SftpManError;
+
+/// Loads the IDs of the paused definitions (see `Manager::pause()`).
+pub fn load_paused_ids(path: &Path) -> Result<BTreeSet<String>, SftpManError> {
+    if !path.exists() {
+        return Ok(BTreeSet::new());
+    }
+
+    let contents =
+        fs::read_to_string(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
+
+    serde_json::from_str(&contents).map_err(|err| SftpManError::JSON(path.to_path_buf(), err))
+}
+
+pub fn save_paused_ids(path: &Path, ids: &BTreeSet<String>) -> Result<(), SftpManError> {
+    if let Some(dir_path) = path.parent() {
+        fs::create_dir_all(dir_path)
+            .map_err(|err| SftpManError::IO(dir_path.to_path_buf(), err))?;
+    }
+
+    let serialized = serde_json::to_string_pretty(ids)
+        .map_err(|err| SftpManError::JSON(path.to_path_buf(), err))?;
+
+    fs::write(path, serialized).map_err(|err| SftpManError::IO(path.to_path_buf(), err))
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::auth_type::AuthType;
+use crate::errors::SftpManError;
+use crate::model::{
+    BANDWIDTH_LIMITER, BIND_MOUNTER, ExtraMountPathMethod, FilesystemMountDefinition,
+};
+
+use super::command::find_program;
+
+/// A cheap check of something that mounting a filesystem relies on (e.g. `sshfs` being installed),
+/// performed before mounting (see `Manager::mount()`), so that a missing prerequisite gets reported clearly,
+/// instead of surfacing as whatever error `sshfs` (or the `ssh` command it runs) happens to fail with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PremountCheck {
+    /// One of the given programs (alternatives, in order of preference) needs to be available.
+    /// Programs specified by path (e.g. `/opt/openssh/bin/ssh`) need to exist there, others need to be found in `PATH`.
+    Program(Vec<String>),
+
+    /// `/dev/fuse` needs to exist and be accessible.
+    Fuse,
+
+    /// The given SSH key (or certificate) file needs to be readable.
+    KeyFile(PathBuf),
+}
+
+impl PremountCheck {
+    /// Returns the checks relevant to mounting the given definition.
+    pub fn for_definition(definition: &FilesystemMountDefinition) -> Vec<Self> {
+        let mut checks = vec![
+            Self::Program(vec!["sshfs".to_owned()]),
+            Self::Program(vec![definition.effective_ssh_binary().to_owned()]),
+        ];
+
+        // Unprivileged FUSE mounts are made via fusermount, which is also what unmounting uses.
+        if cfg!(target_os = "linux") {
+            checks.push(Self::Program(vec![
+                "fusermount3".to_owned(),
+                "fusermount".to_owned(),
+            ]));
+            checks.push(Self::Fuse);
+        }
+
+        if definition.has_bandwidth_limit() {
+            checks.push(Self::Program(vec![BANDWIDTH_LIMITER.to_owned()]));
+        }
+
+        if definition.extra_mount_path_method == ExtraMountPathMethod::Bind
+            && !definition.extra_mount_paths.is_empty()
+        {
+            checks.push(Self::Program(vec![BIND_MOUNTER.to_owned()]));
+        }
+
+        if definition.auth_type == AuthType::PublicKey {
+            checks.extend(
+                definition
+                    .ssh_keys()
+                    .into_iter()
+                    .chain(definition.certificate_file.as_deref())
+                    .filter(|path| !path.is_empty())
+                    .map(|path| Self::KeyFile(PathBuf::from(path))),
+            );
+        }
+
+        checks
+    }
+
+    /// Returns the key the check is remembered by once it passes (see `load_passed_checks()`),
+    /// or `None` if it needs to be performed each time.
+    ///
+    /// Key files are checked each time, as they belong to specific definitions and get replaced a lot more often than programs get uninstalled.
+    pub fn cache_key(&self) -> Option<String> {
+        match self {
+            Self::Program(names) => Some(format!("program:{0}", names.join("|"))),
+            Self::Fuse => Some("fuse".to_owned()),
+            Self::KeyFile(_) => None,
+        }
+    }
+
+    /// Performs the check for the given definition, returning an error describing what's missing, if anything.
+    pub fn run(&self, definition: &FilesystemMountDefinition) -> Result<(), SftpManError> {
+        match self {
+            Self::Program(names) => {
+                if names.iter().any(|name| is_program_available(name)) {
+                    return Ok(());
+                }
+
+                if names.iter().any(|name| name == BANDWIDTH_LIMITER) {
+                    return Err(SftpManError::BandwidthLimiterMissing(definition.id.clone()));
+                }
+
+                Err(SftpManError::ProgramMissing(
+                    definition.id.clone(),
+                    names.join(" or "),
+                ))
+            }
+
+            Self::Fuse => check_fuse()
+                .map_err(|reason| SftpManError::FuseUnavailable(definition.id.clone(), reason)),
+
+            Self::KeyFile(path) => match fs::File::open(path) {
+                Ok(_) => Ok(()),
+                Err(err) => Err(SftpManError::KeyFileUnreadable(
+                    definition.id.clone(),
+                    path.clone(),
+                    err,
+                )),
+            },
+        }
+    }
+}
+
+fn is_program_available(name: &str) -> bool {
+    if name.contains(std::path::MAIN_SEPARATOR) {
+        return Path::new(name).is_file();
+    }
+
+    find_program(name).is_some()
+}
+
+/// Tells why `/dev/fuse` cannot be used (e.g. `/dev/fuse does not exist`), if it cannot.
+#[cfg(unix)]
+fn check_fuse() -> Result<(), String> {
+    use nix::unistd::{AccessFlags, access};
+
+    let path = Path::new("/dev/fuse");
+
+    if !path.exists() {
+        return Err(format!("{0} does not exist", path.display()));
+    }
+
+    access(path, AccessFlags::R_OK | AccessFlags::W_OK)
+        .map_err(|err| format!("{0} is not accessible: {1}", path.display(), err.desc()))
+}
+
+#[cfg(not(unix))]
+fn check_fuse() -> Result<(), String> {
+    Ok(())
+}
+
+/// Loads when each pre-mount check (by its `PremountCheck::cache_key()`) last passed, as UNIX timestamps.
+pub fn load_passed_checks(path: &Path) -> Result<BTreeMap<String, u64>, SftpManError> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let contents =
+        fs::read_to_string(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
+
+    serde_json::from_str(&contents).map_err(|err| SftpManError::JSON(path.to_path_buf(), err))
+}
+
+pub fn save_passed_checks(path: &Path, checks: &BTreeMap<String, u64>) -> Result<(), SftpManError> {
+    if let Some(dir_path) = path.parent() {
+        fs::create_dir_all(dir_path)
+            .map_err(|err| SftpManError::IO(dir_path.to_path_buf(), err))?;
+    }
+
+    let serialized = serde_json::to_string_pretty(checks)
+        .map_err(|err| SftpManError::JSON(path.to_path_buf(), err))?;
+
+    fs::write(path, serialized).map_err(|err| SftpManError::IO(path.to_path_buf(), err))
+}
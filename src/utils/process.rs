@@ -1,17 +1,34 @@
+#[cfg(target_os = "linux")]
+use std::ffi::OsStr;
+use std::path::Path;
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+#[cfg(target_os = "linux")]
 use std::thread;
 use std::time::Duration;
+#[cfg(target_os = "linux")]
+use std::time::Instant;
 
+#[cfg(target_os = "linux")]
 use nix::sys::signal::Signal;
+#[cfg(target_os = "linux")]
 use nix::sys::signal::kill;
+#[cfg(target_os = "linux")]
 use nix::unistd::Pid;
 
+#[cfg(target_os = "linux")]
 use procfs::ProcError;
+#[cfg(target_os = "linux")]
+use procfs::process::FDTarget;
+#[cfg(target_os = "linux")]
 use procfs::process::Process;
+#[cfg(target_os = "linux")]
 use procfs::process::all_processes as get_all_processes;
 
-use crate::FilesystemMountDefinition;
 use crate::errors::SftpManError;
+use crate::{BlockingProcess, FilesystemMountDefinition, TerminationSignal};
 
+#[cfg(target_os = "linux")]
 pub fn sshfs_pid_by_definition(
     definition: &FilesystemMountDefinition,
 ) -> Result<Option<i32>, SftpManError> {
@@ -26,14 +43,12 @@ pub fn sshfs_pid_by_definition(
                 "".to_owned()
             };
 
-            if program != "sshfs" {
+            // sshfs may have been started via its full path (e.g. `/usr/bin/sshfs`) by a wrapper (e.g. `nice`).
+            if Path::new(&program).file_name() != Some(OsStr::new("sshfs")) {
                 continue;
             }
 
-            let expected_arg = format!(
-                "{0}@[{1}]:{2}",
-                definition.user, definition.host, definition.remote_path
-            );
+            let expected_arg = definition.remote_target(&definition.remote_path);
 
             for arg in cmd_line {
                 if arg == expected_arg {
@@ -46,10 +61,164 @@ pub fn sshfs_pid_by_definition(
     Ok(None)
 }
 
+#[cfg(not(target_os = "linux"))]
+pub fn sshfs_pid_by_definition(
+    _definition: &FilesystemMountDefinition,
+) -> Result<Option<i32>, SftpManError> {
+    Err(SftpManError::UnsupportedPlatform("listing processes"))
+}
+
+/// Returns the processes which have files open (or their working directory) within any of the given paths,
+/// as found in `/proc/PID/fd` and `/proc/PID/cwd`.
+///
+/// Processes belonging to other users cannot be inspected (unless running as root), so they're silently skipped.
+#[cfg(target_os = "linux")]
+pub fn processes_using_paths(paths: &[&Path]) -> Result<Vec<BlockingProcess>, SftpManError> {
+    let processes = get_all_processes()
+        .map_err(|err| SftpManError::Generic(format!("failed to list processes: {0}", err)))?;
+
+    let is_within = |path: &Path| paths.iter().any(|prefix| path.starts_with(prefix));
+
+    let mut blocking_processes: Vec<BlockingProcess> = Vec::new();
+
+    // Our own process may have inherited something within the paths (e.g. a redirected output file), which is not worth reporting.
+    let own_pid = std::process::id() as i32;
+
+    for process in processes.flatten() {
+        if process.pid == own_pid {
+            continue;
+        }
+
+        let mut used_paths: Vec<PathBuf> = Vec::new();
+
+        if let Ok(cwd) = process.cwd()
+            && is_within(&cwd)
+        {
+            used_paths.push(cwd);
+        }
+
+        if let Ok(fds) = process.fd() {
+            for fd in fds.flatten() {
+                if let FDTarget::Path(path) = fd.target
+                    && is_within(&path)
+                    && !used_paths.contains(&path)
+                {
+                    used_paths.push(path);
+                }
+            }
+        }
+
+        if used_paths.is_empty() {
+            continue;
+        }
+
+        let command = match process.cmdline() {
+            Ok(cmd_line) if !cmd_line.is_empty() => cmd_line.join(" "),
+            _ => process
+                .stat()
+                .map(|stat| stat.comm)
+                .unwrap_or_else(|_| "?".to_owned()),
+        };
+
+        blocking_processes.push(BlockingProcess {
+            pid: process.pid,
+            command,
+            paths: used_paths,
+        });
+    }
+
+    Ok(blocking_processes)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn processes_using_paths(_paths: &[&Path]) -> Result<Vec<BlockingProcess>, SftpManError> {
+    Err(SftpManError::UnsupportedPlatform("listing open files"))
+}
+
+/// Sends the given signal to the given processes and waits (up to `timeout`, for all of them together) for them to exit.
+///
+/// Returns the processes which are (or cannot be determined not to be) still alive by then.
+#[cfg(target_os = "linux")]
+pub fn terminate_processes(
+    pids: &[i32],
+    signal: TerminationSignal,
+    timeout: Duration,
+) -> Result<Vec<i32>, SftpManError> {
+    let signal = match signal {
+        TerminationSignal::Term => Signal::SIGTERM,
+        TerminationSignal::Int => Signal::SIGINT,
+        TerminationSignal::Hup => Signal::SIGHUP,
+        TerminationSignal::Kill => Signal::SIGKILL,
+    };
+
+    for &pid in pids {
+        // The process may have exited in the meantime, which is just as good.
+        if let Err(err) = kill_pid_with_signal(pid, signal) {
+            log::debug!("{0:?}", err);
+        }
+    }
+
+    let deadline = Instant::now() + timeout;
+
+    let mut remaining_pids: Vec<i32> = Vec::new();
+
+    for &pid in pids {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+
+        if !matches!(wait_for_process_exit(pid, timeout), Ok(true)) {
+            remaining_pids.push(pid);
+        }
+    }
+
+    Ok(remaining_pids)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn terminate_processes(
+    _pids: &[i32],
+    _signal: TerminationSignal,
+    _timeout: Duration,
+) -> Result<Vec<i32>, SftpManError> {
+    Err(SftpManError::UnsupportedPlatform("killing processes"))
+}
+
+/// Returns the total number of bytes read and written by the given process so far.
+#[cfg(target_os = "linux")]
+pub fn process_io_counter(pid: i32) -> Result<u64, SftpManError> {
+    let io = Process::new(pid)
+        .and_then(|process| process.io())
+        .map_err(|err| {
+            SftpManError::Generic(format!("failed to read I/O stats for {0}: {1}", pid, err))
+        })?;
+
+    Ok(io.rchar + io.wchar)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn process_io_counter(_pid: i32) -> Result<u64, SftpManError> {
+    Err(SftpManError::UnsupportedPlatform(
+        "reading process I/O stats",
+    ))
+}
+
+/// How often to check whether a process being killed is gone (see `ensure_process_killed()`).
+#[cfg(target_os = "linux")]
+const KILL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long to wait for a process to be gone after killing it forcefully (`SIGKILL`), before giving up.
+///
+/// `SIGKILL` can't be ignored, so this only needs to cover the process being torn down.
+#[cfg(target_os = "linux")]
+const FORCEFUL_EXIT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Asks the given process to terminate (`SIGTERM`) and waits for it to exit (up to `graceful_exit_timeout`).
+/// If it's still alive by then, it gets killed forcefully (`SIGKILL`) and waited for (up to `FORCEFUL_EXIT_TIMEOUT`).
+///
+/// The process is checked every `KILL_POLL_INTERVAL`, so this returns as soon as the process is gone.
+#[cfg(target_os = "linux")]
 pub fn ensure_process_killed(
     pid: i32,
-    wait_time_before_dead_check: Duration,
-    wait_time_before_forcefully_killing: Duration,
+    graceful_exit_timeout: Duration,
 ) -> Result<(), SftpManError> {
     if let Err(err) = kill_pid_gracefully(pid) {
         log::debug!(
@@ -60,46 +229,32 @@ pub fn ensure_process_killed(
     }
 
     log::debug!(
-        "Sleeping for {0:?} before checking if the process was killed..",
-        wait_time_before_dead_check,
+        "Waiting up to {0:?} for process {1} to exit..",
+        graceful_exit_timeout,
+        pid,
     );
 
-    thread::sleep(wait_time_before_dead_check);
-
-    match is_pid_alive(pid) {
-        Err(err) => {
+    match wait_for_process_exit(pid, graceful_exit_timeout) {
+        Ok(true) => {
+            log::debug!("Process {0} was not found to be alive anymore..", pid);
+            return Ok(());
+        }
+        Ok(false) => {
             log::debug!(
-                "Failed to check if process {0} is still alive after killing it: {1:?}",
+                "Process {0} was found to be alive after graceful killing..",
                 pid,
-                err
             );
-
-            // It may or may not be alive, so..
-            // Fall through to continue killing forcefully.
         }
-        Ok(alive) => {
-            if !alive {
-                log::debug!("Process {0} was not found to be alive anymore..", pid,);
-
-                return Ok(());
-            }
-
-            // Still alive, so we need to do more..
-            // Fall through to continue killing forcefully.
+        Err(err) => {
+            // It may or may not be alive, so we continue killing forcefully.
             log::debug!(
-                "Process {0} was found to be alive after graceful killing..",
+                "Failed to check if process {0} is still alive after killing it: {1:?}",
                 pid,
+                err
             );
         }
     }
 
-    log::debug!(
-        "Sleeping for {0:?} before killing forcefully..",
-        wait_time_before_forcefully_killing,
-    );
-
-    thread::sleep(wait_time_before_dead_check);
-
     if let Err(err) = kill_pid_forcefully(pid) {
         log::debug!(
             "Process {0} failed to be killed forcefully: {1:?}",
@@ -108,39 +263,64 @@ pub fn ensure_process_killed(
         );
     }
 
-    match is_pid_alive(pid) {
+    match wait_for_process_exit(pid, FORCEFUL_EXIT_TIMEOUT) {
+        Ok(false) => Err(SftpManError::Generic(
+            "Ultimately failed to kill process".to_owned(),
+        )),
+        Ok(true) => Ok(()),
         Err(err) => {
             log::debug!(
-                "Failed to check if process {0} is still alive after killing it: {1:?}",
+                "Failed to check if process {0} is still alive after killing it forcefully: {1:?}",
                 pid,
                 err
             );
+            Ok(())
+        }
+    }
+}
+
+/// Waits (up to the given timeout) for the given process to exit.
+///
+/// Returns whether the process is gone, or the error preventing this from being checked (as of the last check).
+#[cfg(target_os = "linux")]
+fn wait_for_process_exit(pid: i32, timeout: Duration) -> Result<bool, ProcError> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let result = is_pid_alive(pid).map(|alive| !alive);
 
-            // It may or may not be alive, so..
-            // Fall through to continue killing forcefully.
+        if let Ok(true) = result {
+            return result;
         }
-        Ok(alive) => {
-            if !alive {
-                return Ok(());
-            }
 
-            return Err(SftpManError::Generic(
-                "Ultimately failed to kill process".to_owned(),
-            ));
+        let now = Instant::now();
+        if now >= deadline {
+            return result;
         }
+
+        thread::sleep(KILL_POLL_INTERVAL.min(deadline - now));
     }
+}
 
-    Ok(())
+#[cfg(not(target_os = "linux"))]
+pub fn ensure_process_killed(
+    _pid: i32,
+    _graceful_exit_timeout: Duration,
+) -> Result<(), SftpManError> {
+    Err(SftpManError::UnsupportedPlatform("killing processes"))
 }
 
+#[cfg(target_os = "linux")]
 fn kill_pid_gracefully(pid: i32) -> Result<(), SftpManError> {
     kill_pid_with_signal(pid, Signal::SIGTERM)
 }
 
+#[cfg(target_os = "linux")]
 fn kill_pid_forcefully(pid: i32) -> Result<(), SftpManError> {
     kill_pid_with_signal(pid, Signal::SIGKILL)
 }
 
+#[cfg(target_os = "linux")]
 fn kill_pid_with_signal(pid: i32, signal: Signal) -> Result<(), SftpManError> {
     let pid = Pid::from_raw(pid);
 
@@ -153,6 +333,7 @@ fn kill_pid_with_signal(pid: i32, signal: Signal) -> Result<(), SftpManError> {
     }
 }
 
+#[cfg(target_os = "linux")]
 fn is_pid_alive(pid: i32) -> Result<bool, ProcError> {
     let p = Process::new(pid);
     match p {
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+/// An SFTP remote (`type = sftp`), as found in rclone's configuration file (`rclone.conf`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RcloneSftpRemote {
+    /// The name of the remote (the `[section]` it's defined in).
+    pub name: String,
+
+    pub host: String,
+
+    pub user: Option<String>,
+
+    pub port: Option<u16>,
+
+    /// The path to the private key (`key_file`), possibly starting with `~/`.
+    pub key_file: Option<String>,
+
+    pub known_hosts_file: Option<String>,
+
+    /// Tells whether a password (`pass`, stored obscured) is configured.
+    pub has_password: bool,
+}
+
+/// Parses the SFTP remotes out of the contents of an rclone configuration file, skipping all other remote types.
+///
+/// Encrypted configuration files can't be read.
+pub fn parse_rclone_sftp_remotes(contents: &str) -> Result<Vec<RcloneSftpRemote>, &'static str> {
+    let mut sections: Vec<(String, HashMap<String, String>)> = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with("RCLONE_ENCRYPT_V") {
+            return Err("the configuration file is encrypted");
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            sections.push((name.trim().to_owned(), HashMap::new()));
+            continue;
+        }
+
+        let (Some((_, settings)), Some((key, value))) = (sections.last_mut(), line.split_once('='))
+        else {
+            continue;
+        };
+
+        settings.insert(key.trim().to_lowercase(), value.trim().to_owned());
+    }
+
+    let non_empty = |settings: &HashMap<String, String>, key: &str| {
+        settings.get(key).filter(|value| !value.is_empty()).cloned()
+    };
+
+    Ok(sections
+        .into_iter()
+        .filter(|(_, settings)| settings.get("type").map(|s| s.as_str()) == Some("sftp"))
+        .filter_map(|(name, settings)| {
+            Some(RcloneSftpRemote {
+                host: non_empty(&settings, "host")?,
+                user: non_empty(&settings, "user"),
+                port: settings.get("port").and_then(|port| port.parse().ok()),
+                key_file: non_empty(&settings, "key_file"),
+                known_hosts_file: non_empty(&settings, "known_hosts_file"),
+                has_password: non_empty(&settings, "pass").is_some(),
+                name,
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::io::Write;
+use std::process::{Child, Command, Output, Stdio};
+
+use crate::errors::SftpManError;
+use crate::model::FilesystemMountDefinition;
+
+/// Resolves the password referenced by the given definition (see `FilesystemMountDefinition::password_command`).
+///
+/// Returns `None` if the definition doesn't reference a password.
+pub(crate) fn resolve_password(
+    definition: &FilesystemMountDefinition,
+) -> Result<Option<String>, SftpManError> {
+    let password = if let Some(command) = &definition.password_command {
+        log::debug!(
+            "{0}: obtaining the password via command: {1}",
+            definition.id,
+            command
+        );
+
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command).stdin(Stdio::null());
+
+        let output = cmd.output().map_err(|err| {
+            SftpManError::PasswordUnavailable(definition.id.clone(), err.to_string())
+        })?;
+
+        if !output.status.success() {
+            return Err(SftpManError::PasswordUnavailable(
+                definition.id.clone(),
+                format!("the password command exited with {0}", output.status),
+            ));
+        }
+
+        // Tools like `pass` print the password on the first line, possibly followed by other data.
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_owned()
+    } else if let Some(name) = &definition.password_env {
+        std::env::var(name).map_err(|err| {
+            SftpManError::PasswordUnavailable(
+                definition.id.clone(),
+                format!("environment variable {0}: {1}", name, err),
+            )
+        })?
+    } else if let Some(path) = &definition.password_file {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            SftpManError::PasswordUnavailable(
+                definition.id.clone(),
+                format!("file {0}: {1}", path, err),
+            )
+        })?;
+
+        contents.lines().next().unwrap_or_default().to_owned()
+    } else {
+        return Ok(None);
+    };
+
+    if password.is_empty() {
+        return Err(SftpManError::PasswordUnavailable(
+            definition.id.clone(),
+            "the password is empty".to_owned(),
+        ));
+    }
+
+    Ok(Some(password))
+}
+
+/// Writes the password to the standard input of the given child process (which must have been spawned with a piped standard input),
+/// as expected by `sshfs -o password_stdin`.
+pub(crate) fn write_password(child: &mut Child, password: &str) -> std::io::Result<()> {
+    let mut stdin = child.stdin.take().expect("stdin should be piped");
+    stdin.write_all(password.as_bytes())?;
+    stdin.write_all(b"\n")
+}
+
+/// Runs a command like `run_command()` does, but feeds the password to its standard input.
+pub(crate) fn run_command_with_password(
+    mut cmd: Command,
+    password: &str,
+) -> Result<Output, SftpManError> {
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+    };
+
+    if let Err(err) = write_password(&mut child, password) {
+        // sshfs may have exited early (e.g. due to a bad option). Its output explains why better than the write error does.
+        log::debug!("Failed to write the password to {0:?}: {1}", cmd, err);
+    }
+
+    let output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(err) => return Err(SftpManError::CommandExecution(cmd, err)),
+    };
+
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(SftpManError::CommandUnsuccessful(cmd, output))
+    }
+}
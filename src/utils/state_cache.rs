@@ -0,0 +1,89 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::model::FilesystemMountDefinition;
+
+use super::fs::MountEntry;
+
+/// Caches snapshots of the mount table and of the stored definitions for a limited time (TTL),
+/// so that frequent polling (e.g. by a GUI) doesn't hit `/proc/mounts` and the config directory each time.
+///
+/// A TTL of zero disables caching.
+#[derive(Default)]
+pub struct StateCache {
+    ttl: Duration,
+    mounts: Mutex<Option<(Instant, Vec<MountEntry>)>>,
+    definitions: Mutex<Option<(Instant, Vec<FilesystemMountDefinition>)>>,
+}
+
+impl StateCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            ..Default::default()
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn mounts<E>(
+        &self,
+        load: impl FnOnce() -> Result<Vec<MountEntry>, E>,
+    ) -> Result<Vec<MountEntry>, E> {
+        get_or_load(&self.mounts, self.ttl, load)
+    }
+
+    pub fn definitions<E>(
+        &self,
+        load: impl FnOnce() -> Result<Vec<FilesystemMountDefinition>, E>,
+    ) -> Result<Vec<FilesystemMountDefinition>, E> {
+        get_or_load(&self.definitions, self.ttl, load)
+    }
+
+    pub fn invalidate(&self) {
+        *self.mounts.lock().unwrap() = None;
+        *self.definitions.lock().unwrap() = None;
+    }
+
+    /// Invalidates the cache now and once more when the returned guard is dropped.
+    ///
+    /// Operations which change the state (mounting, persisting, etc.) hold on to a guard while running,
+    /// so that neither they, nor anyone after them, act on a snapshot taken before the change.
+    pub fn invalidation_guard(&self) -> InvalidationGuard<'_> {
+        self.invalidate();
+        InvalidationGuard(self)
+    }
+}
+
+pub struct InvalidationGuard<'a>(&'a StateCache);
+
+impl Drop for InvalidationGuard<'_> {
+    fn drop(&mut self) {
+        self.0.invalidate();
+    }
+}
+
+fn get_or_load<T: Clone, E>(
+    slot: &Mutex<Option<(Instant, T)>>,
+    ttl: Duration,
+    load: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    if ttl.is_zero() {
+        return load();
+    }
+
+    let mut slot = slot.lock().unwrap();
+
+    if let Some((loaded_at, value)) = slot.as_ref()
+        && loaded_at.elapsed() < ttl
+    {
+        return Ok(value.clone());
+    }
+
+    let value = load()?;
+    *slot = Some((Instant::now(), value.clone()));
+
+    Ok(value)
+}
@@ -0,0 +1,64 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use once_cell::sync::Lazy;
+
+use super::command::wrap_command;
+
+static USER_MANAGER_AVAILABLE: Lazy<bool> = Lazy::new(detect_user_manager);
+
+/// Tells if the system is running systemd and the current user has a systemd user manager running,
+/// so that `systemd-run --user` can be used.
+pub fn is_user_manager_available() -> bool {
+    *USER_MANAGER_AVAILABLE
+}
+
+fn detect_user_manager() -> bool {
+    // See `sd_booted(3)`
+    if !Path::new("/run/systemd/system").is_dir() {
+        return false;
+    }
+
+    let Some(runtime_dir) = env::var_os("XDG_RUNTIME_DIR") else {
+        return false;
+    };
+
+    // The user manager listens on this socket while running.
+    Path::new(&runtime_dir).join("systemd/private").exists()
+}
+
+/// Wraps the given command, so that it runs in a transient systemd scope unit (`systemd-run --user --scope ..`).
+///
+/// The scope unit is named after the given name (e.g. `sftpman-my-fs.scope`)
+/// and gets garbage-collected once all of its processes are gone.
+pub fn wrap_in_scope(name: &str, cmd: Command) -> Command {
+    let unit = format!("--unit=sftpman-{0}", escape_unit_name(name));
+
+    wrap_command(
+        &[
+            "systemd-run",
+            "--user",
+            "--scope",
+            "--quiet",
+            "--collect",
+            &unit,
+        ],
+        cmd,
+    )
+}
+
+/// Escapes a string for use in a unit name, similarly to `systemd-escape`.
+fn escape_unit_name(name: &str) -> String {
+    let mut escaped = String::new();
+
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-' || byte == b'.' {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("\\x{0:02x}", byte));
+        }
+    }
+
+    escaped
+}
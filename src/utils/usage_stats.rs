@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::errors::SftpManError;
+use crate::model::UsageStats;
+
+/// Loads the usage stats of the definitions (see `Manager::usage_stats()`), by ID.
+pub fn load_usage_stats(path: &Path) -> Result<HashMap<String, UsageStats>, SftpManError> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents =
+        fs::read_to_string(path).map_err(|err| SftpManError::IO(path.to_path_buf(), err))?;
+
+    serde_json::from_str(&contents).map_err(|err| SftpManError::JSON(path.to_path_buf(), err))
+}
+
+pub fn save_usage_stats(
+    path: &Path,
+    stats: &HashMap<String, UsageStats>,
+) -> Result<(), SftpManError> {
+    if let Some(dir_path) = path.parent() {
+        fs::create_dir_all(dir_path)
+            .map_err(|err| SftpManError::IO(dir_path.to_path_buf(), err))?;
+    }
+
+    let serialized = serde_json::to_string_pretty(stats)
+        .map_err(|err| SftpManError::JSON(path.to_path_buf(), err))?;
+
+    fs::write(path, serialized).map_err(|err| SftpManError::IO(path.to_path_buf(), err))
+}
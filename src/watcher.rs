@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+use std::os::fd::AsFd;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::poll::{PollFd, PollFlags, PollTimeout, poll};
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+
+use crate::errors::SftpManError;
+use crate::manager::Manager;
+
+/// A change to the stored definitions (see `DefinitionWatcher`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefinitionChange {
+    /// A definition with the given ID was added.
+    Added(String),
+
+    /// The definition with the given ID was changed.
+    Changed(String),
+
+    /// The definition with the given ID was removed.
+    Removed(String),
+}
+
+/// Watches the config directory for definitions being added, changed or removed,
+/// whether through the library or externally (e.g. by editing JSON files by hand).
+///
+/// Created via `Manager::watch()`. Changes also invalidate the manager's state cache (see `Manager::with_state_cache_ttl()`).
+pub struct DefinitionWatcher {
+    manager: Manager,
+    dir_path: PathBuf,
+    inotify: Inotify,
+    known_ids: HashSet<String>,
+}
+
+impl DefinitionWatcher {
+    pub(crate) fn new(manager: Manager, dir_path: PathBuf) -> Result<Self, SftpManError> {
+        let inotify = Inotify::init(InitFlags::IN_CLOEXEC | InitFlags::IN_NONBLOCK)
+            .map_err(|err| SftpManError::IO(dir_path.clone(), err.into()))?;
+
+        // Writes are only reported once the file is closed, and atomic replacements (write + rename) as moves.
+        inotify
+            .add_watch(
+                &dir_path,
+                AddWatchFlags::IN_CREATE
+                    | AddWatchFlags::IN_CLOSE_WRITE
+                    | AddWatchFlags::IN_MOVED_TO
+                    | AddWatchFlags::IN_MOVED_FROM
+                    | AddWatchFlags::IN_DELETE,
+            )
+            .map_err(|err| SftpManError::IO(dir_path.clone(), err.into()))?;
+
+        let known_ids = list_ids(&dir_path)?;
+
+        Ok(Self {
+            manager,
+            dir_path,
+            inotify,
+            known_ids,
+        })
+    }
+
+    /// Waits for changes (up to `timeout`, or indefinitely when `None`) and returns them.
+    ///
+    /// An empty list is returned if the timeout expires without anything changing.
+    pub fn wait(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<DefinitionChange>, SftpManError> {
+        let timeout = match timeout {
+            Some(timeout) => PollTimeout::try_from(timeout).unwrap_or(PollTimeout::MAX),
+            None => PollTimeout::NONE,
+        };
+
+        let mut fds = [PollFd::new(self.inotify.as_fd(), PollFlags::POLLIN)];
+
+        match poll(&mut fds, timeout) {
+            Ok(_) | Err(Errno::EINTR) => {}
+            Err(err) => return Err(SftpManError::IO(self.dir_path.clone(), err.into())),
+        }
+
+        self.changes()
+    }
+
+    /// Returns the changes which happened since the last call, without blocking.
+    pub fn changes(&mut self) -> Result<Vec<DefinitionChange>, SftpManError> {
+        let events = match self.inotify.read_events() {
+            Ok(events) => events,
+            Err(Errno::EAGAIN) => return Ok(vec![]),
+            Err(err) => return Err(SftpManError::IO(self.dir_path.clone(), err.into())),
+        };
+
+        // When events got lost, everything needs to be re-checked.
+        let overflowed = events
+            .iter()
+            .any(|event| event.mask.contains(AddWatchFlags::IN_Q_OVERFLOW));
+
+        let mut affected_ids: Vec<String> = Vec::new();
+
+        if overflowed {
+            affected_ids.extend(self.known_ids.iter().cloned());
+            affected_ids.extend(list_ids(&self.dir_path)?);
+        } else {
+            for event in events {
+                let Some(id) = event
+                    .name
+                    .as_deref()
+                    .and_then(|name| id_from_file_name(Path::new(name)))
+                else {
+                    continue;
+                };
+
+                affected_ids.push(id);
+            }
+        }
+
+        let mut changes: Vec<DefinitionChange> = Vec::new();
+        let mut seen_ids: HashSet<String> = HashSet::new();
+
+        for id in affected_ids {
+            if !seen_ids.insert(id.clone()) {
+                continue;
+            }
+
+            let exists = self.dir_path.join(format!("{0}.json", id)).is_file();
+            let was_known = self.known_ids.contains(&id);
+
+            let change = match (was_known, exists) {
+                (false, true) => DefinitionChange::Added(id.clone()),
+                (true, true) => DefinitionChange::Changed(id.clone()),
+                (true, false) => DefinitionChange::Removed(id.clone()),
+                // Created and removed again before we got to look at it.
+                (false, false) => continue,
+            };
+
+            if exists {
+                self.known_ids.insert(id);
+            } else {
+                self.known_ids.remove(&id);
+            }
+
+            changes.push(change);
+        }
+
+        if !changes.is_empty() {
+            self.manager.invalidate_state_cache();
+        }
+
+        Ok(changes)
+    }
+}
+
+fn list_ids(dir_path: &Path) -> Result<HashSet<String>, SftpManError> {
+    let directory_entries =
+        std::fs::read_dir(dir_path).map_err(|err| SftpManError::IO(dir_path.to_path_buf(), err))?;
+
+    Ok(directory_entries
+        .flatten()
+        .filter_map(|entry| id_from_file_name(Path::new(&entry.file_name())))
+        .collect())
+}
+
+/// Extracts the definition ID from a definition file name (e.g. `my-fs.json` -> `my-fs`).
+fn id_from_file_name(name: &Path) -> Option<String> {
+    if name.extension()? != "json" {
+        return None;
+    }
+
+    Some(name.file_stem()?.to_string_lossy().to_string())
+}